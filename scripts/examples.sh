@@ -3,6 +3,19 @@
 cargo build --release --target wasm32-unknown-unknown
 mkdir -p docs
 
+# textures are fetched at runtime from ./resources, only the ones named in the sources and
+# the scenes are copied. The rest of the resources are embedded or not used by the examples.
+rm -rf ./docs/resources
+assets=$(grep -rhoE '"(textures|objects)/[^"]+\.(png|jpg|jpeg|hdr|ktx2|dds)"' ./src ./resources/scenes | tr -d '"' | sort -u)
+for asset in $assets; do
+  if [ -f "./resources/$asset" ]; then
+    mkdir -p "./docs/resources/$(dirname "$asset")"
+    cp "./resources/$asset" "./docs/resources/$asset"
+  else
+    echo "Missing resource '$asset'"
+  fi
+done
+
 for f in ./target/wasm32-unknown-unknown/release/*.wasm; do
    wasm-bindgen $f --out-dir ./docs --no-modules --browser
 done
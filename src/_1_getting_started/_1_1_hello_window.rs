@@ -1,14 +1,16 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
 use notan::prelude::*;
 
-#[notan_main]
-fn main() -> Result<(), String> {
-    // initialize notan passing the update function
-    notan::init().update(update).build()
-}
+// this example doesn't need any state
+pub struct State;
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_1_1", "Getting started", "Hello window")
+    }
 
-fn update(app: &mut App) {
-    // if esc is pressed close the app
-    if app.keyboard.was_pressed(KeyCode::Escape) {
-        app.exit();
+    fn setup(_app: &mut App, _gfx: &mut Graphics, _config: &Config) -> Self {
+        State
     }
 }
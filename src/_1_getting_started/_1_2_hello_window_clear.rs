@@ -1,28 +1,15 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::utils::{present, tweak_clear_color, StatsRenderer};
+use notan::egui;
 use notan::prelude::*;
 
-#[notan_main]
-fn main() -> Result<(), String> {
-    notan::init()
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
-}
-
-fn update(app: &mut App) {
-    // if esc is pressed close the app
-    if app.keyboard.was_pressed(KeyCode::Escape) {
-        app.exit();
-    }
-}
-
-fn draw(gfx: &mut Graphics) {
+fn draw(gfx: &mut Graphics, state: &mut State) {
     // create a renderer object
-    let mut renderer = gfx.create_renderer();
+    let mut renderer = StatsRenderer::new(gfx);
 
     // define a color to use as clear
-    let clear = ClearOptions::color(Color::from_rgb(0.2, 0.3, 0.3));
+    let clear = ClearOptions::color(state.clear_color);
 
     // begin the pass
     renderer.begin(Some(&clear));
@@ -31,5 +18,29 @@ fn draw(gfx: &mut Graphics) {
     renderer.end();
 
     // render to the screen
-    gfx.render(&renderer);
+    present(gfx, &renderer);
+}
+
+pub struct State {
+    clear_color: Color,
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_1_2", "Getting started", "Hello window clear")
+    }
+
+    fn setup(_app: &mut App, _gfx: &mut Graphics, config: &Config) -> Self {
+        State {
+            clear_color: config.clear_color(Color::from_rgb(0.2, 0.3, 0.3)),
+        }
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+    }
 }
@@ -1,3 +1,7 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::utils::{present, tweak_clear_color, StatsRenderer};
+use notan::egui;
 use notan::prelude::*;
 
 // language=glsl
@@ -23,25 +27,14 @@ const FRAGMENT_SHADER_SOURCE: ShaderSource = notan::fragment_shader! {
 };
 
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     pipeline: Pipeline,
     vbo: Buffer,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    // init notan using setup as initialization callback
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    clear_color: Color,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(gfx: &mut Graphics) -> State {
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new().attr(0, VertexFormat::Float32x3);
 
@@ -69,22 +62,19 @@ fn setup(gfx: &mut Graphics) -> State {
         .build()
         .unwrap();
 
-    State { pipeline, vbo }
-}
-
-fn update(app: &mut App) {
-    // if esc is pressed close the app
-    if app.keyboard.was_pressed(KeyCode::Escape) {
-        app.exit();
+    State {
+        pipeline,
+        vbo,
+        clear_color: config.clear_color(Color::from_rgb(0.2, 0.3, 0.3)),
     }
 }
 
 fn draw(gfx: &mut Graphics, state: &mut State) {
     // create a renderer object
-    let mut renderer = gfx.create_renderer();
+    let mut renderer = StatsRenderer::new(gfx);
 
     // define a color to use as clear
-    let clear = ClearOptions::color(Color::from_rgb(0.2, 0.3, 0.3));
+    let clear = ClearOptions::color(state.clear_color);
 
     // begin the pass
     renderer.begin(Some(&clear));
@@ -98,5 +88,23 @@ fn draw(gfx: &mut Graphics, state: &mut State) {
     renderer.end();
 
     // render to the screen
-    gfx.render(&renderer);
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_2_1", "Getting started", "Hello triangle")
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+    }
 }
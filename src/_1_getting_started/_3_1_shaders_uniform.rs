@@ -1,3 +1,8 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{elapsed_time, present, tweak_clear_color, StatsRenderer};
+use notan::egui;
 use notan::prelude::*;
 
 // language=glsl
@@ -26,26 +31,15 @@ const FRAGMENT_SHADER_SOURCE: ShaderSource = notan::fragment_shader! {
 };
 
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     pipeline: Pipeline,
     vbo: Buffer,
     ubo: Buffer,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    // init notan using setup as initialization callback
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    clear_color: Color,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(gfx: &mut Graphics) -> State {
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new().attr(0, VertexFormat::Float32x3);
 
@@ -80,28 +74,26 @@ fn setup(gfx: &mut Graphics) -> State {
         .build()
         .unwrap();
 
-    State { pipeline, vbo, ubo }
-}
-
-fn update(app: &mut App) {
-    // if esc is pressed close the app
-    if app.keyboard.was_pressed(KeyCode::Escape) {
-        app.exit();
+    State {
+        pipeline,
+        vbo,
+        ubo,
+        clear_color: config.clear_color(Color::from_rgb(0.2, 0.3, 0.3)),
     }
 }
 
-fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
+fn draw(gfx: &mut Graphics, state: &mut State) {
     // calculate the green color
-    let time = app.timer.time_since_init();
+    let time = elapsed_time();
     let green_value = time.sin() / 2.0 + 0.5;
 
     // update the uniform block data
-    gfx.set_buffer_data(&state.ubo, &[0.0, green_value, 0.0, 1.0]);
+    set_buffer_data!(gfx, &state.ubo, &[0.0, green_value, 0.0, 1.0]);
 
     // create a renderer object
-    let mut renderer = gfx.create_renderer();
+    let mut renderer = StatsRenderer::new(gfx);
 
-    let clear = ClearOptions::color(Color::from_rgb(0.2, 0.3, 0.3));
+    let clear = ClearOptions::color(state.clear_color);
     renderer.begin(Some(&clear));
     renderer.set_pipeline(&state.pipeline);
     renderer.bind_buffer(&state.vbo);
@@ -109,5 +101,23 @@ fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
     renderer.end();
 
     // render to the screen
-    gfx.render(&renderer);
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_3_1", "Getting started", "Shaders uniform")
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+    }
 }
@@ -1,3 +1,7 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::utils::{present, tweak_clear_color, AssetManager, StatsRenderer, TextureHandle};
+use notan::egui;
 use notan::prelude::*;
 
 // language=glsl
@@ -40,27 +44,17 @@ const FRAGMENT_SHADER_SOURCE: ShaderSource = notan::fragment_shader! {
 };
 
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     pipeline: Pipeline,
     vbo: Buffer,
     ebo: Buffer,
-    texture: Texture,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    // init notan using setup as initialization callback
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    texture: TextureHandle,
+    assets: AssetManager,
+    clear_color: Color,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(gfx: &mut Graphics) -> State {
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new()
         .attr(0, VertexFormat::Float32x3)
@@ -107,41 +101,56 @@ fn setup(gfx: &mut Graphics) -> State {
         .build()
         .unwrap();
 
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+
     // load and create the gpu texture
-    let texture = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/container.jpg"))
-        .build()
-        .unwrap();
+    let texture = assets.load_texture("textures/container.jpg");
 
     State {
         pipeline,
         vbo,
         ebo,
         texture,
-    }
-}
-
-fn update(app: &mut App) {
-    // if esc is pressed close the app
-    if app.keyboard.was_pressed(KeyCode::Escape) {
-        app.exit();
+        assets,
+        clear_color: config.clear_color(Color::from_rgb(0.2, 0.3, 0.3)),
     }
 }
 
 fn draw(gfx: &mut Graphics, state: &mut State) {
-    let mut renderer = gfx.create_renderer();
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
+    let mut renderer = StatsRenderer::new(gfx);
 
-    let clear = ClearOptions::color(Color::from_rgb(0.2, 0.3, 0.3));
+    let clear = ClearOptions::color(state.clear_color);
 
     renderer.begin(Some(&clear));
 
     renderer.set_pipeline(&state.pipeline);
     renderer.bind_buffers(&[&state.vbo, &state.ebo]);
-    renderer.bind_texture(0, &state.texture);
+    renderer.bind_texture(0, state.assets.texture(&state.texture));
     renderer.draw(0, 6);
     renderer.end();
 
     // render to the screen
-    gfx.render(&renderer);
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_4_1", "Getting started", "Textures")
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+    }
 }
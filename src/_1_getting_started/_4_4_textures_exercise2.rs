@@ -1,3 +1,9 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::utils::{
+    present, tweak_clear_color, AssetManager, StatsRenderer, TextureHandle, TextureOptions,
+};
+use notan::egui;
 use notan::prelude::*;
 
 // language=glsl
@@ -41,28 +47,18 @@ const FRAGMENT_SHADER_SOURCE: ShaderSource = notan::fragment_shader! {
 };
 
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     pipeline: Pipeline,
     vbo: Buffer,
     ebo: Buffer,
-    texture1: Texture,
-    texture2: Texture,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    // init notan using setup as initialization callback
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    texture1: TextureHandle,
+    texture2: TextureHandle,
+    assets: AssetManager,
+    clear_color: Color,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(gfx: &mut Graphics) -> State {
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new()
         .attr(0, VertexFormat::Float32x3) // positions
@@ -110,20 +106,21 @@ fn setup(gfx: &mut Graphics) -> State {
         .build()
         .unwrap();
 
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+
     // load and create the gpu texture1
-    let texture1 = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/container.jpg"))
-        .build()
-        .unwrap();
+    let texture1 = assets.load_texture("textures/container.jpg");
 
     // load and create the gpu texture2
-    let texture2 = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/awesomeface.png"))
-        .with_wrap(TextureWrap::Repeat, TextureWrap::Repeat)
-        .build()
-        .unwrap();
+    let texture2 = assets.load_texture_with(
+        "textures/awesomeface.png",
+        TextureOptions {
+            wrap_x: TextureWrap::Repeat,
+            wrap_y: TextureWrap::Repeat,
+            ..Default::default()
+        },
+    );
 
     State {
         pipeline,
@@ -131,30 +128,46 @@ fn setup(gfx: &mut Graphics) -> State {
         ebo,
         texture1,
         texture2,
-    }
-}
-
-fn update(app: &mut App) {
-    // if esc is pressed close the app
-    if app.keyboard.was_pressed(KeyCode::Escape) {
-        app.exit();
+        assets,
+        clear_color: config.clear_color(Color::from_rgb(0.2, 0.3, 0.3)),
     }
 }
 
 fn draw(gfx: &mut Graphics, state: &mut State) {
-    let mut renderer = gfx.create_renderer();
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
+    let mut renderer = StatsRenderer::new(gfx);
 
-    let clear = ClearOptions::color(Color::from_rgb(0.2, 0.3, 0.3));
+    let clear = ClearOptions::color(state.clear_color);
 
     renderer.begin(Some(&clear));
 
     renderer.set_pipeline(&state.pipeline);
     renderer.bind_buffers(&[&state.vbo, &state.ebo]);
-    renderer.bind_texture_slot(0, 0, &state.texture1);
-    renderer.bind_texture_slot(1, 1, &state.texture2);
+    renderer.bind_texture_slot(0, 0, state.assets.texture(&state.texture1));
+    renderer.bind_texture_slot(1, 1, state.assets.texture(&state.texture2));
     renderer.draw(0, 6);
     renderer.end();
 
     // render to the screen
-    gfx.render(&renderer);
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_4_4", "Getting started", "Textures exercise 2")
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+    }
 }
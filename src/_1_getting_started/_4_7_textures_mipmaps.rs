@@ -0,0 +1,436 @@
+use crate::config::{Config, ConfigOption, OptionKind};
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    delta_time, present, tweak_camera, tweak_clear_color, AssetManager, Camera, CameraMovement,
+    MipFilter, MipmapOptions, SamplerSettings, StatsRenderer, TextureHandle, TextureUsage,
+};
+use notan::egui;
+use notan::math::{vec3, Mat4};
+use notan::prelude::*;
+
+// language=glsl
+const VERTEX_SHADER_SOURCE: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec3 aPos;
+    layout (location = 1) in vec2 aTexCoord;
+
+    layout(location = 0) out vec2 TexCoord;
+
+    layout(set = 0, binding = 0) uniform Transform {
+        mat4 model;
+        mat4 view;
+        mat4 projection;
+    };
+
+    void main()
+    {
+        gl_Position = projection * view * model * vec4(aPos, 1.0);
+        TexCoord = aTexCoord;
+    }
+  "#
+};
+
+// language=glsl
+const FRAGMENT_SHADER_SOURCE: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    #define MAX_MIP_LEVELS 13
+
+    layout(location = 0) out vec4 color;
+    layout(location = 0) in vec2 TexCoord;
+
+    layout(binding = 0) uniform sampler2D atlas;
+
+    // levels packed in the atlas (see utils/mipmaps.rs)
+    layout(set = 0, binding = 1) uniform MipmapData {
+        vec4 levels[MAX_MIP_LEVELS];
+        vec2 atlasSize;
+        vec2 baseSize;
+        uint levelCount;
+        uint minFilter;
+        uint magFilter;
+        uint mipFilter;
+        uint anisotropy;
+        uint wrapX;
+        uint wrapY;
+    };
+
+    vec2 wrapUV(vec2 uv)
+    {
+        return vec2(
+            wrapX == 1u ? fract(uv.x) : clamp(uv.x, 0.0, 1.0),
+            wrapY == 1u ? fract(uv.y) : clamp(uv.y, 0.0, 1.0)
+        );
+    }
+
+    // the hardware filter is always linear, nearest snaps to the texel center
+    vec4 sampleLevel(sampler2D tex, vec2 uv, int level, bool linear)
+    {
+        vec4 rect = levels[level];
+        vec2 p = wrapUV(uv) * rect.zw;
+        if (!linear) {
+            p = floor(min(p, rect.zw - 0.5)) + 0.5;
+        }
+        return textureLod(tex, (rect.xy + p) / atlasSize, 0.0);
+    }
+
+    vec4 sampleMip(sampler2D tex, vec2 uv, float lod)
+    {
+        bool linear = minFilter == 1u;
+        if (mipFilter == 0u) {
+            return sampleLevel(tex, uv, 0, linear);
+        }
+
+        lod = min(lod, float(levelCount - 1u));
+        if (mipFilter == 1u) {
+            return sampleLevel(tex, uv, int(floor(lod + 0.5)), linear);
+        }
+
+        int level = int(floor(lod));
+        int next = min(level + 1, int(levelCount - 1u));
+        return mix(sampleLevel(tex, uv, level, linear), sampleLevel(tex, uv, next, linear), fract(lod));
+    }
+
+    vec4 textureMip(sampler2D tex, vec2 uv)
+    {
+        // texel footprint of the pixel
+        vec2 dx = dFdx(uv * baseSize);
+        vec2 dy = dFdy(uv * baseSize);
+        float lx = length(dx);
+        float ly = length(dy);
+        float major = max(lx, ly);
+        float minor = max(min(lx, ly), 1e-6);
+
+        // take several samples along the major axis, using a lower lod for each one
+        float taps = clamp(ceil(major / minor), 1.0, float(anisotropy));
+        float lod = log2(max(major / taps, 1e-6));
+
+        if (lod <= 0.0) {
+            return sampleLevel(tex, uv, 0, magFilter == 1u);
+        }
+
+        vec2 axis = (lx > ly ? dx : dy) / baseSize;
+        vec4 result = vec4(0.0);
+        for (int i = 0; i < 16; i++) {
+            if (float(i) >= taps) {
+                break;
+            }
+            float t = (float(i) + 0.5) / taps - 0.5;
+            result += sampleMip(tex, uv + axis * t, lod);
+        }
+        return result / taps;
+    }
+
+    void main()
+    {
+        color = textureMip(atlas, TexCoord);
+    }
+  "#
+};
+
+// Represent our transform data
+#[uniform]
+#[derive(Copy, Clone)]
+struct Transform {
+    model: Mat4,
+    view: Mat4,
+    projection: Mat4,
+}
+
+const FILTERS: [MipFilter; 3] = [MipFilter::Box, MipFilter::Kaiser, MipFilter::Lanczos];
+
+// Create a struct to store the app's state
+pub struct State {
+    pipeline: Pipeline,
+    vbo: Buffer,
+    transform_ubo: Buffer,
+    mipmap_ubo: Buffer,
+    // one texture per filter, to compare them
+    textures: Vec<TextureHandle>,
+    selected: usize,
+    sampler: SamplerSettings,
+    assets: AssetManager,
+    camera: Camera,
+    last_x: f32,
+    last_y: f32,
+    first_mouse: bool,
+    clear_color: Color,
+}
+
+// initialize the state and return it to be used by notan
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
+    // Declare the vertex attributes
+    let vertex_info = VertexInfo::new()
+        .attr(0, VertexFormat::Float32x3) // positions
+        .attr(1, VertexFormat::Float32x2); // texture coords
+
+    // Enable depth test
+    let depth_test = DepthStencil {
+        write: true,
+        compare: CompareMode::Less,
+    };
+
+    // build the pipeline
+    let pipeline = gfx
+        .create_pipeline()
+        .from(&VERTEX_SHADER_SOURCE, &FRAGMENT_SHADER_SOURCE)
+        .with_vertex_info(&vertex_info)
+        .with_depth_stencil(depth_test)
+        .with_texture_location(0, "atlas")
+        .build()
+        .unwrap();
+
+    // a big floor, the texture repeats 50 times to see the minification at the distance
+    #[rustfmt::skip]
+    let vertices = [
+        // positions          // texture coords
+        -50.0, 0.0, -50.0,    0.0, 50.0,
+        50.0, 0.0, -50.0,     50.0, 50.0,
+        50.0, 0.0, 50.0,      50.0, 0.0,
+        50.0, 0.0, 50.0,      50.0, 0.0,
+        -50.0, 0.0, 50.0,     0.0, 0.0,
+        -50.0, 0.0, -50.0,    0.0, 50.0,
+    ];
+
+    // create the vertex buffer object
+    let vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    // create the uniform buffer objects
+    let transform_ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
+    let mipmap_ubo = gfx.create_uniform_buffer(1, "MipmapData").build().unwrap();
+
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+
+    let sampler = SamplerSettings {
+        anisotropy: config.int("anisotropy").unwrap_or(16).clamp(1, 16) as _,
+        ..Default::default()
+    };
+
+    // start with the filter passed as option, or kaiser by default
+    let selected = match config.text("filter") {
+        Some(name) => FILTERS
+            .iter()
+            .position(|filter| format!("{:?}", filter).eq_ignore_ascii_case(name))
+            .unwrap_or_else(|| {
+                notan::log::warn!("Unknown mipmap filter '{}', using Kaiser", name);
+                1
+            }),
+        None => 1,
+    };
+
+    let textures = FILTERS
+        .iter()
+        .map(|filter| {
+            assets.load_mipmapped_texture(
+                "textures/container2.png",
+                MipmapOptions {
+                    filter: *filter,
+                    usage: TextureUsage::Color,
+                    sampler,
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+
+    let camera = Camera {
+        position: config.camera_position(vec3(0.0, 1.0, 3.0)),
+        ..Default::default()
+    };
+
+    State {
+        pipeline,
+        vbo,
+        transform_ubo,
+        mipmap_ubo,
+        textures,
+        selected,
+        sampler,
+        assets,
+        camera,
+        last_x: 0.0,
+        last_y: 0.0,
+        first_mouse: true,
+        clear_color: config.clear_color(Color::from_rgb(0.2, 0.3, 0.3)),
+    }
+}
+
+fn update(app: &mut App, state: &mut State) {
+    // 1, 2, 3 select the filter used to generate the mipmaps
+    let keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3];
+    if let Some(index) = keys.iter().position(|key| app.keyboard.was_pressed(*key)) {
+        state.selected = index;
+        notan::log::info!("Mipmaps generated with {:?}", FILTERS[index]);
+    }
+
+    // M cycles between no mipmaps, nearest and linear
+    if app.keyboard.was_pressed(KeyCode::M) {
+        state.sampler.mip_filter = match state.sampler.mip_filter {
+            None => Some(TextureFilter::Nearest),
+            Some(TextureFilter::Nearest) => Some(TextureFilter::Linear),
+            Some(_) => None,
+        };
+        notan::log::info!("Mip filter: {:?}", state.sampler.mip_filter);
+    }
+
+    // N toggles the min/mag filter
+    if app.keyboard.was_pressed(KeyCode::N) {
+        let filter = if state.sampler.min_filter == TextureFilter::Linear {
+            TextureFilter::Nearest
+        } else {
+            TextureFilter::Linear
+        };
+        state.sampler.min_filter = filter;
+        state.sampler.mag_filter = filter;
+        notan::log::info!("Min/Mag filter: {:?}", filter);
+    }
+
+    // F toggles the anisotropic filtering
+    if app.keyboard.was_pressed(KeyCode::F) {
+        state.sampler.anisotropy = if state.sampler.anisotropy > 1 { 1 } else { 16 };
+        notan::log::info!("Anisotropy: {}x", state.sampler.anisotropy);
+    }
+
+    // Process all inputs to move the camera
+    let delta = delta_time();
+    if app.keyboard.is_down(KeyCode::W) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Forward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::S) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Backward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::A) {
+        state.camera.process_keyboard(CameraMovement::Left, delta);
+    }
+    if app.keyboard.is_down(KeyCode::D) {
+        state.camera.process_keyboard(CameraMovement::Right, delta);
+    }
+
+    // process mouse move
+    let x = app.mouse.x;
+    let y = app.mouse.y;
+
+    if state.first_mouse {
+        state.first_mouse = false;
+        state.last_x = x;
+        state.last_y = y;
+    }
+
+    let xoffset = x - state.last_x;
+    let yoffset = state.last_y - y;
+    state.last_x = x;
+    state.last_y = y;
+
+    state.camera.process_mouse_movement(xoffset, yoffset, false);
+
+    // process zoom
+    state
+        .camera
+        .process_mouse_scroll(app.mouse.wheel_delta.y * delta);
+}
+
+fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
+    let handle = &state.textures[state.selected];
+
+    // the sampler settings can change without generating the mipmaps again
+    let mipmap = state
+        .assets
+        .mipmap_data(handle)
+        .map(|data| data.with_sampler(&state.sampler));
+    if let Some(mipmap) = &mipmap {
+        set_buffer_data!(gfx, &state.mipmap_ubo, mipmap);
+    }
+
+    // view/projection transformations
+    let size = gfx.size();
+    let (width, height) = (size.0 as f32, size.1 as f32);
+    let aspect_ratio = width / height;
+    let projection =
+        Mat4::perspective_rh_gl(state.camera.zoom.to_radians(), aspect_ratio, 0.1, 100.0);
+    let view = state.camera.get_view_matrix();
+
+    set_buffer_data!(
+        gfx,
+        &state.transform_ubo,
+        &Transform {
+            model: Mat4::IDENTITY,
+            view,
+            projection,
+        },
+    );
+
+    let mut renderer = StatsRenderer::new(gfx);
+
+    let clear = ClearOptions {
+        color: Some(state.clear_color),
+        depth: Some(1.0),
+        stencil: None,
+    };
+
+    renderer.begin(Some(&clear));
+
+    // the floor is drawn once the atlas is loaded
+    if mipmap.is_some() {
+        renderer.set_pipeline(&state.pipeline);
+        renderer.bind_buffers(&[&state.vbo, &state.transform_ubo, &state.mipmap_ubo]);
+        renderer.bind_texture(0, state.assets.texture(handle));
+        renderer.draw(0, 6);
+    }
+
+    renderer.end();
+
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_4_7", "Getting started", "Textures mipmaps").with_capture_cursor()
+    }
+
+    fn options() -> Vec<ConfigOption> {
+        vec![
+            ConfigOption::new(
+                "filter",
+                OptionKind::Text,
+                "Mipmap filter: box, kaiser or lanczos",
+            ),
+            ConfigOption::new(
+                "anisotropy",
+                OptionKind::Int,
+                "Max anisotropic taps (1 - 16)",
+            ),
+        ]
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+        tweak_camera(ui, &mut self.camera);
+    }
+}
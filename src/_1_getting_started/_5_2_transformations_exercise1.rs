@@ -1,3 +1,10 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    elapsed_time, present, tweak_clear_color, AssetManager, StatsRenderer, TextureHandle,
+};
+use notan::egui;
 use notan::math::{vec3, Mat4};
 use notan::prelude::*;
 
@@ -43,25 +50,15 @@ const FRAGMENT_SHADER_SOURCE: ShaderSource = notan::fragment_shader! {
 };
 
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     pipeline: Pipeline,
     vbo: Buffer,
     ebo: Buffer,
     ubo: Buffer,
-    texture1: Texture,
-    texture2: Texture,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    // init notan using setup as initialization callback
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    texture1: TextureHandle,
+    texture2: TextureHandle,
+    assets: AssetManager,
+    clear_color: Color,
 }
 
 // create transformation
@@ -74,7 +71,7 @@ fn create_transform(time: f32) -> Mat4 {
 }
 
 // initialize the state and return it to be used by notan
-fn setup(app: &mut App, gfx: &mut Graphics) -> State {
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new()
         .attr(0, VertexFormat::Float32x3) // positions
@@ -121,7 +118,7 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         .build()
         .unwrap();
 
-    let transform = create_transform(app.timer.time_since_init());
+    let transform = create_transform(elapsed_time());
 
     // create the uniform buffer object
     let ubo = gfx
@@ -130,19 +127,14 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         .build()
         .unwrap();
 
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+
     // load and create the gpu texture1
-    let texture1 = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/container.jpg"))
-        .build()
-        .unwrap();
+    let texture1 = assets.load_texture("textures/container.jpg");
 
     // load and create the gpu texture2
-    let texture2 = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/awesomeface.png"))
-        .build()
-        .unwrap();
+    let texture2 = assets.load_texture("textures/awesomeface.png");
 
     State {
         pipeline,
@@ -151,34 +143,50 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         ubo,
         texture1,
         texture2,
+        assets,
+        clear_color: config.clear_color(Color::from_rgb(0.2, 0.3, 0.3)),
     }
 }
 
-fn update(app: &mut App) {
-    // if esc is pressed close the app
-    if app.keyboard.was_pressed(KeyCode::Escape) {
-        app.exit();
-    }
-}
+fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
 
-fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
     // update the uniform block data
-    let transform = create_transform(app.timer.time_since_init());
-    gfx.set_buffer_data(&state.ubo, &transform);
+    let transform = create_transform(elapsed_time());
+    set_buffer_data!(gfx, &state.ubo, &transform);
 
-    let mut renderer = gfx.create_renderer();
+    let mut renderer = StatsRenderer::new(gfx);
 
-    let clear = ClearOptions::color(Color::from_rgb(0.2, 0.3, 0.3));
+    let clear = ClearOptions::color(state.clear_color);
 
     renderer.begin(Some(&clear));
 
     renderer.set_pipeline(&state.pipeline);
     renderer.bind_buffers(&[&state.vbo, &state.ebo]);
-    renderer.bind_texture_slot(0, 0, &state.texture1);
-    renderer.bind_texture_slot(1, 1, &state.texture2);
+    renderer.bind_texture_slot(0, 0, state.assets.texture(&state.texture1));
+    renderer.bind_texture_slot(1, 1, state.assets.texture(&state.texture2));
     renderer.draw(0, 6);
     renderer.end();
 
     // render to the screen
-    gfx.render(&renderer);
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_5_2", "Getting started", "Transformations exercise 1")
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+    }
 }
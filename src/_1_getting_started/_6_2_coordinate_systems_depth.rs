@@ -1,3 +1,10 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    elapsed_time, present, tweak_clear_color, AssetManager, StatsRenderer, TextureHandle,
+};
+use notan::egui;
 use notan::math::{vec3, Mat4};
 use notan::prelude::*;
 
@@ -62,28 +69,18 @@ fn create_transform(time: f32, aspect_ratio: f32) -> Transform {
 }
 
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     pipeline: Pipeline,
     vbo: Buffer,
     ubo: Buffer,
-    texture1: Texture,
-    texture2: Texture,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    // init notan using setup as initialization callback
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    texture1: TextureHandle,
+    texture2: TextureHandle,
+    assets: AssetManager,
+    clear_color: Color,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(app: &mut App, gfx: &mut Graphics) -> State {
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new()
         .attr(0, VertexFormat::Float32x3) // positions
@@ -161,7 +158,7 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         .unwrap();
 
     // create transformation
-    let time = app.timer.time_since_init();
+    let time = elapsed_time();
     let size = gfx.size();
     let (width, height) = (size.0 as f32, size.1 as f32);
     let aspect_ratio = width / height;
@@ -174,19 +171,14 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         .build()
         .unwrap();
 
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+
     // load and create the gpu texture1
-    let texture1 = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/container.jpg"))
-        .build()
-        .unwrap();
+    let texture1 = assets.load_texture("textures/container.jpg");
 
     // load and create the gpu texture2
-    let texture2 = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/awesomeface.png"))
-        .build()
-        .unwrap();
+    let texture2 = assets.load_texture("textures/awesomeface.png");
 
     State {
         pipeline,
@@ -194,31 +186,29 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         ubo,
         texture1,
         texture2,
+        assets,
+        clear_color: config.clear_color(Color::from_rgb(0.2, 0.3, 0.3)),
     }
 }
 
-fn update(app: &mut App) {
-    // if esc is pressed close the app
-    if app.keyboard.was_pressed(KeyCode::Escape) {
-        app.exit();
-    }
-}
+fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
 
-fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
     // create transformation
-    let time = app.timer.time_since_init();
+    let time = elapsed_time();
     let size = gfx.size();
     let (width, height) = (size.0 as f32, size.1 as f32);
     let aspect_ratio = width / height;
 
     // update uniform buffer object
     let transform = create_transform(time, aspect_ratio);
-    gfx.set_buffer_data(&state.ubo, &transform);
+    set_buffer_data!(gfx, &state.ubo, &transform);
 
-    let mut renderer = gfx.create_renderer();
+    let mut renderer = StatsRenderer::new(gfx);
 
     let clear = ClearOptions {
-        color: Some(Color::from_rgb(0.2, 0.3, 0.3)),
+        color: Some(state.clear_color),
         depth: Some(1.0),
         stencil: None,
     };
@@ -227,11 +217,29 @@ fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
 
     renderer.set_pipeline(&state.pipeline);
     renderer.bind_buffers(&[&state.vbo, &state.ubo]);
-    renderer.bind_texture_slot(0, 0, &state.texture1);
-    renderer.bind_texture_slot(1, 1, &state.texture2);
+    renderer.bind_texture_slot(0, 0, state.assets.texture(&state.texture1));
+    renderer.bind_texture_slot(1, 1, state.assets.texture(&state.texture2));
     renderer.draw(0, 36);
     renderer.end();
 
     // render to the screen
-    gfx.render(&renderer);
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_6_2", "Getting started", "Coordinate systems depth")
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+    }
 }
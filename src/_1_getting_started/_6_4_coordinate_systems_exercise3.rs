@@ -1,3 +1,11 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    elapsed_time, present, tweak_clear_color, AssetManager, ModelInstances, StatsRenderer,
+    TextureHandle,
+};
+use notan::egui;
 use notan::math::{vec3, Mat4, Vec3};
 use notan::prelude::*;
 use std::ops::Rem;
@@ -9,16 +17,22 @@ const VERTEX_SHADER_SOURCE: ShaderSource = notan::vertex_shader! {
     layout (location = 0) in vec3 aPos;
     layout (location = 2) in vec2 aTexCoord;
 
+    // model matrix of each cube, one column per attribute
+    layout (location = 3) in vec4 aModel0;
+    layout (location = 4) in vec4 aModel1;
+    layout (location = 5) in vec4 aModel2;
+    layout (location = 6) in vec4 aModel3;
+
     layout(location = 0) out vec2 TexCoord;
 
     layout(set = 0, binding = 0) uniform Transform {
-        mat4 model;
         mat4 view;
         mat4 projection;
     };
 
     void main()
     {
+        mat4 model = mat4(aModel0, aModel1, aModel2, aModel3);
         gl_Position = projection * view * model * vec4(aPos, 1.0);
         TexCoord = vec2(aTexCoord.x, aTexCoord.y);
     }
@@ -47,48 +61,41 @@ const FRAGMENT_SHADER_SOURCE: ShaderSource = notan::fragment_shader! {
 #[uniform]
 #[derive(Copy, Clone)]
 struct Transform {
-    model: Mat4,
     view: Mat4,
     projection: Mat4,
 }
 
 // create transformation
-fn create_transform(aspect_ratio: f32, translation: Vec3, angle: f32) -> Transform {
-    let translate = Mat4::from_translation(translation);
-    let rotate = Mat4::from_axis_angle(vec3(1.0, 0.3, 0.5).normalize(), angle.to_radians());
-
+fn create_transform(aspect_ratio: f32) -> Transform {
     Transform {
-        model: Mat4::IDENTITY * translate * rotate,
         view: Mat4::IDENTITY * Mat4::from_translation(vec3(0.0, 0.0, -3.0)),
         projection: Mat4::IDENTITY
             * Mat4::perspective_rh_gl(45.0_f32.to_radians(), aspect_ratio, 0.1, 100.0),
     }
 }
 
+// model matrix of each cube
+fn create_model(translation: Vec3, angle: f32) -> Mat4 {
+    let translate = Mat4::from_translation(translation);
+    let rotate = Mat4::from_axis_angle(vec3(1.0, 0.3, 0.5).normalize(), angle.to_radians());
+    Mat4::IDENTITY * translate * rotate
+}
+
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     pipeline: Pipeline,
     vbo: Buffer,
     ubo: Buffer,
-    texture1: Texture,
-    texture2: Texture,
+    instances: ModelInstances,
+    texture1: TextureHandle,
+    texture2: TextureHandle,
+    assets: AssetManager,
     cube_positions: [Vec3; 10],
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    // init notan using setup as initialization callback
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    clear_color: Color,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(gfx: &mut Graphics) -> State {
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new()
         .attr(0, VertexFormat::Float32x3) // positions
@@ -105,6 +112,7 @@ fn setup(gfx: &mut Graphics) -> State {
         .create_pipeline()
         .from(&VERTEX_SHADER_SOURCE, &FRAGMENT_SHADER_SOURCE)
         .with_vertex_info(&vertex_info)
+        .with_vertex_info(&ModelInstances::vertex_info(3))
         .with_texture_location(0, "texture1")
         .with_texture_location(1, "texture2")
         .with_depth_stencil(depth_test)
@@ -168,19 +176,17 @@ fn setup(gfx: &mut Graphics) -> State {
     // create the uniform buffer object
     let ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
 
+    // per instance model matrices, updated on each frame
+    let instances = ModelInstances::new(gfx, 3, &[Mat4::IDENTITY; 10]).unwrap();
+
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+
     // load and create the gpu texture1
-    let texture1 = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/container.jpg"))
-        .build()
-        .unwrap();
+    let texture1 = assets.load_texture("textures/container.jpg");
 
     // load and create the gpu texture2
-    let texture2 = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/awesomeface.png"))
-        .build()
-        .unwrap();
+    let texture2 = assets.load_texture("textures/awesomeface.png");
 
     let cube_positions = [
         vec3(0.0, 0.0, 0.0),
@@ -199,62 +205,76 @@ fn setup(gfx: &mut Graphics) -> State {
         pipeline,
         vbo,
         ubo,
+        instances,
         texture1,
         texture2,
+        assets,
         cube_positions,
+        clear_color: config.clear_color(Color::from_rgb(0.2, 0.3, 0.3)),
     }
 }
 
-fn update(app: &mut App) {
-    // if esc is pressed close the app
-    if app.keyboard.was_pressed(KeyCode::Escape) {
-        app.exit();
-    }
-}
+fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
 
-fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
     // create transformation
     let size = gfx.size();
     let (width, height) = (size.0 as f32, size.1 as f32);
     let aspect_ratio = width / height;
 
-    state
+    let models = state
         .cube_positions
         .into_iter()
         .enumerate()
-        .for_each(|(i, translation)| {
+        .map(|(i, translation)| {
             let angle = if i.rem(3) == 0 {
-                app.timer.time_since_init() * 25.0
+                elapsed_time() * 25.0
             } else {
                 i as f32 * 20.0
             };
+            create_model(translation, angle)
+        })
+        .collect::<Vec<_>>();
+    state.instances.update(gfx, &models);
+
+    // update uniform buffer object
+    let transform = create_transform(aspect_ratio);
+    set_buffer_data!(gfx, &state.ubo, &transform);
+
+    // all the cubes are drawn in the same pass
+    let mut renderer = StatsRenderer::new(gfx);
+    renderer.begin(Some(&ClearOptions {
+        color: Some(state.clear_color),
+        depth: Some(1.0),
+        stencil: None,
+    }));
+
+    renderer.set_pipeline(&state.pipeline);
+    renderer.bind_buffers(&[&state.vbo, state.instances.buffer(), &state.ubo]);
+    renderer.bind_texture_slot(0, 0, state.assets.texture(&state.texture1));
+    renderer.bind_texture_slot(1, 1, state.assets.texture(&state.texture2));
+    renderer.draw_instanced(0, 36, state.instances.len() as i32);
+    renderer.end();
+
+    // render to the screen
+    present(gfx, &renderer);
+}
 
-            // update uniform buffer object
-            let transform = create_transform(aspect_ratio, translation, angle);
-            gfx.set_buffer_data(&state.ubo, &transform);
-
-            let mut renderer = gfx.create_renderer();
-
-            let clear = if i == 0 {
-                Some(ClearOptions {
-                    color: Some(Color::from_rgb(0.2, 0.3, 0.3)),
-                    depth: Some(1.0),
-                    stencil: None,
-                })
-            } else {
-                None
-            };
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_6_4", "Getting started", "Coordinate systems exercise 3")
+    }
 
-            renderer.begin(clear.as_ref());
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
 
-            renderer.set_pipeline(&state.pipeline);
-            renderer.bind_buffers(&[&state.vbo, &state.ubo]);
-            renderer.bind_texture_slot(0, 0, &state.texture1);
-            renderer.bind_texture_slot(1, 1, &state.texture2);
-            renderer.draw(0, 36);
-            renderer.end();
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
 
-            // render to the screen
-            gfx.render(&renderer);
-        });
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+    }
 }
@@ -1,8 +1,14 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    delta_time, present, tweak_clear_color, AssetManager, ModelInstances, StatsRenderer,
+    TextureHandle,
+};
+use notan::egui;
 use notan::math::{vec3, Mat4, Vec3};
 use notan::prelude::*;
 
-const IS_WASM: bool = cfg!(target_arch = "wasm32");
-
 // language=glsl
 const VERTEX_SHADER_SOURCE: ShaderSource = notan::vertex_shader! {
   r#"
@@ -10,16 +16,22 @@ const VERTEX_SHADER_SOURCE: ShaderSource = notan::vertex_shader! {
     layout (location = 0) in vec3 aPos;
     layout (location = 2) in vec2 aTexCoord;
 
+    // model matrix of each cube, one column per attribute
+    layout (location = 3) in vec4 aModel0;
+    layout (location = 4) in vec4 aModel1;
+    layout (location = 5) in vec4 aModel2;
+    layout (location = 6) in vec4 aModel3;
+
     layout(location = 0) out vec2 TexCoord;
 
     layout(set = 0, binding = 0) uniform Transform {
-        mat4 model;
         mat4 view;
         mat4 projection;
     };
 
     void main()
     {
+        mat4 model = mat4(aModel0, aModel1, aModel2, aModel3);
         gl_Position = projection * view * model * vec4(aPos, 1.0);
         TexCoord = vec2(aTexCoord.x, aTexCoord.y);
     }
@@ -48,39 +60,35 @@ const FRAGMENT_SHADER_SOURCE: ShaderSource = notan::fragment_shader! {
 #[uniform]
 #[derive(Copy, Clone)]
 struct Transform {
-    model: Mat4,
     view: Mat4,
     projection: Mat4,
 }
 
 // create transformation
-fn create_transform(
-    fov: f32,
-    view: Mat4,
-    aspect_ratio: f32,
-    translation: Vec3,
-    angle: f32,
-) -> Transform {
-    let translate = Mat4::from_translation(translation);
-    let rotate = Mat4::from_axis_angle(vec3(1.0, 0.3, 0.5).normalize(), angle.to_radians());
-
+fn create_transform(fov: f32, view: Mat4, aspect_ratio: f32) -> Transform {
     Transform {
-        model: Mat4::IDENTITY * translate * rotate,
         view,
         projection: Mat4::IDENTITY
             * Mat4::perspective_rh_gl(fov.to_radians(), aspect_ratio, 0.1, 100.0),
     }
 }
 
+// model matrix of each cube
+fn create_model(translation: Vec3, angle: f32) -> Mat4 {
+    let translate = Mat4::from_translation(translation);
+    let rotate = Mat4::from_axis_angle(vec3(1.0, 0.3, 0.5).normalize(), angle.to_radians());
+    Mat4::IDENTITY * translate * rotate
+}
+
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     pipeline: Pipeline,
     vbo: Buffer,
     ubo: Buffer,
-    texture1: Texture,
-    texture2: Texture,
-    cube_positions: [Vec3; 10],
+    instances: ModelInstances,
+    texture1: TextureHandle,
+    texture2: TextureHandle,
+    assets: AssetManager,
     camera_pos: Vec3,
     camera_front: Vec3,
     camera_up: Vec3,
@@ -90,24 +98,11 @@ struct State {
     last_x: f32,
     last_y: f32,
     first_mouse: bool,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    // init notan using setup as initialization callback
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    clear_color: Color,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(app: &mut App, gfx: &mut Graphics) -> State {
-    // capture the cursor
-    app.window().set_capture_cursor(true);
-
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new()
         .attr(0, VertexFormat::Float32x3) // positions
@@ -124,6 +119,7 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         .create_pipeline()
         .from(&VERTEX_SHADER_SOURCE, &FRAGMENT_SHADER_SOURCE)
         .with_vertex_info(&vertex_info)
+        .with_vertex_info(&ModelInstances::vertex_info(3))
         .with_texture_location(0, "texture1")
         .with_texture_location(1, "texture2")
         .with_depth_stencil(depth_test)
@@ -187,19 +183,14 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
     // create the uniform buffer object
     let ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
 
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+
     // load and create the gpu texture1
-    let texture1 = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/container.jpg"))
-        .build()
-        .unwrap();
+    let texture1 = assets.load_texture("textures/container.jpg");
 
     // load and create the gpu texture2
-    let texture2 = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/awesomeface.png"))
-        .build()
-        .unwrap();
+    let texture2 = assets.load_texture("textures/awesomeface.png");
 
     let cube_positions = [
         vec3(0.0, 0.0, 0.0),
@@ -214,8 +205,19 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         vec3(-1.3, 1.0, -1.5),
     ];
 
+    // the cubes don't move, their model matrices are uploaded once
+    let models = cube_positions
+        .into_iter()
+        .enumerate()
+        .map(|(i, translation)| {
+            let angle = i as f32 * 20.0;
+            create_model(translation, angle)
+        })
+        .collect::<Vec<_>>();
+    let instances = ModelInstances::new(gfx, 3, &models).unwrap();
+
     // camera
-    let camera_pos = vec3(0.0, 0.0, 3.0);
+    let camera_pos = config.camera_position(vec3(0.0, 0.0, 3.0));
     let camera_front = vec3(0.0, 0.0, -1.0);
     let camera_up = vec3(0.0, 1.0, 0.0);
 
@@ -227,9 +229,10 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         pipeline,
         vbo,
         ubo,
+        instances,
         texture1,
         texture2,
-        cube_positions,
+        assets,
         camera_pos,
         camera_front,
         camera_up,
@@ -239,24 +242,13 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         last_x: 0.0,
         last_y: 0.0,
         first_mouse: true,
+        clear_color: config.clear_color(Color::from_rgb(0.2, 0.3, 0.3)),
     }
 }
 
 fn update(app: &mut App, state: &mut State) {
-    if !IS_WASM {
-        // if esc is pressed close the app
-        if app.keyboard.was_pressed(KeyCode::Escape) {
-            app.exit();
-        }
-    }
-
-    // capture the cursor (wasm32 allow escape the cursor using ESC)
-    if app.mouse.was_pressed(MouseButton::Left) && !app.window().capture_cursor() {
-        app.window().set_capture_cursor(true);
-    }
-
     // Process all inputs to move the camera
-    let camera_speed = app.timer.delta_f32() * 2.5;
+    let camera_speed = delta_time() * 2.5;
     if app.keyboard.is_down(KeyCode::W) {
         state.camera_pos += camera_speed * state.camera_front;
     }
@@ -303,55 +295,67 @@ fn update(app: &mut App, state: &mut State) {
 
     let delta_y = app.mouse.wheel_delta.y;
     if delta_y != 0.0 {
-        state.fov -= delta_y * app.timer.delta_f32();
+        state.fov -= delta_y * delta_time();
         state.fov = state.fov.clamp(1.0, 45.0);
     }
 }
 
 fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
     // create transformation
     let size = gfx.size();
     let (width, height) = (size.0 as f32, size.1 as f32);
     let aspect_ratio = width / height;
 
-    state
-        .cube_positions
-        .into_iter()
-        .enumerate()
-        .for_each(|(i, translation)| {
-            let view = Mat4::look_at_rh(
-                state.camera_pos,
-                state.camera_pos + state.camera_front,
-                state.camera_up,
-            );
-            let angle = i as f32 * 20.0;
+    let view = Mat4::look_at_rh(
+        state.camera_pos,
+        state.camera_pos + state.camera_front,
+        state.camera_up,
+    );
 
-            // update uniform buffer object
-            let transform = create_transform(state.fov, view, aspect_ratio, translation, angle);
-            gfx.set_buffer_data(&state.ubo, &transform);
-
-            let mut renderer = gfx.create_renderer();
-
-            let clear = if i == 0 {
-                Some(ClearOptions {
-                    color: Some(Color::from_rgb(0.2, 0.3, 0.3)),
-                    depth: Some(1.0),
-                    stencil: None,
-                })
-            } else {
-                None
-            };
-
-            renderer.begin(clear.as_ref());
-
-            renderer.set_pipeline(&state.pipeline);
-            renderer.bind_buffers(&[&state.vbo, &state.ubo]);
-            renderer.bind_texture_slot(0, 0, &state.texture1);
-            renderer.bind_texture_slot(1, 1, &state.texture2);
-            renderer.draw(0, 36);
-            renderer.end();
-
-            // render to the screen
-            gfx.render(&renderer);
-        });
+    // update uniform buffer object
+    let transform = create_transform(state.fov, view, aspect_ratio);
+    set_buffer_data!(gfx, &state.ubo, &transform);
+
+    // all the cubes are drawn in the same pass
+    let mut renderer = StatsRenderer::new(gfx);
+    renderer.begin(Some(&ClearOptions {
+        color: Some(state.clear_color),
+        depth: Some(1.0),
+        stencil: None,
+    }));
+
+    renderer.set_pipeline(&state.pipeline);
+    renderer.bind_buffers(&[&state.vbo, state.instances.buffer(), &state.ubo]);
+    renderer.bind_texture_slot(0, 0, state.assets.texture(&state.texture1));
+    renderer.bind_texture_slot(1, 1, state.assets.texture(&state.texture2));
+    renderer.draw_instanced(0, 36, state.instances.len() as i32);
+    renderer.end();
+
+    // render to the screen
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("1_7_3", "Getting started", "Camera mouse zoom").with_capture_cursor()
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+    }
 }
@@ -0,0 +1,29 @@
+pub mod _1_1_hello_window;
+pub mod _1_2_hello_window_clear;
+pub mod _2_1_hello_triangle;
+pub mod _2_2_hello_triangle_indexed;
+pub mod _2_3_hello_triangle_exercise1;
+pub mod _2_4_hello_triangle_exercise2;
+pub mod _2_5_hello_triangle_exercise3;
+pub mod _3_1_shaders_uniform;
+pub mod _3_2_shaders_interpolation;
+pub mod _3_4_shaders_exercise1;
+pub mod _3_5_shaders_exercise2;
+pub mod _3_6_shaders_exercise3;
+pub mod _4_1_textures;
+pub mod _4_2_textures_combined;
+pub mod _4_3_textures_exercise1;
+pub mod _4_4_textures_exercise2;
+pub mod _4_5_textures_exercise3;
+pub mod _4_6_textures_exercise4;
+pub mod _4_7_textures_mipmaps;
+pub mod _5_1_transformations;
+pub mod _5_2_transformations_exercise1;
+pub mod _5_3_transformations_exercise2;
+pub mod _6_1_coordinate_systems;
+pub mod _6_2_coordinate_systems_depth;
+pub mod _6_3_coordinate_systems_multiple;
+pub mod _6_4_coordinate_systems_exercise3;
+pub mod _7_1_camera_circle;
+pub mod _7_2_camera_keyboard_dt;
+pub mod _7_3_camera_mouse_zoom;
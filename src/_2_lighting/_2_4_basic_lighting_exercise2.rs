@@ -1,9 +1,13 @@
-use learn_open_gl_notan::utils::{Camera, CameraMovement};
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    delta_time, present, tweak_camera, tweak_clear_color, Camera, CameraMovement, StatsRenderer,
+};
+use notan::egui;
 use notan::math::{vec3, Mat4, Vec3};
 use notan::prelude::*;
 
-const IS_WASM: bool = cfg!(target_arch = "wasm32");
-
 // language=glsl
 const BASIC_LIGHTING_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
   r#"
@@ -131,8 +135,7 @@ struct Light {
 }
 
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     basic_lighting_pipeline: Pipeline,
     light_cube_pipeline: Pipeline,
     vbo: Buffer,
@@ -143,24 +146,11 @@ struct State {
     last_y: f32,
     first_mouse: bool,
     light: Light,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    // init notan using setup as initialization callback
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    clear_color: Color,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(app: &mut App, gfx: &mut Graphics) -> State {
-    // capture the cursor
-    app.window().set_capture_cursor(true);
-
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new()
         .attr(0, VertexFormat::Float32x3) // positions
@@ -252,7 +242,7 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
     let transform_ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
 
     let camera = Camera {
-        position: vec3(0.0, 0.0, 3.0),
+        position: config.camera_position(vec3(0.0, 0.0, 3.0)),
         ..Default::default()
     };
 
@@ -278,24 +268,13 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         last_y: 0.0,
         first_mouse: true,
         light,
+        clear_color: config.clear_color(Color::from_rgb(0.1, 0.1, 0.1)),
     }
 }
 
 fn update(app: &mut App, state: &mut State) {
-    if !IS_WASM {
-        // if esc is pressed close the app
-        if app.keyboard.was_pressed(KeyCode::Escape) {
-            app.exit();
-        }
-    }
-
-    // capture the cursor (wasm32 allow escape the cursor using ESC)
-    if app.mouse.was_pressed(MouseButton::Left) && !app.window().capture_cursor() {
-        app.window().set_capture_cursor(true);
-    }
-
     // Process all inputs to move the camera
-    let delta = app.timer.delta_f32();
+    let delta = delta_time();
     if app.keyboard.is_down(KeyCode::W) {
         state
             .camera
@@ -336,7 +315,7 @@ fn update(app: &mut App, state: &mut State) {
         .process_mouse_scroll(app.mouse.wheel_delta.y * delta);
 }
 
-fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
+fn draw(gfx: &mut Graphics, state: &mut State) {
     // view/projection transformations
     let size = gfx.size();
     let (width, height) = (size.0 as f32, size.1 as f32);
@@ -350,7 +329,8 @@ fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
     let model = Mat4::IDENTITY;
 
     // lighting transform
-    gfx.set_buffer_data(
+    set_buffer_data!(
+        gfx,
         &state.transform_ubo,
         &TransformWithLight {
             light_pos,
@@ -360,10 +340,10 @@ fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
         },
     );
 
-    let mut renderer = gfx.create_renderer();
+    let mut renderer = StatsRenderer::new(gfx);
 
     let clear = ClearOptions {
-        color: Some(Color::from_rgb(0.1, 0.1, 0.1)),
+        color: Some(state.clear_color),
         depth: Some(1.0),
         stencil: None,
     };
@@ -376,16 +356,17 @@ fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
 
     renderer.end();
 
-    gfx.render(&renderer);
+    present(gfx, &renderer);
 
     // --
 
     // lighting
-    let mut renderer = gfx.create_renderer();
+    let mut renderer = StatsRenderer::new(gfx);
     let model = Mat4::from_translation(light_pos);
     let model = model * Mat4::from_scale(Vec3::splat(0.2));
 
-    gfx.set_buffer_data(
+    set_buffer_data!(
+        gfx,
         &state.transform_ubo,
         &Transform {
             model,
@@ -400,5 +381,28 @@ fn draw(app: &mut App, gfx: &mut Graphics, state: &mut State) {
     renderer.draw(0, 36);
     renderer.end();
 
-    gfx.render(&renderer);
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("2_2_4", "Lighting", "Basic lighting exercise 2").with_capture_cursor()
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+        tweak_camera(ui, &mut self.camera);
+    }
 }
@@ -1,9 +1,14 @@
-use learn_open_gl_notan::utils::{Camera, CameraMovement};
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    delta_time, present, tweak_camera, tweak_clear_color, AssetManager, Camera, CameraMovement,
+    StatsRenderer, TextureHandle,
+};
+use notan::egui;
 use notan::math::{vec3, Mat4, Vec3};
 use notan::prelude::*;
 
-const IS_WASM: bool = cfg!(target_arch = "wasm32");
-
 // language=glsl
 const MATERIAL_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
   r#"
@@ -159,36 +164,24 @@ struct MaterialData {
 const LIGHT_POS: Vec3 = vec3(1.2, 1.0, 2.0);
 
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     material_pipeline: Pipeline,
     light_cube_pipeline: Pipeline,
     vbo: Buffer,
     transform_ubo: Buffer,
     material_ubo: Buffer,
-    diffuse_texture: Texture,
-    specular_texture: Texture,
+    diffuse_texture: TextureHandle,
+    specular_texture: TextureHandle,
+    assets: AssetManager,
     camera: Camera,
     last_x: f32,
     last_y: f32,
     first_mouse: bool,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    clear_color: Color,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(app: &mut App, gfx: &mut Graphics) -> State {
-    // capture the cursor
-    app.window().set_capture_cursor(true);
-
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new()
         .attr(0, VertexFormat::Float32x3) // positions
@@ -280,7 +273,7 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
     let transform_ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
 
     let camera = Camera {
-        position: vec3(0.0, 0.0, 3.0),
+        position: config.camera_position(vec3(0.0, 0.0, 3.0)),
         ..Default::default()
     };
 
@@ -289,19 +282,12 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         .build()
         .unwrap();
 
-    let diffuse_texture = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/container2.png"))
-        .build()
-        .unwrap();
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
 
-    let specular_texture = gfx
-        .create_texture()
-        .from_image(include_bytes!(
-            "../../resources/textures/container2_specular.png"
-        ))
-        .build()
-        .unwrap();
+    let diffuse_texture = assets.load_texture("textures/container2.png");
+
+    let specular_texture = assets.load_texture("textures/container2_specular.png");
 
     State {
         material_pipeline,
@@ -312,27 +298,17 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         camera,
         diffuse_texture,
         specular_texture,
+        assets,
         last_x: 0.0,
         last_y: 0.0,
         first_mouse: true,
+        clear_color: config.clear_color(Color::from_rgb(0.1, 0.1, 0.1)),
     }
 }
 
 fn update(app: &mut App, state: &mut State) {
-    if !IS_WASM {
-        // if esc is pressed close the app
-        if app.keyboard.was_pressed(KeyCode::Escape) {
-            app.exit();
-        }
-    }
-
-    // capture the cursor (wasm32 allow escape the cursor using ESC)
-    if app.mouse.was_pressed(MouseButton::Left) && !app.window().capture_cursor() {
-        app.window().set_capture_cursor(true);
-    }
-
     // Process all inputs to move the camera
-    let delta = app.timer.delta_f32();
+    let delta = delta_time();
     if app.keyboard.is_down(KeyCode::W) {
         state
             .camera
@@ -374,6 +350,9 @@ fn update(app: &mut App, state: &mut State) {
 }
 
 fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
     // view/projection transformations
     let size = gfx.size();
     let (width, height) = (size.0 as f32, size.1 as f32);
@@ -381,12 +360,15 @@ fn draw(gfx: &mut Graphics, state: &mut State) {
     let projection =
         Mat4::perspective_rh_gl(state.camera.zoom.to_radians(), aspect_ratio, 0.1, 100.0);
     let view = state.camera.get_view_matrix();
+    // the same pose than the view, between the steps with --fixed-fps
+    let camera = state.camera.interpolated_pose();
 
     // world transformation
     let model = Mat4::IDENTITY;
 
     // lighting transform
-    gfx.set_buffer_data(
+    set_buffer_data!(
+        gfx,
         &state.transform_ubo,
         &Transform {
             model,
@@ -404,19 +386,20 @@ fn draw(gfx: &mut Graphics, state: &mut State) {
 
     let material = Material::default();
 
-    gfx.set_buffer_data(
+    set_buffer_data!(
+        gfx,
         &state.material_ubo,
         &MaterialData {
-            view_pos: state.camera.position,
+            view_pos: camera.position,
             material,
             light,
         },
     );
 
-    let mut renderer = gfx.create_renderer();
+    let mut renderer = StatsRenderer::new(gfx);
 
     let clear = ClearOptions {
-        color: Some(Color::from_rgb(0.1, 0.1, 0.1)),
+        color: Some(state.clear_color),
         depth: Some(1.0),
         stencil: None,
     };
@@ -425,22 +408,23 @@ fn draw(gfx: &mut Graphics, state: &mut State) {
 
     renderer.set_pipeline(&state.material_pipeline);
     renderer.bind_buffers(&[&state.vbo, &state.transform_ubo, &state.material_ubo]);
-    renderer.bind_texture_slot(0, 0, &state.diffuse_texture);
-    renderer.bind_texture_slot(1, 1, &state.specular_texture);
+    renderer.bind_texture_slot(0, 0, state.assets.texture(&state.diffuse_texture));
+    renderer.bind_texture_slot(1, 1, state.assets.texture(&state.specular_texture));
     renderer.draw(0, 36);
 
     renderer.end();
 
-    gfx.render(&renderer);
+    present(gfx, &renderer);
 
     // --
 
-    let mut renderer = gfx.create_renderer();
+    let mut renderer = StatsRenderer::new(gfx);
     // lighting
     let model = Mat4::from_translation(LIGHT_POS);
     let model = model * Mat4::from_scale(Vec3::splat(0.2));
 
-    gfx.set_buffer_data(
+    set_buffer_data!(
+        gfx,
         &state.transform_ubo,
         &Transform {
             model,
@@ -455,5 +439,28 @@ fn draw(gfx: &mut Graphics, state: &mut State) {
     renderer.draw(0, 36);
     renderer.end();
 
-    gfx.render(&renderer);
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("2_4_2", "Lighting", "Lighting maps specular").with_capture_cursor()
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+        tweak_camera(ui, &mut self.camera);
+    }
 }
@@ -1,9 +1,14 @@
-use learn_open_gl_notan::utils::{Camera, CameraMovement};
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    delta_time, present, tweak_camera, tweak_clear_color, tweak_group, tweak_rgb, tweak_slider,
+    tweak_vec3, AssetManager, Camera, CameraMovement, ModelInstances, StatsRenderer, TextureHandle,
+};
+use notan::egui;
 use notan::math::{vec3, Mat4, Vec3};
 use notan::prelude::*;
 
-const IS_WASM: bool = cfg!(target_arch = "wasm32");
-
 // language=glsl
 const MATERIAL_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
   r#"
@@ -12,18 +17,24 @@ const MATERIAL_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
     layout (location = 1) in vec3 aNormal;
     layout (location = 2) in vec2 aTextCoords;
 
+    // model matrix of each cube, one column per attribute
+    layout (location = 3) in vec4 aModel0;
+    layout (location = 4) in vec4 aModel1;
+    layout (location = 5) in vec4 aModel2;
+    layout (location = 6) in vec4 aModel3;
+
     layout (location = 0) out vec3 FragPos;
     layout (location = 1) out vec3 Normal;
     layout (location = 2) out vec2 TexCoords;
 
     layout(set = 0, binding = 0) uniform Transform {
-        mat4 model;
         mat4 view;
         mat4 projection;
     };
 
     void main()
     {
+        mat4 model = mat4(aModel0, aModel1, aModel2, aModel3);
         FragPos = vec3(model * vec4(aPos, 1.0));
         Normal = mat3(transpose(inverse(model))) * aNormal;
         TexCoords = aTextCoords;
@@ -92,7 +103,6 @@ const MATERIAL_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
 #[derive(Copy, Clone, Default)]
 #[uniform]
 struct Transform {
-    model: Mat4,
     view: Mat4,
     projection: Mat4,
 }
@@ -134,35 +144,26 @@ const CUBE_POSITIONS: [Vec3; 10] = [
 ];
 
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     material_pipeline: Pipeline,
     vbo: Buffer,
     transform_ubo: Buffer,
+    cube_instances: ModelInstances,
     material_ubo: Buffer,
-    diffuse_texture: Texture,
-    specular_texture: Texture,
+    diffuse_texture: TextureHandle,
+    specular_texture: TextureHandle,
+    assets: AssetManager,
     camera: Camera,
     last_x: f32,
     last_y: f32,
     first_mouse: bool,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    light: Light,
+    material: Material,
+    clear_color: Color,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(app: &mut App, gfx: &mut Graphics) -> State {
-    // capture the cursor
-    app.window().set_capture_cursor(true);
-
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new()
         .attr(0, VertexFormat::Float32x3) // positions
@@ -180,6 +181,7 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         .create_pipeline()
         .from(&MATERIAL_VERTEX_SHADER, &MATERIAL_FRAGMENT_SHADER)
         .with_vertex_info(&vertex_info)
+        .with_vertex_info(&ModelInstances::vertex_info(3))
         .with_depth_stencil(depth_test)
         .with_texture_location(0, "diffuse_texture")
         .with_texture_location(1, "specular_texture")
@@ -244,8 +246,22 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
     // create the uniform buffer object
     let transform_ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
 
+    // the cubes don't move, their model matrices are uploaded once
+    let models = CUBE_POSITIONS
+        .iter()
+        .enumerate()
+        .map(|(i, &pos)| {
+            let angle = 20.0 * i as f32;
+            let translation = Mat4::from_translation(pos);
+            let rotation =
+                Mat4::from_axis_angle(vec3(1.0, 0.3, 0.5).normalize(), angle.to_radians());
+            translation * rotation
+        })
+        .collect::<Vec<_>>();
+    let cube_instances = ModelInstances::new(gfx, 3, &models).unwrap();
+
     let camera = Camera {
-        position: vec3(0.0, 0.0, 3.0),
+        position: config.camera_position(vec3(0.0, 0.0, 3.0)),
         ..Default::default()
     };
 
@@ -254,49 +270,40 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         .build()
         .unwrap();
 
-    let diffuse_texture = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/container2.png"))
-        .build()
-        .unwrap();
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
 
-    let specular_texture = gfx
-        .create_texture()
-        .from_image(include_bytes!(
-            "../../resources/textures/container2_specular.png"
-        ))
-        .build()
-        .unwrap();
+    let diffuse_texture = assets.load_texture("textures/container2.png");
+
+    let specular_texture = assets.load_texture("textures/container2_specular.png");
 
     State {
         material_pipeline,
         vbo,
         transform_ubo,
+        cube_instances,
         material_ubo,
         camera,
         diffuse_texture,
         specular_texture,
+        assets,
         last_x: 0.0,
         last_y: 0.0,
         first_mouse: true,
+        light: Light {
+            direction: vec3(-0.2, -1.0, -0.3),
+            ambient: Vec3::splat(0.2),
+            diffuse: Vec3::splat(0.5),
+            specular: Vec3::splat(1.0),
+        },
+        material: Material { shininess: 32.0 },
+        clear_color: config.clear_color(Color::from_rgb(0.1, 0.1, 0.1)),
     }
 }
 
 fn update(app: &mut App, state: &mut State) {
-    if !IS_WASM {
-        // if esc is pressed close the app
-        if app.keyboard.was_pressed(KeyCode::Escape) {
-            app.exit();
-        }
-    }
-
-    // capture the cursor (wasm32 allow escape the cursor using ESC)
-    if app.mouse.was_pressed(MouseButton::Left) && !app.window().capture_cursor() {
-        app.window().set_capture_cursor(true);
-    }
-
     // Process all inputs to move the camera
-    let delta = app.timer.delta_f32();
+    let delta = delta_time();
     if app.keyboard.is_down(KeyCode::W) {
         state
             .camera
@@ -338,6 +345,9 @@ fn update(app: &mut App, state: &mut State) {
 }
 
 fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
     // view/projection transformations
     let size = gfx.size();
     let (width, height) = (size.0 as f32, size.1 as f32);
@@ -345,63 +355,80 @@ fn draw(gfx: &mut Graphics, state: &mut State) {
     let projection =
         Mat4::perspective_rh_gl(state.camera.zoom.to_radians(), aspect_ratio, 0.1, 100.0);
     let view = state.camera.get_view_matrix();
+    // the same pose than the view, between the steps with --fixed-fps
+    let camera = state.camera.interpolated_pose();
 
-    let light = Light {
-        direction: vec3(-0.2, -1.0, -0.3),
-        ambient: Vec3::splat(0.2),
-        diffuse: Vec3::splat(0.5),
-        specular: Vec3::splat(1.0),
-    };
+    let light = state.light;
 
-    let material = Material { shininess: 32.0 };
+    let material = state.material;
 
-    gfx.set_buffer_data(
+    set_buffer_data!(
+        gfx,
         &state.material_ubo,
         &MaterialData {
-            view_pos: state.camera.position,
+            view_pos: camera.position,
             material,
             light,
         },
     );
 
-    CUBE_POSITIONS.iter().enumerate().for_each(|(i, &pos)| {
-        let angle = 20.0 * i as f32;
-        let translation = Mat4::from_translation(pos);
-        let rotation = Mat4::from_axis_angle(vec3(1.0, 0.3, 0.5).normalize(), angle.to_radians());
-        let model = translation * rotation;
-
-        // lighting transform
-        gfx.set_buffer_data(
-            &state.transform_ubo,
-            &Transform {
-                model,
-                view,
-                projection,
-            },
-        );
-
-        let mut renderer = gfx.create_renderer();
-
-        let clear = if i == 0 {
-            Some(ClearOptions {
-                color: Some(Color::from_rgb(0.1, 0.1, 0.1)),
-                depth: Some(1.0),
-                stencil: None,
-            })
-        } else {
-            None
-        };
-
-        renderer.begin(clear.as_ref());
-
-        renderer.set_pipeline(&state.material_pipeline);
-        renderer.bind_buffers(&[&state.vbo, &state.transform_ubo, &state.material_ubo]);
-        renderer.bind_texture_slot(0, 0, &state.diffuse_texture);
-        renderer.bind_texture_slot(1, 1, &state.specular_texture);
-        renderer.draw(0, 36);
-
-        renderer.end();
-
-        gfx.render(&renderer);
-    });
+    // lighting transform, the model matrices are in the instance buffer
+    set_buffer_data!(gfx, &state.transform_ubo, &Transform { view, projection });
+
+    // all the cubes are drawn in the same pass
+    let mut renderer = StatsRenderer::new(gfx);
+    renderer.begin(Some(&ClearOptions {
+        color: Some(state.clear_color),
+        depth: Some(1.0),
+        stencil: None,
+    }));
+
+    renderer.set_pipeline(&state.material_pipeline);
+    renderer.bind_buffers(&[
+        &state.vbo,
+        state.cube_instances.buffer(),
+        &state.transform_ubo,
+        &state.material_ubo,
+    ]);
+    renderer.bind_texture_slot(0, 0, state.assets.texture(&state.diffuse_texture));
+    renderer.bind_texture_slot(1, 1, state.assets.texture(&state.specular_texture));
+    renderer.draw_instanced(0, 36, state.cube_instances.len() as i32);
+
+    renderer.end();
+
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("2_5_1", "Lighting", "Light casters directional").with_capture_cursor()
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+        tweak_camera(ui, &mut self.camera);
+
+        tweak_group(ui, "Directional light", |ui| {
+            tweak_vec3(ui, "direction", &mut self.light.direction);
+            tweak_rgb(ui, "ambient", &mut self.light.ambient);
+            tweak_rgb(ui, "diffuse", &mut self.light.diffuse);
+            tweak_rgb(ui, "specular", &mut self.light.specular);
+        });
+
+        tweak_group(ui, "Material", |ui| {
+            tweak_slider(ui, "shininess", &mut self.material.shininess, 1.0..=256.0);
+        });
+    }
 }
@@ -1,9 +1,15 @@
-use learn_open_gl_notan::utils::{Camera, CameraMovement};
-use notan::math::{vec3, Mat4, Vec3};
+use crate::config::{Config, ConfigOption, OptionKind};
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    self, delta_time, present, tweak_attenuation, tweak_camera, tweak_clear_color, tweak_cos_angle,
+    tweak_group, tweak_rgb, tweak_slider, tweak_vec3, AssetManager, Camera, CameraMovement,
+    ModelInstances, Scene, SceneCamera, StatsRenderer, TextureHandle, MAX_POINT_LIGHTS,
+};
+use notan::egui;
+use notan::math::{Mat4, Vec3};
 use notan::prelude::*;
 
-const IS_WASM: bool = cfg!(target_arch = "wasm32");
-
 // language=glsl
 const MATERIAL_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
   r#"
@@ -12,18 +18,24 @@ const MATERIAL_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
     layout (location = 1) in vec3 aNormal;
     layout (location = 2) in vec2 aTextCoords;
 
+    // model matrix of each cube, one column per attribute
+    layout (location = 3) in vec4 aModel0;
+    layout (location = 4) in vec4 aModel1;
+    layout (location = 5) in vec4 aModel2;
+    layout (location = 6) in vec4 aModel3;
+
     layout (location = 0) out vec3 FragPos;
     layout (location = 1) out vec3 Normal;
     layout (location = 2) out vec2 TexCoords;
 
     layout(set = 0, binding = 0) uniform Transform {
-        mat4 model;
         mat4 view;
         mat4 projection;
     };
 
     void main()
     {
+        mat4 model = mat4(aModel0, aModel1, aModel2, aModel3);
         FragPos = vec3(model * vec4(aPos, 1.0));
         Normal = mat3(transpose(inverse(model))) * aNormal;
         TexCoords = aTextCoords;
@@ -197,14 +209,20 @@ const LIGHT_CUBE_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
     #version 450
     layout (location = 0) in vec3 aPos;
 
+    // model matrix of each lamp, one column per attribute
+    layout (location = 3) in vec4 aModel0;
+    layout (location = 4) in vec4 aModel1;
+    layout (location = 5) in vec4 aModel2;
+    layout (location = 6) in vec4 aModel3;
+
     layout(set = 0, binding = 0) uniform Transform {
-        mat4 model;
         mat4 view;
         mat4 projection;
     };
 
     void main()
     {
+        mat4 model = mat4(aModel0, aModel1, aModel2, aModel3);
         gl_Position = projection * view * model * vec4(aPos, 1.0);
     }
   "#
@@ -226,12 +244,11 @@ const LIGHT_CUBE_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
 #[derive(Copy, Clone, Default)]
 #[uniform]
 struct Transform {
-    model: Mat4,
     view: Mat4,
     projection: Mat4,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default)]
 #[uniform]
 struct Material {
     shininess: f32,
@@ -246,6 +263,19 @@ struct DirLight {
     specular: Vec3,
 }
 
+// turned off, used when the scene has no directional light. The direction is normalized
+// by the shader so it can't be zero.
+impl Default for DirLight {
+    fn default() -> Self {
+        Self {
+            direction: Vec3::NEG_Y,
+            ambient: Vec3::ZERO,
+            diffuse: Vec3::ZERO,
+            specular: Vec3::ZERO,
+        }
+    }
+}
+
 #[derive(Copy, Clone)]
 #[uniform]
 struct PointLight {
@@ -258,16 +288,17 @@ struct PointLight {
     specular: Vec3,
 }
 
+// turned off, used when the scene has less lights than the shader
 impl Default for PointLight {
     fn default() -> Self {
         Self {
-            position: Default::default(),
+            position: Vec3::ZERO,
             constant: 1.0,
-            linear: 0.09,
-            quadratic: 0.032,
-            ambient: Vec3::splat(0.05),
-            diffuse: Vec3::splat(0.8),
-            specular: Vec3::splat(1.0),
+            linear: 0.0,
+            quadratic: 0.0,
+            ambient: Vec3::ZERO,
+            diffuse: Vec3::ZERO,
+            specular: Vec3::ZERO,
         }
     }
 }
@@ -287,67 +318,110 @@ struct SpotLight {
     specular: Vec3,
 }
 
+// turned off
+impl Default for SpotLight {
+    fn default() -> Self {
+        Self {
+            position: Vec3::ZERO,
+            direction: Vec3::NEG_Z,
+            cut_off: 1.0,
+            outer_cut_off: 0.0,
+            constant: 1.0,
+            linear: 0.0,
+            quadratic: 0.0,
+            ambient: Vec3::ZERO,
+            diffuse: Vec3::ZERO,
+            specular: Vec3::ZERO,
+        }
+    }
+}
+
+// the lights of the scene have the same fields
+impl From<utils::DirLight> for DirLight {
+    fn from(light: utils::DirLight) -> Self {
+        Self {
+            direction: light.direction,
+            ambient: light.ambient,
+            diffuse: light.diffuse,
+            specular: light.specular,
+        }
+    }
+}
+
+impl From<utils::PointLight> for PointLight {
+    fn from(light: utils::PointLight) -> Self {
+        Self {
+            position: light.position,
+            constant: light.constant,
+            linear: light.linear,
+            quadratic: light.quadratic,
+            ambient: light.ambient,
+            diffuse: light.diffuse,
+            specular: light.specular,
+        }
+    }
+}
+
+impl From<utils::SpotLight> for SpotLight {
+    fn from(light: utils::SpotLight) -> Self {
+        Self {
+            position: light.position,
+            direction: light.direction,
+            cut_off: light.cut_off,
+            outer_cut_off: light.outer_cut_off,
+            constant: light.constant,
+            linear: light.linear,
+            quadratic: light.quadratic,
+            ambient: light.ambient,
+            diffuse: light.diffuse,
+            specular: light.specular,
+        }
+    }
+}
+
+// `Scene::parse` rejects the scenes with more point lights
+const NR_POINT_LIGHTS: usize = MAX_POINT_LIGHTS;
+
 #[derive(Copy, Clone)]
 #[uniform]
 struct MaterialData {
     view_pos: Vec3,
     dir_light: DirLight,
-    point_lights: [PointLight; 4],
+    point_lights: [PointLight; NR_POINT_LIGHTS],
     spot_light: SpotLight,
     material: Material,
 }
 
-const CUBE_POSITIONS: [Vec3; 10] = [
-    vec3(0.0, 0.0, 0.0),
-    vec3(2.0, 5.0, -15.0),
-    vec3(-1.5, -2.2, -2.5),
-    vec3(-3.8, -2.0, -12.3),
-    vec3(2.4, -0.4, -3.5),
-    vec3(-1.7, 3.0, -7.5),
-    vec3(1.3, -2.0, -2.5),
-    vec3(1.5, 2.0, -2.5),
-    vec3(1.5, 0.2, -1.5),
-    vec3(-1.3, 1.0, -1.5),
-];
-
-const POINT_LIGHT_POSITIONS: [Vec3; 4] = [
-    vec3(0.7, 0.2, 2.0),
-    vec3(2.3, -3.3, -4.0),
-    vec3(-4.0, 2.0, -12.0),
-    vec3(0.0, 0.0, -3.0),
-];
+// Objects, materials and lights of the example, `--scene` loads another one
+const DEFAULT_SCENE: &str = include_str!("../../resources/scenes/multiple_lights.json");
+
+// Without `--scene` the tweak panel saves next to the default scene, `--scene` loads it back
+const SAVED_SCENE: &str = "./resources/scenes/2_6_1.json";
 
 // Create a struct to store the app's state
-#[derive(AppState)]
-struct State {
+pub struct State {
     material_pipeline: Pipeline,
     light_cube_pipeline: Pipeline,
     vbo: Buffer,
     transform_ubo: Buffer,
-    material_ubo: Buffer,
-    diffuse_texture: Texture,
-    specular_texture: Texture,
+    // one per material of the scene, like the instances of the objects that use it
+    material_ubos: Vec<Buffer>,
+    object_instances: Vec<ModelInstances>,
+    lamp_instances: ModelInstances,
+    assets: AssetManager,
     camera: Camera,
     last_x: f32,
     last_y: f32,
     first_mouse: bool,
-}
-
-#[notan_main]
-fn main() -> Result<(), String> {
-    notan::init_with(setup)
-        // pass the update function
-        .update(update)
-        // pass the draw function
-        .draw(draw)
-        .build()
+    scene: Scene,
+    // diffuse and specular maps of each material of the scene
+    material_textures: Vec<(TextureHandle, TextureHandle)>,
+    // where the tweak panel saves the scene
+    scene_path: String,
 }
 
 // initialize the state and return it to be used by notan
-fn setup(app: &mut App, gfx: &mut Graphics) -> State {
-    // capture the cursor
-    app.window().set_capture_cursor(true);
-
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
     // Declare the vertex attributes
     let vertex_info = VertexInfo::new()
         .attr(0, VertexFormat::Float32x3) // positions
@@ -365,6 +439,7 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         .create_pipeline()
         .from(&MATERIAL_VERTEX_SHADER, &MATERIAL_FRAGMENT_SHADER)
         .with_vertex_info(&vertex_info)
+        .with_vertex_info(&ModelInstances::vertex_info(3))
         .with_depth_stencil(depth_test)
         .with_texture_location(0, "diffuse_texture")
         .with_texture_location(1, "specular_texture")
@@ -376,6 +451,7 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
         .create_pipeline()
         .from(&LIGHT_CUBE_VERTEX_SHADER, &LIGHT_CUBE_FRAGMENT_SHADER)
         .with_vertex_info(&vertex_info)
+        .with_vertex_info(&ModelInstances::vertex_info(3))
         .with_depth_stencil(depth_test)
         .build()
         .unwrap();
@@ -438,60 +514,95 @@ fn setup(app: &mut App, gfx: &mut Graphics) -> State {
     // create the uniform buffer object
     let transform_ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
 
-    let camera = Camera {
-        position: vec3(0.0, 0.0, 3.0),
-        ..Default::default()
+    let mut scene = match config.text("scene") {
+        Some(path) => Scene::load(path).unwrap_or_else(|err| {
+            notan::log::error!("Cannot load the scene: {}", err);
+            Scene::parse(DEFAULT_SCENE).unwrap()
+        }),
+        None => Scene::parse(DEFAULT_SCENE).unwrap(),
     };
-
-    let material_ubo = gfx
-        .create_uniform_buffer(1, "MaterialData")
-        .build()
-        .unwrap();
-
-    let diffuse_texture = gfx
-        .create_texture()
-        .from_image(include_bytes!("../../resources/textures/container2.png"))
-        .build()
-        .unwrap();
-
-    let specular_texture = gfx
-        .create_texture()
-        .from_image(include_bytes!(
-            "../../resources/textures/container2_specular.png"
-        ))
-        .build()
-        .unwrap();
+    scene.clear_color = config.clear_color(scene.clear_color);
+
+    let mut camera = scene.camera.to_camera();
+    camera.position = config.camera_position(camera.position);
+
+    // the objects of each material are drawn with one instanced call
+    let material_ubos = scene
+        .materials
+        .iter()
+        .map(|_| {
+            gfx.create_uniform_buffer(1, "MaterialData")
+                .build()
+                .unwrap()
+        })
+        .collect();
+
+    let object_instances = models_by_material(&scene)
+        .iter()
+        .map(|models| ModelInstances::new(gfx, 3, models).unwrap())
+        .collect();
+
+    let lamp_instances = ModelInstances::new(gfx, 3, &lamp_models(&scene)).unwrap();
+
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+
+    let material_textures = scene
+        .materials
+        .iter()
+        .map(|material| {
+            (
+                assets.load_texture(&material.diffuse),
+                assets.load_texture(&material.specular),
+            )
+        })
+        .collect();
+
+    let scene_path = config.text("scene").unwrap_or(SAVED_SCENE).to_string();
 
     State {
         material_pipeline,
         light_cube_pipeline,
         vbo,
         transform_ubo,
-        material_ubo,
+        material_ubos,
+        object_instances,
+        lamp_instances,
         camera,
-        diffuse_texture,
-        specular_texture,
+        assets,
         last_x: 0.0,
         last_y: 0.0,
         first_mouse: true,
+        scene,
+        material_textures,
+        scene_path,
     }
 }
 
-fn update(app: &mut App, state: &mut State) {
-    if !IS_WASM {
-        // if esc is pressed close the app
-        if app.keyboard.was_pressed(KeyCode::Escape) {
-            app.exit();
-        }
-    }
+// model matrices of the objects grouped by material
+fn models_by_material(scene: &Scene) -> Vec<Vec<Mat4>> {
+    let mut models = vec![vec![]; scene.materials.len()];
+    scene
+        .objects
+        .iter()
+        .for_each(|object| models[object.material].push(object.model()));
+    models
+}
 
-    // capture the cursor (wasm32 allow escape the cursor using ESC)
-    if app.mouse.was_pressed(MouseButton::Left) && !app.window().capture_cursor() {
-        app.window().set_capture_cursor(true);
-    }
+// a small cube on each point light used by the shader
+fn lamp_models(scene: &Scene) -> Vec<Mat4> {
+    scene
+        .lights
+        .point_lights
+        .iter()
+        .take(NR_POINT_LIGHTS)
+        .map(|light| Mat4::from_translation(light.position) * Mat4::from_scale(Vec3::splat(0.2)))
+        .collect()
+}
 
+fn update(app: &mut App, state: &mut State) {
     // Process all inputs to move the camera
-    let delta = app.timer.delta_f32();
+    let delta = delta_time();
     if app.keyboard.is_down(KeyCode::W) {
         state
             .camera
@@ -533,6 +644,9 @@ fn update(app: &mut App, state: &mut State) {
 }
 
 fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
     // view/projection transformations
     let size = gfx.size();
     let (width, height) = (size.0 as f32, size.1 as f32);
@@ -540,124 +654,184 @@ fn draw(gfx: &mut Graphics, state: &mut State) {
     let projection =
         Mat4::perspective_rh_gl(state.camera.zoom.to_radians(), aspect_ratio, 0.1, 100.0);
     let view = state.camera.get_view_matrix();
+    // the same pose than the view, between the steps with --fixed-fps
+    let camera = state.camera.interpolated_pose();
+
+    let lights = &state.scene.lights;
+    let mut point_lights = [PointLight::default(); NR_POINT_LIGHTS];
+    point_lights
+        .iter_mut()
+        .zip(lights.point_lights.iter())
+        .for_each(|(data, light)| *data = (*light).into());
+
+    let mut spot_light = lights.spot_light.map(SpotLight::from).unwrap_or_default();
+    if state.scene.spot_light_follows_camera {
+        spot_light.position = camera.position;
+        spot_light.direction = camera.front;
+    }
 
-    let material = Material { shininess: 32.0 };
+    let dir_light = lights.dir_light.map(DirLight::from).unwrap_or_default();
 
-    // directional light
-    let dir_light = DirLight {
-        direction: vec3(-0.2, -1.0, -0.3),
-        ambient: Vec3::splat(0.05),
-        diffuse: Vec3::splat(0.4),
-        specular: Vec3::splat(0.5),
-    };
+    state
+        .material_ubos
+        .iter()
+        .zip(state.scene.materials.iter())
+        .for_each(|(ubo, material)| {
+            set_buffer_data!(
+                gfx,
+                ubo,
+                &MaterialData {
+                    view_pos: camera.position,
+                    dir_light,
+                    point_lights,
+                    spot_light,
+                    material: Material {
+                        shininess: material.shininess,
+                    },
+                },
+            );
+        });
+
+    // the objects don't move, their instances are uploaded on setup. The lamps are uploaded
+    // again when the tweak panel moves a point light
+    state.lamp_instances.update(gfx, &lamp_models(&state.scene));
+
+    // lighting transform, the model matrices are in the instance buffers
+    set_buffer_data!(gfx, &state.transform_ubo, &Transform { view, projection });
+
+    // all the objects and lamps are drawn in the same pass
+    let mut renderer = StatsRenderer::new(gfx);
+    renderer.begin(Some(&ClearOptions {
+        color: Some(state.scene.clear_color),
+        depth: Some(1.0),
+        stencil: None,
+    }));
+
+    renderer.set_pipeline(&state.material_pipeline);
+    state
+        .object_instances
+        .iter()
+        .enumerate()
+        .filter(|(_, instances)| !instances.is_empty())
+        .for_each(|(i, instances)| {
+            let (diffuse, specular) = &state.material_textures[i];
+            renderer.bind_buffers(&[
+                &state.vbo,
+                instances.buffer(),
+                &state.transform_ubo,
+                &state.material_ubos[i],
+            ]);
+            renderer.bind_texture_slot(0, 0, state.assets.texture(diffuse));
+            renderer.bind_texture_slot(1, 1, state.assets.texture(specular));
+            renderer.draw_instanced(0, 36, instances.len() as i32);
+        });
+
+    // light points
+    if !state.lamp_instances.is_empty() {
+        renderer.set_pipeline(&state.light_cube_pipeline);
+        renderer.bind_buffers(&[
+            &state.vbo,
+            state.lamp_instances.buffer(),
+            &state.transform_ubo,
+        ]);
+        renderer.draw_instanced(0, 36, state.lamp_instances.len() as i32);
+    }
 
-    // point lights
-    let point_lights = [
-        PointLight {
-            position: POINT_LIGHT_POSITIONS[0],
-            ..Default::default()
-        },
-        PointLight {
-            position: POINT_LIGHT_POSITIONS[1],
-            ..Default::default()
-        },
-        PointLight {
-            position: POINT_LIGHT_POSITIONS[2],
-            ..Default::default()
-        },
-        PointLight {
-            position: POINT_LIGHT_POSITIONS[3],
-            ..Default::default()
-        },
-    ];
+    renderer.end();
 
-    // spot light
-    let spot_light = SpotLight {
-        position: state.camera.position,
-        direction: state.camera.front,
-        cut_off: 12.5_f32.to_radians().cos(),
-        outer_cut_off: 15.0_f32.to_radians().cos(),
-        constant: 1.0,
-        linear: 0.09,
-        quadratic: 0.032,
-        ambient: Vec3::splat(0.0),
-        diffuse: Vec3::splat(1.0),
-        specular: Vec3::splat(1.0),
-    };
+    present(gfx, &renderer);
+}
 
-    gfx.set_buffer_data(
-        &state.material_ubo,
-        &MaterialData {
-            view_pos: state.camera.position,
-            dir_light,
-            point_lights,
-            spot_light,
-            material,
-        },
-    );
-
-    CUBE_POSITIONS.iter().enumerate().for_each(|(i, &pos)| {
-        let angle = 20.0 * i as f32;
-        let translation = Mat4::from_translation(pos);
-        let rotation = Mat4::from_axis_angle(vec3(1.0, 0.3, 0.5).normalize(), angle.to_radians());
-        let model = translation * rotation;
-
-        // lighting transform
-        gfx.set_buffer_data(
-            &state.transform_ubo,
-            &Transform {
-                model,
-                view,
-                projection,
-            },
-        );
-
-        let mut renderer = gfx.create_renderer();
-
-        let clear = if i == 0 {
-            Some(ClearOptions {
-                color: Some(Color::from_rgb(0.1, 0.1, 0.1)),
-                depth: Some(1.0),
-                stencil: None,
-            })
-        } else {
-            None
-        };
-
-        renderer.begin(clear.as_ref());
-
-        renderer.set_pipeline(&state.material_pipeline);
-        renderer.bind_buffers(&[&state.vbo, &state.transform_ubo, &state.material_ubo]);
-        renderer.bind_texture_slot(0, 0, &state.diffuse_texture);
-        renderer.bind_texture_slot(1, 1, &state.specular_texture);
-        renderer.draw(0, 36);
-
-        renderer.end();
-
-        gfx.render(&renderer);
-    });
-
-    // light point
-    POINT_LIGHT_POSITIONS.iter().for_each(|pos| {
-        let mut renderer = gfx.create_renderer();
-        let model = Mat4::from_translation(*pos);
-        let model = model * Mat4::from_scale(Vec3::splat(0.2));
-
-        gfx.set_buffer_data(
-            &state.transform_ubo,
-            &Transform {
-                model,
-                view,
-                projection,
-            },
-        );
-
-        renderer.begin(None);
-        renderer.set_pipeline(&state.light_cube_pipeline);
-        renderer.bind_buffers(&[&state.vbo, &state.transform_ubo]);
-        renderer.draw(0, 36);
-        renderer.end();
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("2_6_1", "Lighting", "Multiple lights").with_capture_cursor()
+    }
+
+    fn options() -> Vec<ConfigOption> {
+        vec![ConfigOption::new(
+            "scene",
+            OptionKind::Text,
+            "Load the scene from this json file",
+        )]
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
 
-        gfx.render(&renderer);
-    });
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.scene.clear_color);
+        tweak_camera(ui, &mut self.camera);
+
+        let lights = &mut self.scene.lights;
+        if let Some(light) = &mut lights.dir_light {
+            tweak_group(ui, "Directional light", |ui| {
+                tweak_vec3(ui, "direction", &mut light.direction);
+                tweak_rgb(ui, "ambient", &mut light.ambient);
+                tweak_rgb(ui, "diffuse", &mut light.diffuse);
+                tweak_rgb(ui, "specular", &mut light.specular);
+            });
+        }
+
+        lights
+            .point_lights
+            .iter_mut()
+            .take(NR_POINT_LIGHTS)
+            .enumerate()
+            .for_each(|(i, light)| {
+                tweak_group(ui, &format!("Point light {}", i), |ui| {
+                    tweak_vec3(ui, "position", &mut light.position);
+                    tweak_rgb(ui, "ambient", &mut light.ambient);
+                    tweak_rgb(ui, "diffuse", &mut light.diffuse);
+                    tweak_rgb(ui, "specular", &mut light.specular);
+                    tweak_attenuation(
+                        ui,
+                        &mut light.constant,
+                        &mut light.linear,
+                        &mut light.quadratic,
+                    );
+                });
+            });
+
+        if let Some(light) = &mut lights.spot_light {
+            tweak_group(ui, "Spot light", |ui| {
+                tweak_cos_angle(ui, "cut off", &mut light.cut_off, 90.0);
+                tweak_cos_angle(ui, "outer cut off", &mut light.outer_cut_off, 90.0);
+                // the outer cone can't be narrower than the inner one, the scene files reject it
+                light.outer_cut_off = light.outer_cut_off.min(light.cut_off);
+                tweak_rgb(ui, "ambient", &mut light.ambient);
+                tweak_rgb(ui, "diffuse", &mut light.diffuse);
+                tweak_rgb(ui, "specular", &mut light.specular);
+                tweak_attenuation(
+                    ui,
+                    &mut light.constant,
+                    &mut light.linear,
+                    &mut light.quadratic,
+                );
+            });
+        }
+
+        self.scene.materials.iter_mut().for_each(|material| {
+            tweak_group(ui, &format!("Material {}", material.name), |ui| {
+                tweak_slider(ui, "shininess", &mut material.shininess, 1.0..=256.0);
+            });
+        });
+
+        ui.separator();
+        if ui.button(format!("Save to {}", self.scene_path)).clicked() {
+            self.scene.camera = SceneCamera::from_camera(&self.camera);
+            match self.scene.save(&self.scene_path) {
+                Ok(_) => notan::log::info!("Scene saved to '{}'", self.scene_path),
+                Err(err) => notan::log::error!("Cannot save the scene: {}", err),
+            }
+        }
+    }
 }
@@ -0,0 +1,17 @@
+pub mod _1_1_colors;
+pub mod _2_1_basic_lighting_diffuse;
+pub mod _2_2_basic_lighting_specular;
+pub mod _2_3_basic_lighting_exercise1;
+pub mod _2_4_basic_lighting_exercise2;
+pub mod _2_5_basic_lighting_exercise3;
+pub mod _3_1_materials;
+pub mod _3_2_materials_exercise1;
+pub mod _4_1_lighting_maps_diffuse;
+pub mod _4_2_lighting_maps_specular;
+pub mod _4_3_lighting_maps_exercise2;
+pub mod _4_4_lighting_maps_exercise4;
+pub mod _5_1_light_casters_directional;
+pub mod _5_2_light_casters_point;
+pub mod _5_3_light_casters_spot;
+pub mod _5_4_light_casters_spot_soft;
+pub mod _6_1_multiple_lights;
@@ -0,0 +1,351 @@
+use crate::config::{Config, ConfigOption, OptionKind};
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    delta_time, present, tweak_camera, tweak_clear_color, tweak_group, tweak_slider, AssetManager,
+    AsteroidRing, Camera, CameraMovement, ModelInstances, ObjMesh, StatsRenderer, TextureHandle,
+};
+use notan::egui;
+use notan::math::{vec3, Mat4, Vec3};
+use notan::prelude::*;
+
+// language=glsl
+const PLANET_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec3 aPos;
+    layout (location = 2) in vec2 aTexCoords;
+
+    layout (location = 0) out vec2 TexCoords;
+
+    layout(set = 0, binding = 0) uniform Transform {
+        mat4 model;
+        mat4 view;
+        mat4 projection;
+    };
+
+    void main()
+    {
+        TexCoords = aTexCoords;
+        gl_Position = projection * view * model * vec4(aPos, 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const ASTEROID_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec3 aPos;
+    layout (location = 2) in vec2 aTexCoords;
+
+    // model matrix of each rock, one column per attribute
+    layout (location = 3) in vec4 aModel0;
+    layout (location = 4) in vec4 aModel1;
+    layout (location = 5) in vec4 aModel2;
+    layout (location = 6) in vec4 aModel3;
+
+    layout (location = 0) out vec2 TexCoords;
+
+    layout(set = 0, binding = 0) uniform Transform {
+        mat4 model;
+        mat4 view;
+        mat4 projection;
+    };
+
+    void main()
+    {
+        mat4 instanceModel = mat4(aModel0, aModel1, aModel2, aModel3);
+        TexCoords = aTexCoords;
+        gl_Position = projection * view * instanceModel * vec4(aPos, 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 TexCoords;
+
+    layout (location = 0) out vec4 FragColor;
+
+    layout(binding = 0) uniform sampler2D texture_diffuse1;
+
+    void main()
+    {
+        FragColor = texture(texture_diffuse1, TexCoords);
+    }
+  "#
+};
+
+// Represent our transform data, the rocks ignore the model
+#[derive(Copy, Clone, Default)]
+#[uniform]
+struct Transform {
+    model: Mat4,
+    view: Mat4,
+    projection: Mat4,
+}
+
+const PLANET_OBJ: &str = include_str!("../../resources/objects/planet/planet.obj");
+const ROCK_OBJ: &str = include_str!("../../resources/objects/rock/rock.obj");
+
+// Create a struct to store the app's state
+pub struct State {
+    planet_pipeline: Pipeline,
+    asteroid_pipeline: Pipeline,
+    planet_vbo: Buffer,
+    planet_vertices: i32,
+    rock_vbo: Buffer,
+    rock_vertices: i32,
+    ubo: Buffer,
+    instances: ModelInstances,
+    ring: AsteroidRing,
+    // the tweak panel changed the ring, the instances are generated again
+    ring_changed: bool,
+    planet_texture: TextureHandle,
+    rock_texture: TextureHandle,
+    assets: AssetManager,
+    camera: Camera,
+    last_x: f32,
+    last_y: f32,
+    first_mouse: bool,
+    clear_color: Color,
+}
+
+// initialize the state and return it to be used by notan
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
+    // Declare the vertex attributes, the same layout than `ObjMesh`
+    let vertex_info = VertexInfo::new()
+        .attr(0, VertexFormat::Float32x3) // positions
+        .attr(1, VertexFormat::Float32x3) // normals
+        .attr(2, VertexFormat::Float32x2); // texture coords
+
+    // Enable depth test
+    let depth_test = DepthStencil {
+        write: true,
+        compare: CompareMode::Less,
+    };
+
+    // build the pipelines
+    let planet_pipeline = gfx
+        .create_pipeline()
+        .from(&PLANET_VERTEX_SHADER, &FRAGMENT_SHADER)
+        .with_vertex_info(&vertex_info)
+        .with_depth_stencil(depth_test)
+        .with_texture_location(0, "texture_diffuse1")
+        .build()
+        .unwrap();
+
+    // the rocks read their model matrix from the instance buffer
+    let asteroid_pipeline = gfx
+        .create_pipeline()
+        .from(&ASTEROID_VERTEX_SHADER, &FRAGMENT_SHADER)
+        .with_vertex_info(&vertex_info)
+        .with_vertex_info(&ModelInstances::vertex_info(3))
+        .with_depth_stencil(depth_test)
+        .with_texture_location(0, "texture_diffuse1")
+        .build()
+        .unwrap();
+
+    // load the models
+    let planet = ObjMesh::parse(PLANET_OBJ).unwrap();
+    let rock = ObjMesh::parse(ROCK_OBJ).unwrap();
+
+    // create the vertex buffer objects
+    let planet_vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&planet.vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    let rock_vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&rock.vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    // create the uniform buffer object
+    let ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
+
+    // generate the model matrices of the rocks and upload them once
+    let mut ring = AsteroidRing::default();
+    if let Some(amount) = config.int("amount") {
+        ring.amount = amount.max(0) as usize;
+    }
+    let instances = ModelInstances::new(gfx, 3, &ring.models()).unwrap();
+
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+    let planet_texture = assets.load_texture("objects/planet/mars.png");
+    let rock_texture = assets.load_texture("objects/rock/rock.png");
+
+    let camera = Camera {
+        position: config.camera_position(vec3(0.0, 0.0, 155.0)),
+        ..Default::default()
+    };
+
+    State {
+        planet_pipeline,
+        asteroid_pipeline,
+        planet_vbo,
+        planet_vertices: planet.vertex_count() as i32,
+        rock_vbo,
+        rock_vertices: rock.vertex_count() as i32,
+        ubo,
+        instances,
+        ring,
+        ring_changed: false,
+        planet_texture,
+        rock_texture,
+        assets,
+        camera,
+        last_x: 0.0,
+        last_y: 0.0,
+        first_mouse: true,
+        clear_color: config.clear_color(Color::from_rgb(0.1, 0.1, 0.1)),
+    }
+}
+
+fn update(app: &mut App, state: &mut State) {
+    // Process all inputs to move the camera
+    let delta = delta_time();
+    if app.keyboard.is_down(KeyCode::W) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Forward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::S) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Backward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::A) {
+        state.camera.process_keyboard(CameraMovement::Left, delta);
+    }
+    if app.keyboard.is_down(KeyCode::D) {
+        state.camera.process_keyboard(CameraMovement::Right, delta);
+    }
+
+    // process mouse move
+    let x = app.mouse.x;
+    let y = app.mouse.y;
+
+    if state.first_mouse {
+        state.first_mouse = false;
+        state.last_x = x;
+        state.last_y = y;
+    }
+
+    let xoffset = x - state.last_x;
+    let yoffset = state.last_y - y;
+    state.last_x = x;
+    state.last_y = y;
+
+    state.camera.process_mouse_movement(xoffset, yoffset, false);
+
+    // process zoom
+    state
+        .camera
+        .process_mouse_scroll(app.mouse.wheel_delta.y * delta);
+}
+
+fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
+    if state.ring_changed {
+        state.ring_changed = false;
+        state.instances.update(gfx, &state.ring.models());
+    }
+
+    // view/projection transformations, the ring needs a far plane
+    let size = gfx.size();
+    let (width, height) = (size.0 as f32, size.1 as f32);
+    let aspect_ratio = width / height;
+    let projection =
+        Mat4::perspective_rh_gl(state.camera.zoom.to_radians(), aspect_ratio, 0.1, 1000.0);
+    let view = state.camera.get_view_matrix();
+
+    // the planet is the only one using the model
+    let model = Mat4::from_translation(vec3(0.0, -3.0, 0.0)) * Mat4::from_scale(Vec3::splat(4.0));
+    set_buffer_data!(
+        gfx,
+        &state.ubo,
+        &Transform {
+            model,
+            view,
+            projection,
+        },
+    );
+
+    let mut renderer = StatsRenderer::new(gfx);
+
+    renderer.begin(Some(&ClearOptions {
+        color: Some(state.clear_color),
+        depth: Some(1.0),
+        stencil: None,
+    }));
+
+    // draw planet
+    renderer.set_pipeline(&state.planet_pipeline);
+    renderer.bind_buffers(&[&state.planet_vbo, &state.ubo]);
+    renderer.bind_texture_slot(0, 0, state.assets.texture(&state.planet_texture));
+    renderer.draw(0, state.planet_vertices);
+
+    // draw meteorites, all of them with one call
+    if !state.instances.is_empty() {
+        renderer.set_pipeline(&state.asteroid_pipeline);
+        renderer.bind_buffers(&[&state.rock_vbo, state.instances.buffer(), &state.ubo]);
+        renderer.bind_texture_slot(0, 0, state.assets.texture(&state.rock_texture));
+        renderer.draw_instanced(0, state.rock_vertices, state.instances.len() as i32);
+    }
+
+    renderer.end();
+
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("4_10_3", "Advanced OpenGL", "Instancing asteroids").with_capture_cursor()
+    }
+
+    fn options() -> Vec<ConfigOption> {
+        vec![ConfigOption::new(
+            "amount",
+            OptionKind::Int,
+            "Number of rocks (10000 by default)",
+        )]
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+        tweak_camera(ui, &mut self.camera);
+
+        let previous = self.ring;
+        let ring = &mut self.ring;
+        tweak_group(ui, "Asteroid ring", |ui| {
+            ui.add(egui::Slider::new(&mut ring.amount, 0..=100000).text("amount"));
+            tweak_slider(ui, "radius", &mut ring.radius, 10.0..=300.0);
+            tweak_slider(ui, "offset", &mut ring.offset, 0.0..=50.0);
+            ui.add(egui::DragValue::new(&mut ring.seed).prefix("seed "));
+        });
+        self.ring_changed |= previous != self.ring;
+    }
+}
@@ -0,0 +1,413 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    delta_time, outline_stencil_write, present, tweak_camera, tweak_clear_color, tweak_group,
+    tweak_rgb, tweak_slider, AssetManager, Camera, CameraMovement, Outline, StatsRenderer,
+    TextureHandle, TextureOptions,
+};
+use notan::egui;
+use notan::math::{vec3, Mat4, Vec3};
+use notan::prelude::*;
+
+// language=glsl
+const VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec3 aPos;
+    layout (location = 1) in vec2 aTexCoords;
+
+    layout (location = 0) out vec2 TexCoords;
+
+    layout(set = 0, binding = 0) uniform Transform {
+        mat4 model;
+        mat4 view;
+        mat4 projection;
+    };
+
+    void main()
+    {
+        TexCoords = aTexCoords;
+        gl_Position = projection * view * model * vec4(aPos, 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 TexCoords;
+
+    layout (location = 0) out vec4 FragColor;
+
+    layout(binding = 0) uniform sampler2D texture1;
+
+    void main()
+    {
+        FragColor = texture(texture1, TexCoords);
+    }
+  "#
+};
+
+// Represent our transform data
+#[derive(Copy, Clone, Default)]
+#[uniform]
+struct Transform {
+    model: Mat4,
+    view: Mat4,
+    projection: Mat4,
+}
+
+const CUBE_POSITIONS: [Vec3; 2] = [Vec3::new(-1.0, 0.0, -1.0), Vec3::new(2.0, 0.0, 0.0)];
+
+// Create a struct to store the app's state
+pub struct State {
+    pipeline: Pipeline,
+    // same than `pipeline` but writing the objects to the stencil buffer
+    outlined_pipeline: Pipeline,
+    cube_vbo: Buffer,
+    plane_vbo: Buffer,
+    cube_ubos: [Buffer; 2],
+    plane_ubo: Buffer,
+    outline: Outline,
+    // the selected cubes are outlined, keys 1 and 2 toggle them
+    selected: [bool; 2],
+    cube_texture: TextureHandle,
+    floor_texture: TextureHandle,
+    assets: AssetManager,
+    camera: Camera,
+    last_x: f32,
+    last_y: f32,
+    first_mouse: bool,
+    clear_color: Color,
+}
+
+// initialize the state and return it to be used by notan
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
+    // Declare the vertex attributes
+    let vertex_info = VertexInfo::new()
+        .attr(0, VertexFormat::Float32x3) // positions
+        .attr(1, VertexFormat::Float32x2); // texture coords
+
+    // Enable depth test
+    let depth_test = DepthStencil {
+        write: true,
+        compare: CompareMode::Less,
+    };
+
+    // build the pipelines
+    let pipeline = gfx
+        .create_pipeline()
+        .from(&VERTEX_SHADER, &FRAGMENT_SHADER)
+        .with_vertex_info(&vertex_info)
+        .with_depth_stencil(depth_test)
+        .with_texture_location(0, "texture1")
+        .build()
+        .unwrap();
+
+    let outlined_pipeline = gfx
+        .create_pipeline()
+        .from(&VERTEX_SHADER, &FRAGMENT_SHADER)
+        .with_vertex_info(&vertex_info)
+        .with_depth_stencil(depth_test)
+        .with_stencil(outline_stencil_write())
+        .with_texture_location(0, "texture1")
+        .build()
+        .unwrap();
+
+    // define vertex data
+    #[rustfmt::skip]
+    let cube_vertices = [
+        // positions          // texture Coords
+        -0.5, -0.5, -0.5,  0.0, 0.0,
+        0.5, -0.5, -0.5,  1.0, 0.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        -0.5,  0.5, -0.5,  0.0, 1.0,
+        -0.5, -0.5, -0.5,  0.0, 0.0,
+
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+        0.5, -0.5,  0.5,  1.0, 0.0,
+        0.5,  0.5,  0.5,  1.0, 1.0,
+        0.5,  0.5,  0.5,  1.0, 1.0,
+        -0.5,  0.5,  0.5,  0.0, 1.0,
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+
+        -0.5,  0.5,  0.5,  1.0, 0.0,
+        -0.5,  0.5, -0.5,  1.0, 1.0,
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+        -0.5,  0.5,  0.5,  1.0, 0.0,
+
+        0.5,  0.5,  0.5,  1.0, 0.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        0.5, -0.5, -0.5,  0.0, 1.0,
+        0.5, -0.5, -0.5,  0.0, 1.0,
+        0.5, -0.5,  0.5,  0.0, 0.0,
+        0.5,  0.5,  0.5,  1.0, 0.0,
+
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+        0.5, -0.5, -0.5,  1.0, 1.0,
+        0.5, -0.5,  0.5,  1.0, 0.0,
+        0.5, -0.5,  0.5,  1.0, 0.0,
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+
+        -0.5,  0.5, -0.5,  0.0, 1.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        0.5,  0.5,  0.5,  1.0, 0.0,
+        0.5,  0.5,  0.5,  1.0, 0.0,
+        -0.5,  0.5,  0.5,  0.0, 0.0,
+        -0.5,  0.5, -0.5,  0.0, 1.0,
+    ];
+
+    // texture coords set higher than 1 (together with the repeat wrap) cause the floor
+    // texture to repeat
+    #[rustfmt::skip]
+    let plane_vertices = [
+        // positions          // texture Coords
+        5.0, -0.5,  5.0,  2.0, 0.0,
+        -5.0, -0.5,  5.0,  0.0, 0.0,
+        -5.0, -0.5, -5.0,  0.0, 2.0,
+
+        5.0, -0.5,  5.0,  2.0, 0.0,
+        -5.0, -0.5, -5.0,  0.0, 2.0,
+        5.0, -0.5, -5.0,  2.0, 2.0,
+    ];
+
+    // create the vertex buffer objects
+    let cube_vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&cube_vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    let plane_vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&plane_vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    // one transform per object, they are drawn in the same pass
+    let mut create_ubo = || gfx.create_uniform_buffer(0, "Transform").build().unwrap();
+    let cube_ubos = [create_ubo(), create_ubo()];
+    let plane_ubo = create_ubo();
+
+    // the silhouettes use the same vertices than the cubes
+    let outline = Outline::new(gfx, &vertex_info).unwrap();
+
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+    let cube_texture = assets.load_texture("textures/marble.jpg");
+    let floor_texture = assets.load_texture_with(
+        "textures/metal.png",
+        TextureOptions {
+            wrap_x: TextureWrap::Repeat,
+            wrap_y: TextureWrap::Repeat,
+            ..Default::default()
+        },
+    );
+
+    let camera = Camera {
+        position: config.camera_position(vec3(0.0, 0.0, 3.0)),
+        ..Default::default()
+    };
+
+    State {
+        pipeline,
+        outlined_pipeline,
+        cube_vbo,
+        plane_vbo,
+        cube_ubos,
+        plane_ubo,
+        outline,
+        selected: [true, true],
+        cube_texture,
+        floor_texture,
+        assets,
+        camera,
+        last_x: 0.0,
+        last_y: 0.0,
+        first_mouse: true,
+        clear_color: config.clear_color(Color::from_rgb(0.1, 0.1, 0.1)),
+    }
+}
+
+fn update(app: &mut App, state: &mut State) {
+    // select or unselect the cubes
+    if app.keyboard.was_pressed(KeyCode::Key1) {
+        state.selected[0] = !state.selected[0];
+    }
+    if app.keyboard.was_pressed(KeyCode::Key2) {
+        state.selected[1] = !state.selected[1];
+    }
+
+    // Process all inputs to move the camera
+    let delta = delta_time();
+    if app.keyboard.is_down(KeyCode::W) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Forward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::S) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Backward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::A) {
+        state.camera.process_keyboard(CameraMovement::Left, delta);
+    }
+    if app.keyboard.is_down(KeyCode::D) {
+        state.camera.process_keyboard(CameraMovement::Right, delta);
+    }
+
+    // process mouse move
+    let x = app.mouse.x;
+    let y = app.mouse.y;
+
+    if state.first_mouse {
+        state.first_mouse = false;
+        state.last_x = x;
+        state.last_y = y;
+    }
+
+    let xoffset = x - state.last_x;
+    let yoffset = state.last_y - y;
+    state.last_x = x;
+    state.last_y = y;
+
+    state.camera.process_mouse_movement(xoffset, yoffset, false);
+
+    // process zoom
+    state
+        .camera
+        .process_mouse_scroll(app.mouse.wheel_delta.y * delta);
+}
+
+fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
+    // view/projection transformations
+    let size = gfx.size();
+    let (width, height) = (size.0 as f32, size.1 as f32);
+    let aspect_ratio = width / height;
+    let projection =
+        Mat4::perspective_rh_gl(state.camera.zoom.to_radians(), aspect_ratio, 0.1, 100.0);
+    let view = state.camera.get_view_matrix();
+
+    let models = CUBE_POSITIONS.map(Mat4::from_translation);
+    state
+        .cube_ubos
+        .iter()
+        .zip(models.iter())
+        .for_each(|(ubo, model)| {
+            set_buffer_data!(
+                gfx,
+                ubo,
+                &Transform {
+                    model: *model,
+                    view,
+                    projection,
+                },
+            );
+        });
+
+    set_buffer_data!(
+        gfx,
+        &state.plane_ubo,
+        &Transform {
+            model: Mat4::IDENTITY,
+            view,
+            projection,
+        },
+    );
+
+    // the silhouettes of the selected cubes
+    let selected_models = models
+        .iter()
+        .zip(state.selected.iter())
+        .filter(|(_, selected)| **selected)
+        .map(|(model, _)| *model)
+        .collect::<Vec<_>>();
+    state
+        .outline
+        .update(gfx, &selected_models, view, projection);
+
+    let mut renderer = StatsRenderer::new(gfx);
+
+    renderer.begin(Some(&ClearOptions {
+        color: Some(state.clear_color),
+        depth: Some(1.0),
+        stencil: Some(0),
+    }));
+
+    // floor, it doesn't write to the stencil buffer
+    renderer.set_pipeline(&state.pipeline);
+    renderer.bind_texture(0, state.assets.texture(&state.floor_texture));
+    renderer.bind_buffers(&[&state.plane_vbo, &state.plane_ubo]);
+    renderer.draw(0, 6);
+
+    // cubes, the selected ones set the stencil buffer to 1 where they are drawn
+    state
+        .cube_ubos
+        .iter()
+        .zip(state.selected.iter())
+        .for_each(|(ubo, selected)| {
+            let pipeline = if *selected {
+                &state.outlined_pipeline
+            } else {
+                &state.pipeline
+            };
+            renderer.set_pipeline(pipeline);
+            renderer.bind_texture(0, state.assets.texture(&state.cube_texture));
+            renderer.bind_buffers(&[&state.cube_vbo, ubo]);
+            renderer.draw(0, 36);
+        });
+
+    // slightly scaled up versions of the selected cubes where the stencil is not 1
+    state.outline.draw(&mut renderer, &state.cube_vbo, 36);
+
+    renderer.end();
+
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("4_2_1", "Advanced OpenGL", "Stencil testing").with_capture_cursor()
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+        tweak_camera(ui, &mut self.camera);
+
+        let outline = &mut self.outline;
+        let selected = &mut self.selected;
+        tweak_group(ui, "Outline", |ui| {
+            let color = outline.color;
+            let mut rgb = vec3(color.r, color.g, color.b);
+            tweak_rgb(ui, "color", &mut rgb);
+            outline.color = Color::from_rgb(rgb.x, rgb.y, rgb.z);
+            tweak_slider(ui, "thickness", &mut outline.thickness, 0.0..=0.5);
+            ui.checkbox(&mut selected[0], "cube 1");
+            ui.checkbox(&mut selected[1], "cube 2");
+        });
+    }
+}
@@ -0,0 +1,522 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    blended_depth_stencil, delta_time, present, tweak_camera, tweak_clear_color, tweak_group,
+    tweak_slider, AssetManager, Camera, CameraMovement, ModelInstances, RenderQueue, StatsRenderer,
+    TextureHandle, TextureOptions, Transparency,
+};
+use notan::egui;
+use notan::math::{vec3, Mat4, Vec3};
+use notan::prelude::*;
+
+// language=glsl
+const VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec3 aPos;
+    layout (location = 1) in vec2 aTexCoords;
+
+    // model matrix of each object, one column per attribute
+    layout (location = 3) in vec4 aModel0;
+    layout (location = 4) in vec4 aModel1;
+    layout (location = 5) in vec4 aModel2;
+    layout (location = 6) in vec4 aModel3;
+
+    layout (location = 0) out vec2 TexCoords;
+
+    layout(set = 0, binding = 0) uniform Transform {
+        mat4 view;
+        mat4 projection;
+    };
+
+    void main()
+    {
+        mat4 model = mat4(aModel0, aModel1, aModel2, aModel3);
+        TexCoords = aTexCoords;
+        gl_Position = projection * view * model * vec4(aPos, 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 TexCoords;
+
+    layout (location = 0) out vec4 FragColor;
+
+    layout(binding = 0) uniform sampler2D texture1;
+
+    layout(set = 0, binding = 1) uniform Material {
+        float alphaCutoff;
+    };
+
+    void main()
+    {
+        vec4 texColor = texture(texture1, TexCoords);
+        // alpha test, 0.0 keeps every fragment
+        if (texColor.a < alphaCutoff) {
+            discard;
+        }
+        FragColor = texColor;
+    }
+  "#
+};
+
+// Represent our transform data
+#[derive(Copy, Clone, Default)]
+#[uniform]
+struct Transform {
+    view: Mat4,
+    projection: Mat4,
+}
+
+#[derive(Copy, Clone)]
+#[uniform]
+struct Material {
+    alpha_cutoff: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Object {
+    Cube,
+    Floor,
+    Grass,
+    Window,
+}
+
+const CUBE_POSITIONS: [Vec3; 2] = [Vec3::new(-1.0, 0.0, -1.0), Vec3::new(2.0, 0.0, 0.0)];
+
+const GRASS_POSITIONS: [Vec3; 3] = [
+    Vec3::new(-2.5, 0.0, -2.0),
+    Vec3::new(0.8, 0.0, -1.9),
+    Vec3::new(2.7, 0.0, -1.2),
+];
+
+const WINDOW_POSITIONS: [Vec3; 5] = [
+    Vec3::new(-1.5, 0.0, -0.48),
+    Vec3::new(1.5, 0.0, 0.51),
+    Vec3::new(0.0, 0.0, 0.7),
+    Vec3::new(-0.3, 0.0, -2.3),
+    Vec3::new(0.5, 0.0, -0.6),
+];
+
+// Create a struct to store the app's state
+pub struct State {
+    pipeline: Pipeline,
+    // alpha blending without depth writes
+    blended_pipeline: Pipeline,
+    cube_vbo: Buffer,
+    plane_vbo: Buffer,
+    quad_vbo: Buffer,
+    ubo: Buffer,
+    opaque_material_ubo: Buffer,
+    cutout_material_ubo: Buffer,
+    cube_instances: ModelInstances,
+    floor_instances: ModelInstances,
+    grass_instances: ModelInstances,
+    window_instances: ModelInstances,
+    queue: RenderQueue<(Object, Mat4)>,
+    // disable it to see the windows blended in the wrong order
+    sort_windows: bool,
+    alpha_cutoff: f32,
+    cube_texture: TextureHandle,
+    floor_texture: TextureHandle,
+    grass_texture: TextureHandle,
+    window_texture: TextureHandle,
+    assets: AssetManager,
+    camera: Camera,
+    last_x: f32,
+    last_y: f32,
+    first_mouse: bool,
+    clear_color: Color,
+}
+
+// initialize the state and return it to be used by notan
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
+    // Declare the vertex attributes
+    let vertex_info = VertexInfo::new()
+        .attr(0, VertexFormat::Float32x3) // positions
+        .attr(1, VertexFormat::Float32x2); // texture coords
+
+    // Enable depth test
+    let depth_test = DepthStencil {
+        write: true,
+        compare: CompareMode::Less,
+    };
+
+    // build the pipelines, the opaque and cutout objects share the first one
+    let pipeline = gfx
+        .create_pipeline()
+        .from(&VERTEX_SHADER, &FRAGMENT_SHADER)
+        .with_vertex_info(&vertex_info)
+        .with_vertex_info(&ModelInstances::vertex_info(3))
+        .with_depth_stencil(depth_test)
+        .with_texture_location(0, "texture1")
+        .build()
+        .unwrap();
+
+    let blended_pipeline = gfx
+        .create_pipeline()
+        .from(&VERTEX_SHADER, &FRAGMENT_SHADER)
+        .with_vertex_info(&vertex_info)
+        .with_vertex_info(&ModelInstances::vertex_info(3))
+        .with_depth_stencil(blended_depth_stencil())
+        .with_color_blend(BlendMode::NORMAL)
+        .with_texture_location(0, "texture1")
+        .build()
+        .unwrap();
+
+    // define vertex data
+    #[rustfmt::skip]
+    let cube_vertices = [
+        // positions          // texture Coords
+        -0.5, -0.5, -0.5,  0.0, 0.0,
+        0.5, -0.5, -0.5,  1.0, 0.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        -0.5,  0.5, -0.5,  0.0, 1.0,
+        -0.5, -0.5, -0.5,  0.0, 0.0,
+
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+        0.5, -0.5,  0.5,  1.0, 0.0,
+        0.5,  0.5,  0.5,  1.0, 1.0,
+        0.5,  0.5,  0.5,  1.0, 1.0,
+        -0.5,  0.5,  0.5,  0.0, 1.0,
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+
+        -0.5,  0.5,  0.5,  1.0, 0.0,
+        -0.5,  0.5, -0.5,  1.0, 1.0,
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+        -0.5,  0.5,  0.5,  1.0, 0.0,
+
+        0.5,  0.5,  0.5,  1.0, 0.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        0.5, -0.5, -0.5,  0.0, 1.0,
+        0.5, -0.5, -0.5,  0.0, 1.0,
+        0.5, -0.5,  0.5,  0.0, 0.0,
+        0.5,  0.5,  0.5,  1.0, 0.0,
+
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+        0.5, -0.5, -0.5,  1.0, 1.0,
+        0.5, -0.5,  0.5,  1.0, 0.0,
+        0.5, -0.5,  0.5,  1.0, 0.0,
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+
+        -0.5,  0.5, -0.5,  0.0, 1.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        0.5,  0.5,  0.5,  1.0, 0.0,
+        0.5,  0.5,  0.5,  1.0, 0.0,
+        -0.5,  0.5,  0.5,  0.0, 0.0,
+        -0.5,  0.5, -0.5,  0.0, 1.0,
+    ];
+
+    // texture coords set higher than 1 (together with the repeat wrap) cause the floor
+    // texture to repeat
+    #[rustfmt::skip]
+    let plane_vertices = [
+        // positions          // texture Coords
+        5.0, -0.5,  5.0,  2.0, 0.0,
+        -5.0, -0.5,  5.0,  0.0, 0.0,
+        -5.0, -0.5, -5.0,  0.0, 2.0,
+
+        5.0, -0.5,  5.0,  2.0, 0.0,
+        -5.0, -0.5, -5.0,  0.0, 2.0,
+        5.0, -0.5, -5.0,  2.0, 2.0,
+    ];
+
+    #[rustfmt::skip]
+    let transparent_vertices = [
+        // positions         // texture Coords
+        0.0,  0.5,  0.0,  0.0,  0.0,
+        0.0, -0.5,  0.0,  0.0,  1.0,
+        1.0, -0.5,  0.0,  1.0,  1.0,
+
+        0.0,  0.5,  0.0,  0.0,  0.0,
+        1.0, -0.5,  0.0,  1.0,  1.0,
+        1.0,  0.5,  0.0,  1.0,  0.0,
+    ];
+
+    // create the vertex buffer objects
+    let cube_vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&cube_vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    let plane_vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&plane_vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    let quad_vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&transparent_vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    // create the uniform buffer objects, one material per alpha cutoff
+    let ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
+    let mut create_material = || gfx.create_uniform_buffer(1, "Material").build().unwrap();
+    let opaque_material_ubo = create_material();
+    let cutout_material_ubo = create_material();
+
+    // the models of each object are uploaded every frame from the queue
+    let mut create_instances = || ModelInstances::new(gfx, 3, &[]).unwrap();
+    let cube_instances = create_instances();
+    let floor_instances = create_instances();
+    let grass_instances = create_instances();
+    let window_instances = create_instances();
+
+    // textures are loaded asynchronously by the asset manager, the grass and the window
+    // are clamped to avoid the semi-transparent borders of repeating the other side
+    let mut assets = AssetManager::new(gfx);
+    let cube_texture = assets.load_texture("textures/marble.jpg");
+    let floor_texture = assets.load_texture_with(
+        "textures/metal.png",
+        TextureOptions {
+            wrap_x: TextureWrap::Repeat,
+            wrap_y: TextureWrap::Repeat,
+            ..Default::default()
+        },
+    );
+    let grass_texture = assets.load_texture("textures/grass.png");
+    let window_texture = assets.load_texture("textures/window.png");
+
+    let camera = Camera {
+        position: config.camera_position(vec3(0.0, 0.0, 3.0)),
+        ..Default::default()
+    };
+
+    State {
+        pipeline,
+        blended_pipeline,
+        cube_vbo,
+        plane_vbo,
+        quad_vbo,
+        ubo,
+        opaque_material_ubo,
+        cutout_material_ubo,
+        cube_instances,
+        floor_instances,
+        grass_instances,
+        window_instances,
+        queue: RenderQueue::new(),
+        sort_windows: true,
+        alpha_cutoff: 0.1,
+        cube_texture,
+        floor_texture,
+        grass_texture,
+        window_texture,
+        assets,
+        camera,
+        last_x: 0.0,
+        last_y: 0.0,
+        first_mouse: true,
+        clear_color: config.clear_color(Color::from_rgb(0.1, 0.1, 0.1)),
+    }
+}
+
+// models of the items of the queue that are `object`
+fn models<'a>(items: impl Iterator<Item = &'a (Object, Mat4)>, object: Object) -> Vec<Mat4> {
+    items
+        .filter(|(item, _)| *item == object)
+        .map(|(_, model)| *model)
+        .collect()
+}
+
+fn update(app: &mut App, state: &mut State) {
+    // Process all inputs to move the camera
+    let delta = delta_time();
+    if app.keyboard.is_down(KeyCode::W) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Forward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::S) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Backward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::A) {
+        state.camera.process_keyboard(CameraMovement::Left, delta);
+    }
+    if app.keyboard.is_down(KeyCode::D) {
+        state.camera.process_keyboard(CameraMovement::Right, delta);
+    }
+
+    // process mouse move
+    let x = app.mouse.x;
+    let y = app.mouse.y;
+
+    if state.first_mouse {
+        state.first_mouse = false;
+        state.last_x = x;
+        state.last_y = y;
+    }
+
+    let xoffset = x - state.last_x;
+    let yoffset = state.last_y - y;
+    state.last_x = x;
+    state.last_y = y;
+
+    state.camera.process_mouse_movement(xoffset, yoffset, false);
+
+    // process zoom
+    state
+        .camera
+        .process_mouse_scroll(app.mouse.wheel_delta.y * delta);
+}
+
+fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
+    // view/projection transformations
+    let size = gfx.size();
+    let (width, height) = (size.0 as f32, size.1 as f32);
+    let aspect_ratio = width / height;
+    let projection =
+        Mat4::perspective_rh_gl(state.camera.zoom.to_radians(), aspect_ratio, 0.1, 100.0);
+    let view = state.camera.get_view_matrix();
+    // the same pose than the view, between the steps with --fixed-fps
+    let camera = state.camera.interpolated_pose();
+
+    set_buffer_data!(gfx, &state.ubo, &Transform { view, projection });
+    set_buffer_data!(
+        gfx,
+        &state.opaque_material_ubo,
+        &Material { alpha_cutoff: 0.0 }
+    );
+    set_buffer_data!(
+        gfx,
+        &state.cutout_material_ubo,
+        &Material {
+            alpha_cutoff: state.alpha_cutoff
+        }
+    );
+
+    // fill the queue, the windows are sorted by their distance to the camera
+    let queue = &mut state.queue;
+    queue.clear();
+    CUBE_POSITIONS.iter().for_each(|position| {
+        let model = Mat4::from_translation(*position);
+        queue.push(Transparency::Opaque, *position, (Object::Cube, model));
+    });
+    queue.push(
+        Transparency::Opaque,
+        Vec3::ZERO,
+        (Object::Floor, Mat4::IDENTITY),
+    );
+    GRASS_POSITIONS.iter().for_each(|position| {
+        let model = Mat4::from_translation(*position);
+        queue.push(Transparency::Cutout, *position, (Object::Grass, model));
+    });
+    WINDOW_POSITIONS.iter().for_each(|position| {
+        let model = Mat4::from_translation(*position);
+        queue.push(Transparency::Blended, *position, (Object::Window, model));
+    });
+    if state.sort_windows {
+        queue.sort(camera.position);
+    }
+
+    let queue = &state.queue;
+    state
+        .cube_instances
+        .update(gfx, &models(queue.opaque().iter(), Object::Cube));
+    state
+        .floor_instances
+        .update(gfx, &models(queue.opaque().iter(), Object::Floor));
+    state
+        .grass_instances
+        .update(gfx, &models(queue.cutout().iter(), Object::Grass));
+    // the instances are drawn in the order of the buffer, back to front
+    state
+        .window_instances
+        .update(gfx, &models(queue.blended(), Object::Window));
+
+    let mut renderer = StatsRenderer::new(gfx);
+
+    renderer.begin(Some(&ClearOptions {
+        color: Some(state.clear_color),
+        depth: Some(1.0),
+        stencil: None,
+    }));
+
+    // opaque objects
+    renderer.set_pipeline(&state.pipeline);
+    renderer.bind_texture(0, state.assets.texture(&state.cube_texture));
+    renderer.bind_buffers(&[
+        &state.cube_vbo,
+        state.cube_instances.buffer(),
+        &state.ubo,
+        &state.opaque_material_ubo,
+    ]);
+    renderer.draw_instanced(0, 36, state.cube_instances.len() as i32);
+
+    renderer.bind_texture(0, state.assets.texture(&state.floor_texture));
+    renderer.bind_buffers(&[&state.plane_vbo, state.floor_instances.buffer()]);
+    renderer.draw_instanced(0, 6, state.floor_instances.len() as i32);
+
+    // cutout objects, the transparent fragments are discarded
+    renderer.bind_texture(0, state.assets.texture(&state.grass_texture));
+    renderer.bind_buffers(&[
+        &state.quad_vbo,
+        state.grass_instances.buffer(),
+        &state.cutout_material_ubo,
+    ]);
+    renderer.draw_instanced(0, 6, state.grass_instances.len() as i32);
+
+    // blended objects after the rest
+    renderer.set_pipeline(&state.blended_pipeline);
+    renderer.bind_texture(0, state.assets.texture(&state.window_texture));
+    renderer.bind_buffers(&[
+        &state.quad_vbo,
+        state.window_instances.buffer(),
+        &state.ubo,
+        &state.opaque_material_ubo,
+    ]);
+    renderer.draw_instanced(0, 6, state.window_instances.len() as i32);
+
+    renderer.end();
+
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("4_3_2", "Advanced OpenGL", "Blending sort").with_capture_cursor()
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+        tweak_camera(ui, &mut self.camera);
+
+        let (sort_windows, alpha_cutoff) = (&mut self.sort_windows, &mut self.alpha_cutoff);
+        tweak_group(ui, "Blending", |ui| {
+            ui.checkbox(sort_windows, "sort the windows");
+            tweak_slider(ui, "grass alpha cutoff", alpha_cutoff, 0.0..=1.0);
+        });
+    }
+}
@@ -0,0 +1,516 @@
+use crate::config::{Config, ConfigOption, OptionKind};
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    delta_time, parse_kernel, present, tweak_camera, tweak_clear_color, tweak_post_passes,
+    AssetManager, Camera, CameraMovement, PostEffect, PostPass, PostProcess, RenderTarget,
+    RenderTargetOptions, StatsRenderer, TextureHandle, TextureOptions, IDENTITY_KERNEL,
+};
+use notan::egui;
+use notan::math::{vec3, Mat4};
+use notan::prelude::*;
+
+// language=glsl
+const VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec3 aPos;
+    layout (location = 1) in vec2 aTexCoords;
+
+    layout (location = 0) out vec2 TexCoords;
+
+    layout(set = 0, binding = 0) uniform Transform {
+        mat4 model;
+        mat4 view;
+        mat4 projection;
+    };
+
+    void main()
+    {
+        TexCoords = aTexCoords;
+        gl_Position = projection * view * model * vec4(aPos, 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 TexCoords;
+
+    layout (location = 0) out vec4 FragColor;
+
+    layout(binding = 0) uniform sampler2D texture1;
+
+    void main()
+    {
+        FragColor = texture(texture1, TexCoords);
+    }
+  "#
+};
+
+// language=glsl
+const SCREEN_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 aPos;
+    layout (location = 1) in vec2 aTexCoords;
+
+    layout (location = 0) out vec2 TexCoords;
+
+    void main()
+    {
+        TexCoords = aTexCoords;
+        gl_Position = vec4(aPos.x, aPos.y, 0.0, 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const SCREEN_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 TexCoords;
+
+    layout (location = 0) out vec4 FragColor;
+
+    layout(binding = 0) uniform sampler2D screenTexture;
+
+    void main()
+    {
+        vec3 col = texture(screenTexture, TexCoords).rgb;
+        FragColor = vec4(col, 1.0);
+    }
+  "#
+};
+
+// Represent our transform data
+#[derive(Copy, Clone, Default)]
+#[uniform]
+struct Transform {
+    model: Mat4,
+    view: Mat4,
+    projection: Mat4,
+}
+
+// Create a struct to store the app's state
+pub struct State {
+    pipeline: Pipeline,
+    screen_pipeline: Pipeline,
+    cube_vbo: Buffer,
+    plane_vbo: Buffer,
+    quad_vbo: Buffer,
+    cube_ubos: [Buffer; 2],
+    plane_ubo: Buffer,
+    // the scene is rendered here and then drawn to the screen with a quad
+    target: RenderTarget,
+    // effects applied to the framebuffer before drawing it
+    post: PostProcess,
+    cube_texture: TextureHandle,
+    floor_texture: TextureHandle,
+    assets: AssetManager,
+    camera: Camera,
+    last_x: f32,
+    last_y: f32,
+    first_mouse: bool,
+    clear_color: Color,
+}
+
+// initialize the state and return it to be used by notan
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
+    // Declare the vertex attributes
+    let vertex_info = VertexInfo::new()
+        .attr(0, VertexFormat::Float32x3) // positions
+        .attr(1, VertexFormat::Float32x2); // texture coords
+
+    let screen_vertex_info = VertexInfo::new()
+        .attr(0, VertexFormat::Float32x2) // positions
+        .attr(1, VertexFormat::Float32x2); // texture coords
+
+    // Enable depth test
+    let depth_test = DepthStencil {
+        write: true,
+        compare: CompareMode::Less,
+    };
+
+    // build the pipelines
+    let pipeline = gfx
+        .create_pipeline()
+        .from(&VERTEX_SHADER, &FRAGMENT_SHADER)
+        .with_vertex_info(&vertex_info)
+        .with_depth_stencil(depth_test)
+        .with_texture_location(0, "texture1")
+        .build()
+        .unwrap();
+
+    // the quad covers the screen, it doesn't need the depth test
+    let screen_pipeline = gfx
+        .create_pipeline()
+        .from(&SCREEN_VERTEX_SHADER, &SCREEN_FRAGMENT_SHADER)
+        .with_vertex_info(&screen_vertex_info)
+        .with_texture_location(0, "screenTexture")
+        .build()
+        .unwrap();
+
+    // define vertex data
+    #[rustfmt::skip]
+    let cube_vertices = [
+        // positions          // texture Coords
+        -0.5, -0.5, -0.5,  0.0, 0.0,
+        0.5, -0.5, -0.5,  1.0, 0.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        -0.5,  0.5, -0.5,  0.0, 1.0,
+        -0.5, -0.5, -0.5,  0.0, 0.0,
+
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+        0.5, -0.5,  0.5,  1.0, 0.0,
+        0.5,  0.5,  0.5,  1.0, 1.0,
+        0.5,  0.5,  0.5,  1.0, 1.0,
+        -0.5,  0.5,  0.5,  0.0, 1.0,
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+
+        -0.5,  0.5,  0.5,  1.0, 0.0,
+        -0.5,  0.5, -0.5,  1.0, 1.0,
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+        -0.5,  0.5,  0.5,  1.0, 0.0,
+
+        0.5,  0.5,  0.5,  1.0, 0.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        0.5, -0.5, -0.5,  0.0, 1.0,
+        0.5, -0.5, -0.5,  0.0, 1.0,
+        0.5, -0.5,  0.5,  0.0, 0.0,
+        0.5,  0.5,  0.5,  1.0, 0.0,
+
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+        0.5, -0.5, -0.5,  1.0, 1.0,
+        0.5, -0.5,  0.5,  1.0, 0.0,
+        0.5, -0.5,  0.5,  1.0, 0.0,
+        -0.5, -0.5,  0.5,  0.0, 0.0,
+        -0.5, -0.5, -0.5,  0.0, 1.0,
+
+        -0.5,  0.5, -0.5,  0.0, 1.0,
+        0.5,  0.5, -0.5,  1.0, 1.0,
+        0.5,  0.5,  0.5,  1.0, 0.0,
+        0.5,  0.5,  0.5,  1.0, 0.0,
+        -0.5,  0.5,  0.5,  0.0, 0.0,
+        -0.5,  0.5, -0.5,  0.0, 1.0,
+    ];
+
+    // texture coords set higher than 1 (together with the repeat wrap) cause the floor
+    // texture to repeat
+    #[rustfmt::skip]
+    let plane_vertices = [
+        // positions          // texture Coords
+        5.0, -0.5,  5.0,  2.0, 0.0,
+        -5.0, -0.5,  5.0,  0.0, 0.0,
+        -5.0, -0.5, -5.0,  0.0, 2.0,
+
+        5.0, -0.5,  5.0,  2.0, 0.0,
+        -5.0, -0.5, -5.0,  0.0, 2.0,
+        5.0, -0.5, -5.0,  2.0, 2.0,
+    ];
+
+    // vertex attributes for a quad that fills the entire screen in normalized device coordinates
+    #[rustfmt::skip]
+    let quad_vertices = [
+        // positions   // texCoords
+        -1.0,  1.0,  0.0, 1.0,
+        -1.0, -1.0,  0.0, 0.0,
+        1.0, -1.0,  1.0, 0.0,
+
+        -1.0,  1.0,  0.0, 1.0,
+        1.0, -1.0,  1.0, 0.0,
+        1.0,  1.0,  1.0, 1.0,
+    ];
+
+    // create the vertex buffer objects
+    let cube_vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&cube_vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    let plane_vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&plane_vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    let quad_vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&quad_vertices)
+        .with_info(&screen_vertex_info)
+        .build()
+        .unwrap();
+
+    // one transform per object, they are drawn in the same pass
+    let mut create_ubo = || gfx.create_uniform_buffer(0, "Transform").build().unwrap();
+    let cube_ubos = [create_ubo(), create_ubo()];
+    let plane_ubo = create_ubo();
+
+    // framebuffer configuration, the samples of `--msaa` are used for the offscreen pass
+    let options = RenderTargetOptions::default()
+        .with_samples(config.int("msaa").unwrap_or(0).clamp(0, 16) as _);
+    let target = RenderTarget::from_window(gfx, options).unwrap();
+    let post = PostProcess::new(gfx, post_passes(gfx, config)).unwrap();
+
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+    let cube_texture = assets.load_texture("textures/container.jpg");
+    let floor_texture = assets.load_texture_with(
+        "textures/metal.png",
+        TextureOptions {
+            wrap_x: TextureWrap::Repeat,
+            wrap_y: TextureWrap::Repeat,
+            ..Default::default()
+        },
+    );
+
+    let camera = Camera {
+        position: config.camera_position(vec3(0.0, 0.0, 3.0)),
+        ..Default::default()
+    };
+
+    State {
+        pipeline,
+        screen_pipeline,
+        cube_vbo,
+        plane_vbo,
+        quad_vbo,
+        cube_ubos,
+        plane_ubo,
+        target,
+        post,
+        cube_texture,
+        floor_texture,
+        assets,
+        camera,
+        last_x: 0.0,
+        last_y: 0.0,
+        first_mouse: true,
+        clear_color: config.clear_color(Color::from_rgb(0.1, 0.1, 0.1)),
+    }
+}
+
+// The effects of `--effects` are enabled in that order, the rest are added disabled to turn
+// them on from the tweak panel. `--kernel` enables the custom kernel.
+fn post_passes(gfx: &mut Graphics, config: &Config) -> Vec<PostPass> {
+    // the effects with their enabled state
+    let mut effects = config
+        .text("effects")
+        .map(|names| {
+            names
+                .split(',')
+                .filter_map(|name| match PostEffect::parse(name) {
+                    Ok(effect) => Some((effect, true)),
+                    Err(err) => {
+                        notan::log::error!("{}", err);
+                        None
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    PostEffect::NAMED.iter().for_each(|effect| {
+        if !effects.iter().any(|(added, _)| added == effect) {
+            effects.push((*effect, false));
+        }
+    });
+
+    let kernel = config.text("kernel").map(parse_kernel);
+    effects.push(match kernel {
+        Some(Ok(kernel)) => (PostEffect::Kernel(kernel), true),
+        Some(Err(err)) => {
+            notan::log::error!("{}", err);
+            (PostEffect::Kernel(IDENTITY_KERNEL), false)
+        }
+        None => (PostEffect::Kernel(IDENTITY_KERNEL), false),
+    });
+
+    effects
+        .into_iter()
+        .map(|(effect, enabled)| {
+            let pass = PostPass::preset(gfx, effect).unwrap();
+            if enabled {
+                pass
+            } else {
+                pass.disabled()
+            }
+        })
+        .collect()
+}
+
+fn update(app: &mut App, state: &mut State) {
+    // Process all inputs to move the camera
+    let delta = delta_time();
+    if app.keyboard.is_down(KeyCode::W) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Forward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::S) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Backward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::A) {
+        state.camera.process_keyboard(CameraMovement::Left, delta);
+    }
+    if app.keyboard.is_down(KeyCode::D) {
+        state.camera.process_keyboard(CameraMovement::Right, delta);
+    }
+
+    // process mouse move
+    let x = app.mouse.x;
+    let y = app.mouse.y;
+
+    if state.first_mouse {
+        state.first_mouse = false;
+        state.last_x = x;
+        state.last_y = y;
+    }
+
+    let xoffset = x - state.last_x;
+    let yoffset = state.last_y - y;
+    state.last_x = x;
+    state.last_y = y;
+
+    state.camera.process_mouse_movement(xoffset, yoffset, false);
+
+    // process zoom
+    state
+        .camera
+        .process_mouse_scroll(app.mouse.wheel_delta.y * delta);
+}
+
+fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
+    // the framebuffer follows the size of the window
+    if let Err(err) = state.target.fit_window(gfx) {
+        notan::log::error!("Cannot resize the framebuffer: {}", err);
+    }
+
+    // view/projection transformations
+    let (width, height) = state.target.size();
+    let aspect_ratio = width as f32 / height as f32;
+    let projection =
+        Mat4::perspective_rh_gl(state.camera.zoom.to_radians(), aspect_ratio, 0.1, 100.0);
+    let view = state.camera.get_view_matrix();
+
+    let cube_positions = [vec3(-1.0, 0.0, -1.0), vec3(2.0, 0.0, 0.0)];
+    state
+        .cube_ubos
+        .iter()
+        .zip(cube_positions.iter())
+        .for_each(|(ubo, position)| {
+            set_buffer_data!(
+                gfx,
+                ubo,
+                &Transform {
+                    model: Mat4::from_translation(*position),
+                    view,
+                    projection,
+                },
+            );
+        });
+
+    set_buffer_data!(
+        gfx,
+        &state.plane_ubo,
+        &Transform {
+            model: Mat4::IDENTITY,
+            view,
+            projection,
+        },
+    );
+
+    // first pass: draw the scene to the framebuffer
+    let mut renderer = StatsRenderer::new(gfx);
+
+    renderer.begin(Some(&ClearOptions {
+        color: Some(state.clear_color),
+        depth: Some(1.0),
+        stencil: None,
+    }));
+
+    // cubes
+    renderer.set_pipeline(&state.pipeline);
+    renderer.bind_texture(0, state.assets.texture(&state.cube_texture));
+    state.cube_ubos.iter().for_each(|ubo| {
+        renderer.bind_buffers(&[&state.cube_vbo, ubo]);
+        renderer.draw(0, 36);
+    });
+
+    // floor
+    renderer.bind_texture(0, state.assets.texture(&state.floor_texture));
+    renderer.bind_buffers(&[&state.plane_vbo, &state.plane_ubo]);
+    renderer.draw(0, 6);
+
+    renderer.end();
+
+    state.target.render(gfx, 0, &renderer);
+    state.target.resolve(gfx);
+
+    // post-processing passes, one per enabled effect
+    let screen_texture = state.post.apply(gfx, state.target.texture(0));
+
+    // last pass: draw a quad plane with the processed framebuffer color texture
+    let mut renderer = StatsRenderer::new(gfx);
+
+    renderer.begin(Some(&ClearOptions::color(Color::WHITE)));
+    renderer.set_pipeline(&state.screen_pipeline);
+    renderer.bind_buffers(&[&state.quad_vbo]);
+    renderer.bind_texture(0, screen_texture);
+    renderer.draw(0, 6);
+    renderer.end();
+
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("4_5_1", "Advanced OpenGL", "Framebuffers").with_capture_cursor()
+    }
+
+    fn options() -> Vec<ConfigOption> {
+        vec![
+            ConfigOption::new(
+                "effects",
+                OptionKind::Text,
+                "Post-processing effects in order: inversion, grayscale, sharpen, blur, edge",
+            ),
+            ConfigOption::new(
+                "kernel",
+                OptionKind::Text,
+                "Custom 3x3 kernel, 9 values row by row",
+            ),
+        ]
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+        tweak_camera(ui, &mut self.camera);
+        tweak_post_passes(ui, &mut self.post.passes);
+    }
+}
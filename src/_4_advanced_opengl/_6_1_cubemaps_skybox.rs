@@ -0,0 +1,309 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::utils::{
+    delta_time, present, tweak_camera, tweak_clear_color, AssetManager, Camera, CameraMovement,
+    Skybox, TextureHandle,
+};
+use notan::egui;
+use notan::math::{vec3, Mat4};
+use notan::prelude::*;
+
+// language=glsl
+const CUBE_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec3 aPos;
+    layout (location = 1) in vec2 aTexCoords;
+
+    layout (location = 0) out vec2 TexCoords;
+
+    layout(set = 0, binding = 0) uniform Transform {
+        mat4 model;
+        mat4 view;
+        mat4 projection;
+    };
+
+    void main()
+    {
+        TexCoords = aTexCoords;
+        gl_Position = projection * view * model * vec4(aPos, 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const CUBE_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout(location = 0) in vec2 TexCoords;
+
+    layout(location = 0) out vec4 color;
+
+    layout(binding = 0) uniform sampler2D texture1;
+
+    void main()
+    {
+        color = texture(texture1, TexCoords);
+    }
+  "#
+};
+
+// Represent our transform data
+#[derive(Copy, Clone, Default)]
+#[uniform]
+struct Transform {
+    model: Mat4,
+    view: Mat4,
+    projection: Mat4,
+}
+
+// Create a struct to store the app's state
+pub struct State {
+    pipeline: Pipeline,
+    vbo: Buffer,
+    transform_ubo: Buffer,
+    skybox: Skybox,
+    cube_texture: TextureHandle,
+    cubemap_texture: TextureHandle,
+    assets: AssetManager,
+    camera: Camera,
+    last_x: f32,
+    last_y: f32,
+    first_mouse: bool,
+    clear_color: Color,
+}
+
+// initialize the state and return it to be used by notan
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
+    // Declare the vertex attributes
+    let vertex_info = VertexInfo::new()
+        .attr(0, VertexFormat::Float32x3) // positions
+        .attr(1, VertexFormat::Float32x2); // texture coords
+
+    // Enable depth test
+    let depth_test = DepthStencil {
+        write: true,
+        compare: CompareMode::Less,
+    };
+
+    // build the pipeline
+    let pipeline = gfx
+        .create_pipeline()
+        .from(&CUBE_VERTEX_SHADER, &CUBE_FRAGMENT_SHADER)
+        .with_vertex_info(&vertex_info)
+        .with_depth_stencil(depth_test)
+        .with_texture_location(0, "texture1")
+        .build()
+        .unwrap();
+
+    // define vertex data
+    #[rustfmt::skip]
+    let vertices = [
+        // positions          // texture Coords
+        -0.5, -0.5, -0.5,     0.0, 0.0,
+        0.5, -0.5, -0.5,      1.0, 0.0,
+        0.5,  0.5, -0.5,      1.0, 1.0,
+        0.5,  0.5, -0.5,      1.0, 1.0,
+        -0.5,  0.5, -0.5,     0.0, 1.0,
+        -0.5, -0.5, -0.5,     0.0, 0.0,
+
+        -0.5, -0.5,  0.5,     0.0, 0.0,
+        0.5, -0.5,  0.5,      1.0, 0.0,
+        0.5,  0.5,  0.5,      1.0, 1.0,
+        0.5,  0.5,  0.5,      1.0, 1.0,
+        -0.5,  0.5,  0.5,     0.0, 1.0,
+        -0.5, -0.5,  0.5,     0.0, 0.0,
+
+        -0.5,  0.5,  0.5,     1.0, 0.0,
+        -0.5,  0.5, -0.5,     1.0, 1.0,
+        -0.5, -0.5, -0.5,     0.0, 1.0,
+        -0.5, -0.5, -0.5,     0.0, 1.0,
+        -0.5, -0.5,  0.5,     0.0, 0.0,
+        -0.5,  0.5,  0.5,     1.0, 0.0,
+
+        0.5,  0.5,  0.5,      1.0, 0.0,
+        0.5,  0.5, -0.5,      1.0, 1.0,
+        0.5, -0.5, -0.5,      0.0, 1.0,
+        0.5, -0.5, -0.5,      0.0, 1.0,
+        0.5, -0.5,  0.5,      0.0, 0.0,
+        0.5,  0.5,  0.5,      1.0, 0.0,
+
+        -0.5, -0.5, -0.5,     0.0, 1.0,
+        0.5, -0.5, -0.5,      1.0, 1.0,
+        0.5, -0.5,  0.5,      1.0, 0.0,
+        0.5, -0.5,  0.5,      1.0, 0.0,
+        -0.5, -0.5,  0.5,     0.0, 0.0,
+        -0.5, -0.5, -0.5,     0.0, 1.0,
+
+        -0.5,  0.5, -0.5,     0.0, 1.0,
+        0.5,  0.5, -0.5,      1.0, 1.0,
+        0.5,  0.5,  0.5,      1.0, 0.0,
+        0.5,  0.5,  0.5,      1.0, 0.0,
+        -0.5,  0.5,  0.5,     0.0, 0.0,
+        -0.5,  0.5, -0.5,     0.0, 1.0
+    ];
+
+    // create the vertex buffer object
+    let vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    // create the uniform buffer object
+    let transform_ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
+
+    // the skybox has its own pipeline and buffers
+    let skybox = Skybox::new(gfx).unwrap();
+
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+
+    let cube_texture = assets.load_texture("textures/container.jpg");
+
+    // the faces are packed into a single texture by the asset manager
+    let cubemap_texture = assets.load_cubemap([
+        "textures/skybox/right.jpg",
+        "textures/skybox/left.jpg",
+        "textures/skybox/top.jpg",
+        "textures/skybox/bottom.jpg",
+        "textures/skybox/front.jpg",
+        "textures/skybox/back.jpg",
+    ]);
+
+    let camera = Camera {
+        position: config.camera_position(vec3(0.0, 0.0, 3.0)),
+        ..Default::default()
+    };
+
+    State {
+        pipeline,
+        vbo,
+        transform_ubo,
+        skybox,
+        cube_texture,
+        cubemap_texture,
+        assets,
+        camera,
+        last_x: 0.0,
+        last_y: 0.0,
+        first_mouse: true,
+        clear_color: config.clear_color(Color::from_rgb(0.1, 0.1, 0.1)),
+    }
+}
+
+fn update(app: &mut App, state: &mut State) {
+    // Process all inputs to move the camera
+    let delta = delta_time();
+    if app.keyboard.is_down(KeyCode::W) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Forward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::S) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Backward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::A) {
+        state.camera.process_keyboard(CameraMovement::Left, delta);
+    }
+    if app.keyboard.is_down(KeyCode::D) {
+        state.camera.process_keyboard(CameraMovement::Right, delta);
+    }
+
+    // process mouse move
+    let x = app.mouse.x;
+    let y = app.mouse.y;
+
+    if state.first_mouse {
+        state.first_mouse = false;
+        state.last_x = x;
+        state.last_y = y;
+    }
+
+    let xoffset = x - state.last_x;
+    let yoffset = state.last_y - y;
+    state.last_x = x;
+    state.last_y = y;
+
+    state.camera.process_mouse_movement(xoffset, yoffset, false);
+
+    // process zoom
+    state
+        .camera
+        .process_mouse_scroll(app.mouse.wheel_delta.y * delta);
+}
+
+fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
+    // view/projection transformations
+    let size = gfx.size();
+    let (width, height) = (size.0 as f32, size.1 as f32);
+    let aspect_ratio = width / height;
+    let projection =
+        Mat4::perspective_rh_gl(state.camera.zoom.to_radians(), aspect_ratio, 0.1, 100.0);
+    let view = state.camera.get_view_matrix();
+
+    set_buffer_data!(
+        gfx,
+        &state.transform_ubo,
+        &Transform {
+            model: Mat4::IDENTITY,
+            view,
+            projection,
+        },
+    );
+    state.skybox.update(gfx, view, projection);
+
+    // draw the scene as normal
+    let mut renderer = StatsRenderer::new(gfx);
+
+    let clear = ClearOptions {
+        color: Some(state.clear_color),
+        depth: Some(1.0),
+        stencil: None,
+    };
+
+    renderer.begin(Some(&clear));
+
+    renderer.set_pipeline(&state.pipeline);
+    renderer.bind_buffers(&[&state.vbo, &state.transform_ubo]);
+    renderer.bind_texture(0, state.assets.texture(&state.cube_texture));
+    renderer.draw(0, 36);
+
+    // draw the skybox as last, in the same pass
+    state
+        .skybox
+        .draw(&mut renderer, state.assets.texture(&state.cubemap_texture));
+
+    renderer.end();
+
+    present(gfx, &renderer);
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("4_6_1", "Advanced OpenGL", "Cubemaps skybox").with_capture_cursor()
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+        tweak_camera(ui, &mut self.camera);
+    }
+}
@@ -0,0 +1,525 @@
+use crate::config::Config;
+use crate::example::{Example, ExampleInfo};
+use crate::set_buffer_data;
+use crate::utils::{
+    delta_time, present, tweak_camera, tweak_clear_color, AssetManager, Camera, CameraMovement,
+    EnvironmentMaterial, EnvironmentMaterialData, Skybox, StatsRenderer, TextureHandle,
+    IOR_DIAMOND, IOR_GLASS, IOR_WATER,
+};
+use notan::egui;
+use notan::math::{vec3, Mat4, Vec3};
+use notan::prelude::*;
+
+// language=glsl
+const MATERIAL_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec3 aPos;
+    layout (location = 1) in vec3 aNormal;
+    layout (location = 2) in vec2 aTexCoords;
+
+    layout (location = 0) out vec3 FragPos;
+    layout (location = 1) out vec3 Normal;
+    layout (location = 2) out vec2 TexCoords;
+
+    layout(set = 0, binding = 0) uniform Transform {
+        mat4 model;
+        mat4 view;
+        mat4 projection;
+    };
+
+    void main()
+    {
+        FragPos = vec3(model * vec4(aPos, 1.0));
+        Normal = mat3(transpose(inverse(model))) * aNormal;
+        TexCoords = aTexCoords;
+
+        gl_Position = projection * view * vec4(FragPos, 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const MATERIAL_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+
+    struct DirLight {
+        vec3 direction;
+
+        vec3 ambient;
+        vec3 diffuse;
+        vec3 specular;
+    };
+
+    struct EnvMaterial {
+        uint mode;
+        float ior;
+        float dispersion;
+        float f0;
+    };
+
+    layout(location = 0) in vec3 FragPos;
+    layout(location = 1) in vec3 Normal;
+    layout(location = 2) in vec2 TexCoords;
+
+    layout(location = 0) out vec4 color;
+
+    layout(binding = 0) uniform sampler2D diffuse_texture;
+    layout(binding = 1) uniform sampler2D specular_texture;
+    layout(binding = 2) uniform sampler2D skybox;
+
+    layout(set = 0, binding = 1) uniform MaterialData {
+        vec3 viewPos;
+        DirLight dirLight;
+        float shininess;
+        EnvMaterial env;
+    };
+
+    // SAMPLE_CUBE_GLSL of utils/cubemap.rs, samples the faces packed in a 3x2 grid
+    vec4 sampleCube(sampler2D cube, vec3 dir)
+    {
+        vec3 a = abs(dir);
+        float face;
+        float ma;
+        vec2 st;
+        if (a.x >= a.y && a.x >= a.z) {
+            ma = a.x;
+            face = dir.x > 0.0 ? 0.0 : 1.0;
+            st = dir.x > 0.0 ? vec2(-dir.z, -dir.y) : vec2(dir.z, -dir.y);
+        } else if (a.y >= a.z) {
+            ma = a.y;
+            face = dir.y > 0.0 ? 2.0 : 3.0;
+            st = dir.y > 0.0 ? vec2(dir.x, dir.z) : vec2(dir.x, -dir.z);
+        } else {
+            ma = a.z;
+            face = dir.z > 0.0 ? 4.0 : 5.0;
+            st = dir.z > 0.0 ? vec2(dir.x, -dir.y) : vec2(-dir.x, -dir.y);
+        }
+
+        st = (st / ma + 1.0) * 0.5;
+
+        // keep the samples inside the face to avoid bleeding from the neighbours
+        vec2 faceSize = vec2(textureSize(cube, 0)) / vec2(3.0, 2.0);
+        vec2 halfTexel = 0.5 / faceSize;
+        st = clamp(st, halfTexel, 1.0 - halfTexel);
+
+        vec2 cell = vec2(mod(face, 3.0), floor(face / 3.0));
+        return textureLod(cube, (cell + st) / vec2(3.0, 2.0), 0.0);
+    }
+
+    vec3 CalcDirLight(DirLight light, vec3 normal, vec3 viewDir)
+    {
+        vec3 lightDir = normalize(-light.direction);
+        // diffuse shading
+        float diff = max(dot(normal, lightDir), 0.0);
+        // specular shading
+        vec3 reflectDir = reflect(-lightDir, normal);
+        float spec = pow(max(dot(viewDir, reflectDir), 0.0), shininess);
+        // combine results
+        vec3 ambient = light.ambient * vec3(texture(diffuse_texture, TexCoords));
+        vec3 diffuse = light.diffuse * diff * vec3(texture(diffuse_texture, TexCoords));
+        vec3 specular = light.specular * spec * vec3(texture(specular_texture, TexCoords));
+        return (ambient + diffuse + specular);
+    }
+
+    vec3 CalcRefraction(vec3 I, vec3 normal)
+    {
+        float ratio = 1.0 / env.ior;
+        if (env.dispersion <= 0.0) {
+            return sampleCube(skybox, refract(I, normal, ratio)).rgb;
+        }
+
+        // every channel bends a bit different, the index of refraction is higher for the
+        // shorter wavelengths so the blue bends more than the red
+        float r = sampleCube(skybox, refract(I, normal, ratio * (1.0 + env.dispersion))).r;
+        float g = sampleCube(skybox, refract(I, normal, ratio)).g;
+        float b = sampleCube(skybox, refract(I, normal, ratio * (1.0 - env.dispersion))).b;
+        return vec3(r, g, b);
+    }
+
+    void main()
+    {
+        vec3 norm = normalize(Normal);
+        vec3 viewDir = normalize(viewPos - FragPos);
+        vec3 I = -viewDir;
+
+        vec3 result;
+        if (env.mode == 1u) {
+            // reflective
+            result = sampleCube(skybox, reflect(I, norm)).rgb;
+        } else if (env.mode == 2u) {
+            // refractive
+            result = CalcRefraction(I, norm);
+        } else if (env.mode == 3u) {
+            // fresnel-schlick
+            float cosTheta = max(dot(viewDir, norm), 0.0);
+            float fresnel = env.f0 + (1.0 - env.f0) * pow(1.0 - cosTheta, 5.0);
+            vec3 reflection = sampleCube(skybox, reflect(I, norm)).rgb;
+            vec3 refraction = CalcRefraction(I, norm);
+            result = mix(refraction, reflection, fresnel);
+        } else {
+            // phong
+            result = CalcDirLight(dirLight, norm, viewDir);
+        }
+
+        color = vec4(result, 1.0);
+    }
+  "#
+};
+
+// Represent our transform data
+#[derive(Copy, Clone, Default)]
+#[uniform]
+struct Transform {
+    model: Mat4,
+    view: Mat4,
+    projection: Mat4,
+}
+
+#[derive(Copy, Clone)]
+#[uniform]
+struct DirLight {
+    direction: Vec3,
+    ambient: Vec3,
+    diffuse: Vec3,
+    specular: Vec3,
+}
+
+#[derive(Copy, Clone)]
+#[uniform]
+struct MaterialData {
+    view_pos: Vec3,
+    dir_light: DirLight,
+    shininess: f32,
+    env: EnvironmentMaterialData,
+}
+
+// Every object selects its own material
+struct Object {
+    position: Vec3,
+    material: EnvironmentMaterial,
+}
+
+// Create a struct to store the app's state
+pub struct State {
+    material_pipeline: Pipeline,
+    vbo: Buffer,
+    transform_ubo: Buffer,
+    material_ubo: Buffer,
+    skybox: Skybox,
+    objects: Vec<Object>,
+    diffuse_texture: TextureHandle,
+    specular_texture: TextureHandle,
+    cubemap_texture: TextureHandle,
+    assets: AssetManager,
+    camera: Camera,
+    last_x: f32,
+    last_y: f32,
+    first_mouse: bool,
+    clear_color: Color,
+}
+
+// initialize the state and return it to be used by notan
+fn setup(gfx: &mut Graphics, config: &Config) -> State {
+    // Declare the vertex attributes
+    let vertex_info = VertexInfo::new()
+        .attr(0, VertexFormat::Float32x3) // positions
+        .attr(1, VertexFormat::Float32x3) // normals
+        .attr(2, VertexFormat::Float32x2); // texture coords
+
+    // Enable depth test
+    let depth_test = DepthStencil {
+        write: true,
+        compare: CompareMode::Less,
+    };
+
+    // build the pipeline
+    let material_pipeline = gfx
+        .create_pipeline()
+        .from(&MATERIAL_VERTEX_SHADER, &MATERIAL_FRAGMENT_SHADER)
+        .with_vertex_info(&vertex_info)
+        .with_depth_stencil(depth_test)
+        .with_texture_location(0, "diffuse_texture")
+        .with_texture_location(1, "specular_texture")
+        .with_texture_location(2, "skybox")
+        .build()
+        .unwrap();
+
+    // define vertex data
+    #[rustfmt::skip]
+    let vertices = [
+        // pos                // normals          // tex coords
+        -0.5, -0.5, -0.5,     0.0,  0.0, -1.0,    0.0,  0.0,
+        0.5, -0.5, -0.5,      0.0,  0.0, -1.0,    1.0,  0.0,
+        0.5,  0.5, -0.5,      0.0,  0.0, -1.0,    1.0,  1.0,
+        0.5,  0.5, -0.5,      0.0,  0.0, -1.0,    1.0,  1.0,
+        -0.5,  0.5, -0.5,     0.0,  0.0, -1.0,    0.0,  1.0,
+        -0.5, -0.5, -0.5,     0.0,  0.0, -1.0,    0.0,  0.0,
+
+        -0.5, -0.5,  0.5,     0.0,  0.0,  1.0,    0.0,  0.0,
+        0.5, -0.5,  0.5,      0.0,  0.0,  1.0,    1.0,  0.0,
+        0.5,  0.5,  0.5,      0.0,  0.0,  1.0,    1.0,  1.0,
+        0.5,  0.5,  0.5,      0.0,  0.0,  1.0,    1.0,  1.0,
+        -0.5,  0.5,  0.5,     0.0,  0.0,  1.0,    0.0,  1.0,
+        -0.5, -0.5,  0.5,     0.0,  0.0,  1.0,    0.0,  0.0,
+
+        -0.5,  0.5,  0.5,    -1.0,  0.0,  0.0,    1.0,  0.0,
+        -0.5,  0.5, -0.5,    -1.0,  0.0,  0.0,    1.0,  1.0,
+        -0.5, -0.5, -0.5,    -1.0,  0.0,  0.0,    0.0,  1.0,
+        -0.5, -0.5, -0.5,    -1.0,  0.0,  0.0,    0.0,  1.0,
+        -0.5, -0.5,  0.5,    -1.0,  0.0,  0.0,    0.0,  0.0,
+        -0.5,  0.5,  0.5,    -1.0,  0.0,  0.0,    1.0,  0.0,
+
+        0.5,  0.5,  0.5,      1.0,  0.0,  0.0,    1.0,  0.0,
+        0.5,  0.5, -0.5,      1.0,  0.0,  0.0,    1.0,  1.0,
+        0.5, -0.5, -0.5,      1.0,  0.0,  0.0,    0.0,  1.0,
+        0.5, -0.5, -0.5,      1.0,  0.0,  0.0,    0.0,  1.0,
+        0.5, -0.5,  0.5,      1.0,  0.0,  0.0,    0.0,  0.0,
+        0.5,  0.5,  0.5,      1.0,  0.0,  0.0,    1.0,  0.0,
+
+        -0.5, -0.5, -0.5,     0.0, -1.0,  0.0,    0.0,  1.0,
+        0.5, -0.5, -0.5,      0.0, -1.0,  0.0,    1.0,  1.0,
+        0.5, -0.5,  0.5,      0.0, -1.0,  0.0,    1.0,  0.0,
+        0.5, -0.5,  0.5,      0.0, -1.0,  0.0,    1.0,  0.0,
+        -0.5, -0.5,  0.5,     0.0, -1.0,  0.0,    0.0,  0.0,
+        -0.5, -0.5, -0.5,     0.0, -1.0,  0.0,    0.0,  1.0,
+
+        -0.5,  0.5, -0.5,     0.0,  1.0,  0.0,    0.0,  1.0,
+        0.5,  0.5, -0.5,      0.0,  1.0,  0.0,    1.0,  1.0,
+        0.5,  0.5,  0.5,      0.0,  1.0,  0.0,    1.0,  0.0,
+        0.5,  0.5,  0.5,      0.0,  1.0,  0.0,    1.0,  0.0,
+        -0.5,  0.5,  0.5,     0.0,  1.0,  0.0,    0.0,  0.0,
+        -0.5,  0.5, -0.5,     0.0,  1.0,  0.0,    0.0,  1.0,
+    ];
+
+    // create the vertex buffer object
+    let vbo = gfx
+        .create_vertex_buffer()
+        .with_data(&vertices)
+        .with_info(&vertex_info)
+        .build()
+        .unwrap();
+
+    // create the uniform buffer objects
+    let transform_ubo = gfx.create_uniform_buffer(0, "Transform").build().unwrap();
+    let material_ubo = gfx
+        .create_uniform_buffer(1, "MaterialData")
+        .build()
+        .unwrap();
+
+    // the skybox has its own pipeline and buffers
+    let skybox = Skybox::new(gfx).unwrap();
+
+    // textures are loaded asynchronously by the asset manager
+    let mut assets = AssetManager::new(gfx);
+
+    let diffuse_texture = assets.load_texture("textures/container2.png");
+    let specular_texture = assets.load_texture("textures/container2_specular.png");
+    let cubemap_texture = assets.load_cubemap([
+        "textures/skybox/right.jpg",
+        "textures/skybox/left.jpg",
+        "textures/skybox/top.jpg",
+        "textures/skybox/bottom.jpg",
+        "textures/skybox/front.jpg",
+        "textures/skybox/back.jpg",
+    ]);
+
+    // one object per material mode
+    let objects = vec![
+        Object {
+            position: vec3(-3.0, 0.0, 0.0),
+            material: EnvironmentMaterial::phong(),
+        },
+        Object {
+            position: vec3(-1.5, 0.0, 0.0),
+            material: EnvironmentMaterial::reflective(),
+        },
+        Object {
+            position: vec3(0.0, 0.0, 0.0),
+            material: EnvironmentMaterial::refractive(IOR_GLASS),
+        },
+        Object {
+            position: vec3(1.5, 0.0, 0.0),
+            material: EnvironmentMaterial::fresnel(IOR_WATER),
+        },
+        Object {
+            position: vec3(3.0, 0.0, 0.0),
+            material: EnvironmentMaterial::fresnel(IOR_DIAMOND).with_dispersion(0.02),
+        },
+    ];
+
+    let camera = Camera {
+        position: config.camera_position(vec3(0.0, 0.0, 5.0)),
+        ..Default::default()
+    };
+
+    State {
+        material_pipeline,
+        vbo,
+        transform_ubo,
+        material_ubo,
+        skybox,
+        objects,
+        diffuse_texture,
+        specular_texture,
+        cubemap_texture,
+        assets,
+        camera,
+        last_x: 0.0,
+        last_y: 0.0,
+        first_mouse: true,
+        clear_color: config.clear_color(Color::from_rgb(0.1, 0.1, 0.1)),
+    }
+}
+
+fn update(app: &mut App, state: &mut State) {
+    // Process all inputs to move the camera
+    let delta = delta_time();
+    if app.keyboard.is_down(KeyCode::W) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Forward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::S) {
+        state
+            .camera
+            .process_keyboard(CameraMovement::Backward, delta);
+    }
+    if app.keyboard.is_down(KeyCode::A) {
+        state.camera.process_keyboard(CameraMovement::Left, delta);
+    }
+    if app.keyboard.is_down(KeyCode::D) {
+        state.camera.process_keyboard(CameraMovement::Right, delta);
+    }
+
+    // process mouse move
+    let x = app.mouse.x;
+    let y = app.mouse.y;
+
+    if state.first_mouse {
+        state.first_mouse = false;
+        state.last_x = x;
+        state.last_y = y;
+    }
+
+    let xoffset = x - state.last_x;
+    let yoffset = state.last_y - y;
+    state.last_x = x;
+    state.last_y = y;
+
+    state.camera.process_mouse_movement(xoffset, yoffset, false);
+
+    // process zoom
+    state
+        .camera
+        .process_mouse_scroll(app.mouse.wheel_delta.y * delta);
+}
+
+fn draw(gfx: &mut Graphics, state: &mut State) {
+    // upload the textures that finished loading
+    state.assets.update(gfx);
+
+    // view/projection transformations
+    let size = gfx.size();
+    let (width, height) = (size.0 as f32, size.1 as f32);
+    let aspect_ratio = width / height;
+    let projection =
+        Mat4::perspective_rh_gl(state.camera.zoom.to_radians(), aspect_ratio, 0.1, 100.0);
+    let view = state.camera.get_view_matrix();
+    // the same pose than the view, between the steps with --fixed-fps
+    let camera = state.camera.interpolated_pose();
+
+    // directional light, only used by the phong material
+    let dir_light = DirLight {
+        direction: vec3(-0.2, -1.0, -0.3),
+        ambient: Vec3::splat(0.2),
+        diffuse: Vec3::splat(0.5),
+        specular: Vec3::splat(1.0),
+    };
+
+    state.objects.iter().enumerate().for_each(|(i, obj)| {
+        let angle = 20.0 * i as f32;
+        let translation = Mat4::from_translation(obj.position);
+        let rotation = Mat4::from_axis_angle(vec3(1.0, 0.3, 0.5).normalize(), angle.to_radians());
+        let model = translation * rotation;
+
+        set_buffer_data!(
+            gfx,
+            &state.transform_ubo,
+            &Transform {
+                model,
+                view,
+                projection,
+            },
+        );
+
+        set_buffer_data!(
+            gfx,
+            &state.material_ubo,
+            &MaterialData {
+                view_pos: camera.position,
+                dir_light,
+                shininess: 32.0,
+                env: obj.material.to_uniform(),
+            },
+        );
+
+        let mut renderer = StatsRenderer::new(gfx);
+
+        let clear = if i == 0 {
+            Some(ClearOptions {
+                color: Some(state.clear_color),
+                depth: Some(1.0),
+                stencil: None,
+            })
+        } else {
+            None
+        };
+
+        renderer.begin(clear.as_ref());
+
+        renderer.set_pipeline(&state.material_pipeline);
+        renderer.bind_buffers(&[&state.vbo, &state.transform_ubo, &state.material_ubo]);
+        renderer.bind_texture_slot(0, 0, state.assets.texture(&state.diffuse_texture));
+        renderer.bind_texture_slot(1, 1, state.assets.texture(&state.specular_texture));
+        renderer.bind_texture_slot(2, 2, state.assets.texture(&state.cubemap_texture));
+        renderer.draw(0, 36);
+
+        renderer.end();
+
+        present(gfx, &renderer);
+    });
+
+    // draw the skybox as last
+    state.skybox.render(
+        gfx,
+        state.assets.texture(&state.cubemap_texture),
+        view,
+        projection,
+    );
+}
+
+impl Example for State {
+    fn info() -> ExampleInfo {
+        ExampleInfo::new("4_6_2", "Advanced OpenGL", "Cubemaps environment mapping")
+            .with_capture_cursor()
+    }
+
+    fn setup(_app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        setup(gfx, config)
+    }
+
+    fn update(&mut self, app: &mut App) {
+        update(app, self);
+    }
+
+    fn draw(&mut self, _app: &mut App, gfx: &mut Graphics) {
+        draw(gfx, self);
+    }
+
+    fn tweak(&mut self, ui: &mut egui::Ui) {
+        tweak_clear_color(ui, &mut self.clear_color);
+        tweak_camera(ui, &mut self.camera);
+    }
+}
@@ -0,0 +1,6 @@
+pub mod _10_3_asteroids_instanced;
+pub mod _2_1_stencil_testing;
+pub mod _3_2_blending_sort;
+pub mod _5_1_framebuffers;
+pub mod _6_1_cubemaps_skybox;
+pub mod _6_2_cubemaps_environment_mapping;
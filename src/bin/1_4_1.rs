@@ -0,0 +1,8 @@
+use learn_open_gl_notan::_1_getting_started::_4_1_textures::State;
+use learn_open_gl_notan::example::run_example;
+use notan::prelude::*;
+
+#[notan_main]
+fn main() -> Result<(), String> {
+    run_example::<State>()
+}
@@ -0,0 +1,8 @@
+use learn_open_gl_notan::_2_lighting::_5_4_light_casters_spot_soft::State;
+use learn_open_gl_notan::example::run_example;
+use notan::prelude::*;
+
+#[notan_main]
+fn main() -> Result<(), String> {
+    run_example::<State>()
+}
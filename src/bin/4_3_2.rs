@@ -0,0 +1,8 @@
+use learn_open_gl_notan::_4_advanced_opengl::_3_2_blending_sort::State;
+use learn_open_gl_notan::example::run_example;
+use notan::prelude::*;
+
+#[notan_main]
+fn main() -> Result<(), String> {
+    run_example::<State>()
+}
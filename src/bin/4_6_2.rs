@@ -0,0 +1,8 @@
+use learn_open_gl_notan::_4_advanced_opengl::_6_2_cubemaps_environment_mapping::State;
+use learn_open_gl_notan::example::run_example;
+use notan::prelude::*;
+
+#[notan_main]
+fn main() -> Result<(), String> {
+    run_example::<State>()
+}
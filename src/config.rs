@@ -0,0 +1,473 @@
+use notan::math::{vec3, Vec3};
+use notan::prelude::*;
+use std::collections::HashMap;
+
+// Runtime options of the examples. They are read from the command line on native
+// (`cargo run --bin 2_6_1 -- --width 1280 --msaa 4 --camera 0,1,5`) and from the url query
+// on wasm (`2_6_1.html?width=1280&msaa=4&camera=0,1,5`)
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionKind {
+    // no value means true, but "true" or "false" are valid too
+    Flag,
+    Int,
+    Float,
+    // "x,y,z"
+    Vec3,
+    // "r,g,b", "r,g,b,a" (0.0 - 1.0) or hex "#rrggbb", "#rrggbbaa"
+    Color,
+    Text,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ConfigOption {
+    pub name: &'static str,
+    pub kind: OptionKind,
+    pub help: &'static str,
+}
+
+impl ConfigOption {
+    pub const fn new(name: &'static str, kind: OptionKind, help: &'static str) -> Self {
+        Self { name, kind, help }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum OptionValue {
+    Flag(bool),
+    Int(i64),
+    Float(f32),
+    Vec3(Vec3),
+    Color(Color),
+    Text(String),
+}
+
+// Options available for all the examples
+pub const COMMON_OPTIONS: &[ConfigOption] = &[
+    ConfigOption::new("width", OptionKind::Int, "Window width"),
+    ConfigOption::new("height", OptionKind::Int, "Window height"),
+    ConfigOption::new("vsync", OptionKind::Flag, "Enable vertical sync"),
+    ConfigOption::new("msaa", OptionKind::Int, "MSAA sample count (0, 2, 4, 8)"),
+    ConfigOption::new("camera", OptionKind::Vec3, "Start position of the camera"),
+    ConfigOption::new("clear", OptionKind::Color, "Clear color"),
+    ConfigOption::new(
+        "screenshot",
+        OptionKind::Text,
+        "Save a screenshot here and exit",
+    ),
+    ConfigOption::new(
+        "screenshot-frame",
+        OptionKind::Int,
+        "Frame of the screenshot (10)",
+    ),
+    ConfigOption::new(
+        "record",
+        OptionKind::Text,
+        "Record a gif file or a folder of pngs and exit",
+    ),
+    ConfigOption::new(
+        "record-format",
+        OptionKind::Text,
+        "Format of the recordings (gif, png)",
+    ),
+    ConfigOption::new(
+        "record-fps",
+        OptionKind::Int,
+        "Frames per second of the recordings (30)",
+    ),
+    ConfigOption::new(
+        "record-frames",
+        OptionKind::Int,
+        "Stop recording after this many frames",
+    ),
+    ConfigOption::new(
+        "fixed-fps",
+        OptionKind::Int,
+        "Update at this fixed rate, interpolating the frames",
+    ),
+    ConfigOption::new(
+        "deterministic",
+        OptionKind::Flag,
+        "Advance one fixed step per frame ignoring the real time",
+    ),
+    ConfigOption::new(
+        "input",
+        OptionKind::Text,
+        "Replay this input script (implies --deterministic)",
+    ),
+    ConfigOption::new("stats", OptionKind::Flag, "Show the stats overlay (F2)"),
+    ConfigOption::new("help", OptionKind::Flag, "Show the available options"),
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    values: HashMap<String, OptionValue>,
+}
+
+impl Config {
+    // Reads the options from the command line or the url query
+    pub fn from_env(options: &[ConfigOption]) -> Result<Self, String> {
+        #[cfg(target_arch = "wasm32")]
+        {
+            let query = web_sys::window()
+                .and_then(|window| window.location().search().ok())
+                .unwrap_or_default();
+            Self::from_query(&query, options)
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Self::from_args(std::env::args().skip(1), options)
+        }
+    }
+
+    // Parses `--name value`, `--name=value` and `--flag`
+    pub fn from_args<I, S>(args: I, options: &[ConfigOption]) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let args = args
+            .into_iter()
+            .map(|arg| arg.as_ref().to_string())
+            .collect::<Vec<_>>();
+
+        let mut pairs = vec![];
+        let mut i = 0;
+        while i < args.len() {
+            let arg = &args[i];
+            let name = arg
+                .strip_prefix("--")
+                .ok_or_else(|| format!("Invalid argument '{}', options start with '--'", arg))?;
+
+            i += 1;
+            match name.split_once('=') {
+                Some((name, value)) => pairs.push((name.to_string(), Some(value.to_string()))),
+                None => {
+                    // flags don't need a value, the rest take the next argument
+                    let kind = find_option(name, options)?.kind;
+                    let value = if kind == OptionKind::Flag {
+                        None
+                    } else {
+                        let value = args
+                            .get(i)
+                            .ok_or_else(|| format!("Missing value for '--{}'", name))?;
+                        i += 1;
+                        Some(value.clone())
+                    };
+                    pairs.push((name.to_string(), value));
+                }
+            }
+        }
+
+        Self::from_pairs(pairs, options)
+    }
+
+    // Parses `?name=value&flag`
+    pub fn from_query(query: &str, options: &[ConfigOption]) -> Result<Self, String> {
+        let pairs = query
+            .trim_start_matches('?')
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((name, value)) => (decode_uri(name), Some(decode_uri(value))),
+                None => (decode_uri(pair), None),
+            })
+            .collect::<Vec<_>>();
+
+        Self::from_pairs(pairs, options)
+    }
+
+    fn from_pairs(
+        pairs: Vec<(String, Option<String>)>,
+        options: &[ConfigOption],
+    ) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        for (name, value) in pairs {
+            let option = find_option(&name, options)?;
+            let value = parse_value(option.kind, value.as_deref())
+                .map_err(|err| format!("Invalid value for '{}': {}", name, err))?;
+            values.insert(name, value);
+        }
+
+        Ok(Self { values })
+    }
+
+    // Text listing the common options and the ones passed in
+    pub fn usage(options: &[ConfigOption]) -> String {
+        COMMON_OPTIONS
+            .iter()
+            .chain(options.iter())
+            .map(|opt| {
+                let kind = format!("{:?}", opt.kind);
+                format!("  --{:<18} {:<7} {}", opt.name, kind, opt.help)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn window_config(&self, title: &str) -> WindowConfig {
+        let mut config = WindowConfig::new()
+            .title(title)
+            .vsync(self.flag("vsync"))
+            .multisampling(self.int("msaa").unwrap_or(0).clamp(0, 16) as _);
+
+        if self.int("width").is_some() || self.int("height").is_some() {
+            let width = self.int("width").unwrap_or(800);
+            let height = self.int("height").unwrap_or(600);
+            config = config.size(width as _, height as _);
+        }
+
+        config
+    }
+
+    pub fn clear_color(&self, default: Color) -> Color {
+        self.color("clear").unwrap_or(default)
+    }
+
+    pub fn camera_position(&self, default: Vec3) -> Vec3 {
+        self.vec3("camera").unwrap_or(default)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OptionValue> {
+        self.values.get(name)
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        matches!(self.get(name), Some(OptionValue::Flag(true)))
+    }
+
+    pub fn int(&self, name: &str) -> Option<i64> {
+        match self.get(name) {
+            Some(OptionValue::Int(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn float(&self, name: &str) -> Option<f32> {
+        match self.get(name) {
+            Some(OptionValue::Float(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn vec3(&self, name: &str) -> Option<Vec3> {
+        match self.get(name) {
+            Some(OptionValue::Vec3(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn color(&self, name: &str) -> Option<Color> {
+        match self.get(name) {
+            Some(OptionValue::Color(v)) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, name: &str) -> Option<&str> {
+        match self.get(name) {
+            Some(OptionValue::Text(v)) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+fn find_option(name: &str, options: &[ConfigOption]) -> Result<ConfigOption, String> {
+    COMMON_OPTIONS
+        .iter()
+        .chain(options.iter())
+        .find(|opt| opt.name == name)
+        .copied()
+        .ok_or_else(|| format!("Unknown option '{}'\n{}", name, Config::usage(options)))
+}
+
+fn parse_value(kind: OptionKind, value: Option<&str>) -> Result<OptionValue, String> {
+    if kind == OptionKind::Flag {
+        return match value {
+            None | Some("true") | Some("1") => Ok(OptionValue::Flag(true)),
+            Some("false") | Some("0") => Ok(OptionValue::Flag(false)),
+            Some(v) => Err(format!("'{}' is not true or false", v)),
+        };
+    }
+
+    let value = value.ok_or_else(|| "missing value".to_string())?;
+    match kind {
+        OptionKind::Int => value
+            .parse()
+            .map(OptionValue::Int)
+            .map_err(|_| format!("'{}' is not an integer", value)),
+        OptionKind::Float => parse_float(value).map(OptionValue::Float),
+        OptionKind::Vec3 => match parse_floats(value)?.as_slice() {
+            [x, y, z] => Ok(OptionValue::Vec3(vec3(*x, *y, *z))),
+            _ => Err(format!("'{}' is not 'x,y,z'", value)),
+        },
+        OptionKind::Color => parse_color(value).map(OptionValue::Color),
+        _ => Ok(OptionValue::Text(value.to_string())),
+    }
+}
+
+fn parse_float(value: &str) -> Result<f32, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))
+}
+
+fn parse_floats(value: &str) -> Result<Vec<f32>, String> {
+    value.split(',').map(parse_float).collect()
+}
+
+fn parse_color(value: &str) -> Result<Color, String> {
+    let hex = value.trim_start_matches('#');
+    let is_hex = (hex.len() == 6 || hex.len() == 8) && hex.chars().all(|c| c.is_ascii_hexdigit());
+    if is_hex {
+        let hex = if hex.len() == 6 {
+            format!("{}ff", hex)
+        } else {
+            hex.to_string()
+        };
+        let rgba = u32::from_str_radix(&hex, 16).map_err(|err| err.to_string())?;
+        return Ok(Color::from_hex(rgba));
+    }
+
+    match parse_floats(value)?.as_slice() {
+        [r, g, b] => Ok(Color::from_rgb(*r, *g, *b)),
+        [r, g, b, a] => Ok(Color::new(*r, *g, *b, *a)),
+        _ => Err(format!(
+            "'{}' is not 'r,g,b', 'r,g,b,a' or '#rrggbb'",
+            value
+        )),
+    }
+}
+
+// decodes the %XX sequences of the url
+fn decode_uri(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                out.push(byte);
+                i += 3;
+            }
+            (b'+', _) => {
+                out.push(b' ');
+                i += 1;
+            }
+            (byte, _) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTIONS: &[ConfigOption] = &[
+        ConfigOption::new("speed", OptionKind::Float, "Speed"),
+        ConfigOption::new("title", OptionKind::Text, "Title"),
+    ];
+
+    #[test]
+    fn args_are_parsed() {
+        let config = Config::from_args(
+            [
+                "--width",
+                "1280",
+                "--camera=0,1,5",
+                "--vsync",
+                "--speed",
+                "2.5",
+                "--stats=false",
+            ],
+            OPTIONS,
+        )
+        .unwrap();
+        assert_eq!(config.int("width"), Some(1280));
+        assert_eq!(config.camera_position(Vec3::ZERO), vec3(0.0, 1.0, 5.0));
+        assert!(config.flag("vsync"));
+        assert!(!config.flag("stats"));
+        assert_eq!(config.float("speed"), Some(2.5));
+        assert_eq!(config.int("height"), None);
+
+        // a flag doesn't take the next argument
+        let config = Config::from_args(["--vsync", "--msaa", "4"], OPTIONS).unwrap();
+        assert!(config.flag("vsync"));
+        assert_eq!(config.int("msaa"), Some(4));
+
+        let err = Config::from_args(["--width"], OPTIONS).unwrap_err();
+        assert_eq!(err, "Missing value for '--width'");
+        assert!(Config::from_args(["width", "10"], OPTIONS).is_err());
+        assert!(Config::from_args(["--depth", "10"], OPTIONS).is_err());
+        assert!(Config::from_args(["--width", "wide"], OPTIONS).is_err());
+        assert!(Config::from_args(["--width="], OPTIONS).is_err());
+    }
+
+    #[test]
+    fn queries_are_parsed() {
+        let config = Config::from_query(
+            "?width=1280&msaa=4&vsync&title=Hello%20world+again&clear=%23ff8000",
+            OPTIONS,
+        )
+        .unwrap();
+        assert_eq!(config.int("width"), Some(1280));
+        assert_eq!(config.int("msaa"), Some(4));
+        assert!(config.flag("vsync"));
+        assert_eq!(config.text("title"), Some("Hello world again"));
+        assert_eq!(
+            config.clear_color(Color::BLACK),
+            Color::new(1.0, 128.0 / 255.0, 0.0, 1.0)
+        );
+
+        assert!(Config::from_query("", OPTIONS)
+            .unwrap()
+            .get("width")
+            .is_none());
+        assert!(Config::from_query("?width", OPTIONS).is_err());
+    }
+
+    #[test]
+    fn uris_are_decoded() {
+        assert_eq!(decode_uri("a%20b+c"), "a b c");
+        assert_eq!(decode_uri("%2C%2c"), ",,");
+        assert_eq!(decode_uri("%C3%A9t%C3%A9"), "été");
+        // incomplete sequences are kept as they are
+        assert_eq!(decode_uri("100%"), "100%");
+        assert_eq!(decode_uri("%zz"), "%zz");
+    }
+
+    #[test]
+    fn colors_are_parsed() {
+        assert_eq!(
+            parse_color("#ff000080").unwrap(),
+            Color::new(1.0, 0.0, 0.0, 128.0 / 255.0)
+        );
+        assert_eq!(
+            parse_color("00ff00").unwrap(),
+            Color::new(0.0, 1.0, 0.0, 1.0)
+        );
+        assert_eq!(
+            parse_color("0.1, 0.2, 0.3").unwrap(),
+            Color::new(0.1, 0.2, 0.3, 1.0)
+        );
+        assert_eq!(
+            parse_color("0.1,0.2,0.3,0.5").unwrap(),
+            Color::new(0.1, 0.2, 0.3, 0.5)
+        );
+        assert!(parse_color("#ff00").is_err());
+        assert!(parse_color("0.1,0.2").is_err());
+        assert!(parse_color("red").is_err());
+    }
+}
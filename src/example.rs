@@ -0,0 +1,526 @@
+use crate::config::{Config, ConfigOption};
+use crate::utils::{
+    advance_clock, begin_capture, clear_input_events, end_capture, frame_stats, reset_clock,
+    reset_frame_stats, set_interpolation_alpha, stats_panel, CapturedFrame, FixedTimestep,
+    FrameStats, FrameTimes, InputScript, PendingInput, RecordFormat, Recorder,
+};
+use notan::egui::{self, EguiConfig, EguiPluginSugar};
+use notan::prelude::*;
+
+const IS_WASM: bool = cfg!(target_arch = "wasm32");
+
+// Metadata of an example, used by the launcher and the window title
+#[derive(Clone, Copy, Debug)]
+pub struct ExampleInfo {
+    // same as the binary name, like "1_4_1"
+    pub id: &'static str,
+    pub chapter: &'static str,
+    pub title: &'static str,
+    // examples with a fps camera capture the cursor on start and on click
+    pub capture_cursor: bool,
+}
+
+impl ExampleInfo {
+    pub const fn new(id: &'static str, chapter: &'static str, title: &'static str) -> Self {
+        Self {
+            id,
+            chapter,
+            title,
+            capture_cursor: false,
+        }
+    }
+
+    pub const fn with_capture_cursor(mut self) -> Self {
+        self.capture_cursor = true;
+        self
+    }
+}
+
+// Every example implements this trait, the runner takes care of everything else
+// (closing with esc, capturing the cursor, detecting resizes...)
+pub trait Example {
+    fn info() -> ExampleInfo
+    where
+        Self: Sized;
+
+    // extra options of this example, besides the common ones of the config
+    fn options() -> Vec<ConfigOption>
+    where
+        Self: Sized,
+    {
+        vec![]
+    }
+
+    fn setup(app: &mut App, gfx: &mut Graphics, config: &Config) -> Self
+    where
+        Self: Sized;
+
+    fn update(&mut self, _app: &mut App) {}
+
+    fn draw(&mut self, _app: &mut App, _gfx: &mut Graphics) {}
+
+    // called before draw when the size of the framebuffer changes
+    fn resize(&mut self, _app: &mut App, _gfx: &mut Graphics, _width: u32, _height: u32) {}
+
+    // called after draw to render anything on top of the example
+    fn ui(&mut self, _app: &mut App, _gfx: &mut Graphics) {}
+
+    // called inside the tweak panel (F1) to expose the parameters of the example
+    fn tweak(&mut self, _ui: &mut egui::Ui) {}
+}
+
+pub type CreateExample = fn(&mut App, &mut Graphics, &Config) -> Box<dyn Example>;
+
+#[derive(Clone, Copy)]
+pub struct ExampleEntry {
+    pub info: ExampleInfo,
+    pub options: fn() -> Vec<ConfigOption>,
+    pub create: CreateExample,
+}
+
+impl ExampleEntry {
+    pub fn of<E: Example + 'static>() -> Self {
+        Self {
+            info: E::info(),
+            options: E::options,
+            create: |app, gfx, config| Box::new(E::setup(app, gfx, config)),
+        }
+    }
+}
+
+// How the recordings are made, from the config
+#[derive(Clone, Copy, Debug)]
+struct RecordSettings {
+    format: Option<RecordFormat>,
+    fps: u32,
+    max_frames: Option<u32>,
+}
+
+impl RecordSettings {
+    fn from_config(config: &Config) -> Result<Self, String> {
+        Ok(Self {
+            format: config
+                .text("record-format")
+                .map(RecordFormat::from_name)
+                .transpose()?,
+            fps: config.int("record-fps").unwrap_or(30).clamp(1, 120) as _,
+            max_frames: config.int("record-frames").map(|frames| frames.max(1) as _),
+        })
+    }
+}
+
+// How the time of the example advances
+enum TimeMode {
+    // the real time of each frame
+    Real,
+    // the real time split in steps of the same size
+    Fixed(FixedTimestep, PendingInput),
+    // one step per frame no matter how long the frames take, the runs are reproducible
+    Synthetic(f32),
+}
+
+impl TimeMode {
+    fn from_config(config: &Config) -> Self {
+        let fps = config.int("fixed-fps").map(|fps| fps.clamp(1, 1000) as u32);
+        if config.flag("deterministic") || config.text("input").is_some() {
+            TimeMode::Synthetic(1.0 / fps.unwrap_or(60) as f32)
+        } else if let Some(fps) = fps {
+            TimeMode::Fixed(FixedTimestep::new(fps), PendingInput::default())
+        } else {
+            TimeMode::Real
+        }
+    }
+}
+
+// Holds a running example and gives it the behaviour shared by all the examples
+pub struct ExampleRunner {
+    info: ExampleInfo,
+    example: Box<dyn Example>,
+    size: (u32, u32),
+    frame: u64,
+    // path of the screenshot requested with F12
+    screenshot: Option<String>,
+    // screenshot requested by the config, the app closes after taking it
+    screenshot_at: Option<(u64, String)>,
+    record_settings: RecordSettings,
+    recorder: Option<Recorder>,
+    // the recording was requested by the config, the app closes after it
+    exit_after_recording: bool,
+    time_mode: TimeMode,
+    // scripted input that replaces the real one
+    input: Option<InputScript>,
+    show_tweaks: bool,
+    // position of the mouse when the tweak panel was opened, the example keeps seeing it
+    // so the camera doesn't turn while using the panel
+    frozen_mouse: Option<(f32, f32)>,
+    show_stats: bool,
+    frame_times: FrameTimes,
+    // stats of the last frame drawn
+    stats: FrameStats,
+}
+
+impl ExampleRunner {
+    pub fn new(entry: &ExampleEntry, app: &mut App, gfx: &mut Graphics, config: &Config) -> Self {
+        if entry.info.capture_cursor {
+            app.window().set_capture_cursor(true);
+        }
+
+        let record_settings = RecordSettings::from_config(config).unwrap_or_else(|err| {
+            notan::log::error!("{}", err);
+            RecordSettings {
+                format: None,
+                fps: 30,
+                max_frames: None,
+            }
+        });
+
+        // the examples start from 0 even if the launcher was open before
+        reset_clock();
+
+        let (width, height) = gfx.size();
+        let mut runner = Self {
+            info: entry.info,
+            example: (entry.create)(app, gfx, config),
+            size: (width as u32, height as u32),
+            frame: 0,
+            screenshot: None,
+            screenshot_at: config.text("screenshot").map(|path| {
+                let frame = config.int("screenshot-frame").unwrap_or(10).max(0) as u64;
+                (frame, path.to_string())
+            }),
+            record_settings,
+            recorder: None,
+            exit_after_recording: false,
+            time_mode: TimeMode::from_config(config),
+            input: config.text("input").and_then(|path| {
+                InputScript::load(path)
+                    .map_err(|err| notan::log::error!("{}", err))
+                    .ok()
+            }),
+            show_tweaks: false,
+            frozen_mouse: None,
+            show_stats: config.flag("stats"),
+            frame_times: FrameTimes::new(240),
+            stats: FrameStats::default(),
+        };
+
+        if let Some(path) = config.text("record") {
+            runner.exit_after_recording = true;
+            runner.start_recording(path);
+        }
+
+        runner
+    }
+
+    pub fn info(&self) -> &ExampleInfo {
+        &self.info
+    }
+
+    pub fn update(&mut self, app: &mut App) {
+        // capture the cursor (wasm32 allow escape the cursor using ESC)
+        if self.info.capture_cursor
+            && !self.show_tweaks
+            && app.mouse.was_pressed(MouseButton::Left)
+            && !app.window().capture_cursor()
+        {
+            app.window().set_capture_cursor(true);
+        }
+
+        // F12 saves the next frame, it can be scaled down with `scripts/resize.sh`
+        if app.keyboard.was_pressed(KeyCode::F12) {
+            self.screenshot = Some(format!("./screenshots/{}.source.png", self.info.id));
+        }
+
+        // F1 shows the tweak panel, the cursor is released to use it
+        if app.keyboard.was_pressed(KeyCode::F1) {
+            self.show_tweaks = !self.show_tweaks;
+            if self.show_tweaks {
+                self.frozen_mouse = Some((app.mouse.x, app.mouse.y));
+                app.window().set_capture_cursor(false);
+            } else {
+                self.frozen_mouse = None;
+                if self.info.capture_cursor {
+                    app.window().set_capture_cursor(true);
+                }
+            }
+        }
+
+        // F2 shows the stats overlay
+        if app.keyboard.was_pressed(KeyCode::F2) {
+            self.show_stats = !self.show_stats;
+        }
+
+        // F9 starts and stops recording
+        if app.keyboard.was_pressed(KeyCode::F9) {
+            if self.recorder.is_some() {
+                self.stop_recording(app);
+            } else {
+                let path = match self.record_settings.format {
+                    Some(RecordFormat::Png) => format!("./recordings/{}", self.info.id),
+                    _ => format!("./recordings/{}.gif", self.info.id),
+                };
+                self.start_recording(&path);
+            }
+        }
+
+        // while recording the time goes at the pace of the clip instead of the real time
+        let (steps, delta) = match (&self.recorder, &mut self.time_mode) {
+            (Some(recorder), _) => (1, recorder.step()),
+            (None, TimeMode::Real) => (1, app.timer.delta_f32()),
+            (None, TimeMode::Fixed(timestep, pending)) => {
+                let steps = timestep.accumulate(app.timer.delta_f32());
+                if steps == 0 {
+                    pending.store(app);
+                } else {
+                    pending.restore(app);
+                }
+                (steps, timestep.step())
+            }
+            (None, TimeMode::Synthetic(step)) => (1, *step),
+        };
+
+        if let Some(input) = self.input.as_mut() {
+            input.apply(self.frame, delta, app);
+        }
+
+        if let Some((x, y)) = self.frozen_mouse {
+            app.mouse.x = x;
+            app.mouse.y = y;
+            app.mouse.wheel_delta = Default::default();
+        }
+
+        (0..steps).for_each(|i| {
+            if i > 0 {
+                clear_input_events(app);
+            }
+
+            advance_clock(delta);
+            self.example.update(app);
+        });
+
+        let alpha = match &self.time_mode {
+            TimeMode::Fixed(timestep, _) if self.recorder.is_none() => timestep.alpha(),
+            _ => 0.0,
+        };
+        set_interpolation_alpha(alpha);
+    }
+
+    pub fn draw(&mut self, app: &mut App, gfx: &mut Graphics, plugins: &mut Plugins) {
+        reset_frame_stats();
+        self.frame_times.push(app.timer.delta_f32() * 1000.0);
+
+        let (width, height) = gfx.size();
+        let size = (width as u32, height as u32);
+        if size != self.size {
+            self.size = size;
+            self.example.resize(app, gfx, size.0, size.1);
+        }
+
+        let scheduled = match &self.screenshot_at {
+            Some((frame, path)) if *frame == self.frame => Some(path.clone()),
+            _ => None,
+        };
+        let exit = scheduled.is_some();
+
+        let screenshot = scheduled.or_else(|| self.screenshot.take());
+        if screenshot.is_none() && self.recorder.is_none() {
+            self.example.draw(app, gfx);
+        } else if let Some(frame) = self.draw_captured(app, gfx) {
+            if let Some(path) = screenshot {
+                match frame.save_png(&path) {
+                    Ok(_) => notan::log::info!("Screenshot saved to '{}'", path),
+                    Err(err) => notan::log::error!("Cannot take the screenshot: {}", err),
+                }
+            }
+
+            self.record_frame(app, &frame);
+        }
+
+        self.example.ui(app, gfx);
+        self.stats = frame_stats();
+
+        // the panels are drawn directly to the screen, so they're not captured
+        if self.show_tweaks || self.show_stats {
+            let (show_tweaks, show_stats) = (self.show_tweaks, self.show_stats);
+            let title = self.info.title;
+            let example = &mut self.example;
+            let (times, stats) = (&self.frame_times, &self.stats);
+            let output = plugins.egui(|ctx| {
+                if show_tweaks {
+                    egui::Window::new(title).show(ctx, |ui| example.tweak(ui));
+                }
+
+                if show_stats {
+                    egui::Window::new("Stats")
+                        .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+                        .show(ctx, |ui| stats_panel(ui, times, stats));
+                }
+            });
+            gfx.render(&output);
+        }
+        self.frame += 1;
+
+        if exit {
+            app.exit();
+        }
+    }
+
+    // draws the example to a texture to read the frame
+    fn draw_captured(&mut self, app: &mut App, gfx: &mut Graphics) -> Option<CapturedFrame> {
+        if let Err(err) = begin_capture(gfx) {
+            notan::log::error!("Cannot capture the frame: {}", err);
+            self.example.draw(app, gfx);
+            return None;
+        }
+
+        self.example.draw(app, gfx);
+
+        end_capture(gfx)
+            .map_err(|err| notan::log::error!("Cannot capture the frame: {}", err))
+            .ok()
+    }
+
+    fn start_recording(&mut self, path: &str) {
+        let settings = self.record_settings;
+        let format = settings
+            .format
+            .unwrap_or_else(|| RecordFormat::from_path(path));
+
+        match Recorder::new(path, format, settings.fps, settings.max_frames) {
+            Ok(recorder) => {
+                notan::log::info!("Recording to '{}'", path);
+                self.recorder = Some(recorder);
+            }
+            Err(err) => notan::log::error!("Cannot start recording: {}", err),
+        }
+    }
+
+    fn record_frame(&mut self, app: &mut App, frame: &CapturedFrame) {
+        let recorder = match self.recorder.as_mut() {
+            Some(recorder) => recorder,
+            None => return,
+        };
+
+        if let Err(err) = recorder.add_frame(frame) {
+            notan::log::error!("Cannot record the frame: {}", err);
+            self.stop_recording(app);
+            return;
+        }
+
+        if recorder.is_full() {
+            self.stop_recording(app);
+        }
+    }
+
+    fn stop_recording(&mut self, app: &mut App) {
+        if let Some(recorder) = self.recorder.take() {
+            let path = recorder.path().to_string();
+            match recorder.finish() {
+                Ok(frames) => notan::log::info!("Recorded {} frames to '{}'", frames, path),
+                Err(err) => notan::log::error!("Cannot save the recording: {}", err),
+            }
+        }
+
+        if self.exit_after_recording {
+            app.exit();
+        }
+    }
+}
+
+#[derive(AppState)]
+struct RunnerState {
+    runner: ExampleRunner,
+}
+
+// Runs a single example, used by the binaries of each example
+pub fn run_example<E: Example + 'static>() -> Result<(), String> {
+    let entry = ExampleEntry::of::<E>();
+    let options = (entry.options)();
+    let config = Config::from_env(&options)?;
+    if config.flag("help") {
+        println!("Options:\n{}", Config::usage(&options));
+        return Ok(());
+    }
+
+    let title = format!("{} - {}", entry.info.id, entry.info.title);
+    let window = config.window_config(&title);
+
+    notan::init_with(move |app: &mut App, gfx: &mut Graphics| RunnerState {
+        runner: ExampleRunner::new(&entry, app, gfx, &config),
+    })
+    .add_config(window)
+    .add_config(EguiConfig)
+    .update(|app: &mut App, state: &mut RunnerState| {
+        // if esc is pressed close the app
+        if !IS_WASM && app.keyboard.was_pressed(KeyCode::Escape) {
+            app.exit();
+            return;
+        }
+
+        state.runner.update(app);
+    })
+    .draw(
+        |app: &mut App, gfx: &mut Graphics, plugins: &mut Plugins, state: &mut RunnerState| {
+            state.runner.draw(app, gfx, plugins)
+        },
+    )
+    .build()
+}
+
+// All the examples, in the same order as the tutorials
+pub fn examples() -> Vec<ExampleEntry> {
+    vec![
+        ExampleEntry::of::<crate::_1_getting_started::_1_1_hello_window::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_1_2_hello_window_clear::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_2_1_hello_triangle::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_2_2_hello_triangle_indexed::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_2_3_hello_triangle_exercise1::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_2_4_hello_triangle_exercise2::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_2_5_hello_triangle_exercise3::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_3_1_shaders_uniform::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_3_2_shaders_interpolation::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_3_4_shaders_exercise1::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_3_5_shaders_exercise2::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_3_6_shaders_exercise3::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_4_1_textures::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_4_2_textures_combined::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_4_3_textures_exercise1::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_4_4_textures_exercise2::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_4_5_textures_exercise3::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_4_6_textures_exercise4::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_4_7_textures_mipmaps::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_5_1_transformations::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_5_2_transformations_exercise1::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_5_3_transformations_exercise2::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_6_1_coordinate_systems::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_6_2_coordinate_systems_depth::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_6_3_coordinate_systems_multiple::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_6_4_coordinate_systems_exercise3::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_7_1_camera_circle::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_7_2_camera_keyboard_dt::State>(),
+        ExampleEntry::of::<crate::_1_getting_started::_7_3_camera_mouse_zoom::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_1_1_colors::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_2_1_basic_lighting_diffuse::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_2_2_basic_lighting_specular::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_2_3_basic_lighting_exercise1::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_2_4_basic_lighting_exercise2::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_2_5_basic_lighting_exercise3::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_3_1_materials::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_3_2_materials_exercise1::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_4_1_lighting_maps_diffuse::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_4_2_lighting_maps_specular::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_4_3_lighting_maps_exercise2::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_4_4_lighting_maps_exercise4::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_5_1_light_casters_directional::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_5_2_light_casters_point::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_5_3_light_casters_spot::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_5_4_light_casters_spot_soft::State>(),
+        ExampleEntry::of::<crate::_2_lighting::_6_1_multiple_lights::State>(),
+        ExampleEntry::of::<crate::_4_advanced_opengl::_2_1_stencil_testing::State>(),
+        ExampleEntry::of::<crate::_4_advanced_opengl::_3_2_blending_sort::State>(),
+        ExampleEntry::of::<crate::_4_advanced_opengl::_5_1_framebuffers::State>(),
+        ExampleEntry::of::<crate::_4_advanced_opengl::_6_1_cubemaps_skybox::State>(),
+        ExampleEntry::of::<crate::_4_advanced_opengl::_6_2_cubemaps_environment_mapping::State>(),
+        ExampleEntry::of::<crate::_4_advanced_opengl::_10_3_asteroids_instanced::State>(),
+    ]
+}
@@ -1 +1,6 @@
+pub mod _1_getting_started;
+pub mod _2_lighting;
+pub mod _4_advanced_opengl;
+pub mod config;
+pub mod example;
 pub mod utils;
@@ -1,3 +1,229 @@
-pub mod utils;
+use learn_open_gl_notan::config::{Config, ConfigOption};
+use learn_open_gl_notan::example::{examples, ExampleEntry, ExampleRunner};
+use notan::draw::*;
+use notan::egui::EguiConfig;
+use notan::prelude::*;
 
-fn main() {}
+const IS_WASM: bool = cfg!(target_arch = "wasm32");
+
+const PADDING: f32 = 20.0;
+const LINE_HEIGHT: f32 = 26.0;
+const HEADER_SIZE: f32 = 26.0;
+const ITEM_SIZE: f32 = 20.0;
+
+// The menu is a list of chapter headers followed by its examples
+enum Row {
+    Chapter(&'static str),
+    Example(usize),
+}
+
+fn menu_rows(examples: &[ExampleEntry]) -> Vec<Row> {
+    let mut rows = vec![];
+    let mut chapter = "";
+    examples.iter().enumerate().for_each(|(i, entry)| {
+        if entry.info.chapter != chapter {
+            chapter = entry.info.chapter;
+            rows.push(Row::Chapter(chapter));
+        }
+        rows.push(Row::Example(i));
+    });
+    rows
+}
+
+#[derive(AppState)]
+struct State {
+    font: Font,
+    config: Config,
+    examples: Vec<ExampleEntry>,
+    rows: Vec<Row>,
+    selected: usize,
+    scroll: f32,
+    last_mouse: (f32, f32),
+    // example requested by the menu, it's created on draw because it needs the graphics
+    launch: Option<usize>,
+    current: Option<ExampleRunner>,
+}
+
+impl State {
+    fn row_of(&self, index: usize) -> usize {
+        self.rows
+            .iter()
+            .position(|row| matches!(row, Row::Example(i) if *i == index))
+            .unwrap_or(0)
+    }
+
+    // scroll the menu to keep the selected example inside the window
+    fn scroll_to_selected(&mut self, height: f32) {
+        let y = self.row_of(self.selected) as f32 * LINE_HEIGHT;
+        if y < self.scroll {
+            self.scroll = y;
+        } else if y + LINE_HEIGHT > self.scroll + height - PADDING * 2.0 {
+            self.scroll = y + LINE_HEIGHT - (height - PADDING * 2.0);
+        }
+    }
+
+    fn max_scroll(&self, height: f32) -> f32 {
+        (self.rows.len() as f32 * LINE_HEIGHT - (height - PADDING * 2.0)).max(0.0)
+    }
+}
+
+// the launcher accepts the options of every example
+fn all_options(examples: &[ExampleEntry]) -> Vec<ConfigOption> {
+    let mut options: Vec<ConfigOption> = vec![];
+    examples.iter().for_each(|entry| {
+        (entry.options)().into_iter().for_each(|opt| {
+            if !options.iter().any(|o| o.name == opt.name) {
+                options.push(opt);
+            }
+        });
+    });
+    options
+}
+
+#[notan_main]
+fn main() -> Result<(), String> {
+    let options = all_options(&examples());
+    let config = Config::from_env(&options)?;
+    if config.flag("help") {
+        println!("Options:\n{}", Config::usage(&options));
+        return Ok(());
+    }
+
+    let window = config.window_config("LearnOpenGL-Notan");
+
+    notan::init_with(move |gfx: &mut Graphics| setup(gfx, config))
+        .add_config(window)
+        .add_config(DrawConfig)
+        .add_config(EguiConfig)
+        .update(update)
+        .draw(draw)
+        .build()
+}
+
+fn setup(gfx: &mut Graphics, config: Config) -> State {
+    let font = gfx
+        .create_font(include_bytes!("../resources/fonts/Antonio-Regular.ttf"))
+        .unwrap();
+
+    let examples = examples();
+    State {
+        font,
+        config,
+        rows: menu_rows(&examples),
+        examples,
+        selected: 0,
+        scroll: 0.0,
+        last_mouse: (0.0, 0.0),
+        launch: None,
+        current: None,
+    }
+}
+
+fn update(app: &mut App, state: &mut State) {
+    if let Some(runner) = state.current.as_mut() {
+        // esc goes back to the menu instead of closing the app
+        if app.keyboard.was_pressed(KeyCode::Escape) {
+            state.current = None;
+            app.window().set_capture_cursor(false);
+            return;
+        }
+
+        runner.update(app);
+        return;
+    }
+
+    // if esc is pressed in the menu close the app
+    if app.keyboard.was_pressed(KeyCode::Escape) && !IS_WASM {
+        app.exit();
+        return;
+    }
+
+    let (_, height) = app.window().size();
+    let height = height as f32;
+
+    // keyboard navigation
+    if app.keyboard.was_pressed(KeyCode::Down) {
+        state.selected = (state.selected + 1).min(state.examples.len() - 1);
+        state.scroll_to_selected(height);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::Up) {
+        state.selected = state.selected.saturating_sub(1);
+        state.scroll_to_selected(height);
+    }
+
+    if app.keyboard.was_pressed(KeyCode::Return) {
+        state.launch = Some(state.selected);
+    }
+
+    // mouse navigation
+    state.scroll = (state.scroll - app.mouse.wheel_delta.y).clamp(0.0, state.max_scroll(height));
+
+    let row = ((app.mouse.y - PADDING + state.scroll) / LINE_HEIGHT).floor();
+    let hovered = if row >= 0.0 {
+        match state.rows.get(row as usize) {
+            Some(Row::Example(i)) => Some(*i),
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    // only follow the mouse when it moves, so it doesn't fight with the keyboard
+    let mouse = (app.mouse.x, app.mouse.y);
+    let mouse_moved = mouse != state.last_mouse;
+    state.last_mouse = mouse;
+
+    if let Some(index) = hovered {
+        if mouse_moved {
+            state.selected = index;
+        }
+
+        if app.mouse.was_pressed(MouseButton::Left) {
+            state.selected = index;
+            state.launch = Some(index);
+        }
+    }
+}
+
+fn draw(app: &mut App, gfx: &mut Graphics, plugins: &mut Plugins, state: &mut State) {
+    if let Some(index) = state.launch.take() {
+        let entry = state.examples[index];
+        state.current = Some(ExampleRunner::new(&entry, app, gfx, &state.config));
+    }
+
+    if let Some(runner) = state.current.as_mut() {
+        runner.draw(app, gfx, plugins);
+        return;
+    }
+
+    let mut draw = gfx.create_draw();
+    draw.clear(Color::from_rgb(0.1, 0.1, 0.1));
+
+    state.rows.iter().enumerate().for_each(|(i, row)| {
+        let y = PADDING + i as f32 * LINE_HEIGHT - state.scroll;
+        match row {
+            Row::Chapter(chapter) => {
+                draw.text(&state.font, chapter)
+                    .position(PADDING, y)
+                    .size(HEADER_SIZE)
+                    .color(Color::ORANGE);
+            }
+            Row::Example(index) => {
+                let info = &state.examples[*index].info;
+                let color = if *index == state.selected {
+                    Color::WHITE
+                } else {
+                    Color::GRAY
+                };
+
+                draw.text(&state.font, &format!("{}  {}", info.id, info.title))
+                    .position(PADDING * 2.0, y)
+                    .size(ITEM_SIZE)
+                    .color(color);
+            }
+        }
+    });
+
+    gfx.render(&draw);
+}
@@ -0,0 +1,501 @@
+use super::atlas::{create_atlas_texture, AtlasBuilder, AtlasLayout};
+use super::compressed::{create_container_texture, is_texture_container, parse_texture_container};
+use super::cubemap::create_cubemap;
+use super::hdr::{create_hdr_texture, decode_hdr};
+use super::mipmaps::{create_mipmapped_texture, MipmapData, MipmapOptions};
+use super::procedural::GeneratedImage;
+use notan::prelude::*;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// Resources are read from this folder on native, and fetched relative to the page on wasm
+pub const RESOURCES_PATH: &str = "./resources";
+
+type LoadFuture = Pin<Box<dyn Future<Output = Result<Vec<Vec<u8>>, String>>>>;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextureOptions {
+    pub wrap_x: TextureWrap,
+    pub wrap_y: TextureWrap,
+    pub min_filter: TextureFilter,
+    pub mag_filter: TextureFilter,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            wrap_x: TextureWrap::Clamp,
+            wrap_y: TextureWrap::Clamp,
+            min_filter: TextureFilter::Linear,
+            mag_filter: TextureFilter::Linear,
+        }
+    }
+}
+
+// Cheap reference to a texture owned by the AssetManager
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TextureHandle(String);
+
+impl TextureHandle {
+    pub fn path(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AssetError {
+    pub path: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for AssetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error loading '{}': {}", self.path, self.message)
+    }
+}
+
+enum LoadState<T> {
+    Pending(LoadFuture),
+    Loaded(T),
+    Failed,
+}
+
+enum TextureKind {
+    Image,
+    CubeMap,
+    Mipmapped(MipmapOptions),
+    Atlas,
+}
+
+struct TextureEntry<T> {
+    kind: TextureKind,
+    options: TextureOptions,
+    state: LoadState<T>,
+    // levels of the atlas for mipmapped textures, and KTX2 or DDS files with a mip chain
+    mipmap: Option<MipmapData>,
+    // regions of the packed images for atlases
+    atlas: Option<AtlasLayout>,
+}
+
+// The textures are generic so the loading can be tested without a gpu, the examples use
+// the default
+pub struct AssetManager<T = Texture> {
+    root: String,
+    placeholder: T,
+    textures: HashMap<String, TextureEntry<T>>,
+    errors: Vec<AssetError>,
+}
+
+impl AssetManager {
+    pub fn new(gfx: &mut Graphics) -> Self {
+        Self::with_root(gfx, RESOURCES_PATH)
+    }
+
+    pub fn with_root(gfx: &mut Graphics, root: &str) -> Self {
+        Self {
+            root: root.trim_end_matches('/').to_string(),
+            placeholder: create_placeholder(gfx),
+            textures: HashMap::new(),
+            errors: vec![],
+        }
+    }
+
+    // Registers an image created at runtime, like the ones from utils/procedural.rs,
+    // so it can be used with a handle like the loaded ones
+    pub fn add_texture(
+        &mut self,
+        gfx: &mut Graphics,
+        name: &str,
+        image: &GeneratedImage,
+        options: TextureOptions,
+    ) -> Result<TextureHandle, String> {
+        let texture = gfx
+            .create_texture()
+            .from_bytes(&image.data, image.width, image.height)
+            .with_wrap(options.wrap_x, options.wrap_y)
+            .with_filter(options.min_filter, options.mag_filter)
+            .build()?;
+
+        let key = format!("generated:{}", name);
+        self.textures.insert(
+            key.clone(),
+            TextureEntry {
+                kind: TextureKind::Image,
+                options,
+                state: LoadState::Loaded(texture),
+                mipmap: None,
+                atlas: None,
+            },
+        );
+
+        Ok(TextureHandle(key))
+    }
+
+    // Poll the pending loads and upload the finished ones to the gpu, must be called every frame
+    pub fn update(&mut self, gfx: &mut Graphics) {
+        self.update_with(|path, entry, files| create_texture(gfx, path, entry, files));
+    }
+}
+
+impl<T> AssetManager<T> {
+    // Path is relative to the resources folder, like "textures/container2.png".
+    // KTX2 and DDS files with more than one level are uploaded as a mip atlas, their
+    // `mipmap_data` is Some once they're loaded.
+    pub fn load_texture(&mut self, path: &str) -> TextureHandle {
+        self.load_texture_with(path, TextureOptions::default())
+    }
+
+    pub fn load_texture_with(&mut self, path: &str, options: TextureOptions) -> TextureHandle {
+        match self.textures.get(path) {
+            Some(entry) => {
+                if entry.options != options {
+                    notan::log::warn!(
+                        "Texture '{}' was already requested with different options, the first ones will be used",
+                        path
+                    );
+                }
+            }
+            None => {
+                let future = self.load_files(&[path]);
+                self.textures.insert(
+                    path.to_string(),
+                    TextureEntry {
+                        kind: TextureKind::Image,
+                        options,
+                        state: LoadState::Pending(future),
+                        mipmap: None,
+                        atlas: None,
+                    },
+                );
+            }
+        }
+
+        TextureHandle(path.to_string())
+    }
+
+    // The mip chain is generated on the cpu, the shaders must sample it using `textureMip`
+    // with the data returned by `mipmap_data` (see utils/mipmaps.rs).
+    // Each filter and usage generates its own chain, so they can be compared side by side.
+    pub fn load_mipmapped_texture(&mut self, path: &str, options: MipmapOptions) -> TextureHandle {
+        let key = format!("mipmaps:{:?}:{:?}:{}", options.filter, options.usage, path);
+        match self.textures.get(&key) {
+            Some(TextureEntry {
+                kind: TextureKind::Mipmapped(current),
+                ..
+            }) if *current != options => {
+                notan::log::warn!(
+                    "Texture '{}' was already requested with different sampler settings, the first ones will be used",
+                    path
+                );
+            }
+            Some(_) => {}
+            None => {
+                let future = self.load_files(&[path]);
+                self.textures.insert(
+                    key.clone(),
+                    TextureEntry {
+                        kind: TextureKind::Mipmapped(options),
+                        options: TextureOptions::default(),
+                        state: LoadState::Pending(future),
+                        mipmap: None,
+                        atlas: None,
+                    },
+                );
+            }
+        }
+
+        TextureHandle(key)
+    }
+
+    // Faces in the order right, left, top, bottom, front, back (see `create_cubemap`)
+    pub fn load_cubemap(&mut self, faces: [&str; 6]) -> TextureHandle {
+        let key = faces.join("|");
+        if !self.textures.contains_key(&key) {
+            let future = self.load_files(&faces);
+            self.textures.insert(
+                key.clone(),
+                TextureEntry {
+                    kind: TextureKind::CubeMap,
+                    options: TextureOptions::default(),
+                    state: LoadState::Pending(future),
+                    mipmap: None,
+                    atlas: None,
+                },
+            );
+        }
+
+        TextureHandle(key)
+    }
+
+    // Packs the images into a single texture, the regions are named after the file
+    // without the extension, like "powerup_speed" for "textures/powerup_speed.png"
+    pub fn load_atlas(&mut self, paths: &[&str]) -> TextureHandle {
+        let key = format!("atlas:{}", paths.join("|"));
+        if !self.textures.contains_key(&key) {
+            let future = self.load_files(paths);
+            self.textures.insert(
+                key.clone(),
+                TextureEntry {
+                    kind: TextureKind::Atlas,
+                    options: TextureOptions::default(),
+                    state: LoadState::Pending(future),
+                    mipmap: None,
+                    atlas: None,
+                },
+            );
+        }
+
+        TextureHandle(key)
+    }
+
+    fn load_files(&self, paths: &[&str]) -> LoadFuture {
+        let paths = paths
+            .iter()
+            .map(|path| format!("{}/{}", self.root, path))
+            .collect::<Vec<_>>();
+
+        Box::pin(async move {
+            let mut files = vec![];
+            for path in paths {
+                let data = platter::load_file(&path)
+                    .await
+                    .map_err(|e| format!("{}: {}", path, e))?;
+                files.push(data);
+            }
+
+            Ok(files)
+        })
+    }
+
+    // Polls the pending loads, `create` makes the textures of the finished ones. The
+    // textures stay on the placeholder until then, the failed ones forever.
+    fn update_with<F>(&mut self, mut create: F)
+    where
+        F: FnMut(&str, &mut TextureEntry<T>, &[Vec<u8>]) -> Result<T, String>,
+    {
+        let waker = futures::task::noop_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        for (path, entry) in self.textures.iter_mut() {
+            let result = match &mut entry.state {
+                LoadState::Pending(future) => match future.as_mut().poll(&mut cx) {
+                    Poll::Ready(result) => result,
+                    Poll::Pending => continue,
+                },
+                _ => continue,
+            };
+
+            let texture = result.and_then(|files| create(path, entry, &files));
+            entry.state = match texture {
+                Ok(texture) => LoadState::Loaded(texture),
+                Err(message) => {
+                    let error = AssetError {
+                        path: path.clone(),
+                        message,
+                    };
+                    notan::log::error!("{}", error);
+                    self.errors.push(error);
+                    LoadState::Failed
+                }
+            };
+        }
+    }
+
+    // Returns the loaded texture, or the placeholder if it's still loading or failed
+    pub fn texture(&self, handle: &TextureHandle) -> &T {
+        match self.textures.get(handle.path()).map(|entry| &entry.state) {
+            Some(LoadState::Loaded(texture)) => texture,
+            _ => &self.placeholder,
+        }
+    }
+
+    // Atlas levels and sampler settings of a mipmapped texture, None until it's loaded.
+    // Also set for the KTX2 and DDS files with a mip chain loaded by `load_texture`.
+    pub fn mipmap_data(&self, handle: &TextureHandle) -> Option<MipmapData> {
+        self.textures
+            .get(handle.path())
+            .and_then(|entry| entry.mipmap)
+    }
+
+    // Regions of an atlas created with `load_atlas`, None until it's loaded
+    pub fn atlas_layout(&self, handle: &TextureHandle) -> Option<&AtlasLayout> {
+        self.textures
+            .get(handle.path())
+            .and_then(|entry| entry.atlas.as_ref())
+    }
+
+    pub fn is_loaded(&self, handle: &TextureHandle) -> bool {
+        matches!(
+            self.textures.get(handle.path()).map(|entry| &entry.state),
+            Some(LoadState::Loaded(_))
+        )
+    }
+
+    // True when there is nothing left to load (failed textures count as done)
+    pub fn is_idle(&self) -> bool {
+        self.textures
+            .values()
+            .all(|entry| !matches!(entry.state, LoadState::Pending(_)))
+    }
+
+    pub fn errors(&self) -> &[AssetError] {
+        &self.errors
+    }
+
+    pub fn take_errors(&mut self) -> Vec<AssetError> {
+        std::mem::take(&mut self.errors)
+    }
+}
+
+// Uploads the files of a finished load, the kind of the entry says how
+fn create_texture(
+    gfx: &mut Graphics,
+    path: &str,
+    entry: &mut TextureEntry<Texture>,
+    files: &[Vec<u8>],
+) -> Result<Texture, String> {
+    match &entry.kind {
+        TextureKind::Mipmapped(options) => {
+            let (texture, mipmap) = create_mipmapped_texture(gfx, &files[0], options)?;
+            entry.mipmap = Some(mipmap);
+            Ok(texture)
+        }
+        TextureKind::Atlas => {
+            let names = path.trim_start_matches("atlas:").split('|');
+            let atlas = names
+                .zip(files.iter())
+                .try_fold(AtlasBuilder::new(), |builder, (name, bytes)| {
+                    builder.add_image(region_name(name), bytes)
+                })?
+                .build()?;
+            let texture = create_atlas_texture(gfx, &atlas)?;
+            entry.atlas = Some(atlas.layout);
+            Ok(texture)
+        }
+        TextureKind::CubeMap => {
+            let faces: [&[u8]; 6] = [
+                &files[0], &files[1], &files[2], &files[3], &files[4], &files[5],
+            ];
+            create_cubemap(gfx).from_faces(faces).build()
+        }
+        // KTX2 and DDS payloads are decoded to RGBA8, their mip chains go to a mip atlas
+        TextureKind::Image if is_texture_container(&files[0]) => {
+            let container = parse_texture_container(&files[0])?;
+            let (texture, mipmap) = create_container_texture(gfx, &container, &entry.options)?;
+            entry.mipmap = mipmap;
+            Ok(texture)
+        }
+        // radiance files are decoded to a float texture
+        TextureKind::Image if path.ends_with(".hdr") => {
+            let image = decode_hdr(&files[0])?;
+            create_hdr_texture(gfx, &image)
+        }
+        TextureKind::Image => gfx
+            .create_texture()
+            .from_image(&files[0])
+            .with_wrap(entry.options.wrap_x, entry.options.wrap_y)
+            .with_filter(entry.options.min_filter, entry.options.mag_filter)
+            .build(),
+    }
+}
+
+fn region_name(path: &str) -> &str {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    file.split('.').next().unwrap_or(file)
+}
+
+// 2x2 magenta/black checker, easy to spot on screen
+fn create_placeholder(gfx: &mut Graphics) -> Texture {
+    #[rustfmt::skip]
+    let pixels = [
+        255, 0, 255, 255,   0, 0, 0, 255,
+        0, 0, 0, 255,       255, 0, 255, 255,
+    ];
+
+    gfx.create_texture()
+        .from_bytes(&pixels, 2, 2)
+        .with_filter(TextureFilter::Nearest, TextureFilter::Nearest)
+        .with_wrap(TextureWrap::Repeat, TextureWrap::Repeat)
+        .build()
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The textures are the size of the first file, the placeholder is 0
+    fn manager(loads: Vec<(&str, LoadFuture)>) -> AssetManager<usize> {
+        let textures = loads
+            .into_iter()
+            .map(|(path, future)| {
+                let entry = TextureEntry {
+                    kind: TextureKind::Image,
+                    options: TextureOptions::default(),
+                    state: LoadState::Pending(future),
+                    mipmap: None,
+                    atlas: None,
+                };
+                (path.to_string(), entry)
+            })
+            .collect();
+
+        AssetManager {
+            root: RESOURCES_PATH.to_string(),
+            placeholder: 0,
+            textures,
+            errors: vec![],
+        }
+    }
+
+    #[test]
+    fn loads_end_ready_or_failed() {
+        let mut assets = manager(vec![
+            ("ready.png", Box::pin(async { Ok(vec![vec![1, 2, 3]]) })),
+            (
+                "missing.png",
+                Box::pin(async { Err("not found".to_string()) }),
+            ),
+            ("slow.png", Box::pin(std::future::pending())),
+        ]);
+        let handle = |path: &str| TextureHandle(path.to_string());
+
+        // everything uses the placeholder until it's polled
+        assert_eq!(*assets.texture(&handle("ready.png")), 0);
+        assert!(!assets.is_idle());
+
+        assets.update_with(|_, _, files| Ok(files[0].len()));
+        assert_eq!(*assets.texture(&handle("ready.png")), 3);
+        assert!(assets.is_loaded(&handle("ready.png")));
+
+        // the failed load keeps the placeholder and reports the error once
+        assert_eq!(*assets.texture(&handle("missing.png")), 0);
+        assert!(!assets.is_loaded(&handle("missing.png")));
+        assert_eq!(
+            assets.errors()[0].to_string(),
+            "Error loading 'missing.png': not found"
+        );
+
+        // the pending one is polled again on the next update
+        assert_eq!(*assets.texture(&handle("slow.png")), 0);
+        assert!(!assets.is_idle());
+        assets.update_with(|_, _, files| Ok(files[0].len()));
+        assert_eq!(assets.errors().len(), 1);
+        assert_eq!(assets.take_errors().len(), 1);
+        assert!(assets.errors().is_empty());
+    }
+
+    #[test]
+    fn failed_textures_are_errors() {
+        let mut assets = manager(vec![("broken.png", Box::pin(async { Ok(vec![vec![0]]) }))]);
+        assets.update_with(|_, _, _| Err("Invalid image".to_string()));
+
+        let handle = TextureHandle("broken.png".to_string());
+        assert_eq!(*assets.texture(&handle), 0);
+        assert_eq!(assets.errors()[0].message, "Invalid image");
+        // failed textures don't keep it busy
+        assert!(assets.is_idle());
+    }
+}
@@ -0,0 +1,816 @@
+// ASTC block decoder for the LDR profile. Every block is 128 bits whatever its footprint, the
+// weights are stored reversed from the top of the block and the color endpoints after the
+// header, both with the integer sequence encoding (bits, trits or quints).
+// HDR endpoints and invalid blocks decode to the error color, magenta like the GPUs do.
+
+const ERROR_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+// Number of levels of every quantization range, in the order used by the block modes
+const RANGES: [u32; 21] = [
+    2, 3, 4, 5, 6, 8, 10, 12, 16, 20, 24, 32, 40, 48, 64, 80, 96, 128, 160, 192, 256,
+];
+
+struct BlockMode {
+    grid_width: u32,
+    grid_height: u32,
+    weight_levels: u32,
+    dual_plane: bool,
+}
+
+// Reads the bits of an integer sequence from the lowest one
+struct BitReader {
+    bits: u128,
+    pos: u32,
+}
+
+impl BitReader {
+    fn read(&mut self, count: u32) -> u32 {
+        let bits = self.bits.checked_shr(self.pos).unwrap_or(0) as u32;
+        self.pos += count;
+        bits & ((1u64 << count) - 1) as u32
+    }
+}
+
+fn low_bits(count: u32) -> u128 {
+    1u128.checked_shl(count).map_or(u128::MAX, |bit| bit - 1)
+}
+
+// Decodes a 16 bytes block, the texels are in row order
+pub fn decode_astc_block(
+    block: &[u8],
+    block_width: u32,
+    block_height: u32,
+    srgb: bool,
+) -> Vec<[u8; 4]> {
+    decode_block(block, block_width, block_height, srgb)
+        .unwrap_or_else(|| vec![ERROR_COLOR; (block_width * block_height) as usize])
+}
+
+fn decode_block(
+    block: &[u8],
+    block_width: u32,
+    block_height: u32,
+    srgb: bool,
+) -> Option<Vec<[u8; 4]>> {
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(&block[..16]);
+    let bits = u128::from_le_bytes(bytes);
+    let field = |start: u32, count: u32| ((bits >> start) & low_bits(count)) as u32;
+    let texel_count = (block_width * block_height) as usize;
+
+    // void extent blocks have a single UNORM16 color, the HDR ones are half floats
+    if field(0, 9) == 0x1fc {
+        if field(9, 1) == 1 {
+            return None;
+        }
+        let color = [0, 1, 2, 3].map(|i| (field(64 + i * 16, 16) >> 8) as u8);
+        return Some(vec![color; texel_count]);
+    }
+
+    let mode = decode_block_mode(field(0, 11))?;
+    if mode.grid_width > block_width || mode.grid_height > block_height {
+        return None;
+    }
+
+    let partitions = field(11, 2) + 1;
+    if partitions == 4 && mode.dual_plane {
+        return None;
+    }
+
+    let planes = if mode.dual_plane { 2 } else { 1 };
+    let weight_count = mode.grid_width * mode.grid_height * planes;
+    let weight_bits = ise_bits(mode.weight_levels, weight_count);
+    if weight_count > 64 || !(24..=96).contains(&weight_bits) {
+        return None;
+    }
+
+    // with several partitions the modes can be different, the extra bits that don't fit in the
+    // header are stored below the weights
+    let mut cems = [field(13, 4); 4];
+    let mut extra_bits = 0;
+    let color_start = if partitions == 1 { 17 } else { 29 };
+    if partitions > 1 {
+        let cem_field = field(23, 6);
+        let selector = cem_field & 3;
+        if selector == 0 {
+            cems = [cem_field >> 2; 4];
+        } else {
+            extra_bits = 3 * partitions - 4;
+            let extra = field(128 - weight_bits - extra_bits, extra_bits);
+            let cem_bits = (cem_field >> 2) | (extra << 4);
+            for (i, cem) in cems.iter_mut().take(partitions as usize).enumerate() {
+                let class = selector - 1 + ((cem_bits >> i) & 1);
+                let cem_mode = (cem_bits >> (partitions as usize + i * 2)) & 3;
+                *cem = (class << 2) | cem_mode;
+            }
+        }
+    }
+
+    // the dual plane selects the channel that uses the second set of weights
+    let plane_bits = if mode.dual_plane { 2 } else { 0 };
+    let color_end = 128 - weight_bits - extra_bits - plane_bits;
+    let plane_channel = mode.dual_plane.then(|| field(color_end, 2) as usize);
+    if color_end < color_start {
+        return None;
+    }
+
+    let cems = &cems[..partitions as usize];
+    let color_count = cems.iter().map(|cem| ((cem >> 2) + 1) * 2).sum::<u32>();
+    let available = color_end - color_start;
+    if color_count > 18 || available < (13 * color_count).div_ceil(5) {
+        return None;
+    }
+
+    // the colors use the largest range that fits in the space left
+    let color_levels = *RANGES
+        .iter()
+        .rev()
+        .find(|levels| ise_bits(**levels, color_count) <= available)?;
+    let color_stream = (bits >> color_start) & low_bits(ise_bits(color_levels, color_count));
+    let colors = decode_ise(color_stream, color_count, color_levels)
+        .into_iter()
+        .map(|value| unquantize_color(color_levels, value) as i32)
+        .collect::<Vec<_>>();
+
+    let mut endpoints = Vec::with_capacity(cems.len());
+    let mut next = 0;
+    for cem in cems {
+        let count = (((cem >> 2) + 1) * 2) as usize;
+        endpoints.push(decode_endpoints(*cem, &colors[next..next + count])?);
+        next += count;
+    }
+
+    let weight_stream = bits.reverse_bits() & low_bits(weight_bits);
+    let weights = decode_ise(weight_stream, weight_count, mode.weight_levels)
+        .into_iter()
+        .map(|value| unquantize_weight(mode.weight_levels, value))
+        .collect::<Vec<_>>();
+
+    let seed = field(13, 10);
+    let small_block = texel_count < 31;
+    let mut texels = vec![[0; 4]; texel_count];
+    for y in 0..block_height {
+        for x in 0..block_width {
+            let partition = if partitions > 1 {
+                select_partition(seed, x, y, partitions, small_block)
+            } else {
+                0
+            };
+            let (e0, e1) = endpoints[partition];
+            let texel = &mut texels[(y * block_width + x) as usize];
+            for channel in 0..4 {
+                let plane = (plane_channel == Some(channel)) as u32;
+                let weight = infill_weight(
+                    &weights,
+                    &mode,
+                    planes,
+                    plane,
+                    (x, y),
+                    (block_width, block_height),
+                );
+                texel[channel] = interpolate(e0[channel], e1[channel], weight, srgb && channel < 3);
+            }
+        }
+    }
+
+    Some(texels)
+}
+
+fn decode_block_mode(mode: u32) -> Option<BlockMode> {
+    let a = (mode >> 5) & 3;
+    let mut high_precision = (mode >> 9) & 1;
+    let mut dual_plane = (mode >> 10) & 1 == 1;
+    let mut range = (mode >> 4) & 1;
+
+    let (grid_width, grid_height) = if mode & 3 != 0 {
+        range |= (mode & 3) << 1;
+        let b = (mode >> 7) & 3;
+        match (mode >> 2) & 3 {
+            0 => (b + 4, a + 2),
+            1 => (b + 8, a + 2),
+            2 => (a + 2, b + 8),
+            _ if (mode >> 8) & 1 == 1 => ((b & 1) + 2, a + 2),
+            _ => (a + 2, (b & 1) + 6),
+        }
+    } else {
+        range |= ((mode >> 2) & 3) << 1;
+        if (mode >> 2) & 3 == 0 {
+            return None;
+        }
+        match (mode >> 7) & 3 {
+            0 => (12, a + 2),
+            1 => (a + 2, 12),
+            2 => {
+                // the high bits are the grid height here
+                high_precision = 0;
+                dual_plane = false;
+                (a + 6, ((mode >> 9) & 3) + 6)
+            }
+            _ => match a {
+                0 => (6, 10),
+                1 => (10, 6),
+                _ => return None,
+            },
+        }
+    };
+
+    Some(BlockMode {
+        grid_width,
+        grid_height,
+        weight_levels: RANGES[(range - 2 + 6 * high_precision) as usize],
+        dual_plane,
+    })
+}
+
+// Trits, quints and bits per value of a range
+fn range_encoding(levels: u32) -> (bool, bool, u32) {
+    if levels.is_multiple_of(3) {
+        (true, false, (levels / 3).trailing_zeros())
+    } else if levels.is_multiple_of(5) {
+        (false, true, (levels / 5).trailing_zeros())
+    } else {
+        (false, false, levels.trailing_zeros())
+    }
+}
+
+// Bits used by a sequence, 5 trits are packed in 8 bits and 3 quints in 7 bits
+fn ise_bits(levels: u32, count: u32) -> u32 {
+    let (trits, quints, bits) = range_encoding(levels);
+    let packed = if trits {
+        (8 * count).div_ceil(5)
+    } else if quints {
+        (7 * count).div_ceil(3)
+    } else {
+        0
+    };
+    count * bits + packed
+}
+
+fn decode_ise(stream: u128, count: u32, levels: u32) -> Vec<u32> {
+    let (trits, quints, bits) = range_encoding(levels);
+    let mut reader = BitReader {
+        bits: stream,
+        pos: 0,
+    };
+    let mut values = Vec::with_capacity(count as usize + 4);
+
+    while values.len() < count as usize {
+        if trits {
+            // the 8 bits of the trits are interleaved with the low bits of the 5 values
+            let mut low = [0; 5];
+            let mut packed = 0;
+            for (i, size) in [2, 2, 1, 2, 1].iter().enumerate() {
+                low[i] = reader.read(bits);
+                packed |= reader.read(*size) << [0, 2, 4, 5, 7][i];
+            }
+            let high = decode_trits(packed);
+            values.extend((0..5).map(|i| (high[i] << bits) | low[i]));
+        } else if quints {
+            let mut low = [0; 3];
+            let mut packed = 0;
+            for (i, size) in [3, 2, 2].iter().enumerate() {
+                low[i] = reader.read(bits);
+                packed |= reader.read(*size) << [0, 3, 5][i];
+            }
+            let high = decode_quints(packed);
+            values.extend((0..3).map(|i| (high[i] << bits) | low[i]));
+        } else {
+            values.push(reader.read(bits));
+        }
+    }
+
+    values.truncate(count as usize);
+    values
+}
+
+fn decode_trits(t: u32) -> [u32; 5] {
+    let bit = |value: u32, i: u32| (value >> i) & 1;
+
+    let (c, t4, t3) = if (t >> 2) & 7 == 7 {
+        ((((t >> 5) & 7) << 2) | (t & 3), 2, 2)
+    } else if (t >> 5) & 3 == 3 {
+        (t & 0x1f, 2, bit(t, 7))
+    } else {
+        (t & 0x1f, bit(t, 7), (t >> 5) & 3)
+    };
+
+    let (t2, t1, t0) = if c & 3 == 3 {
+        (
+            2,
+            bit(c, 4),
+            (bit(c, 3) << 1) | (bit(c, 2) & !bit(c, 3) & 1),
+        )
+    } else if (c >> 2) & 3 == 3 {
+        (2, 2, c & 3)
+    } else {
+        (
+            bit(c, 4),
+            (c >> 2) & 3,
+            (bit(c, 1) << 1) | (bit(c, 0) & !bit(c, 1) & 1),
+        )
+    };
+
+    [t0, t1, t2, t3, t4]
+}
+
+fn decode_quints(q: u32) -> [u32; 3] {
+    let bit = |i: u32| (q >> i) & 1;
+
+    if (q >> 1) & 3 == 3 && (q >> 5) & 3 == 0 {
+        let q2 = (bit(0) << 2) | ((bit(4) & !bit(0) & 1) << 1) | (bit(3) & !bit(0) & 1);
+        return [4, 4, q2];
+    }
+
+    let (q2, c) = if (q >> 1) & 3 == 3 {
+        (4, (((q >> 3) & 3) << 3) | ((!(q >> 5) & 3) << 1) | bit(0))
+    } else {
+        ((q >> 5) & 3, q & 0x1f)
+    };
+
+    let (q1, q0) = if c & 7 == 5 {
+        (4, (c >> 3) & 3)
+    } else {
+        ((c >> 3) & 3, c & 7)
+    };
+
+    [q0, q1, q2]
+}
+
+// Repeats the bits of a value until it has the new size
+fn replicate(value: u32, bits: u32, new_bits: u32) -> u32 {
+    let mut out = 0;
+    let mut filled = 0;
+    while filled < new_bits {
+        out = (out << bits) | value;
+        filled += bits;
+    }
+    out >> (filled - new_bits)
+}
+
+// Scales a color value to 8 bits. With trits and quints the high part selects a third or a
+// fifth of the range and the low bits are spread like the spec tables do.
+fn unquantize_color(levels: u32, value: u32) -> u8 {
+    let (trits, quints, bits) = range_encoding(levels);
+    if !trits && !quints {
+        return replicate(value, bits, 8) as u8;
+    }
+
+    let d = value >> bits;
+    let bit = |i: u32| (value >> i) & 1;
+    let a = if bit(0) == 1 { 0x1ff } else { 0 };
+    let (b, c) = match (trits, bits) {
+        (true, 1) => (0, 204),
+        (true, 2) => (
+            (bit(1) << 8) | (bit(1) << 4) | (bit(1) << 2) | (bit(1) << 1),
+            93,
+        ),
+        (true, 3) => {
+            let (b, c) = (bit(1), bit(2));
+            ((c << 8) | (b << 7) | (c << 3) | (b << 2) | (c << 1) | b, 44)
+        }
+        (true, 4) => {
+            let (b, c, d) = (bit(1), bit(2), bit(3));
+            ((d << 8) | (c << 7) | (b << 6) | (d << 2) | (c << 1) | b, 22)
+        }
+        (true, 5) => {
+            let (b, c, d, e) = (bit(1), bit(2), bit(3), bit(4));
+            ((e << 8) | (d << 7) | (c << 6) | (b << 5) | (e << 1) | d, 11)
+        }
+        (true, _) => {
+            let (b, c, d, e, f) = (bit(1), bit(2), bit(3), bit(4), bit(5));
+            ((f << 8) | (e << 7) | (d << 6) | (c << 5) | (b << 4) | f, 5)
+        }
+        (false, 1) => (0, 113),
+        (false, 2) => ((bit(1) << 8) | (bit(1) << 3) | (bit(1) << 2), 54),
+        (false, 3) => {
+            let (b, c) = (bit(1), bit(2));
+            ((c << 8) | (b << 7) | (c << 2) | (b << 1) | c, 26)
+        }
+        (false, 4) => {
+            let (b, c, d) = (bit(1), bit(2), bit(3));
+            ((d << 8) | (c << 7) | (b << 6) | (d << 1) | c, 13)
+        }
+        (false, _) => {
+            let (b, c, d, e) = (bit(1), bit(2), bit(3), bit(4));
+            ((e << 8) | (d << 7) | (c << 6) | (b << 5) | e, 6)
+        }
+    };
+
+    let t = (d * c + b) ^ a;
+    ((a & 0x80) | (t >> 2)) as u8
+}
+
+// Scales a weight to [0, 64]
+fn unquantize_weight(levels: u32, value: u32) -> u32 {
+    let (trits, quints, bits) = range_encoding(levels);
+    let weight = if !trits && !quints {
+        replicate(value, bits, 6)
+    } else if bits == 0 {
+        if trits {
+            [0, 32, 63][value as usize]
+        } else {
+            [0, 16, 32, 47, 63][value as usize]
+        }
+    } else {
+        let d = value >> bits;
+        let bit = |i: u32| (value >> i) & 1;
+        let a = if bit(0) == 1 { 0x7f } else { 0 };
+        let (b, c) = match (trits, bits) {
+            (true, 1) => (0, 50),
+            (true, 2) => ((bit(1) << 6) | (bit(1) << 2) | bit(1), 23),
+            (true, _) => {
+                let (b, c) = (bit(1), bit(2));
+                ((c << 6) | (b << 5) | (c << 1) | b, 11)
+            }
+            (false, 1) => (0, 28),
+            (false, _) => ((bit(1) << 6) | (bit(1) << 1), 13),
+        };
+        let t = (d * c + b) ^ a;
+        (a & 0x20) | (t >> 2)
+    };
+
+    if weight > 32 {
+        weight + 1
+    } else {
+        weight
+    }
+}
+
+// Moves a bit from the base to the offset, which becomes a signed 6 bits value
+fn bit_transfer_signed(offset: i32, base: i32) -> (i32, i32) {
+    let base = (base >> 1) | (offset & 0x80);
+    let offset = (offset >> 1) & 0x3f;
+    let offset = if offset & 0x20 != 0 {
+        offset - 0x40
+    } else {
+        offset
+    };
+    (offset, base)
+}
+
+fn blue_contract([r, g, b, a]: [i32; 4]) -> [i32; 4] {
+    [(r + b) >> 1, (g + b) >> 1, b, a]
+}
+
+// The two RGBA endpoints of a partition from its color values, None for the HDR modes
+fn decode_endpoints(cem: u32, v: &[i32]) -> Option<([i32; 4], [i32; 4])> {
+    let (e0, e1) = match cem {
+        // luminance
+        0 => ([v[0], v[0], v[0], 255], [v[1], v[1], v[1], 255]),
+        1 => {
+            let l0 = (v[0] >> 2) | (v[1] & 0xc0);
+            let l1 = (l0 + (v[1] & 0x3f)).min(255);
+            ([l0, l0, l0, 255], [l1, l1, l1, 255])
+        }
+        // luminance and alpha
+        4 => ([v[0], v[0], v[0], v[2]], [v[1], v[1], v[1], v[3]]),
+        5 => {
+            let (l_offset, l) = bit_transfer_signed(v[1], v[0]);
+            let (a_offset, a) = bit_transfer_signed(v[3], v[2]);
+            let l1 = l + l_offset;
+            ([l, l, l, a], [l1, l1, l1, a + a_offset])
+        }
+        // RGB with a scale for the first endpoint
+        6 => (
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                255,
+            ],
+            [v[0], v[1], v[2], 255],
+        ),
+        10 => (
+            [
+                (v[0] * v[3]) >> 8,
+                (v[1] * v[3]) >> 8,
+                (v[2] * v[3]) >> 8,
+                v[4],
+            ],
+            [v[0], v[1], v[2], v[5]],
+        ),
+        // RGB(A), the endpoints are swapped to move the precision to the blue channel
+        8 | 12 => {
+            let alpha = if cem == 12 { [v[6], v[7]] } else { [255, 255] };
+            let e0 = [v[0], v[2], v[4], alpha[0]];
+            let e1 = [v[1], v[3], v[5], alpha[1]];
+            if v[1] + v[3] + v[5] >= v[0] + v[2] + v[4] {
+                (e0, e1)
+            } else {
+                (blue_contract(e1), blue_contract(e0))
+            }
+        }
+        9 | 13 => {
+            let (r_offset, r) = bit_transfer_signed(v[1], v[0]);
+            let (g_offset, g) = bit_transfer_signed(v[3], v[2]);
+            let (b_offset, b) = bit_transfer_signed(v[5], v[4]);
+            let (a_offset, a) = if cem == 13 {
+                bit_transfer_signed(v[7], v[6])
+            } else {
+                (0, 255)
+            };
+            let e0 = [r, g, b, a];
+            let e1 = [r + r_offset, g + g_offset, b + b_offset, a + a_offset];
+            if r_offset + g_offset + b_offset >= 0 {
+                (e0, e1)
+            } else {
+                (blue_contract(e1), blue_contract(e0))
+            }
+        }
+        _ => return None,
+    };
+
+    Some((e0.map(|c| c.clamp(0, 255)), e1.map(|c| c.clamp(0, 255))))
+}
+
+fn hash52(mut p: u32) -> u32 {
+    p ^= p >> 15;
+    p = p.wrapping_sub(p << 17);
+    p = p.wrapping_add(p << 7);
+    p = p.wrapping_add(p << 4);
+    p ^= p >> 5;
+    p = p.wrapping_add(p << 16);
+    p ^= p >> 7;
+    p ^= p >> 3;
+    p ^= p << 6;
+    p ^= p >> 17;
+    p
+}
+
+// The partitions aren't stored in a table, the spec computes them from a hash of the seed
+fn select_partition(seed: u32, x: u32, y: u32, partitions: u32, small_block: bool) -> usize {
+    let (x, y) = if small_block {
+        (x << 1, y << 1)
+    } else {
+        (x, y)
+    };
+    let seed = seed + (partitions - 1) * 1024;
+    let rnum = hash52(seed);
+
+    let mut seeds = [
+        rnum,
+        rnum >> 4,
+        rnum >> 8,
+        rnum >> 12,
+        rnum >> 16,
+        rnum >> 20,
+        rnum >> 24,
+        rnum >> 28,
+    ]
+    .map(|s| s & 0xf);
+    seeds.iter_mut().for_each(|s| *s *= *s);
+
+    let (sh1, sh2) = if seed & 1 != 0 {
+        (
+            if seed & 2 != 0 { 4 } else { 5 },
+            if partitions == 3 { 6 } else { 5 },
+        )
+    } else {
+        (
+            if partitions == 3 { 6 } else { 5 },
+            if seed & 2 != 0 { 4 } else { 5 },
+        )
+    };
+    seeds
+        .iter_mut()
+        .enumerate()
+        .for_each(|(i, s)| *s >>= if i % 2 == 0 { sh1 } else { sh2 });
+
+    // the z seeds aren't needed for 2D blocks
+    let a = (seeds[0] * x + seeds[1] * y + (rnum >> 14)) & 0x3f;
+    let b = (seeds[2] * x + seeds[3] * y + (rnum >> 10)) & 0x3f;
+    let c = if partitions > 2 {
+        (seeds[4] * x + seeds[5] * y + (rnum >> 6)) & 0x3f
+    } else {
+        0
+    };
+    let d = if partitions > 3 {
+        (seeds[6] * x + seeds[7] * y + (rnum >> 2)) & 0x3f
+    } else {
+        0
+    };
+
+    if a >= b && a >= c && a >= d {
+        0
+    } else if b >= c && b >= d {
+        1
+    } else if c >= d {
+        2
+    } else {
+        3
+    }
+}
+
+// Bilinear sampling of the weight grid at a texel, in 1/16 steps like the spec
+fn infill_weight(
+    weights: &[u32],
+    mode: &BlockMode,
+    planes: u32,
+    plane: u32,
+    (x, y): (u32, u32),
+    (block_width, block_height): (u32, u32),
+) -> u32 {
+    let ds = (1024 + block_width / 2) / (block_width - 1);
+    let dt = (1024 + block_height / 2) / (block_height - 1);
+    let gs = (ds * x * (mode.grid_width - 1) + 32) >> 6;
+    let gt = (dt * y * (mode.grid_height - 1) + 32) >> 6;
+    let (js, fs) = (gs >> 4, gs & 0xf);
+    let (jt, ft) = (gt >> 4, gt & 0xf);
+
+    let w11 = (fs * ft + 8) >> 4;
+    let w10 = ft - w11;
+    let w01 = fs - w11;
+    let w00 = 16 - fs - ft + w11;
+
+    // the texels on the last row and column have no weight for the points outside the grid
+    let weight = |gx: u32, gy: u32| {
+        let index = ((gy * mode.grid_width + gx) * planes + plane) as usize;
+        weights.get(index).copied().unwrap_or(0)
+    };
+    let sum = weight(js, jt) * w00
+        + weight(js + 1, jt) * w01
+        + weight(js, jt + 1) * w10
+        + weight(js + 1, jt + 1) * w11;
+    (sum + 8) >> 4
+}
+
+// The endpoints are expanded to 16 bits, sRGB colors keep the 8 bits in the top byte
+fn interpolate(e0: i32, e1: i32, weight: u32, srgb: bool) -> u8 {
+    let expand = |c: i32| {
+        if srgb {
+            (c << 8) | 0x80
+        } else {
+            c * 257
+        }
+    };
+    let weight = weight as i32;
+    let c = (expand(e0) * (64 - weight) + expand(e1) * weight + 32) >> 6;
+    (c >> 8) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    // Header fields from the lowest bit, and the weights stored reversed from the top
+    fn pack(fields: &[(u128, u32)], weights: &[(u128, u32)]) -> [u8; 16] {
+        let write = |fields: &[(u128, u32)]| {
+            let mut bits = 0u128;
+            let mut pos = 0;
+            for (value, count) in fields {
+                bits |= value << pos;
+                pos += count;
+            }
+            bits
+        };
+        (write(fields) | write(weights).reverse_bits()).to_le_bytes()
+    }
+
+    // 4x4 grid of 2 bits weights, the 4 levels give the weights 0, 21, 43 and 64
+    const MODE_4X4: u128 = 0x42;
+
+    #[test]
+    fn single_partition_blocks_are_decoded() {
+        // luminance endpoints 0 and 255, the texels use the weights 0, 1, 2, 3 in every row
+        let block = pack(
+            &[(MODE_4X4, 11), (0, 2), (0, 4), (0, 8), (255, 8)],
+            &[(0b11_10_01_00, 8)].repeat(4),
+        );
+        let texels = decode_astc_block(&block, 4, 4, false);
+        assert_eq!(texels.len(), 16);
+        assert_eq!(
+            texels[..4],
+            [
+                [0, 0, 0, 255],
+                [84, 84, 84, 255],
+                [171, 171, 171, 255],
+                [255, 255, 255, 255]
+            ]
+        );
+        assert_eq!(texels[12..], texels[..4]);
+    }
+
+    #[test]
+    fn dual_plane_weights_are_interleaved() {
+        // luminance and alpha endpoints (0, 255) and (255, 0), the alpha uses the second
+        // plane, the first plane weights are 3 and the second ones 0
+        let block = pack(
+            &[
+                (MODE_4X4 | 1 << 10, 11),
+                (0, 2),
+                (4, 4),
+                (0, 8),
+                (255, 8),
+                (255, 8),
+                (0, 8),
+                (0, 13),
+                (3, 2),
+            ],
+            &[(0b00_11, 4)].repeat(16),
+        );
+        let texels = decode_astc_block(&block, 4, 4, false);
+        assert!(texels.iter().all(|texel| *texel == [255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn partitions_use_their_own_endpoints() {
+        // 2 partitions with the luminance mode, black and white
+        let block = pack(
+            &[
+                (MODE_4X4, 11),
+                (1, 2),
+                (5, 10),
+                (0, 6),
+                (0, 8),
+                (0, 8),
+                (255, 8),
+                (255, 8),
+            ],
+            &[],
+        );
+        let texels = decode_astc_block(&block, 4, 4, false);
+        for y in 0..4 {
+            for x in 0..4 {
+                let expected = if select_partition(5, x, y, 2, true) == 0 {
+                    0
+                } else {
+                    255
+                };
+                assert_eq!(
+                    texels[(y * 4 + x) as usize],
+                    [expected, expected, expected, 255]
+                );
+            }
+        }
+        assert!(texels.iter().any(|texel| texel[0] == 0));
+        assert!(texels.iter().any(|texel| texel[0] == 255));
+    }
+
+    #[test]
+    fn void_extent_and_invalid_blocks() {
+        // UNORM16 RGBA color after the header and the extent coordinates
+        let block = pack(
+            &[
+                (0x1fc, 9),
+                (0, 1),
+                (3, 2),
+                ((1 << 52) - 1, 52),
+                (0xffff, 16),
+                (0x8000, 16),
+                (0, 16),
+                (0xffff, 16),
+            ],
+            &[],
+        );
+        assert_eq!(
+            decode_astc_block(&block, 6, 6, false),
+            vec![[255, 128, 0, 255]; 36]
+        );
+
+        // HDR void extent and the reserved block mode 0
+        let mut hdr = block;
+        hdr[1] |= 0x02;
+        assert_eq!(decode_astc_block(&hdr, 4, 4, false)[0], ERROR_COLOR);
+        assert_eq!(decode_astc_block(&[0; 16], 4, 4, false)[0], ERROR_COLOR);
+    }
+
+    #[test]
+    fn trits_and_quints_cover_every_combination() {
+        let trits = (0..256).map(decode_trits).collect::<HashSet<_>>();
+        assert_eq!(trits.len(), 243);
+        assert!(trits.iter().flatten().all(|t| *t < 3));
+
+        let quints = (0..128).map(decode_quints).collect::<HashSet<_>>();
+        assert_eq!(quints.len(), 125);
+        assert!(quints.iter().flatten().all(|q| *q < 5));
+    }
+
+    #[test]
+    fn unquantized_values_are_evenly_spaced() {
+        // the colors use at least 6 levels, the space left for them can't be smaller. The
+        // trits and quints leave a gap a bit larger in the middle of the range.
+        for levels in &RANGES[4..] {
+            let levels = *levels;
+            let mut colors = (0..levels)
+                .map(|v| unquantize_color(levels, v) as f32)
+                .collect::<Vec<_>>();
+            colors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let step = 255.0 / (levels - 1) as f32;
+            for (i, color) in colors.iter().enumerate() {
+                assert!((color - i as f32 * step).abs() < 2.0, "{} levels", levels);
+            }
+        }
+
+        for levels in &RANGES[..12] {
+            let mut weights = (0..*levels)
+                .map(|v| unquantize_weight(*levels, v) as f32)
+                .collect::<Vec<_>>();
+            weights.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            let step = 64.0 / (levels - 1) as f32;
+            for (i, weight) in weights.iter().enumerate() {
+                assert!((weight - i as f32 * step).abs() < 2.0, "{} levels", levels);
+            }
+        }
+    }
+}
@@ -0,0 +1,569 @@
+use notan::math::{vec4, Vec4};
+use notan::prelude::*;
+
+// Packs several images into a single texture so sprites can share the same texture
+// (and the same draw call). The images are placed with a skyline bottom-left packer,
+// sorted by height, growing the atlas in powers of two until everything fits.
+
+// Keeps the atlas under the texture size supported by most WebGL devices
+pub const DEFAULT_MAX_ATLAS_SIZE: u32 = 4096;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AtlasRegion {
+    pub name: String,
+    // position and size in pixels, without the padding or the extrusion
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AtlasLayout {
+    pub width: u32,
+    pub height: u32,
+    pub regions: Vec<AtlasRegion>,
+}
+
+impl AtlasLayout {
+    pub fn region(&self, name: &str) -> Option<&AtlasRegion> {
+        self.regions.iter().find(|region| region.name == name)
+    }
+
+    // Normalized rect as (u0, v0, u1, v1)
+    pub fn uv(&self, name: &str) -> Option<Vec4> {
+        self.region(name).map(|region| {
+            let (w, h) = (self.width as f32, self.height as f32);
+            vec4(
+                region.x as f32 / w,
+                region.y as f32 / h,
+                (region.x + region.width) as f32 / w,
+                (region.y + region.height) as f32 / h,
+            )
+        })
+    }
+
+    // Text format, one region per line after the size:
+    // atlas <width> <height>
+    // <name> <x> <y> <width> <height>
+    pub fn to_layout_string(&self) -> String {
+        let mut text = format!("atlas {} {}\n", self.width, self.height);
+        self.regions.iter().for_each(|region| {
+            text.push_str(&format!(
+                "{} {} {} {} {}\n",
+                region.name, region.x, region.y, region.width, region.height
+            ));
+        });
+        text
+    }
+
+    pub fn from_layout_str(text: &str) -> Result<Self, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+
+        let parse = |line: usize, value: &str| {
+            value
+                .parse::<u32>()
+                .map_err(|_| format!("Invalid number '{}' at line {}", value, line))
+        };
+
+        let (line, header) = lines
+            .next()
+            .ok_or_else(|| "Empty atlas layout".to_string())?;
+        let (width, height) = match header.split_whitespace().collect::<Vec<_>>()[..] {
+            ["atlas", width, height] => (parse(line, width)?, parse(line, height)?),
+            _ => return Err(format!("Invalid atlas header at line {}", line)),
+        };
+
+        let regions = lines
+            .map(|(line, text)| {
+                // the name is the first token, so names can't contain spaces
+                match text.split_whitespace().collect::<Vec<_>>()[..] {
+                    [name, x, y, w, h] => {
+                        let region = AtlasRegion {
+                            name: name.to_string(),
+                            x: parse(line, x)?,
+                            y: parse(line, y)?,
+                            width: parse(line, w)?,
+                            height: parse(line, h)?,
+                        };
+
+                        if region.x + region.width > width || region.y + region.height > height {
+                            return Err(format!(
+                                "Region '{}' at line {} is outside of the atlas",
+                                name, line
+                            ));
+                        }
+
+                        Ok(region)
+                    }
+                    _ => Err(format!("Invalid region at line {}", line)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            width,
+            height,
+            regions,
+        })
+    }
+}
+
+// Packed RGBA8 pixels and their layout
+pub struct AtlasImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+    pub layout: AtlasLayout,
+}
+
+struct AtlasSource {
+    name: String,
+    width: u32,
+    height: u32,
+    data: Vec<u8>,
+}
+
+pub struct AtlasBuilder {
+    sources: Vec<AtlasSource>,
+    padding: u32,
+    extrude: u32,
+    max_size: u32,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AtlasBuilder {
+    pub fn new() -> Self {
+        Self {
+            sources: vec![],
+            padding: 2,
+            extrude: 1,
+            max_size: DEFAULT_MAX_ATLAS_SIZE,
+        }
+    }
+
+    // Empty pixels between the images
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    // Pixels of the image border repeated around it, avoids bleeding with linear filtering
+    pub fn with_extrude(mut self, extrude: u32) -> Self {
+        self.extrude = extrude;
+        self
+    }
+
+    pub fn with_max_size(mut self, size: u32) -> Self {
+        self.max_size = size;
+        self
+    }
+
+    // Encoded image (png, jpg...)
+    pub fn add_image(self, name: &str, bytes: &[u8]) -> Result<Self, String> {
+        let image = image::load_from_memory(bytes)
+            .map_err(|e| format!("{}: {}", name, e))?
+            .into_rgba8();
+        self.add_rgba(name, image.width(), image.height(), image.into_raw())
+    }
+
+    // Raw RGBA8 pixels, `data` must have `width * height * 4` bytes
+    pub fn add_rgba(
+        mut self,
+        name: &str,
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    ) -> Result<Self, String> {
+        if width == 0 || height == 0 {
+            return Err(format!(
+                "{}: the image is empty ({}x{})",
+                name, width, height
+            ));
+        }
+
+        let expected = width as usize * height as usize * 4;
+        if data.len() != expected {
+            return Err(format!(
+                "{}: expected {} bytes for {}x{} RGBA pixels, found {}",
+                name,
+                expected,
+                width,
+                height,
+                data.len()
+            ));
+        }
+
+        self.sources.push(AtlasSource {
+            name: name.to_string(),
+            width,
+            height,
+            data,
+        });
+        Ok(self)
+    }
+
+    pub fn build(self) -> Result<AtlasImage, String> {
+        if let Some(source) = self.sources.iter().find(|source| {
+            self.sources
+                .iter()
+                .filter(|other| other.name == source.name)
+                .count()
+                > 1
+        }) {
+            return Err(format!("Duplicated atlas region '{}'", source.name));
+        }
+
+        // each image takes its size plus the extrusion on both sides and the padding
+        let margin = self.extrude * 2 + self.padding;
+        let sizes = self
+            .sources
+            .iter()
+            .map(|source| (source.width + margin, source.height + margin))
+            .collect::<Vec<_>>();
+
+        // taller images first
+        let mut order = (0..self.sources.len()).collect::<Vec<_>>();
+        order.sort_by(|a, b| {
+            let (wa, ha) = sizes[*a];
+            let (wb, hb) = sizes[*b];
+            hb.cmp(&ha).then(wb.cmp(&wa))
+        });
+
+        let area: u32 = sizes.iter().map(|(w, h)| w * h).sum();
+        let widest = sizes.iter().map(|(w, _)| *w).max().unwrap_or(1);
+        let tallest = sizes.iter().map(|(_, h)| *h).max().unwrap_or(1);
+
+        // start with the smallest power of two square that could hold the area
+        let mut width = widest
+            .max((area as f32).sqrt().ceil() as u32)
+            .next_power_of_two();
+        let mut height = tallest.next_power_of_two();
+        let positions = loop {
+            if width > self.max_size || height > self.max_size {
+                return Err(format!(
+                    "The images don't fit in a {}x{} atlas",
+                    self.max_size, self.max_size
+                ));
+            }
+
+            if let Some(positions) = pack_skyline(width, height, &order, &sizes) {
+                break positions;
+            }
+
+            // grow the smallest side
+            if height < width {
+                height *= 2;
+            } else {
+                width *= 2;
+            }
+        };
+
+        let mut data = vec![0; (width * height * 4) as usize];
+        let regions = self
+            .sources
+            .iter()
+            .zip(positions.iter())
+            .map(|(source, (x, y))| {
+                let region = AtlasRegion {
+                    name: source.name.clone(),
+                    x: x + self.extrude,
+                    y: y + self.extrude,
+                    width: source.width,
+                    height: source.height,
+                };
+                blit_extruded(&mut data, width, source, &region, self.extrude);
+                region
+            })
+            .collect();
+
+        Ok(AtlasImage {
+            width,
+            height,
+            data,
+            layout: AtlasLayout {
+                width,
+                height,
+                regions,
+            },
+        })
+    }
+}
+
+pub fn create_atlas_texture(gfx: &mut Graphics, atlas: &AtlasImage) -> Result<Texture, String> {
+    gfx.create_texture()
+        .from_bytes(&atlas.data, atlas.width, atlas.height)
+        .with_filter(TextureFilter::Linear, TextureFilter::Linear)
+        .build()
+}
+
+// Segment of the skyline, `y` is the top of the used space below it
+#[derive(Copy, Clone, Debug)]
+struct SkylineNode {
+    x: u32,
+    y: u32,
+    width: u32,
+}
+
+// Returns the position of each size in the original order, or None if they don't fit
+fn pack_skyline(
+    width: u32,
+    height: u32,
+    order: &[usize],
+    sizes: &[(u32, u32)],
+) -> Option<Vec<(u32, u32)>> {
+    let mut skyline = vec![SkylineNode { x: 0, y: 0, width }];
+    let mut positions = vec![(0, 0); sizes.len()];
+
+    for &index in order {
+        let (w, h) = sizes[index];
+
+        // bottom-left: lowest y, then the narrowest segment
+        let mut best: Option<(usize, u32, u32)> = None;
+        for i in 0..skyline.len() {
+            if let Some(y) = fit_skyline(&skyline, i, w, h, width, height) {
+                let better = match best {
+                    None => true,
+                    Some((best_i, best_y, _)) => {
+                        y < best_y || (y == best_y && skyline[i].width < skyline[best_i].width)
+                    }
+                };
+
+                if better {
+                    best = Some((i, y, skyline[i].x));
+                }
+            }
+        }
+
+        let (i, y, x) = best?;
+        positions[index] = (x, y);
+        add_skyline_level(&mut skyline, i, x, y + h, w);
+    }
+
+    Some(positions)
+}
+
+// Y where a rect of w x h can be placed starting at the node `index`
+fn fit_skyline(
+    skyline: &[SkylineNode],
+    index: usize,
+    w: u32,
+    h: u32,
+    width: u32,
+    height: u32,
+) -> Option<u32> {
+    let x = skyline[index].x;
+    if x + w > width {
+        return None;
+    }
+
+    let mut remaining = w as i64;
+    let mut y = 0;
+    let mut i = index;
+    while remaining > 0 {
+        let node = skyline.get(i)?;
+        y = y.max(node.y);
+        if y + h > height {
+            return None;
+        }
+        remaining -= node.width as i64;
+        i += 1;
+    }
+
+    Some(y)
+}
+
+fn add_skyline_level(skyline: &mut Vec<SkylineNode>, index: usize, x: u32, y: u32, w: u32) {
+    skyline.insert(index, SkylineNode { x, y, width: w });
+
+    // shrink or remove the nodes covered by the new one
+    let right = x + w;
+    let i = index + 1;
+    while i < skyline.len() {
+        let node = skyline[i];
+        if node.x >= right {
+            break;
+        }
+
+        let node_right = node.x + node.width;
+        if node_right <= right {
+            skyline.remove(i);
+        } else {
+            skyline[i].x = right;
+            skyline[i].width = node_right - right;
+            break;
+        }
+    }
+
+    // merge the nodes at the same level
+    let mut i = 0;
+    while i + 1 < skyline.len() {
+        if skyline[i].y == skyline[i + 1].y {
+            skyline[i].width += skyline[i + 1].width;
+            skyline.remove(i + 1);
+        } else {
+            i += 1;
+        }
+    }
+}
+
+// Copies the image to its region, repeating the border pixels `extrude` times around it
+fn blit_extruded(
+    data: &mut [u8],
+    atlas_width: u32,
+    source: &AtlasSource,
+    region: &AtlasRegion,
+    extrude: u32,
+) {
+    let extrude = extrude as i64;
+    for y in -extrude..source.height as i64 + extrude {
+        let sy = y.clamp(0, source.height as i64 - 1) as u32;
+        for x in -extrude..source.width as i64 + extrude {
+            let sx = x.clamp(0, source.width as i64 - 1) as u32;
+            let src = ((sy * source.width + sx) * 4) as usize;
+            let dx = (region.x as i64 + x) as u32;
+            let dy = (region.y as i64 + y) as u32;
+            let dst = ((dy * atlas_width + dx) * 4) as usize;
+            data[dst..dst + 4].copy_from_slice(&source.data[src..src + 4]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(value: u8, width: u32, height: u32) -> Vec<u8> {
+        vec![value; (width * height * 4) as usize]
+    }
+
+    fn overlaps(a: &AtlasRegion, b: &AtlasRegion, margin: u32) -> bool {
+        a.x < b.x + b.width + margin
+            && b.x < a.x + a.width + margin
+            && a.y < b.y + b.height + margin
+            && b.y < a.y + a.height + margin
+    }
+
+    #[test]
+    fn packed_regions_dont_overlap() {
+        let sizes = [
+            (30, 10),
+            (8, 40),
+            (16, 16),
+            (16, 16),
+            (50, 5),
+            (3, 3),
+            (20, 25),
+        ];
+        let atlas = sizes
+            .iter()
+            .enumerate()
+            .try_fold(AtlasBuilder::new(), |builder, (i, (w, h))| {
+                builder.add_rgba(&format!("image{}", i), *w, *h, solid(i as u8, *w, *h))
+            })
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let layout = &atlas.layout;
+        assert!(layout.width.is_power_of_two() && layout.height.is_power_of_two());
+        assert_eq!(layout.regions.len(), sizes.len());
+
+        // the extrusion of both sides (1px) and the padding (2px) separate the images
+        let margin = 4;
+        layout.regions.iter().enumerate().for_each(|(i, a)| {
+            assert!(a.x >= 1 && a.y >= 1);
+            assert!(a.x + a.width < layout.width && a.y + a.height < layout.height);
+            layout.regions[i + 1..].iter().for_each(|b| {
+                assert!(!overlaps(a, b, margin), "{:?} overlaps {:?}", a, b);
+            });
+        });
+
+        // every region has the pixels of its image, and its border extruded around it
+        layout.regions.iter().enumerate().for_each(|(i, region)| {
+            let pixel = |x: u32, y: u32| atlas.data[((y * atlas.width + x) * 4) as usize];
+            assert_eq!(pixel(region.x, region.y), i as u8);
+            assert_eq!(pixel(region.x - 1, region.y - 1), i as u8);
+            assert_eq!(
+                pixel(region.x + region.width, region.y + region.height - 1),
+                i as u8
+            );
+        });
+    }
+
+    #[test]
+    fn layout_round_trip() {
+        let atlas = AtlasBuilder::new()
+            .add_rgba("grass", 16, 8, solid(1, 16, 8))
+            .unwrap()
+            .add_rgba("window", 4, 12, solid(2, 4, 12))
+            .unwrap()
+            .build()
+            .unwrap();
+
+        let text = atlas.layout.to_layout_string();
+        assert!(text.starts_with(&format!("atlas {} {}\n", atlas.width, atlas.height)));
+        assert_eq!(AtlasLayout::from_layout_str(&text).unwrap(), atlas.layout);
+
+        let uv = atlas.layout.uv("window").unwrap();
+        let region = atlas.layout.region("window").unwrap();
+        assert_eq!(uv.x, region.x as f32 / atlas.width as f32);
+        assert_eq!(
+            uv.w,
+            (region.y + region.height) as f32 / atlas.height as f32
+        );
+    }
+
+    #[test]
+    fn invalid_layouts_and_images_are_errors() {
+        assert_eq!(
+            AtlasLayout::from_layout_str("atlas 8 8\nbig 4 4 8 2").unwrap_err(),
+            "Region 'big' at line 2 is outside of the atlas"
+        );
+        assert_eq!(
+            AtlasLayout::from_layout_str("atlas 8 x").unwrap_err(),
+            "Invalid number 'x' at line 1"
+        );
+
+        assert_eq!(
+            AtlasBuilder::new()
+                .add_rgba("short", 2, 2, vec![0; 12])
+                .err()
+                .unwrap(),
+            "short: expected 16 bytes for 2x2 RGBA pixels, found 12"
+        );
+        assert_eq!(
+            AtlasBuilder::new()
+                .add_rgba("empty", 0, 4, vec![])
+                .err()
+                .unwrap(),
+            "empty: the image is empty (0x4)"
+        );
+
+        let duplicated = AtlasBuilder::new()
+            .add_rgba("a", 1, 1, solid(0, 1, 1))
+            .and_then(|builder| builder.add_rgba("a", 1, 1, solid(0, 1, 1)))
+            .unwrap()
+            .build();
+        assert_eq!(duplicated.err().unwrap(), "Duplicated atlas region 'a'");
+
+        let too_big = AtlasBuilder::new()
+            .with_max_size(16)
+            .add_rgba("big", 20, 1, solid(0, 20, 1))
+            .unwrap()
+            .build();
+        assert_eq!(
+            too_big.err().unwrap(),
+            "The images don't fit in a 16x16 atlas"
+        );
+    }
+}
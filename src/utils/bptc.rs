@@ -0,0 +1,893 @@
+// BC6H and BC7 (BPTC) block decoders, both formats use 128 bits blocks of 4x4 texels
+// with up to 3 subsets picked from a table of partitions.
+// BC6H is decoded to RGBA8 clamping the half floats to [0, 1], the texture is meant to be
+// seen as a preview and not to keep the HDR range.
+
+// Subset of each texel for the 64 partitions of 2 subsets
+const PARTITIONS_2: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1],
+    [0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 1, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 1, 1],
+    [0, 0, 0, 1, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1],
+    [0, 0, 0, 0, 1, 0, 0, 0, 1, 1, 1, 0, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 1, 1, 0],
+    [0, 1, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0],
+    [0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 1, 0, 0, 0, 1, 1, 0, 0, 1, 1, 1, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 1, 1, 0, 0],
+    [0, 1, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 1],
+    [0, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0],
+    [0, 0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 1, 0, 0],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0],
+    [0, 0, 1, 1, 0, 1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 0],
+    [0, 0, 0, 1, 0, 1, 1, 1, 1, 1, 1, 0, 1, 0, 0, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0],
+    [0, 1, 1, 1, 0, 0, 0, 1, 1, 0, 0, 0, 1, 1, 1, 0],
+    [0, 0, 1, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 1, 0, 0],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1],
+    [0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 0, 1, 1, 1, 1],
+    [0, 1, 0, 1, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0],
+    [0, 0, 1, 1, 0, 0, 1, 1, 1, 1, 0, 0, 1, 1, 0, 0],
+    [0, 0, 1, 1, 1, 1, 0, 0, 0, 0, 1, 1, 1, 1, 0, 0],
+    [0, 1, 0, 1, 0, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0],
+    [0, 1, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 1],
+    [0, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1],
+    [0, 1, 1, 1, 0, 0, 1, 1, 1, 1, 0, 0, 1, 1, 1, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 1, 1, 0, 0, 1, 0, 0, 0],
+    [0, 0, 1, 1, 0, 0, 1, 0, 0, 1, 0, 0, 1, 1, 0, 0],
+    [0, 0, 1, 1, 1, 0, 1, 1, 1, 1, 0, 1, 1, 1, 0, 0],
+    [0, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 1, 1, 0],
+    [0, 0, 1, 1, 1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 1, 1],
+    [0, 1, 1, 0, 0, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 1],
+    [0, 0, 0, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0],
+    [0, 1, 0, 0, 1, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0, 0],
+    [0, 0, 1, 0, 0, 1, 1, 1, 0, 0, 1, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 1, 0, 0, 1, 0],
+    [0, 0, 0, 0, 0, 1, 0, 0, 1, 1, 1, 0, 0, 1, 0, 0],
+    [0, 1, 1, 0, 1, 1, 0, 0, 1, 0, 0, 1, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 1, 1, 0, 1, 1, 0, 0, 1, 0, 0, 1],
+    [0, 1, 1, 0, 0, 0, 1, 1, 1, 0, 0, 1, 1, 1, 0, 0],
+    [0, 0, 1, 1, 1, 0, 0, 1, 1, 1, 0, 0, 0, 1, 1, 0],
+    [0, 1, 1, 0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 1],
+    [0, 1, 1, 0, 0, 0, 1, 1, 0, 0, 1, 1, 1, 0, 0, 1],
+    [0, 1, 1, 1, 1, 1, 1, 0, 1, 0, 0, 0, 0, 0, 0, 1],
+    [0, 0, 0, 1, 1, 0, 0, 0, 1, 1, 1, 0, 0, 1, 1, 1],
+    [0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0],
+    [0, 0, 1, 0, 0, 0, 1, 0, 1, 1, 1, 0, 1, 1, 1, 0],
+    [0, 1, 0, 0, 0, 1, 0, 0, 0, 1, 1, 1, 0, 1, 1, 1],
+];
+
+// Subset of each texel for the 64 partitions of 3 subsets
+const PARTITIONS_3: [[u8; 16]; 64] = [
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 1, 2, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 2, 0, 0, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 1, 0, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2],
+    [0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2, 0, 1, 1, 2],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0, 2, 2, 2, 0],
+    [0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 1, 2, 1, 1, 2, 2],
+    [0, 1, 1, 1, 0, 0, 1, 1, 2, 0, 0, 1, 2, 2, 0, 0],
+    [0, 0, 0, 0, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 2, 2, 0, 0, 2, 2, 1, 1, 1, 1],
+    [0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2, 0, 2, 2, 2],
+    [0, 0, 0, 1, 0, 0, 0, 1, 2, 2, 2, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2],
+    [0, 0, 0, 0, 1, 1, 0, 0, 2, 2, 1, 0, 2, 2, 1, 0],
+    [0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1, 0, 0, 0, 0],
+    [0, 0, 1, 2, 0, 0, 1, 2, 1, 1, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1, 0, 1, 1, 0],
+    [0, 0, 0, 0, 0, 1, 1, 0, 1, 2, 2, 1, 1, 2, 2, 1],
+    [0, 0, 2, 2, 1, 1, 0, 2, 1, 1, 0, 2, 0, 0, 2, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 0, 0, 2, 2, 2, 2, 2],
+    [0, 0, 1, 1, 0, 1, 2, 2, 0, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 0, 0, 2, 0, 0, 0, 2, 2, 1, 1, 2, 2, 2, 1],
+    [0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 2, 2, 2],
+    [0, 2, 2, 2, 0, 0, 2, 2, 0, 0, 1, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 0, 0, 1, 2, 0, 0, 2, 2, 0, 2, 2, 2],
+    [0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0, 0, 1, 2, 0],
+    [0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0],
+    [0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0],
+    [0, 1, 2, 0, 2, 0, 1, 2, 1, 2, 0, 1, 0, 1, 2, 0],
+    [0, 0, 1, 1, 2, 2, 0, 0, 1, 1, 2, 2, 0, 0, 1, 1],
+    [0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, 1, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 0, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2, 1, 1, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 2, 2, 0, 0, 1, 1],
+    [0, 2, 2, 0, 1, 2, 2, 1, 0, 2, 2, 0, 1, 2, 2, 1],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 0, 1, 0, 1],
+    [0, 0, 0, 0, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1, 2, 1],
+    [0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 0, 1, 2, 2, 2, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 2, 2, 2, 0, 1, 1, 1],
+    [0, 0, 0, 2, 1, 1, 1, 2, 0, 0, 0, 2, 1, 1, 1, 2],
+    [0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 2, 2, 2, 0, 1, 1, 1, 0, 1, 1, 1, 0, 2, 2, 2],
+    [0, 0, 0, 2, 1, 1, 1, 2, 1, 1, 1, 2, 0, 0, 0, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2, 2, 1, 1, 2],
+    [0, 1, 1, 0, 0, 1, 1, 0, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 0, 2, 2, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 2, 2],
+    [0, 0, 2, 2, 1, 1, 2, 2, 1, 1, 2, 2, 0, 0, 2, 2],
+    [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2, 1, 1, 2],
+    [0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2, 0, 0, 0, 1],
+    [0, 2, 2, 2, 1, 2, 2, 2, 0, 2, 2, 2, 1, 2, 2, 2],
+    [0, 1, 0, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2],
+    [0, 1, 1, 1, 2, 0, 1, 1, 2, 2, 0, 1, 2, 2, 2, 0],
+];
+
+// The texel of the second subset that stores its index with one bit less, for 2 subsets
+#[rustfmt::skip]
+const ANCHORS_2: [u8; 64] = [
+    15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15,
+    15, 2, 8, 2, 2, 8, 8, 15, 2, 8, 2, 2, 8, 8, 2, 2,
+    15, 15, 6, 8, 2, 8, 15, 15, 2, 8, 2, 2, 2, 15, 15, 6,
+    6, 2, 6, 8, 15, 15, 2, 2, 15, 15, 15, 15, 15, 2, 2, 15,
+];
+
+// The same for the second and third subsets of the partitions of 3 subsets
+#[rustfmt::skip]
+const ANCHORS_3_SECOND: [u8; 64] = [
+    3, 3, 15, 15, 8, 3, 15, 15, 8, 8, 6, 6, 6, 5, 3, 3,
+    3, 3, 8, 15, 3, 3, 6, 10, 5, 8, 8, 6, 8, 5, 15, 15,
+    8, 15, 3, 5, 6, 10, 8, 15, 15, 3, 15, 5, 15, 15, 15, 15,
+    3, 15, 5, 5, 5, 8, 5, 10, 5, 10, 8, 13, 15, 12, 3, 3,
+];
+
+#[rustfmt::skip]
+const ANCHORS_3_THIRD: [u8; 64] = [
+    15, 8, 8, 3, 15, 15, 3, 8, 15, 15, 15, 15, 15, 15, 15, 8,
+    15, 8, 15, 3, 15, 8, 15, 8, 3, 15, 6, 10, 15, 15, 10, 8,
+    15, 3, 15, 10, 10, 8, 9, 10, 6, 15, 8, 15, 3, 6, 6, 8,
+    15, 3, 15, 15, 15, 15, 15, 15, 15, 15, 15, 15, 3, 15, 15, 8,
+];
+
+const WEIGHTS_2: [u32; 4] = [0, 21, 43, 64];
+const WEIGHTS_3: [u32; 8] = [0, 9, 18, 27, 37, 46, 55, 64];
+const WEIGHTS_4: [u32; 16] = [0, 4, 9, 13, 17, 21, 26, 30, 34, 38, 43, 47, 51, 55, 60, 64];
+
+// Reads the fields of a block from the lowest bit
+struct BlockBits {
+    bits: u128,
+    pos: u32,
+}
+
+impl BlockBits {
+    fn new(block: &[u8]) -> Self {
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&block[..16]);
+        Self {
+            bits: u128::from_le_bytes(bytes),
+            pos: 0,
+        }
+    }
+
+    fn read(&mut self, count: u32) -> u32 {
+        let bits = self.bits.checked_shr(self.pos).unwrap_or(0) as u32;
+        let value = bits & ((1u64 << count) - 1) as u32;
+        self.pos += count;
+        value
+    }
+}
+
+fn subset_of(subsets: usize, partition: usize, texel: usize) -> usize {
+    match subsets {
+        2 => PARTITIONS_2[partition][texel] as usize,
+        3 => PARTITIONS_3[partition][texel] as usize,
+        _ => 0,
+    }
+}
+
+// The anchors are stored with one bit less because their highest bit is always 0
+fn is_anchor(subsets: usize, partition: usize, texel: usize) -> bool {
+    texel == 0
+        || match subsets {
+            2 => ANCHORS_2[partition] as usize == texel,
+            3 => {
+                ANCHORS_3_SECOND[partition] as usize == texel
+                    || ANCHORS_3_THIRD[partition] as usize == texel
+            }
+            _ => false,
+        }
+}
+
+fn weight(index_bits: u32, index: u32) -> u32 {
+    match index_bits {
+        2 => WEIGHTS_2[index as usize],
+        3 => WEIGHTS_3[index as usize],
+        _ => WEIGHTS_4[index as usize],
+    }
+}
+
+fn interpolate(e0: u32, e1: u32, weight: u32) -> u32 {
+    ((64 - weight) * e0 + weight * e1 + 32) >> 6
+}
+
+struct Bc7Mode {
+    subsets: usize,
+    partition_bits: u32,
+    rotation_bits: u32,
+    index_selection_bits: u32,
+    color_bits: u32,
+    alpha_bits: u32,
+    // a p-bit per endpoint, or one shared by the two endpoints of a subset
+    endpoint_pbits: bool,
+    shared_pbits: bool,
+    index_bits: u32,
+    index2_bits: u32,
+}
+
+const BC7_MODES: [Bc7Mode; 8] = [
+    Bc7Mode {
+        subsets: 3,
+        partition_bits: 4,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 4,
+        alpha_bits: 0,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 3,
+        index2_bits: 0,
+    },
+    Bc7Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 6,
+        alpha_bits: 0,
+        endpoint_pbits: false,
+        shared_pbits: true,
+        index_bits: 3,
+        index2_bits: 0,
+    },
+    Bc7Mode {
+        subsets: 3,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 5,
+        alpha_bits: 0,
+        endpoint_pbits: false,
+        shared_pbits: false,
+        index_bits: 2,
+        index2_bits: 0,
+    },
+    Bc7Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 0,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 2,
+        index2_bits: 0,
+    },
+    Bc7Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 2,
+        index_selection_bits: 1,
+        color_bits: 5,
+        alpha_bits: 6,
+        endpoint_pbits: false,
+        shared_pbits: false,
+        index_bits: 2,
+        index2_bits: 3,
+    },
+    Bc7Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 2,
+        index_selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 8,
+        endpoint_pbits: false,
+        shared_pbits: false,
+        index_bits: 2,
+        index2_bits: 2,
+    },
+    Bc7Mode {
+        subsets: 1,
+        partition_bits: 0,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 7,
+        alpha_bits: 7,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 4,
+        index2_bits: 0,
+    },
+    Bc7Mode {
+        subsets: 2,
+        partition_bits: 6,
+        rotation_bits: 0,
+        index_selection_bits: 0,
+        color_bits: 5,
+        alpha_bits: 5,
+        endpoint_pbits: true,
+        shared_pbits: false,
+        index_bits: 2,
+        index2_bits: 0,
+    },
+];
+
+// Decodes a 16 bytes BC7 block, the texels are in row order
+pub fn decode_bc7_block(block: &[u8]) -> [[u8; 4]; 16] {
+    let mut bits = BlockBits::new(block);
+
+    // the mode is the number of 0 bits before the first 1, the reserved mode is transparent black
+    let mode = match (0..8).find(|_| bits.read(1) == 1) {
+        Some(mode) => &BC7_MODES[mode],
+        None => return [[0; 4]; 16],
+    };
+
+    let partition = bits.read(mode.partition_bits) as usize;
+    let rotation = bits.read(mode.rotation_bits);
+    let index_selection = bits.read(mode.index_selection_bits);
+
+    // the channels are stored one after the other, each one for all the endpoints
+    let endpoints = mode.subsets * 2;
+    let mut colors = [[0u32; 4]; 6];
+    for channel in 0..4 {
+        let channel_bits = if channel < 3 {
+            mode.color_bits
+        } else {
+            mode.alpha_bits
+        };
+        for color in colors.iter_mut().take(endpoints) {
+            color[channel] = bits.read(channel_bits);
+        }
+    }
+
+    let pbit_count = (mode.endpoint_pbits || mode.shared_pbits) as u32;
+    if pbit_count > 0 {
+        let pbits = if mode.endpoint_pbits {
+            (0..endpoints).map(|_| bits.read(1)).collect::<Vec<_>>()
+        } else {
+            (0..mode.subsets)
+                .flat_map(|_| {
+                    let pbit = bits.read(1);
+                    [pbit, pbit]
+                })
+                .collect()
+        };
+        for (color, pbit) in colors.iter_mut().zip(pbits) {
+            for (channel, value) in color.iter_mut().enumerate() {
+                if channel < 3 || mode.alpha_bits > 0 {
+                    *value = (*value << 1) | pbit;
+                }
+            }
+        }
+    }
+
+    // every channel is expanded to 8 bits replicating its highest bits
+    for color in colors.iter_mut().take(endpoints) {
+        for (channel, value) in color.iter_mut().enumerate() {
+            let channel_bits = if channel < 3 {
+                mode.color_bits
+            } else {
+                mode.alpha_bits
+            };
+            if channel_bits == 0 {
+                *value = 255;
+            } else {
+                let count = channel_bits + pbit_count;
+                *value <<= 8 - count;
+                *value |= *value >> count;
+            }
+        }
+    }
+
+    let indices = read_indices(&mut bits, mode.subsets, partition, mode.index_bits);
+    let indices2 = (mode.index2_bits > 0).then(|| read_indices(&mut bits, 1, 0, mode.index2_bits));
+
+    let mut texels = [[0; 4]; 16];
+    for (i, texel) in texels.iter_mut().enumerate() {
+        let subset = subset_of(mode.subsets, partition, i);
+        let (e0, e1) = (colors[subset * 2], colors[subset * 2 + 1]);
+
+        // with two sets of indices one is used for the color and the other one for the alpha
+        let (color_weight, alpha_weight) = match indices2 {
+            Some(indices2) => {
+                let primary = weight(mode.index_bits, indices[i]);
+                let secondary = weight(mode.index2_bits, indices2[i]);
+                if index_selection == 1 {
+                    (secondary, primary)
+                } else {
+                    (primary, secondary)
+                }
+            }
+            None => {
+                let weight = weight(mode.index_bits, indices[i]);
+                (weight, weight)
+            }
+        };
+
+        for channel in 0..4 {
+            let weight = if channel < 3 {
+                color_weight
+            } else {
+                alpha_weight
+            };
+            texel[channel] = interpolate(e0[channel], e1[channel], weight) as u8;
+        }
+
+        // the rotation swaps the alpha with a color channel
+        if rotation > 0 {
+            texel.swap(3, rotation as usize - 1);
+        }
+    }
+
+    texels
+}
+
+fn read_indices(
+    bits: &mut BlockBits,
+    subsets: usize,
+    partition: usize,
+    index_bits: u32,
+) -> [u32; 16] {
+    let mut indices = [0; 16];
+    for (i, index) in indices.iter_mut().enumerate() {
+        let count = if is_anchor(subsets, partition, i) {
+            index_bits - 1
+        } else {
+            index_bits
+        };
+        *index = bits.read(count);
+    }
+    indices
+}
+
+#[derive(Copy, Clone)]
+enum Field {
+    // mode bits
+    M,
+    // shape (partition)
+    D,
+    // the 4 endpoints, w and x are the first subset and y and z the second one
+    Rw,
+    Rx,
+    Ry,
+    Rz,
+    Gw,
+    Gx,
+    Gy,
+    Gz,
+    Bw,
+    Bx,
+    By,
+    Bz,
+}
+
+// A run of bits of a field, from the first bit stored in the block to the last one.
+// Some modes store the high bits of the endpoints reversed.
+type Segment = (Field, u32, u32);
+
+struct Bc6hMode {
+    subsets: usize,
+    endpoint_bits: u32,
+    delta_bits: [u32; 3],
+    transformed: bool,
+    layout: &'static [Segment],
+}
+
+use Field::*;
+
+#[rustfmt::skip]
+const BC6H_MODE_1: &[Segment] = &[
+    (M, 0, 1), (Gy, 4, 4), (By, 4, 4), (Bz, 4, 4), (Rw, 0, 9), (Gw, 0, 9), (Bw, 0, 9),
+    (Rx, 0, 4), (Gz, 4, 4), (Gy, 0, 3), (Gx, 0, 4), (Bz, 0, 0), (Gz, 0, 3), (Bx, 0, 4),
+    (Bz, 1, 1), (By, 0, 3), (Ry, 0, 4), (Bz, 2, 2), (Rz, 0, 4), (Bz, 3, 3), (D, 0, 4),
+];
+#[rustfmt::skip]
+const BC6H_MODE_2: &[Segment] = &[
+    (M, 0, 1), (Gy, 5, 5), (Gz, 4, 5), (Rw, 0, 6), (Bz, 0, 1), (By, 4, 4), (Gw, 0, 6),
+    (By, 5, 5), (Bz, 2, 2), (Gy, 4, 4), (Bw, 0, 6), (Bz, 3, 3), (Bz, 5, 5), (Bz, 4, 4),
+    (Rx, 0, 5), (Gy, 0, 3), (Gx, 0, 5), (Gz, 0, 3), (Bx, 0, 5), (By, 0, 3), (Ry, 0, 5),
+    (Rz, 0, 5), (D, 0, 4),
+];
+#[rustfmt::skip]
+const BC6H_MODE_3: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 9), (Gw, 0, 9), (Bw, 0, 9), (Rx, 0, 4), (Rw, 10, 10), (Gy, 0, 3),
+    (Gx, 0, 3), (Gw, 10, 10), (Bz, 0, 0), (Gz, 0, 3), (Bx, 0, 3), (Bw, 10, 10), (Bz, 1, 1),
+    (By, 0, 3), (Ry, 0, 4), (Bz, 2, 2), (Rz, 0, 4), (Bz, 3, 3), (D, 0, 4),
+];
+#[rustfmt::skip]
+const BC6H_MODE_4: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 9), (Gw, 0, 9), (Bw, 0, 9), (Rx, 0, 3), (Rw, 10, 10), (Gz, 4, 4),
+    (Gy, 0, 3), (Gx, 0, 4), (Gw, 10, 10), (Gz, 0, 3), (Bx, 0, 3), (Bw, 10, 10), (Bz, 1, 1),
+    (By, 0, 3), (Ry, 0, 3), (Bz, 0, 0), (Bz, 2, 2), (Rz, 0, 3), (Gy, 4, 4), (Bz, 3, 3),
+    (D, 0, 4),
+];
+#[rustfmt::skip]
+const BC6H_MODE_5: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 9), (Gw, 0, 9), (Bw, 0, 9), (Rx, 0, 3), (Rw, 10, 10), (By, 4, 4),
+    (Gy, 0, 3), (Gx, 0, 3), (Gw, 10, 10), (Bz, 0, 0), (Gz, 0, 3), (Bx, 0, 4), (Bw, 10, 10),
+    (By, 0, 3), (Ry, 0, 3), (Bz, 1, 2), (Rz, 0, 3), (Bz, 4, 4), (Bz, 3, 3), (D, 0, 4),
+];
+#[rustfmt::skip]
+const BC6H_MODE_6: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 8), (By, 4, 4), (Gw, 0, 8), (Gy, 4, 4), (Bw, 0, 8), (Bz, 4, 4),
+    (Rx, 0, 4), (Gz, 4, 4), (Gy, 0, 3), (Gx, 0, 4), (Bz, 0, 0), (Gz, 0, 3), (Bx, 0, 4),
+    (Bz, 1, 1), (By, 0, 3), (Ry, 0, 4), (Bz, 2, 2), (Rz, 0, 4), (Bz, 3, 3), (D, 0, 4),
+];
+#[rustfmt::skip]
+const BC6H_MODE_7: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 7), (Gz, 4, 4), (By, 4, 4), (Gw, 0, 7), (Bz, 2, 2), (Gy, 4, 4),
+    (Bw, 0, 7), (Bz, 3, 4), (Rx, 0, 5), (Gy, 0, 3), (Gx, 0, 4), (Bz, 0, 0), (Gz, 0, 3),
+    (Bx, 0, 4), (Bz, 1, 1), (By, 0, 3), (Ry, 0, 5), (Rz, 0, 5), (D, 0, 4),
+];
+#[rustfmt::skip]
+const BC6H_MODE_8: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 7), (Bz, 0, 0), (By, 4, 4), (Gw, 0, 7), (Gy, 5, 5), (Gy, 4, 4),
+    (Bw, 0, 7), (Gz, 5, 5), (Bz, 4, 4), (Rx, 0, 4), (Gz, 4, 4), (Gy, 0, 3), (Gx, 0, 5),
+    (Gz, 0, 3), (Bx, 0, 4), (Bz, 1, 1), (By, 0, 3), (Ry, 0, 4), (Bz, 2, 2), (Rz, 0, 4),
+    (Bz, 3, 3), (D, 0, 4),
+];
+#[rustfmt::skip]
+const BC6H_MODE_9: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 7), (Bz, 1, 1), (By, 4, 4), (Gw, 0, 7), (By, 5, 5), (Gy, 4, 4),
+    (Bw, 0, 7), (Bz, 5, 5), (Bz, 4, 4), (Rx, 0, 4), (Gz, 4, 4), (Gy, 0, 3), (Gx, 0, 4),
+    (Bz, 0, 0), (Gz, 0, 3), (Bx, 0, 5), (By, 0, 3), (Ry, 0, 4), (Bz, 2, 2), (Rz, 0, 4),
+    (Bz, 3, 3), (D, 0, 4),
+];
+#[rustfmt::skip]
+const BC6H_MODE_10: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 5), (Gz, 4, 4), (Bz, 0, 1), (By, 4, 4), (Gw, 0, 5), (Gy, 5, 5),
+    (By, 5, 5), (Bz, 2, 2), (Gy, 4, 4), (Bw, 0, 5), (Gz, 5, 5), (Bz, 3, 3), (Bz, 5, 5),
+    (Bz, 4, 4), (Rx, 0, 5), (Gy, 0, 3), (Gx, 0, 5), (Gz, 0, 3), (Bx, 0, 5), (By, 0, 3),
+    (Ry, 0, 5), (Rz, 0, 5), (D, 0, 4),
+];
+#[rustfmt::skip]
+const BC6H_MODE_11: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 9), (Gw, 0, 9), (Bw, 0, 9), (Rx, 0, 9), (Gx, 0, 9), (Bx, 0, 9),
+];
+#[rustfmt::skip]
+const BC6H_MODE_12: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 9), (Gw, 0, 9), (Bw, 0, 9), (Rx, 0, 8), (Rw, 10, 10), (Gx, 0, 8),
+    (Gw, 10, 10), (Bx, 0, 8), (Bw, 10, 10),
+];
+#[rustfmt::skip]
+const BC6H_MODE_13: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 9), (Gw, 0, 9), (Bw, 0, 9), (Rx, 0, 7), (Rw, 11, 10), (Gx, 0, 7),
+    (Gw, 11, 10), (Bx, 0, 7), (Bw, 11, 10),
+];
+#[rustfmt::skip]
+const BC6H_MODE_14: &[Segment] = &[
+    (M, 0, 4), (Rw, 0, 9), (Gw, 0, 9), (Bw, 0, 9), (Rx, 0, 3), (Rw, 15, 10), (Gx, 0, 3),
+    (Gw, 15, 10), (Bx, 0, 3), (Bw, 15, 10),
+];
+
+const fn bc6h_mode(
+    subsets: usize,
+    endpoint_bits: u32,
+    delta_bits: [u32; 3],
+    transformed: bool,
+    layout: &'static [Segment],
+) -> Bc6hMode {
+    Bc6hMode {
+        subsets,
+        endpoint_bits,
+        delta_bits,
+        transformed,
+        layout,
+    }
+}
+
+// The modes by the value of their 2 or 5 mode bits
+fn bc6h_mode_of(mode_bits: u32) -> Option<Bc6hMode> {
+    let mode = match mode_bits {
+        0 => bc6h_mode(2, 10, [5, 5, 5], true, BC6H_MODE_1),
+        1 => bc6h_mode(2, 7, [6, 6, 6], true, BC6H_MODE_2),
+        2 => bc6h_mode(2, 11, [5, 4, 4], true, BC6H_MODE_3),
+        6 => bc6h_mode(2, 11, [4, 5, 4], true, BC6H_MODE_4),
+        10 => bc6h_mode(2, 11, [4, 4, 5], true, BC6H_MODE_5),
+        14 => bc6h_mode(2, 9, [5, 5, 5], true, BC6H_MODE_6),
+        18 => bc6h_mode(2, 8, [6, 5, 5], true, BC6H_MODE_7),
+        22 => bc6h_mode(2, 8, [5, 6, 5], true, BC6H_MODE_8),
+        26 => bc6h_mode(2, 8, [5, 5, 6], true, BC6H_MODE_9),
+        30 => bc6h_mode(2, 6, [6, 6, 6], false, BC6H_MODE_10),
+        3 => bc6h_mode(1, 10, [10, 10, 10], false, BC6H_MODE_11),
+        7 => bc6h_mode(1, 11, [9, 9, 9], true, BC6H_MODE_12),
+        11 => bc6h_mode(1, 12, [8, 8, 8], true, BC6H_MODE_13),
+        15 => bc6h_mode(1, 16, [4, 4, 4], true, BC6H_MODE_14),
+        _ => return None,
+    };
+    Some(mode)
+}
+
+fn sign_extend(value: u32, bits: u32) -> i32 {
+    let shift = 32 - bits;
+    ((value << shift) as i32) >> shift
+}
+
+// Decodes a 16 bytes BC6H block, the texels are in row order
+pub fn decode_bc6h_block(block: &[u8], signed: bool) -> [[u8; 4]; 16] {
+    let mut bits = BlockBits::new(block);
+
+    // the modes with 2 subsets and 10 bits or less use only 2 mode bits
+    let short_mode = bits.bits as u32 & 3;
+    let mode_bits = if short_mode < 2 {
+        short_mode
+    } else {
+        bits.bits as u32 & 0x1f
+    };
+
+    // the reserved modes decode to black
+    let mode = match bc6h_mode_of(mode_bits) {
+        Some(mode) => mode,
+        None => return [[0, 0, 0, 255]; 16],
+    };
+
+    // every field is scattered in the header, the bits are moved to their place
+    let mut fields = [0u32; 14];
+    for &(field, first, last) in mode.layout {
+        let (lo, hi) = (first.min(last), first.max(last));
+        for i in 0..=hi - lo {
+            let bit = if first <= last { lo + i } else { hi - i };
+            fields[field as usize] |= bits.read(1) << bit;
+        }
+    }
+
+    let partition = fields[D as usize] as usize;
+    let bases = [Rw, Gw, Bw];
+    let others = [[Rx, Gx, Bx], [Ry, Gy, By], [Rz, Gz, Bz]];
+    let mask = (1u32 << mode.endpoint_bits) - 1;
+
+    // the endpoints as signed integers with the precision of the mode
+    let mut endpoints = [[0i32; 3]; 4];
+    for channel in 0..3 {
+        let base = fields[bases[channel] as usize];
+        endpoints[0][channel] = if signed {
+            sign_extend(base, mode.endpoint_bits)
+        } else {
+            base as i32
+        };
+
+        for (endpoint, other) in others.iter().take(mode.subsets * 2 - 1).enumerate() {
+            let value = fields[other[channel] as usize];
+            let value = if mode.transformed {
+                // the other endpoints are deltas from the first one
+                let delta = sign_extend(value, mode.delta_bits[channel]);
+                let value = (base as i32).wrapping_add(delta) as u32 & mask;
+                if signed {
+                    sign_extend(value, mode.endpoint_bits)
+                } else {
+                    value as i32
+                }
+            } else if signed {
+                sign_extend(value, mode.endpoint_bits)
+            } else {
+                value as i32
+            };
+            endpoints[endpoint + 1][channel] = value;
+        }
+    }
+
+    let endpoints = endpoints.map(|e| e.map(|c| unquantize_bc6h(c, mode.endpoint_bits, signed)));
+
+    bits.pos = if mode.subsets == 1 { 65 } else { 82 };
+    let index_bits = if mode.subsets == 1 { 4 } else { 3 };
+    let indices = read_indices(&mut bits, mode.subsets, partition, index_bits);
+
+    let mut texels = [[0, 0, 0, 255]; 16];
+    for (i, texel) in texels.iter_mut().enumerate() {
+        let subset = subset_of(mode.subsets, partition, i);
+        let (e0, e1) = (endpoints[subset * 2], endpoints[subset * 2 + 1]);
+        let w = weight(index_bits, indices[i]) as i32;
+        for channel in 0..3 {
+            let value = ((64 - w) * e0[channel] + w * e1[channel] + 32) >> 6;
+            texel[channel] = half_to_unorm8(finish_bc6h(value, signed));
+        }
+    }
+
+    texels
+}
+
+// Scales the endpoints to 16 bits, signed values keep their sign
+fn unquantize_bc6h(value: i32, bits: u32, signed: bool) -> i32 {
+    if !signed {
+        if bits >= 15 || value == 0 {
+            value
+        } else if value == (1 << bits) - 1 {
+            0xffff
+        } else {
+            ((value << 16) + 0x8000) >> bits
+        }
+    } else {
+        if bits >= 16 {
+            return value;
+        }
+        let magnitude = value.abs();
+        let unquantized = if magnitude == 0 {
+            0
+        } else if magnitude >= (1 << (bits - 1)) - 1 {
+            0x7fff
+        } else {
+            ((magnitude << 15) + 0x4000) >> (bits - 1)
+        };
+        if value < 0 {
+            -unquantized
+        } else {
+            unquantized
+        }
+    }
+}
+
+// The interpolated values are scaled to the half float bits, 31/64 or 31/32 of the range
+fn finish_bc6h(value: i32, signed: bool) -> u16 {
+    if !signed {
+        ((value * 31) >> 6) as u16
+    } else if value < 0 {
+        (((-value * 31) >> 5) as u16) | 0x8000
+    } else {
+        ((value * 31) >> 5) as u16
+    }
+}
+
+fn half_to_unorm8(half: u16) -> u8 {
+    let exp = ((half >> 10) & 0x1f) as i32;
+    let mantissa = (half & 0x3ff) as f32;
+    let value = match exp {
+        0 => mantissa * 2f32.powi(-24),
+        0x1f => f32::INFINITY,
+        _ => (1.0 + mantissa / 1024.0) * 2f32.powi(exp - 15),
+    };
+
+    // negative values and NaN end as 0
+    if half & 0x8000 != 0 || value.is_nan() {
+        return 0;
+    }
+    (value.min(1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Writes the fields of a block from the lowest bit
+    fn pack(fields: &[(u128, u32)]) -> [u8; 16] {
+        let mut bits = 0u128;
+        let mut pos = 0;
+        for (value, count) in fields {
+            bits |= value << pos;
+            pos += count;
+        }
+        assert!(pos <= 128);
+        bits.to_le_bytes()
+    }
+
+    #[test]
+    fn anchors_belong_to_their_subset() {
+        for partition in 0..64 {
+            assert_eq!(PARTITIONS_2[partition][ANCHORS_2[partition] as usize], 1);
+            assert_eq!(
+                PARTITIONS_3[partition][ANCHORS_3_SECOND[partition] as usize],
+                1
+            );
+            assert_eq!(
+                PARTITIONS_3[partition][ANCHORS_3_THIRD[partition] as usize],
+                2
+            );
+            assert!(PARTITIONS_2[partition][0] == 0 && PARTITIONS_3[partition][0] == 0);
+        }
+    }
+
+    #[test]
+    fn bc7_mode_6_is_decoded() {
+        // RGBA endpoints 0 and 127 with the p-bits 0 and 1 give black and white,
+        // the texels use the 16 indices in order
+        let mut fields = vec![(1 << 6, 7)];
+        fields.extend([(0, 7), (127, 7)].repeat(4));
+        fields.extend([(0, 1), (1, 1), (0, 3)]);
+        fields.extend((1..16).map(|i| (i, 4)));
+        let texels = decode_bc7_block(&pack(&fields));
+        assert_eq!(texels[0], [0, 0, 0, 0]);
+        assert_eq!(texels[1], [16, 16, 16, 16]);
+        assert_eq!(texels[8], [135, 135, 135, 135]);
+        assert_eq!(texels[15], [255; 4]);
+    }
+
+    #[test]
+    fn bc7_mode_5_rotates_the_alpha() {
+        // red endpoints 127, transparent alpha and the rotation 1 that swaps the red and
+        // alpha channels, all the color indices are 0 and the alpha indices 3
+        let mut fields = vec![(1 << 5, 6), (1, 2)];
+        fields.extend([(127, 7), (127, 7), (0, 7), (0, 7), (0, 7), (0, 7)]);
+        fields.extend([(0, 8), (0, 8), (0, 31), (1, 1)]);
+        fields.extend([(3, 2)].repeat(15));
+        let texels = decode_bc7_block(&pack(&fields));
+        assert!(texels.iter().all(|texel| *texel == [0, 0, 0, 255]));
+    }
+
+    #[test]
+    fn bc7_subsets_follow_the_partition() {
+        // mode 1 with the partition 0, the 2 left columns use the first subset (black)
+        // and the right ones the second subset (white), the p-bits are shared
+        let mut fields = vec![(1 << 1, 2), (0, 6)];
+        fields.extend([(0, 6), (0, 6), (63, 6), (63, 6)].repeat(3));
+        fields.extend([(0, 1), (1, 1), (0, 46)]);
+        let texels = decode_bc7_block(&pack(&fields));
+        for (i, texel) in texels.iter().enumerate() {
+            let expected = if i % 4 < 2 { 0 } else { 255 };
+            assert_eq!(*texel, [expected, expected, expected, 255]);
+        }
+
+        // the reserved mode is transparent black
+        assert_eq!(decode_bc7_block(&[0; 16]), [[0; 4]; 16]);
+    }
+
+    #[test]
+    fn bc6h_mode_11_is_decoded() {
+        // 10 bits endpoints 0 and 1023, the largest value is the largest half float and
+        // clamps to white, the green endpoint 512 is a bit more than 1.0
+        let mut fields = vec![(3, 5), (0, 10), (0, 10), (0, 10)];
+        fields.extend([(1023, 10), (512, 10), (0, 10)]);
+        fields.extend([(0, 3)]);
+        fields.extend((1..16).map(|i| (i, 4)));
+        let texels = decode_bc6h_block(&pack(&fields), false);
+        assert_eq!(texels[0], [0, 0, 0, 255]);
+        assert_eq!(texels[15], [255, 255, 0, 255]);
+
+        // the weight 30 of the index 7 gives the half floats 0x3a20 (0.77) and 0x1d17 (0.005)
+        assert_eq!(texels[7], [195, 1, 0, 255]);
+        assert_eq!(half_to_unorm8(0x3800), 128);
+    }
+
+    #[test]
+    fn bc6h_signed_endpoints_are_clamped() {
+        // the same block read as signed, 1023 is -1 and the negative values are black
+        let mut fields = vec![(3, 5), (0, 10), (0, 10), (0, 10)];
+        fields.extend([(1023, 10), (511, 10), (0, 10)]);
+        fields.extend([(0, 3)]);
+        fields.extend([(15, 4)].repeat(15));
+        let texels = decode_bc6h_block(&pack(&fields), true);
+        assert_eq!(texels[15], [0, 255, 0, 255]);
+        assert_eq!(
+            decode_bc6h_block(&pack(&[(0x13, 5)]), false)[0],
+            [0, 0, 0, 255]
+        );
+    }
+
+    #[test]
+    fn bc6h_deltas_are_added_to_the_base() {
+        // mode 12 (11 bits base and 9 bits deltas), the base is 800 and the delta -100 in
+        // every channel, the halfs are 0x3077 (0.14) and 0x2a69 (0.05)
+        let mut fields = vec![(7, 5), (800, 10), (800, 10), (800, 10)];
+        fields.extend([(412, 9), (0, 1), (412, 9), (0, 1), (412, 9), (0, 1)]);
+        fields.extend([(0, 3)]);
+        fields.extend([(15, 4)].repeat(15));
+        let texels = decode_bc6h_block(&pack(&fields), false);
+        assert_eq!(texels[0], [36, 36, 36, 255]);
+        assert_eq!(texels[15], [13, 13, 13, 255]);
+    }
+}
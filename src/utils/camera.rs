@@ -1,3 +1,4 @@
+use super::clock::{frame_time, interpolation_alpha};
 use notan::app::assets::LoaderCallback::P;
 use notan::math::{vec3, Mat4, Vec3};
 
@@ -14,6 +15,16 @@ pub enum CameraMovement {
     Right,
 }
 
+// Where the camera was before the step that moved it last
+#[derive(Clone, Copy, Debug)]
+pub struct CameraPose {
+    pub position: Vec3,
+    pub front: Vec3,
+    pub up: Vec3,
+    // step of the clock that moved the camera
+    pub frame: u64,
+}
+
 pub struct Camera {
     // Camera attrs
     pub position: Vec3,
@@ -28,6 +39,8 @@ pub struct Camera {
     pub movement_speed: f32,
     pub mouse_sensitivity: f32,
     pub zoom: f32,
+    // used to render the camera between steps when the updates run at a fixed timestep
+    pub previous: Option<CameraPose>,
 }
 
 impl Default for Camera {
@@ -43,6 +56,7 @@ impl Default for Camera {
             movement_speed: SPEED,
             mouse_sensitivity: SENSITIVITY,
             zoom: ZOOM,
+            previous: None,
         };
         update_camera_vectors(&mut camera);
         camera
@@ -50,11 +64,47 @@ impl Default for Camera {
 }
 
 impl Camera {
+    // Pose to render. When the updates run at a fixed timestep and the camera moved in the
+    // last step, it's between the last two poses following `interpolation_alpha`.
+    pub fn interpolated_pose(&self) -> CameraPose {
+        let frame = frame_time().frame;
+        let alpha = interpolation_alpha();
+        match self.previous {
+            Some(prev) if prev.frame == frame && alpha > 0.0 => CameraPose {
+                position: prev.position.lerp(self.position, alpha),
+                front: prev.front.lerp(self.front, alpha).normalize_or_zero(),
+                up: prev.up.lerp(self.up, alpha).normalize_or_zero(),
+                frame,
+            },
+            _ => CameraPose {
+                position: self.position,
+                front: self.front,
+                up: self.up,
+                frame,
+            },
+        }
+    }
+
     pub fn get_view_matrix(&self) -> Mat4 {
-        Mat4::look_at_rh(self.position, self.position + self.front, self.up)
+        let pose = self.interpolated_pose();
+        Mat4::look_at_rh(pose.position, pose.position + pose.front, pose.up)
+    }
+
+    // stores the pose before the first movement of each step
+    fn save_previous(&mut self) {
+        let frame = frame_time().frame;
+        if !matches!(self.previous, Some(prev) if prev.frame == frame) {
+            self.previous = Some(CameraPose {
+                position: self.position,
+                front: self.front,
+                up: self.up,
+                frame,
+            });
+        }
     }
 
     pub fn process_keyboard(&mut self, direction: CameraMovement, delta: f32) {
+        self.save_previous();
         let velocity = self.movement_speed * delta;
         match direction {
             CameraMovement::Forward => self.position += self.front * velocity,
@@ -65,6 +115,7 @@ impl Camera {
     }
 
     pub fn process_mouse_movement(&mut self, xoffset: f32, yoffset: f32, constrain_pitch: bool) {
+        self.save_previous();
         self.yaw += xoffset * self.mouse_sensitivity;
         self.pitch += yoffset * self.mouse_sensitivity;
 
@@ -75,6 +126,13 @@ impl Camera {
         update_camera_vectors(self);
     }
 
+    // Points the camera to these angles (degrees) without recording a movement
+    pub fn set_angles(&mut self, yaw: f32, pitch: f32) {
+        self.yaw = yaw;
+        self.pitch = pitch.clamp(-89.0, 89.0);
+        update_camera_vectors(self);
+    }
+
     pub fn process_mouse_scroll(&mut self, yoffset: f32) {
         if yoffset != 0.0 {
             self.zoom -= yoffset;
@@ -94,3 +152,32 @@ fn update_camera_vectors(camera: &mut Camera) {
     camera.right = camera.front.cross(camera.world_up).normalize();
     camera.up = camera.right.cross(camera.front).normalize();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{advance_clock, reset_clock, set_interpolation_alpha};
+
+    #[test]
+    fn camera_is_interpolated_between_steps() {
+        reset_clock();
+        let mut camera = Camera::default();
+        advance_clock(0.1);
+        camera.process_keyboard(CameraMovement::Forward, 0.4);
+        assert!(camera.position.abs_diff_eq(vec3(0.0, 0.0, -1.0), 1e-6));
+
+        // the frame is drawn halfway to the next step
+        set_interpolation_alpha(0.5);
+        let pose = camera.interpolated_pose();
+        assert!(pose.position.abs_diff_eq(vec3(0.0, 0.0, -0.5), 1e-6));
+        let view = camera.get_view_matrix();
+        assert!(view
+            .transform_point3(vec3(0.0, 0.0, -0.5))
+            .abs_diff_eq(Vec3::ZERO, 1e-6));
+
+        // without a movement in the step the current pose is used
+        advance_clock(0.1);
+        set_interpolation_alpha(0.5);
+        assert_eq!(camera.interpolated_pose().position, camera.position);
+    }
+}
@@ -0,0 +1,126 @@
+use super::stats::{add_frame_stats, StatsRenderer};
+use image::RgbaImage;
+use notan::draw::*;
+use notan::graphics::Renderer;
+use notan::prelude::*;
+use std::cell::RefCell;
+
+// Frame capture. Notan can't read back the screen, only textures, so the examples present
+// their passes using `present` and while a capture is active they are redirected to a
+// render texture that we can read.
+
+// The render texture is kept between captures, it's only created again when the screen
+// changes its size
+struct CaptureTarget {
+    rt: RenderTexture,
+    active: bool,
+}
+
+thread_local! {
+    static TARGET: RefCell<Option<CaptureTarget>> = RefCell::new(None);
+}
+
+// Use this instead of `gfx.render` to allow capturing the frame, it also adds the
+// commands of the renderer to the frame stats
+pub fn present(gfx: &mut Graphics, renderer: &StatsRenderer) {
+    add_frame_stats(renderer.stats());
+
+    let renderer: &Renderer = renderer;
+    TARGET.with(|target| match target.borrow().as_ref() {
+        Some(target) if target.active => gfx.render_to(&target.rt, renderer),
+        _ => gfx.render(renderer),
+    });
+}
+
+#[derive(Clone, Debug)]
+pub struct CapturedFrame {
+    pub width: u32,
+    pub height: u32,
+    // RGBA, top to bottom
+    pub data: Vec<u8>,
+}
+
+impl CapturedFrame {
+    pub fn to_image(&self) -> Result<RgbaImage, String> {
+        RgbaImage::from_raw(self.width, self.height, self.data.clone())
+            .ok_or_else(|| "Invalid frame size".to_string())
+    }
+
+    pub fn save_png(&self, path: &str) -> Result<(), String> {
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+
+        self.to_image()?
+            .save_with_format(path, image::ImageFormat::Png)
+            .map_err(|err| format!("Cannot save '{}': {}", path, err))
+    }
+}
+
+// Starts redirecting `present` to a render texture of the size of the screen. It has a depth
+// buffer like the screen, the examples using the depth test are captured as they are drawn.
+pub fn begin_capture(gfx: &mut Graphics) -> Result<(), String> {
+    let (width, height) = gfx.size();
+    let same_size = |rt: &RenderTexture| (rt.width(), rt.height()) == (width as _, height as _);
+    let cached = TARGET.with(|target| target.borrow_mut().take());
+    let rt = match cached {
+        Some(target) if same_size(&target.rt) => target.rt,
+        _ => gfx
+            .create_render_texture(width as _, height as _)
+            .with_depth()
+            .build()?,
+    };
+
+    // start from black in case the example doesn't clear the screen
+    let mut renderer = gfx.create_renderer();
+    renderer.begin(Some(&ClearOptions {
+        color: Some(Color::BLACK),
+        depth: Some(1.0),
+        stencil: Some(0),
+    }));
+    renderer.end();
+    gfx.render_to(&rt, &renderer);
+
+    TARGET.with(|target| *target.borrow_mut() = Some(CaptureTarget { rt, active: true }));
+    Ok(())
+}
+
+// Stops the capture and reads the frame, the captured texture is drawn to the screen
+// so this frame is visible too
+pub fn end_capture(gfx: &mut Graphics) -> Result<CapturedFrame, String> {
+    let rt = TARGET
+        .with(|target| {
+            target
+                .borrow_mut()
+                .as_mut()
+                .filter(|target| target.active)
+                .map(|target| {
+                    target.active = false;
+                    target.rt.clone()
+                })
+        })
+        .ok_or_else(|| "There is no capture in progress".to_string())?;
+
+    let (width, height) = (rt.width() as u32, rt.height() as u32);
+    let mut data = vec![0; (width * height * 4) as usize];
+    gfx.read_pixels(rt.texture()).read_to(&mut data)?;
+
+    // render textures are stored bottom to top
+    let stride = (width * 4) as usize;
+    let data = data
+        .chunks_exact(stride)
+        .rev()
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>();
+
+    let mut draw = gfx.create_draw();
+    draw.image(rt.texture()).size(width as _, height as _);
+    gfx.render(&draw);
+
+    Ok(CapturedFrame {
+        width,
+        height,
+        data,
+    })
+}
@@ -0,0 +1,158 @@
+use std::cell::Cell;
+
+// Time of the examples. The runner advances it following the real time, by fixed steps
+// (see `FixedTimestep`) or by a synthetic step per frame when recording or running in
+// deterministic mode, so the examples must use these functions instead of `app.timer`.
+
+thread_local! {
+    static CLOCK: Cell<FrameTime> = Cell::new(FrameTime::default());
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameTime {
+    // seconds since the last step
+    pub delta: f32,
+    // seconds since the example started
+    pub elapsed: f32,
+    // number of steps simulated
+    pub frame: u64,
+    // how far the render is between the last step and the next one (0.0 - 1.0), it's only
+    // set during draw when the updates run at a fixed timestep
+    pub alpha: f32,
+}
+
+pub fn frame_time() -> FrameTime {
+    CLOCK.with(|clock| clock.get())
+}
+
+pub fn delta_time() -> f32 {
+    frame_time().delta
+}
+
+// During draw this includes the time accumulated since the last step, so animations
+// based on it stay smooth when the updates run at a lower rate than the frames
+pub fn elapsed_time() -> f32 {
+    let time = frame_time();
+    time.elapsed + time.delta * time.alpha
+}
+
+pub fn interpolation_alpha() -> f32 {
+    frame_time().alpha
+}
+
+// Moves the clock to the next step
+pub fn advance_clock(delta: f32) -> FrameTime {
+    CLOCK.with(|clock| {
+        let time = clock.get();
+        let time = FrameTime {
+            delta,
+            elapsed: time.elapsed + delta,
+            frame: time.frame + 1,
+            alpha: 0.0,
+        };
+        clock.set(time);
+        time
+    })
+}
+
+pub fn set_interpolation_alpha(alpha: f32) {
+    CLOCK.with(|clock| {
+        let mut time = clock.get();
+        time.alpha = alpha.clamp(0.0, 1.0);
+        clock.set(time);
+    });
+}
+
+pub fn reset_clock() {
+    CLOCK.with(|clock| clock.set(FrameTime::default()));
+}
+
+// Accumulates the real time of the frames and splits it in steps of the same size
+#[derive(Clone, Copy, Debug)]
+pub struct FixedTimestep {
+    step: f32,
+    accumulator: f32,
+    // when the frames are too slow the time over this is dropped, to avoid spending
+    // each frame simulating more steps than the last one
+    max_steps: u32,
+}
+
+impl FixedTimestep {
+    pub fn new(fps: u32) -> Self {
+        Self {
+            step: 1.0 / fps.max(1) as f32,
+            accumulator: 0.0,
+            max_steps: 8,
+        }
+    }
+
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    // Adds the time of a frame and returns how many steps must be simulated
+    pub fn accumulate(&mut self, delta: f32) -> u32 {
+        self.accumulator += delta.max(0.0);
+        let steps = (self.accumulator / self.step).floor() as u32;
+        if steps > self.max_steps {
+            self.accumulator = 0.0;
+            return self.max_steps;
+        }
+
+        self.accumulator -= steps as f32 * self.step;
+        steps
+    }
+
+    // Fraction of a step left in the accumulator, used to interpolate the render
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_accumulates_the_steps() {
+        reset_clock();
+        advance_clock(0.5);
+        let time = advance_clock(0.25);
+        assert_eq!(time.delta, 0.25);
+        assert_eq!(time.elapsed, 0.75);
+        assert_eq!(time.frame, 2);
+        assert_eq!(frame_time(), time);
+
+        reset_clock();
+        assert_eq!(elapsed_time(), 0.0);
+    }
+
+    #[test]
+    fn elapsed_time_is_interpolated() {
+        reset_clock();
+        advance_clock(0.5);
+        set_interpolation_alpha(0.5);
+        assert_eq!(elapsed_time(), 0.75);
+
+        // the next step starts from the simulated time
+        advance_clock(0.5);
+        assert_eq!(elapsed_time(), 1.0);
+        assert_eq!(interpolation_alpha(), 0.0);
+    }
+
+    #[test]
+    fn fixed_timestep_keeps_the_remainder() {
+        let mut timestep = FixedTimestep::new(10);
+        assert_eq!(timestep.accumulate(0.05), 0);
+        assert!((timestep.alpha() - 0.5).abs() < 1e-5);
+        assert_eq!(timestep.accumulate(0.175), 2);
+        assert!((timestep.alpha() - 0.25).abs() < 1e-4);
+    }
+
+    #[test]
+    fn fixed_timestep_drops_the_time_of_slow_frames() {
+        let mut timestep = FixedTimestep::new(60);
+        assert_eq!(timestep.accumulate(5.0), 8);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+}
@@ -0,0 +1,1128 @@
+use super::assets::TextureOptions;
+use super::astc::decode_astc_block;
+use super::bptc::{decode_bc6h_block, decode_bc7_block};
+use super::mipmaps::{upload_mip_levels, MipLevel, MipmapData, MipmapOptions, SamplerSettings};
+use notan::prelude::*;
+
+// KTX2 and DDS containers with their pre-built mip chains.
+// Notan only uploads uncompressed formats, so the payloads are always decoded on
+// the CPU to RGBA8: BC1-7, ETC2/EAC and ASTC LDR. BC6H is clamped to [0, 1] and the
+// ASTC HDR blocks decode to magenta.
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CompressedFormat {
+    Rgba8,
+    Bgra8,
+    // alpha is 1 bit when the block uses the 3 colors mode
+    Bc1 { alpha: bool },
+    Bc2,
+    Bc3,
+    Bc4 { signed: bool },
+    Bc5 { signed: bool },
+    Etc2Rgb,
+    // 1 bit alpha (punchthrough)
+    Etc2Rgba1,
+    // ETC2 colors with EAC alpha
+    Etc2Rgba,
+    // half floats, decoded clamped to [0, 1]
+    Bc6h { signed: bool },
+    Bc7,
+    // the block footprint goes from 4x4 to 12x12 texels
+    Astc { block_width: u32, block_height: u32 },
+}
+
+impl CompressedFormat {
+    // Texels per row and column of a block, and bytes per block
+    fn block_info(&self) -> (u32, u32, usize) {
+        match *self {
+            CompressedFormat::Rgba8 | CompressedFormat::Bgra8 => (1, 1, 4),
+            CompressedFormat::Bc1 { .. }
+            | CompressedFormat::Bc4 { .. }
+            | CompressedFormat::Etc2Rgb
+            | CompressedFormat::Etc2Rgba1 => (4, 4, 8),
+            CompressedFormat::Bc2
+            | CompressedFormat::Bc3
+            | CompressedFormat::Bc5 { .. }
+            | CompressedFormat::Etc2Rgba
+            | CompressedFormat::Bc6h { .. }
+            | CompressedFormat::Bc7 => (4, 4, 16),
+            CompressedFormat::Astc {
+                block_width,
+                block_height,
+            } => (block_width, block_height, 16),
+        }
+    }
+
+    // Bytes used by a level of this size, the headers can declare sizes that don't fit in memory
+    pub fn level_size(&self, width: u32, height: u32) -> Result<usize, String> {
+        let (block_width, block_height, bytes) = self.block_info();
+        let blocks_x = width.div_ceil(block_width).max(1) as u64;
+        let blocks_y = height.div_ceil(block_height).max(1) as u64;
+        blocks_x
+            .checked_mul(blocks_y)
+            .and_then(|blocks| blocks.checked_mul(bytes as u64))
+            .and_then(|size| usize::try_from(size).ok())
+            .ok_or_else(|| format!("A {}x{} level is too big", width, height))
+    }
+}
+
+pub struct TextureContainer {
+    pub format: CompressedFormat,
+    // the texels are sRGB encoded
+    pub srgb: bool,
+    pub width: u32,
+    pub height: u32,
+    // raw payload of each mip level, the first one is the full size
+    pub levels: Vec<Vec<u8>>,
+}
+
+impl TextureContainer {
+    pub fn level_dimensions(&self, level: usize) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+
+    pub fn decode_level(&self, level: usize) -> Result<MipLevel, String> {
+        let data = self
+            .levels
+            .get(level)
+            .ok_or_else(|| format!("Missing mip level {}", level))?;
+        let (width, height) = self.level_dimensions(level);
+        let data = decode_payload(self.format, self.srgb, width, height, data)?;
+        Ok(MipLevel {
+            width,
+            height,
+            data,
+        })
+    }
+
+    pub fn decode_levels(&self) -> Result<Vec<MipLevel>, String> {
+        (0..self.levels.len())
+            .map(|level| self.decode_level(level))
+            .collect()
+    }
+}
+
+pub fn is_texture_container(bytes: &[u8]) -> bool {
+    bytes.starts_with(&KTX2_IDENTIFIER) || bytes.starts_with(DDS_MAGIC)
+}
+
+// Reads a KTX2 or DDS file, detected by its magic number
+pub fn parse_texture_container(bytes: &[u8]) -> Result<TextureContainer, String> {
+    if bytes.starts_with(&KTX2_IDENTIFIER) {
+        parse_ktx2(bytes)
+    } else if bytes.starts_with(DDS_MAGIC) {
+        parse_dds(bytes)
+    } else {
+        Err("Unknown texture container".to_string())
+    }
+}
+
+// Uploads the container decoded to RGBA8. Notan can't upload the levels of a texture, so when
+// the file has a mip chain it's packed in a mip atlas like `upload_mip_levels` does, and the
+// MipmapData needed to sample it with `textureMip` is returned too.
+pub fn create_container_texture(
+    gfx: &mut Graphics,
+    container: &TextureContainer,
+    options: &TextureOptions,
+) -> Result<(Texture, Option<MipmapData>), String> {
+    if container.levels.len() > 1 {
+        let mip_options = MipmapOptions {
+            sampler: SamplerSettings {
+                min_filter: options.min_filter,
+                mag_filter: options.mag_filter,
+                ..Default::default()
+            },
+            wrap_x: options.wrap_x,
+            wrap_y: options.wrap_y,
+            ..Default::default()
+        };
+        let (texture, mipmap) = upload_mip_levels(gfx, &container.decode_levels()?, &mip_options)?;
+        return Ok((texture, Some(mipmap)));
+    }
+
+    let level = container.decode_level(0)?;
+    let texture = gfx
+        .create_texture()
+        .from_bytes(&level.data, level.width, level.height)
+        .with_wrap(options.wrap_x, options.wrap_y)
+        .with_filter(options.min_filter, options.mag_filter)
+        .build()?;
+    Ok((texture, None))
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, String> {
+    bytes
+        .get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| "Unexpected end of file".to_string())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64, String> {
+    Ok(read_u32(bytes, offset)? as u64 | (read_u32(bytes, offset + 4)? as u64) << 32)
+}
+
+// Bytes of a RGBA8 image of this size
+fn rgba_size(width: u32, height: u32) -> Result<usize, String> {
+    (width as u64)
+        .checked_mul(height as u64)
+        .and_then(|pixels| pixels.checked_mul(4))
+        .and_then(|size| usize::try_from(size).ok())
+        .ok_or_else(|| format!("A {}x{} image is too big", width, height))
+}
+
+// Levels can't be smaller than 1x1, so a chain has at most one level per bit of the size
+fn check_level_count(width: u32, height: u32, level_count: u32) -> Result<(), String> {
+    let max_levels = 32 - width.max(height).max(1).leading_zeros();
+    if level_count > max_levels {
+        return Err(format!(
+            "{} mip levels declared for a {}x{} texture, the maximum is {}",
+            level_count, width, height, max_levels
+        ));
+    }
+
+    Ok(())
+}
+
+fn read_level(bytes: &[u8], offset: usize, len: usize) -> Result<Vec<u8>, String> {
+    offset
+        .checked_add(len)
+        .and_then(|end| bytes.get(offset..end))
+        .map(|data| data.to_vec())
+        .ok_or_else(|| "Mip level outside of the file".to_string())
+}
+
+fn parse_ktx2(bytes: &[u8]) -> Result<TextureContainer, String> {
+    let vk_format = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 20)?;
+    let height = read_u32(bytes, 24)?.max(1);
+    let depth = read_u32(bytes, 28)?;
+    let layers = read_u32(bytes, 32)?;
+    let faces = read_u32(bytes, 36)?;
+    let level_count = read_u32(bytes, 40)?.max(1);
+    let supercompression = read_u32(bytes, 44)?;
+
+    if depth > 1 || layers > 1 || faces > 1 {
+        return Err("Only 2D KTX2 textures are supported".to_string());
+    }
+
+    if supercompression != 0 {
+        return Err(format!(
+            "Unsupported KTX2 supercompression scheme {}",
+            supercompression
+        ));
+    }
+
+    let (format, srgb) = vk_format_info(vk_format)?;
+    check_level_count(width, height, level_count)?;
+
+    // the level index starts after the header and the data format index
+    let levels = (0..level_count as usize)
+        .map(|level| {
+            let entry = 80 + level * 24;
+            let offset = usize::try_from(read_u64(bytes, entry)?)
+                .map_err(|_| format!("Mip level {} is outside of the file", level))?;
+            let len = usize::try_from(read_u64(bytes, entry + 8)?)
+                .map_err(|_| format!("Mip level {} is too big", level))?;
+            let expected = format.level_size((width >> level).max(1), (height >> level).max(1))?;
+            if len < expected {
+                return Err(format!("Mip level {} is too small", level));
+            }
+            read_level(bytes, offset, expected)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(TextureContainer {
+        format,
+        srgb,
+        width,
+        height,
+        levels,
+    })
+}
+
+const ASTC_FOOTPRINTS: [(u32, u32); 14] = [
+    (4, 4),
+    (5, 4),
+    (5, 5),
+    (6, 5),
+    (6, 6),
+    (8, 5),
+    (8, 6),
+    (8, 8),
+    (10, 5),
+    (10, 6),
+    (10, 8),
+    (10, 10),
+    (12, 10),
+    (12, 12),
+];
+
+fn vk_format_info(format: u32) -> Result<(CompressedFormat, bool), String> {
+    let info = match format {
+        37 => (CompressedFormat::Rgba8, false),
+        43 => (CompressedFormat::Rgba8, true),
+        44 => (CompressedFormat::Bgra8, false),
+        50 => (CompressedFormat::Bgra8, true),
+        131 => (CompressedFormat::Bc1 { alpha: false }, false),
+        132 => (CompressedFormat::Bc1 { alpha: false }, true),
+        133 => (CompressedFormat::Bc1 { alpha: true }, false),
+        134 => (CompressedFormat::Bc1 { alpha: true }, true),
+        135 => (CompressedFormat::Bc2, false),
+        136 => (CompressedFormat::Bc2, true),
+        137 => (CompressedFormat::Bc3, false),
+        138 => (CompressedFormat::Bc3, true),
+        139 => (CompressedFormat::Bc4 { signed: false }, false),
+        140 => (CompressedFormat::Bc4 { signed: true }, false),
+        141 => (CompressedFormat::Bc5 { signed: false }, false),
+        142 => (CompressedFormat::Bc5 { signed: true }, false),
+        147 => (CompressedFormat::Etc2Rgb, false),
+        148 => (CompressedFormat::Etc2Rgb, true),
+        149 => (CompressedFormat::Etc2Rgba1, false),
+        150 => (CompressedFormat::Etc2Rgba1, true),
+        151 => (CompressedFormat::Etc2Rgba, false),
+        152 => (CompressedFormat::Etc2Rgba, true),
+        143 => (CompressedFormat::Bc6h { signed: false }, false),
+        144 => (CompressedFormat::Bc6h { signed: true }, false),
+        145 => (CompressedFormat::Bc7, false),
+        146 => (CompressedFormat::Bc7, true),
+        // every footprint has a UNORM and a SRGB format
+        157..=184 => {
+            let (block_width, block_height) = ASTC_FOOTPRINTS[(format - 157) as usize / 2];
+            let format_info = CompressedFormat::Astc {
+                block_width,
+                block_height,
+            };
+            (format_info, format.is_multiple_of(2))
+        }
+        _ => return Err(format!("Unsupported KTX2 format {}", format)),
+    };
+
+    Ok(info)
+}
+
+fn parse_dds(bytes: &[u8]) -> Result<TextureContainer, String> {
+    const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+    const DDPF_FOURCC: u32 = 0x4;
+    const DDPF_RGB: u32 = 0x40;
+    const DDSCAPS2_CUBEMAP: u32 = 0x200;
+
+    if read_u32(bytes, 4)? != 124 {
+        return Err("Invalid DDS header".to_string());
+    }
+
+    let flags = read_u32(bytes, 8)?;
+    let height = read_u32(bytes, 12)?;
+    let width = read_u32(bytes, 16)?;
+    let mip_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+        read_u32(bytes, 28)?.max(1)
+    } else {
+        1
+    };
+    let pf_flags = read_u32(bytes, 80)?;
+    let four_cc = bytes
+        .get(84..88)
+        .ok_or_else(|| "Unexpected end of file".to_string())?;
+    let caps2 = read_u32(bytes, 112)?;
+
+    if caps2 & DDSCAPS2_CUBEMAP != 0 {
+        return Err("DDS cubemaps are not supported".to_string());
+    }
+
+    let (format, srgb, data_offset) = if pf_flags & DDPF_FOURCC != 0 {
+        match four_cc {
+            b"DXT1" => (CompressedFormat::Bc1 { alpha: true }, false, 128),
+            b"DXT2" | b"DXT3" => (CompressedFormat::Bc2, false, 128),
+            b"DXT4" | b"DXT5" => (CompressedFormat::Bc3, false, 128),
+            b"ATI1" | b"BC4U" => (CompressedFormat::Bc4 { signed: false }, false, 128),
+            b"BC4S" => (CompressedFormat::Bc4 { signed: true }, false, 128),
+            b"ATI2" | b"BC5U" => (CompressedFormat::Bc5 { signed: false }, false, 128),
+            b"BC5S" => (CompressedFormat::Bc5 { signed: true }, false, 128),
+            b"DX10" => {
+                // the DX10 header is dxgiFormat, resourceDimension, miscFlag, arraySize
+                // and miscFlags2
+                if read_u32(bytes, 140)? > 1 {
+                    return Err("DDS texture arrays are not supported".to_string());
+                }
+                let (format, srgb) = dxgi_format_info(read_u32(bytes, 128)?)?;
+                (format, srgb, 148)
+            }
+            _ => {
+                return Err(format!(
+                    "Unsupported DDS format '{}'",
+                    String::from_utf8_lossy(four_cc)
+                ))
+            }
+        }
+    } else if pf_flags & DDPF_RGB != 0 && read_u32(bytes, 88)? == 32 {
+        match read_u32(bytes, 92)? {
+            0x0000_00ff => (CompressedFormat::Rgba8, false, 128),
+            0x00ff_0000 => (CompressedFormat::Bgra8, false, 128),
+            mask => return Err(format!("Unsupported DDS red mask {:#x}", mask)),
+        }
+    } else {
+        return Err("Unsupported DDS pixel format".to_string());
+    };
+
+    check_level_count(width, height, mip_count)?;
+
+    // the levels are stored one after the other
+    let mut offset = data_offset;
+    let levels = (0..mip_count)
+        .map(|level| {
+            let len = format.level_size((width >> level).max(1), (height >> level).max(1))?;
+            let data = read_level(bytes, offset, len)?;
+            offset += len;
+            Ok(data)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(TextureContainer {
+        format,
+        srgb,
+        width,
+        height,
+        levels,
+    })
+}
+
+fn dxgi_format_info(format: u32) -> Result<(CompressedFormat, bool), String> {
+    let info = match format {
+        28 => (CompressedFormat::Rgba8, false),
+        29 => (CompressedFormat::Rgba8, true),
+        87 => (CompressedFormat::Bgra8, false),
+        91 => (CompressedFormat::Bgra8, true),
+        71 => (CompressedFormat::Bc1 { alpha: true }, false),
+        72 => (CompressedFormat::Bc1 { alpha: true }, true),
+        74 => (CompressedFormat::Bc2, false),
+        75 => (CompressedFormat::Bc2, true),
+        77 => (CompressedFormat::Bc3, false),
+        78 => (CompressedFormat::Bc3, true),
+        80 => (CompressedFormat::Bc4 { signed: false }, false),
+        81 => (CompressedFormat::Bc4 { signed: true }, false),
+        83 => (CompressedFormat::Bc5 { signed: false }, false),
+        84 => (CompressedFormat::Bc5 { signed: true }, false),
+        95 => (CompressedFormat::Bc6h { signed: false }, false),
+        96 => (CompressedFormat::Bc6h { signed: true }, false),
+        98 => (CompressedFormat::Bc7, false),
+        99 => (CompressedFormat::Bc7, true),
+        _ => return Err(format!("Unsupported DXGI format {}", format)),
+    };
+
+    Ok(info)
+}
+
+// Decodes a level to RGBA8
+fn decode_payload(
+    format: CompressedFormat,
+    srgb: bool,
+    width: u32,
+    height: u32,
+    data: &[u8],
+) -> Result<Vec<u8>, String> {
+    if data.len() < format.level_size(width, height)? {
+        return Err("Mip level is too small".to_string());
+    }
+
+    let size = rgba_size(width, height)?;
+    match format {
+        CompressedFormat::Rgba8 => Ok(data[..size].to_vec()),
+        CompressedFormat::Bgra8 => Ok(data[..size]
+            .chunks_exact(4)
+            .flat_map(|px| [px[2], px[1], px[0], px[3]])
+            .collect()),
+        CompressedFormat::Bc1 { alpha } => decode_blocks(format, width, height, data, |block| {
+            decode_bc1_colors(block, alpha, false)
+        }),
+        CompressedFormat::Bc2 => decode_blocks(format, width, height, data, |block| {
+            let mut texels = decode_bc1_colors(&block[8..], false, true);
+            texels.iter_mut().enumerate().for_each(|(i, texel)| {
+                let alpha = (block[i / 2] >> ((i % 2) * 4)) & 0xf;
+                texel[3] = alpha * 17;
+            });
+            texels
+        }),
+        CompressedFormat::Bc3 => decode_blocks(format, width, height, data, |block| {
+            let alpha = decode_bc4_channel(&block[..8], false);
+            let mut texels = decode_bc1_colors(&block[8..], false, true);
+            texels
+                .iter_mut()
+                .zip(alpha)
+                .for_each(|(texel, a)| texel[3] = a);
+            texels
+        }),
+        CompressedFormat::Bc4 { signed } => decode_blocks(format, width, height, data, |block| {
+            decode_bc4_channel(block, signed).map(|r| [r, 0, 0, 255])
+        }),
+        CompressedFormat::Bc5 { signed } => decode_blocks(format, width, height, data, |block| {
+            let red = decode_bc4_channel(&block[..8], signed);
+            let green = decode_bc4_channel(&block[8..], signed);
+            let mut texels = [[0, 0, 0, 255]; 16];
+            texels.iter_mut().enumerate().for_each(|(i, texel)| {
+                texel[0] = red[i];
+                texel[1] = green[i];
+            });
+            texels
+        }),
+        CompressedFormat::Etc2Rgb => decode_blocks(format, width, height, data, |block| {
+            decode_etc2_colors(block, false)
+        }),
+        CompressedFormat::Etc2Rgba1 => decode_blocks(format, width, height, data, |block| {
+            decode_etc2_colors(block, true)
+        }),
+        CompressedFormat::Etc2Rgba => decode_blocks(format, width, height, data, |block| {
+            let alpha = decode_eac_alpha(&block[..8]);
+            let mut texels = decode_etc2_colors(&block[8..], false);
+            texels
+                .iter_mut()
+                .zip(alpha)
+                .for_each(|(texel, a)| texel[3] = a);
+            texels
+        }),
+        CompressedFormat::Bc6h { signed } => decode_blocks(format, width, height, data, |block| {
+            decode_bc6h_block(block, signed)
+        }),
+        CompressedFormat::Bc7 => decode_blocks(format, width, height, data, decode_bc7_block),
+        CompressedFormat::Astc {
+            block_width,
+            block_height,
+        } => decode_blocks(format, width, height, data, |block| {
+            decode_astc_block(block, block_width, block_height, srgb)
+        }),
+    }
+}
+
+// Walks the blocks of the format, the decoder returns the texels in row order
+fn decode_blocks<F, T>(
+    format: CompressedFormat,
+    width: u32,
+    height: u32,
+    data: &[u8],
+    decode: F,
+) -> Result<Vec<u8>, String>
+where
+    F: Fn(&[u8]) -> T,
+    T: AsRef<[[u8; 4]]>,
+{
+    let (block_width, block_height, block_size) = format.block_info();
+    let blocks_x = width.div_ceil(block_width).max(1);
+    let blocks_y = height.div_ceil(block_height).max(1);
+    let mut out = vec![0; rgba_size(width, height)?];
+
+    for by in 0..blocks_y {
+        for bx in 0..blocks_x {
+            let offset = (by as usize * blocks_x as usize + bx as usize) * block_size;
+            let texels = decode(&data[offset..offset + block_size]);
+
+            // the blocks on the borders can be partially outside of the image
+            for (i, texel) in texels.as_ref().iter().enumerate() {
+                let x = bx * block_width + i as u32 % block_width;
+                let y = by * block_height + i as u32 / block_width;
+                if x < width && y < height {
+                    let dst = (y as usize * width as usize + x as usize) * 4;
+                    out[dst..dst + 4].copy_from_slice(texel);
+                }
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn rgb565(color: u16) -> [u8; 3] {
+    let r = ((color >> 11) & 0x1f) as u8;
+    let g = ((color >> 5) & 0x3f) as u8;
+    let b = (color & 0x1f) as u8;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+    ]
+}
+
+// `four_colors` forces the 4 colors mode, BC2 and BC3 always use it
+fn decode_bc1_colors(block: &[u8], alpha: bool, four_colors: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let (a, b) = (rgb565(c0), rgb565(c1));
+
+    let mix = |wa: u32, wb: u32, total: u32| -> [u8; 4] {
+        let mut color = [0, 0, 0, 255];
+        (0..3).for_each(|i| {
+            color[i] = ((a[i] as u32 * wa + b[i] as u32 * wb) / total) as u8;
+        });
+        color
+    };
+
+    let palette = if c0 > c1 || four_colors {
+        [mix(1, 0, 1), mix(0, 1, 1), mix(2, 1, 3), mix(1, 2, 3)]
+    } else {
+        let black = [0, 0, 0, if alpha { 0 } else { 255 }];
+        [mix(1, 0, 1), mix(0, 1, 1), mix(1, 1, 2), black]
+    };
+
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    let mut texels = [[0; 4]; 16];
+    texels.iter_mut().enumerate().for_each(|(i, texel)| {
+        *texel = palette[((indices >> (i * 2)) & 3) as usize];
+    });
+    texels
+}
+
+// Single channel block used by BC3 alpha, BC4 and BC5
+fn decode_bc4_channel(block: &[u8], signed: bool) -> [u8; 16] {
+    // signed values are remapped from [-127, 127] to [0, 255]
+    let (e0, e1, min, max) = if signed {
+        let e0 = (block[0] as i8).max(-127) as f32;
+        let e1 = (block[1] as i8).max(-127) as f32;
+        (e0, e1, -127.0, 127.0)
+    } else {
+        (block[0] as f32, block[1] as f32, 0.0, 255.0)
+    };
+
+    let mut palette = [0.0; 8];
+    palette[0] = e0;
+    palette[1] = e1;
+    if e0 > e1 {
+        (1..7).for_each(|i| palette[i + 1] = ((7 - i) as f32 * e0 + i as f32 * e1) / 7.0);
+    } else {
+        (1..5).for_each(|i| palette[i + 1] = ((5 - i) as f32 * e0 + i as f32 * e1) / 5.0);
+        palette[6] = min;
+        palette[7] = max;
+    }
+
+    let bits = block[2..8]
+        .iter()
+        .rev()
+        .fold(0u64, |bits, byte| (bits << 8) | *byte as u64);
+
+    let mut values = [0; 16];
+    values.iter_mut().enumerate().for_each(|(i, value)| {
+        let v = palette[((bits >> (i * 3)) & 7) as usize];
+        *value = ((v - min) / (max - min) * 255.0).round() as u8;
+    });
+    values
+}
+
+const ETC_MODIFIERS: [[i32; 2]; 8] = [
+    [2, 8],
+    [5, 17],
+    [9, 29],
+    [13, 42],
+    [18, 60],
+    [24, 80],
+    [33, 106],
+    [47, 183],
+];
+
+const ETC_DISTANCES: [i32; 8] = [3, 6, 11, 16, 23, 32, 41, 64];
+
+fn extend_4(v: u64) -> i32 {
+    ((v << 4) | v) as i32
+}
+
+fn extend_5(v: u64) -> i32 {
+    ((v << 3) | (v >> 2)) as i32
+}
+
+fn extend_6(v: u64) -> i32 {
+    ((v << 2) | (v >> 4)) as i32
+}
+
+fn extend_7(v: u64) -> i32 {
+    ((v << 1) | (v >> 6)) as i32
+}
+
+fn clamp_color(c: [i32; 3]) -> [u8; 4] {
+    [
+        c[0].clamp(0, 255) as u8,
+        c[1].clamp(0, 255) as u8,
+        c[2].clamp(0, 255) as u8,
+        255,
+    ]
+}
+
+// ETC1 compatible modes plus the ETC2 T, H and planar modes.
+// With `punchthrough` the differential bit is the opaque flag (RGB8A1 format).
+fn decode_etc2_colors(block: &[u8], punchthrough: bool) -> [[u8; 4]; 16] {
+    let bits = u64::from_be_bytes([
+        block[0], block[1], block[2], block[3], block[4], block[5], block[6], block[7],
+    ]);
+
+    let diff = (bits >> 33) & 1 == 1;
+    let flip = (bits >> 32) & 1 == 1;
+    let opaque = !punchthrough || diff;
+
+    // pixel indices are stored by columns, msb and lsb in separated halves
+    let index = |i: usize| -> usize {
+        let (x, y) = (i % 4, i / 4);
+        let bit = x * 4 + y;
+        let msb = (bits >> (16 + bit)) & 1;
+        let lsb = (bits >> bit) & 1;
+        ((msb << 1) | lsb) as usize
+    };
+
+    let transparent = [0, 0, 0, 0];
+    let mut texels = [[0; 4]; 16];
+
+    // individual mode doesn't exist with punchthrough alpha, the bit is the opaque flag
+    if !diff && !punchthrough {
+        let base = [
+            [extend_4((bits >> 60) & 0xf), extend_4((bits >> 56) & 0xf)],
+            [extend_4((bits >> 52) & 0xf), extend_4((bits >> 48) & 0xf)],
+            [extend_4((bits >> 44) & 0xf), extend_4((bits >> 40) & 0xf)],
+        ];
+        let colors = [
+            [base[0][0], base[1][0], base[2][0]],
+            [base[0][1], base[1][1], base[2][1]],
+        ];
+        decode_etc_subblocks(bits, flip, colors, opaque, &index, &mut texels);
+        return texels;
+    }
+
+    let r = ((bits >> 59) & 0x1f) as i32;
+    let g = ((bits >> 51) & 0x1f) as i32;
+    let b = ((bits >> 43) & 0x1f) as i32;
+    let signed_3 = |v: u64| -> i32 { ((v as i32) << 29) >> 29 };
+    let r2 = r + signed_3((bits >> 56) & 7);
+    let g2 = g + signed_3((bits >> 48) & 7);
+    let b2 = b + signed_3((bits >> 40) & 7);
+
+    if !(0..32).contains(&r2) {
+        // T mode
+        let c1 = [
+            extend_4(((bits >> 59) & 3) << 2 | (bits >> 56) & 3),
+            extend_4((bits >> 52) & 0xf),
+            extend_4((bits >> 48) & 0xf),
+        ];
+        let c2 = [
+            extend_4((bits >> 44) & 0xf),
+            extend_4((bits >> 40) & 0xf),
+            extend_4((bits >> 36) & 0xf),
+        ];
+        let d = ETC_DISTANCES[(((bits >> 34) & 3) << 1 | (bits >> 32) & 1) as usize];
+        let paint = [
+            clamp_color(c1),
+            clamp_color([c2[0] + d, c2[1] + d, c2[2] + d]),
+            clamp_color(c2),
+            clamp_color([c2[0] - d, c2[1] - d, c2[2] - d]),
+        ];
+        texels.iter_mut().enumerate().for_each(|(i, texel)| {
+            let idx = index(i);
+            *texel = if !opaque && idx == 2 {
+                transparent
+            } else {
+                paint[idx]
+            };
+        });
+    } else if !(0..32).contains(&g2) {
+        // H mode
+        let r1 = (bits >> 59) & 0xf;
+        let g1 = ((bits >> 56) & 7) << 1 | (bits >> 52) & 1;
+        let b1 = ((bits >> 51) & 1) << 3 | (bits >> 47) & 7;
+        let r2 = (bits >> 43) & 0xf;
+        let g2 = ((bits >> 40) & 7) << 1 | (bits >> 39) & 1;
+        let b2 = (bits >> 35) & 0xf;
+        let order = ((r1 << 8 | g1 << 4 | b1) >= (r2 << 8 | g2 << 4 | b2)) as u64;
+        let d = ETC_DISTANCES[(((bits >> 34) & 1) << 2 | ((bits >> 32) & 1) << 1 | order) as usize];
+        let c1 = [extend_4(r1), extend_4(g1), extend_4(b1)];
+        let c2 = [extend_4(r2), extend_4(g2), extend_4(b2)];
+        let paint = [
+            clamp_color([c1[0] + d, c1[1] + d, c1[2] + d]),
+            clamp_color([c1[0] - d, c1[1] - d, c1[2] - d]),
+            clamp_color([c2[0] + d, c2[1] + d, c2[2] + d]),
+            clamp_color([c2[0] - d, c2[1] - d, c2[2] - d]),
+        ];
+        texels.iter_mut().enumerate().for_each(|(i, texel)| {
+            let idx = index(i);
+            *texel = if !opaque && idx == 2 {
+                transparent
+            } else {
+                paint[idx]
+            };
+        });
+    } else if !(0..32).contains(&b2) {
+        // planar mode, the opaque flag is ignored
+        let o = [
+            extend_6((bits >> 57) & 0x3f),
+            extend_7(((bits >> 56) & 1) << 6 | (bits >> 49) & 0x3f),
+            extend_6(((bits >> 48) & 1) << 5 | ((bits >> 43) & 3) << 3 | (bits >> 39) & 7),
+        ];
+        let h = [
+            extend_6(((bits >> 34) & 0x1f) << 1 | (bits >> 32) & 1),
+            extend_7((bits >> 25) & 0x7f),
+            extend_6((bits >> 19) & 0x3f),
+        ];
+        let v = [
+            extend_6((bits >> 13) & 0x3f),
+            extend_7((bits >> 6) & 0x7f),
+            extend_6(bits & 0x3f),
+        ];
+        texels.iter_mut().enumerate().for_each(|(i, texel)| {
+            let (x, y) = ((i % 4) as i32, (i / 4) as i32);
+            let mut color = [0; 3];
+            (0..3).for_each(|c| {
+                color[c] = (x * (h[c] - o[c]) + y * (v[c] - o[c]) + 4 * o[c] + 2) >> 2;
+            });
+            *texel = clamp_color(color);
+        });
+    } else {
+        // differential mode
+        let colors = [
+            [extend_5(r as u64), extend_5(g as u64), extend_5(b as u64)],
+            [
+                extend_5(r2 as u64),
+                extend_5(g2 as u64),
+                extend_5(b2 as u64),
+            ],
+        ];
+        decode_etc_subblocks(bits, flip, colors, opaque, &index, &mut texels);
+    }
+
+    texels
+}
+
+fn decode_etc_subblocks<F>(
+    bits: u64,
+    flip: bool,
+    colors: [[i32; 3]; 2],
+    opaque: bool,
+    index: &F,
+    texels: &mut [[u8; 4]; 16],
+) where
+    F: Fn(usize) -> usize,
+{
+    let tables = [(bits >> 37) & 7, (bits >> 34) & 7];
+    texels.iter_mut().enumerate().for_each(|(i, texel)| {
+        let (x, y) = (i % 4, i / 4);
+        let sub = if flip { y / 2 } else { x / 2 };
+        let [small, large] = ETC_MODIFIERS[tables[sub] as usize];
+        let idx = index(i);
+
+        // without the opaque flag index 2 is transparent and index 0 doesn't modify the color
+        let modifier = match (idx, opaque) {
+            (2, false) => {
+                *texel = [0, 0, 0, 0];
+                return;
+            }
+            (0, false) => 0,
+            (0, true) => small,
+            (1, _) => large,
+            (2, true) => -small,
+            _ => -large,
+        };
+
+        let base = colors[sub];
+        *texel = clamp_color([base[0] + modifier, base[1] + modifier, base[2] + modifier]);
+    });
+}
+
+const EAC_MODIFIERS: [[i32; 8]; 16] = [
+    [-3, -6, -9, -15, 2, 5, 8, 14],
+    [-3, -7, -10, -13, 2, 6, 9, 12],
+    [-2, -5, -8, -13, 1, 4, 7, 12],
+    [-2, -4, -6, -13, 1, 3, 5, 12],
+    [-3, -6, -8, -12, 2, 5, 7, 11],
+    [-3, -7, -9, -11, 2, 6, 8, 10],
+    [-4, -7, -8, -11, 3, 6, 7, 10],
+    [-3, -5, -8, -11, 2, 4, 7, 10],
+    [-2, -6, -8, -10, 1, 5, 7, 9],
+    [-2, -5, -8, -10, 1, 4, 7, 9],
+    [-2, -4, -8, -10, 1, 3, 7, 9],
+    [-2, -5, -7, -10, 1, 4, 6, 9],
+    [-3, -4, -7, -10, 2, 3, 6, 9],
+    [-1, -2, -3, -10, 0, 1, 2, 9],
+    [-4, -6, -8, -9, 3, 5, 7, 8],
+    [-3, -5, -7, -9, 2, 4, 6, 8],
+];
+
+fn decode_eac_alpha(block: &[u8]) -> [u8; 16] {
+    let base = block[0] as i32;
+    let multiplier = (block[1] >> 4) as i32;
+    let table = EAC_MODIFIERS[(block[1] & 0xf) as usize];
+    let bits = block[2..8]
+        .iter()
+        .fold(0u64, |bits, byte| (bits << 8) | *byte as u64);
+
+    // the indices are stored by columns, starting from the most significant bits
+    let mut values = [0; 16];
+    values.iter_mut().enumerate().for_each(|(i, value)| {
+        let (x, y) = (i % 4, i / 4);
+        let pixel = x * 4 + y;
+        let idx = (bits >> (45 - pixel * 3)) & 7;
+        *value = (base + table[idx as usize] * multiplier).clamp(0, 255) as u8;
+    });
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode_block(format: CompressedFormat, block: &[u8]) -> Vec<[u8; 4]> {
+        decode_payload(format, false, 4, 4, block)
+            .unwrap()
+            .chunks_exact(4)
+            .map(|px| [px[0], px[1], px[2], px[3]])
+            .collect()
+    }
+
+    #[test]
+    fn bc1_blocks_are_decoded() {
+        // red and blue endpoints, the texels use the indices 0, 1, 2, 3 in every row
+        let block = [0x00, 0xf8, 0x1f, 0x00, 0xe4, 0xe4, 0xe4, 0xe4];
+        let texels = decode_block(CompressedFormat::Bc1 { alpha: true }, &block);
+        assert_eq!(
+            texels[..4],
+            [
+                [255, 0, 0, 255],
+                [0, 0, 255, 255],
+                [170, 0, 85, 255],
+                [85, 0, 170, 255]
+            ]
+        );
+        assert_eq!(texels[4..8], texels[..4]);
+
+        // with c0 <= c1 the block has 3 colors and the index 3 is transparent black
+        let block = [0x1f, 0x00, 0x00, 0xf8, 0xe4, 0xe4, 0xe4, 0xe4];
+        let texels = decode_block(CompressedFormat::Bc1 { alpha: true }, &block);
+        assert_eq!(
+            texels[..4],
+            [
+                [0, 0, 255, 255],
+                [255, 0, 0, 255],
+                [127, 0, 127, 255],
+                [0, 0, 0, 0]
+            ]
+        );
+        let texels = decode_block(CompressedFormat::Bc1 { alpha: false }, &block);
+        assert_eq!(texels[3], [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn bc2_and_bc3_alpha_is_decoded() {
+        let color = [0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+        // 4 bits per texel, the first texel is in the low nibble
+        let mut block = [0x0f, 0xf0, 0, 0, 0, 0, 0, 0x80].to_vec();
+        block.extend(color);
+        let texels = decode_block(CompressedFormat::Bc2, &block);
+        assert_eq!(texels[0], [255, 255, 255, 255]);
+        assert_eq!(texels[1], [255, 255, 255, 0]);
+        assert_eq!(texels[2], [255, 255, 255, 0]);
+        assert_eq!(texels[3], [255, 255, 255, 255]);
+        assert_eq!(texels[15][3], 136);
+
+        // 255 and 0 endpoints give 8 levels, the texels use the indices 0, 1, 2 and then 0
+        let mut block = [255, 0, 0b1000_1000, 0, 0, 0, 0, 0].to_vec();
+        block.extend(color);
+        let alpha = decode_block(CompressedFormat::Bc3, &block)
+            .iter()
+            .map(|texel| texel[3])
+            .collect::<Vec<_>>();
+        assert_eq!(alpha[..4], [255, 0, 219, 255]);
+    }
+
+    #[test]
+    fn bc4_and_bc5_channels_are_decoded() {
+        // with e0 <= e1 the indices 6 and 7 are the ends of the range
+        let block = [50, 100, 0b1011_1110, 0, 0, 0, 0, 0];
+        let texels = decode_block(CompressedFormat::Bc4 { signed: false }, &block);
+        assert_eq!(texels[0], [0, 0, 0, 255]);
+        assert_eq!(texels[1], [255, 0, 0, 255]);
+        assert_eq!(texels[2], [60, 0, 0, 255]);
+        assert_eq!(texels[3], [50, 0, 0, 255]);
+
+        // signed endpoints are remapped from [-127, 127]
+        let red = [0x7f, 0x81, 0, 0, 0, 0, 0, 0];
+        let green = [0x81, 0x7f, 0, 0, 0, 0, 0, 0];
+        let block = [red, green].concat();
+        let texels = decode_block(CompressedFormat::Bc5 { signed: true }, &block);
+        assert!(texels.iter().all(|texel| *texel == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn etc2_blocks_are_decoded() {
+        // individual mode, the left half is 0xf and the right half 0x0 in red, the table 0
+        // adds 2 with the index 0
+        let block = [0xf0, 0x00, 0x00, 0x00, 0, 0, 0, 0];
+        let texels = decode_block(CompressedFormat::Etc2Rgb, &block);
+        assert_eq!(texels[0], [255, 2, 2, 255]);
+        assert_eq!(texels[1], [255, 2, 2, 255]);
+        assert_eq!(texels[2], [2, 2, 2, 255]);
+        assert_eq!(texels[15], [2, 2, 2, 255]);
+
+        // differential mode with flip, the bottom half is 3 steps of 5 bits greener. The first
+        // texel uses the index 3 (-8) and the last one the index 1 (+8).
+        let block = [0x80, 0x83, 0x80, 0x03, 0x00, 0x01, 0x80, 0x01];
+        let texels = decode_block(CompressedFormat::Etc2Rgb, &block);
+        assert_eq!(texels[0], [124, 124, 124, 255]);
+        assert_eq!(texels[1], [134, 134, 134, 255]);
+        assert_eq!(texels[8], [134, 158, 134, 255]);
+        assert_eq!(texels[15], [140, 164, 140, 255]);
+
+        // punchthrough alpha without the opaque bit, the index 2 is transparent
+        let block = [0x80, 0x80, 0x80, 0x00, 0x00, 0x01, 0x00, 0x00];
+        let texels = decode_block(CompressedFormat::Etc2Rgba1, &block);
+        assert_eq!(texels[0], [0, 0, 0, 0]);
+        assert_eq!(texels[1], [132, 132, 132, 255]);
+    }
+
+    #[test]
+    fn eac_alpha_is_decoded() {
+        // base 128, multiplier 1 and table 13, the first texel uses the index 3 (-10) and
+        // the rest the index 7 (+9)
+        let alpha = [128, 0x1d, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff];
+        let color = [0xf0, 0x00, 0x00, 0x00, 0, 0, 0, 0];
+        let block = [alpha, color].concat();
+        let texels = decode_block(CompressedFormat::Etc2Rgba, &block);
+        assert_eq!(texels[0], [255, 2, 2, 118]);
+        assert!(texels[1..].iter().all(|texel| texel[3] == 137));
+    }
+
+    #[test]
+    fn partial_blocks_are_cropped() {
+        let block = [0x00, 0xf8, 0x1f, 0x00, 0xe4, 0xe4, 0xe4, 0xe4];
+        let data =
+            decode_payload(CompressedFormat::Bc1 { alpha: false }, false, 3, 2, &block).unwrap();
+        assert_eq!(data.len(), 3 * 2 * 4);
+        assert_eq!(data[12..16], [255, 0, 0, 255]);
+        assert!(
+            decode_payload(CompressedFormat::Bc1 { alpha: false }, false, 8, 4, &block).is_err()
+        );
+    }
+
+    #[test]
+    fn oversized_levels_are_rejected() {
+        // 2^30 x 2^30 blocks of 16 bytes overflow even a 64 bits size
+        assert!(CompressedFormat::Bc3
+            .level_size(u32::MAX, u32::MAX)
+            .is_err());
+        assert!(rgba_size(u32::MAX, u32::MAX).is_err());
+        assert_eq!(CompressedFormat::Bc3.level_size(5, 4), Ok(32));
+
+        // an 8x4 texture has at most the 8x4, 4x2, 2x1 and 1x1 levels
+        assert!(check_level_count(8, 4, 4).is_ok());
+        let error = parse_texture_container(&dds_header(b"DXT1", 8, 4, 40)).err();
+        assert!(error.unwrap().contains("mip levels"));
+    }
+
+    #[test]
+    fn astc_footprints_are_walked() {
+        // 4 void extent blocks of 6x5 texels cover 10x8, the last ones are cropped
+        let mut block = [0; 16];
+        block[..2].copy_from_slice(&0xdfcu16.to_le_bytes());
+        block[8..].copy_from_slice(&[0xff, 0xff, 0x00, 0x80, 0x00, 0x00, 0xff, 0xff]);
+        let data = block.repeat(4);
+        let format = CompressedFormat::Astc {
+            block_width: 6,
+            block_height: 5,
+        };
+        assert_eq!(format.level_size(10, 8), Ok(64));
+        let pixels = decode_payload(format, false, 10, 8, &data).unwrap();
+        assert_eq!(pixels, [255, 128, 0, 255].repeat(10 * 8));
+    }
+
+    fn dds_header(four_cc: &[u8; 4], width: u32, height: u32, mip_count: u32) -> Vec<u8> {
+        let mut bytes = vec![0; 128];
+        bytes[..4].copy_from_slice(b"DDS ");
+        let mut write = |offset: usize, value: u32| {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes())
+        };
+        write(4, 124);
+        write(8, 0x20000);
+        write(12, height);
+        write(16, width);
+        write(28, mip_count);
+        write(76, 32);
+        write(80, 0x4);
+        bytes[84..88].copy_from_slice(four_cc);
+        bytes
+    }
+
+    #[test]
+    fn dds_headers_are_parsed() {
+        // 8x4 DXT1 with 3 levels of 2, 1 and 1 blocks
+        let mut bytes = dds_header(b"DXT1", 8, 4, 3);
+        bytes.extend((0..32).map(|i| i as u8));
+        let dds = parse_texture_container(&bytes).unwrap();
+        assert_eq!(dds.format, CompressedFormat::Bc1 { alpha: true });
+        assert_eq!((dds.width, dds.height, dds.srgb), (8, 4, false));
+        assert_eq!(dds.levels.len(), 3);
+        assert_eq!(dds.levels[0], (0..16).collect::<Vec<_>>());
+        assert_eq!(dds.levels[2], (24..32).collect::<Vec<_>>());
+        assert_eq!(dds.level_dimensions(1), (4, 2));
+        assert_eq!(dds.decode_levels().unwrap()[2].data.len(), 2 * 4);
+
+        bytes.truncate(140);
+        assert!(parse_texture_container(&bytes).is_err());
+
+        // DX10 header with BC3 sRGB, a miscFlag and a single element array
+        let mut bytes = dds_header(b"DX10", 4, 4, 1);
+        [78, 3, 4, 1, 0]
+            .iter()
+            .for_each(|value: &u32| bytes.extend(value.to_le_bytes()));
+        bytes.extend([0; 16]);
+        let dds = parse_texture_container(&bytes).unwrap();
+        assert_eq!((dds.format, dds.srgb), (CompressedFormat::Bc3, true));
+
+        bytes[140] = 6;
+        assert!(parse_texture_container(&bytes).is_err());
+
+        bytes[128] = 99;
+        bytes[140] = 1;
+        let dds = parse_texture_container(&bytes).unwrap();
+        assert_eq!((dds.format, dds.srgb), (CompressedFormat::Bc7, true));
+        assert_eq!(dds.decode_level(0).unwrap().data, vec![0; 16 * 4]);
+    }
+
+    #[test]
+    fn ktx2_headers_are_parsed() {
+        // 2x2 sRGB RGBA8 with 2 levels, the smallest one first in the file like the spec says
+        let mut bytes = KTX2_IDENTIFIER.to_vec();
+        [43, 1, 2, 2, 0, 0, 1, 2, 0]
+            .iter()
+            .for_each(|value: &u32| bytes.extend(value.to_le_bytes()));
+        bytes.resize(80, 0);
+        [(136, 16), (128, 4)]
+            .iter()
+            .for_each(|(offset, len): &(u64, u64)| {
+                [*offset, *len, *len]
+                    .iter()
+                    .for_each(|value| bytes.extend(value.to_le_bytes()))
+            });
+        bytes.extend([9, 9, 9, 9]);
+        bytes.resize(bytes.len() + 4, 0);
+        bytes.extend(1..=16);
+
+        let ktx2 = parse_texture_container(&bytes).unwrap();
+        assert_eq!(ktx2.format, CompressedFormat::Rgba8);
+        assert_eq!((ktx2.width, ktx2.height, ktx2.srgb), (2, 2, true));
+        assert_eq!(ktx2.levels, vec![(1..=16).collect::<Vec<_>>(), vec![9; 4]]);
+
+        // a level count that would shift the size past 32 bits
+        bytes[40] = 40;
+        let error = parse_texture_container(&bytes).err();
+        assert!(error.unwrap().contains("mip levels"));
+        bytes[40] = 2;
+
+        // a 2x2 ASTC level is a whole block, 16 bytes
+        bytes[12] = 157;
+        assert!(parse_texture_container(&bytes).is_err());
+        assert_eq!(
+            vk_format_info(184),
+            Ok((
+                CompressedFormat::Astc {
+                    block_width: 12,
+                    block_height: 12
+                },
+                true
+            ))
+        );
+        assert!(parse_texture_container(b"not a texture").is_err());
+    }
+}
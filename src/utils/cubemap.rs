@@ -0,0 +1,510 @@
+use super::hdr::HdrImage;
+use image::imageops::FilterType;
+use image::RgbaImage;
+use notan::math::{vec3, Vec3};
+use notan::prelude::*;
+use std::f32::consts::PI;
+
+// Notan doesn't have cube textures, so the six faces are packed in a 3x2 grid inside
+// a regular 2D texture, and the shaders sample it using `sampleCube` (see `SAMPLE_CUBE_GLSL`).
+//
+// | +X (right)  | -X (left)  | +Y (top)  |
+// | -Y (bottom) | +Z (front) | -Z (back) |
+//
+// Every face follows the OpenGL cubemap orientation, so LearnOpenGL's images can be used as they are.
+pub const CUBEMAP_COLUMNS: u32 = 3;
+pub const CUBEMAP_ROWS: u32 = 2;
+
+// Keeps the packed texture under 4096px wide, the limit on a lot of WebGL devices
+pub const DEFAULT_MAX_FACE_SIZE: u32 = 1024;
+
+// GLSL function used by every shader that samples a packed cubemap, it selects the face the
+// same way as `CubeFace::from_direction`. Notan compiles the shaders from a single literal,
+// so the shaders paste it as it is, and the tests check that their copies don't change.
+// language=glsl
+pub const SAMPLE_CUBE_GLSL: &str = r#"
+// SAMPLE_CUBE_GLSL of utils/cubemap.rs, samples the faces packed in a 3x2 grid
+vec4 sampleCube(sampler2D cube, vec3 dir)
+{
+    vec3 a = abs(dir);
+    float face;
+    float ma;
+    vec2 st;
+    if (a.x >= a.y && a.x >= a.z) {
+        ma = a.x;
+        face = dir.x > 0.0 ? 0.0 : 1.0;
+        st = dir.x > 0.0 ? vec2(-dir.z, -dir.y) : vec2(dir.z, -dir.y);
+    } else if (a.y >= a.z) {
+        ma = a.y;
+        face = dir.y > 0.0 ? 2.0 : 3.0;
+        st = dir.y > 0.0 ? vec2(dir.x, dir.z) : vec2(dir.x, -dir.z);
+    } else {
+        ma = a.z;
+        face = dir.z > 0.0 ? 4.0 : 5.0;
+        st = dir.z > 0.0 ? vec2(dir.x, -dir.y) : vec2(-dir.x, -dir.y);
+    }
+
+    st = (st / ma + 1.0) * 0.5;
+
+    // keep the samples inside the face to avoid bleeding from the neighbours
+    vec2 faceSize = vec2(textureSize(cube, 0)) / vec2(3.0, 2.0);
+    vec2 halfTexel = 0.5 / faceSize;
+    st = clamp(st, halfTexel, 1.0 - halfTexel);
+
+    vec2 cell = vec2(mod(face, 3.0), floor(face / 3.0));
+    return textureLod(cube, (cell + st) / vec2(3.0, 2.0), 0.0);
+}
+"#;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CubeFace {
+    Right,
+    Left,
+    Top,
+    Bottom,
+    Front,
+    Back,
+}
+
+impl CubeFace {
+    pub const ALL: [CubeFace; 6] = [
+        CubeFace::Right,
+        CubeFace::Left,
+        CubeFace::Top,
+        CubeFace::Bottom,
+        CubeFace::Front,
+        CubeFace::Back,
+    ];
+
+    // Direction for the face coordinates s, t in the range [0, 1]
+    pub fn direction(&self, s: f32, t: f32) -> Vec3 {
+        let sc = s * 2.0 - 1.0;
+        let tc = t * 2.0 - 1.0;
+        match self {
+            CubeFace::Right => vec3(1.0, -tc, -sc),
+            CubeFace::Left => vec3(-1.0, -tc, sc),
+            CubeFace::Top => vec3(sc, 1.0, tc),
+            CubeFace::Bottom => vec3(sc, -1.0, -tc),
+            CubeFace::Front => vec3(sc, -tc, 1.0),
+            CubeFace::Back => vec3(-sc, -tc, -1.0),
+        }
+        .normalize()
+    }
+
+    // Face and coordinates s, t of a direction, the inverse of `direction` (like `sampleCube`)
+    pub fn from_direction(dir: Vec3) -> (CubeFace, f32, f32) {
+        let a = dir.abs();
+        let (face, ma, s, t) = if a.x >= a.y && a.x >= a.z {
+            if dir.x > 0.0 {
+                (CubeFace::Right, a.x, -dir.z, -dir.y)
+            } else {
+                (CubeFace::Left, a.x, dir.z, -dir.y)
+            }
+        } else if a.y >= a.z {
+            if dir.y > 0.0 {
+                (CubeFace::Top, a.y, dir.x, dir.z)
+            } else {
+                (CubeFace::Bottom, a.y, dir.x, -dir.z)
+            }
+        } else if dir.z > 0.0 {
+            (CubeFace::Front, a.z, dir.x, -dir.y)
+        } else {
+            (CubeFace::Back, a.z, -dir.x, -dir.y)
+        };
+
+        (face, (s / ma + 1.0) * 0.5, (t / ma + 1.0) * 0.5)
+    }
+
+    // Cell inside the packed texture
+    fn cell(&self) -> (u32, u32) {
+        let index = *self as u32;
+        (index % CUBEMAP_COLUMNS, index / CUBEMAP_COLUMNS)
+    }
+}
+
+// Image layout used by `CubeMapBuilder::from_cross`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum CrossLayout {
+    // 4x3 cells, -X +Z +X -Z in the middle row
+    Horizontal,
+    // 3x4 cells, -Z is the last row rotated 180 degrees
+    Vertical,
+}
+
+enum CubeMapSource<'a> {
+    Faces([&'a [u8]; 6]),
+    Cross(&'a [u8]),
+    Equirectangular(&'a [u8]),
+    Hdr(&'a HdrImage),
+}
+
+pub struct CubeMapBuilder<'a> {
+    gfx: &'a mut Graphics,
+    source: Option<CubeMapSource<'a>>,
+    face_size: Option<u32>,
+    filter: TextureFilter,
+}
+
+pub fn create_cubemap(gfx: &mut Graphics) -> CubeMapBuilder {
+    CubeMapBuilder {
+        gfx,
+        source: None,
+        face_size: None,
+        filter: TextureFilter::Linear,
+    }
+}
+
+impl<'a> CubeMapBuilder<'a> {
+    // Encoded images in the order right, left, top, bottom, front, back
+    pub fn from_faces(mut self, faces: [&'a [u8]; 6]) -> Self {
+        self.source = Some(CubeMapSource::Faces(faces));
+        self
+    }
+
+    // Single encoded image with the faces laid out as a cross
+    pub fn from_cross(mut self, image: &'a [u8]) -> Self {
+        self.source = Some(CubeMapSource::Cross(image));
+        self
+    }
+
+    // Single encoded panorama image (2:1) projected to the faces
+    pub fn from_equirectangular(mut self, image: &'a [u8]) -> Self {
+        self.source = Some(CubeMapSource::Equirectangular(image));
+        self
+    }
+
+    // Same as `from_equirectangular` but keeps the range using a float texture
+    pub fn from_hdr(mut self, image: &'a HdrImage) -> Self {
+        self.source = Some(CubeMapSource::Hdr(image));
+        self
+    }
+
+    pub fn with_face_size(mut self, size: u32) -> Self {
+        self.face_size = Some(size);
+        self
+    }
+
+    pub fn with_filter(mut self, filter: TextureFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn build(self) -> Result<Texture, String> {
+        let Self {
+            gfx,
+            source,
+            face_size,
+            filter,
+        } = self;
+
+        let source = source.ok_or_else(|| "Missing cubemap source".to_string())?;
+        let (size, data, format) = match source {
+            CubeMapSource::Faces(faces) => {
+                let faces = faces
+                    .iter()
+                    .map(|bytes| decode(bytes))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                let size = face_size.unwrap_or_else(|| default_face_size(faces[0].width()));
+                let faces = faces
+                    .into_iter()
+                    .map(|face| resize(face, size))
+                    .collect::<Vec<_>>();
+
+                (size, pack_faces(&faces, size), TextureFormat::Rgba32)
+            }
+            CubeMapSource::Cross(bytes) => {
+                let image = decode(bytes)?;
+                let faces = split_cross(&image)?;
+                let size = face_size.unwrap_or_else(|| default_face_size(faces[0].width()));
+                let faces = faces
+                    .into_iter()
+                    .map(|face| resize(face, size))
+                    .collect::<Vec<_>>();
+
+                (size, pack_faces(&faces, size), TextureFormat::Rgba32)
+            }
+            CubeMapSource::Equirectangular(bytes) => {
+                let image = decode(bytes)?;
+                let size = face_size.unwrap_or_else(|| default_face_size(image.width() / 4));
+                let data = project_equirectangular(image.width(), image.height(), size, |x, y| {
+                    let [r, g, b, a] = image.get_pixel(x, y).0;
+                    [r as f32, g as f32, b as f32, a as f32]
+                })
+                .into_iter()
+                .map(|v| v.round().clamp(0.0, 255.0) as u8)
+                .collect();
+
+                (size, data, TextureFormat::Rgba32)
+            }
+            CubeMapSource::Hdr(image) => {
+                let size = face_size.unwrap_or_else(|| default_face_size(image.width / 4));
+                let pixels = project_equirectangular(image.width, image.height, size, |x, y| {
+                    let [r, g, b] = image.pixel(x, y);
+                    [r, g, b, 1.0]
+                });
+
+                (
+                    size,
+                    bytemuck::cast_slice(&pixels).to_vec(),
+                    TextureFormat::Rgba32Float,
+                )
+            }
+        };
+
+        gfx.create_texture()
+            .from_bytes(&data, size * CUBEMAP_COLUMNS, size * CUBEMAP_ROWS)
+            .with_format(format)
+            .with_filter(filter, filter)
+            .build()
+    }
+}
+
+fn default_face_size(size: u32) -> u32 {
+    size.min(DEFAULT_MAX_FACE_SIZE)
+}
+
+fn decode(bytes: &[u8]) -> Result<RgbaImage, String> {
+    image::load_from_memory(bytes)
+        .map(|img| img.into_rgba8())
+        .map_err(|e| e.to_string())
+}
+
+fn resize(image: RgbaImage, size: u32) -> RgbaImage {
+    if image.width() == size && image.height() == size {
+        return image;
+    }
+
+    image::imageops::resize(&image, size, size, FilterType::Triangle)
+}
+
+fn split_cross(image: &RgbaImage) -> Result<Vec<RgbaImage>, String> {
+    let (w, h) = image.dimensions();
+    let layout = if w * 3 == h * 4 {
+        CrossLayout::Horizontal
+    } else if w * 4 == h * 3 {
+        CrossLayout::Vertical
+    } else {
+        return Err(format!("Invalid cubemap cross size {}x{}", w, h));
+    };
+
+    let size = match layout {
+        CrossLayout::Horizontal => w / 4,
+        CrossLayout::Vertical => w / 3,
+    };
+
+    let faces = CubeFace::ALL
+        .iter()
+        .map(|face| {
+            let (col, row, rotate) = match (layout, face) {
+                (_, CubeFace::Top) => (1, 0, false),
+                (_, CubeFace::Left) => (0, 1, false),
+                (_, CubeFace::Front) => (1, 1, false),
+                (_, CubeFace::Right) => (2, 1, false),
+                (_, CubeFace::Bottom) => (1, 2, false),
+                (CrossLayout::Horizontal, CubeFace::Back) => (3, 1, false),
+                (CrossLayout::Vertical, CubeFace::Back) => (1, 3, true),
+            };
+
+            let face =
+                image::imageops::crop_imm(image, col * size, row * size, size, size).to_image();
+            if rotate {
+                image::imageops::rotate180(&face)
+            } else {
+                face
+            }
+        })
+        .collect();
+
+    Ok(faces)
+}
+
+fn pack_faces(faces: &[RgbaImage], size: u32) -> Vec<u8> {
+    let width = size * CUBEMAP_COLUMNS;
+    let height = size * CUBEMAP_ROWS;
+    let mut data = vec![0; (width * height * 4) as usize];
+
+    CubeFace::ALL
+        .iter()
+        .zip(faces.iter())
+        .for_each(|(face, image)| {
+            let (col, row) = face.cell();
+            let row_len = (size * 4) as usize;
+            for y in 0..size {
+                let src = y as usize * row_len;
+                let dst = (((row * size + y) * width + col * size) * 4) as usize;
+                data[dst..dst + row_len].copy_from_slice(&image.as_raw()[src..src + row_len]);
+            }
+        });
+
+    data
+}
+
+// Projects a panorama to the packed layout, returns RGBA values
+fn project_equirectangular<F>(width: u32, height: u32, size: u32, pixel: F) -> Vec<f32>
+where
+    F: Fn(u32, u32) -> [f32; 4],
+{
+    let packed_width = size * CUBEMAP_COLUMNS;
+    let packed_height = size * CUBEMAP_ROWS;
+    let mut data = vec![0.0; (packed_width * packed_height * 4) as usize];
+
+    let sample = |u: f32, v: f32| -> [f32; 4] {
+        // bilinear, wrapping horizontally
+        let x = u * width as f32 - 0.5;
+        let y = (v * height as f32 - 0.5).clamp(0.0, (height - 1) as f32);
+        let x0 = x.floor();
+        let y0 = y.floor();
+        let (fx, fy) = (x - x0, y - y0);
+        let wrap = |x: f32| (x as i64).rem_euclid(width as i64) as u32;
+        let (x0, x1) = (wrap(x0), wrap(x0 + 1.0));
+        let (y0, y1) = (y0 as u32, (y0 as u32 + 1).min(height - 1));
+
+        let (a, b, c, d) = (pixel(x0, y0), pixel(x1, y0), pixel(x0, y1), pixel(x1, y1));
+        let mut out = [0.0; 4];
+        out.iter_mut().enumerate().for_each(|(i, v)| {
+            let top = a[i] + (b[i] - a[i]) * fx;
+            let bottom = c[i] + (d[i] - c[i]) * fx;
+            *v = top + (bottom - top) * fy;
+        });
+        out
+    };
+
+    CubeFace::ALL.iter().for_each(|face| {
+        let (col, row) = face.cell();
+        for y in 0..size {
+            for x in 0..size {
+                let s = (x as f32 + 0.5) / size as f32;
+                let t = (y as f32 + 0.5) / size as f32;
+                let dir = face.direction(s, t);
+
+                // same mapping as LearnOpenGL's SampleSphericalMap, with the image top at v = 0
+                let u = dir.z.atan2(dir.x) / (2.0 * PI) + 0.5;
+                let v = 0.5 - dir.y.clamp(-1.0, 1.0).asin() / PI;
+
+                let i = (((row * size + y) * packed_width + col * size + x) * 4) as usize;
+                data[i..i + 4].copy_from_slice(&sample(u, v));
+            }
+        }
+    });
+
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    // the lines of the snippet, without the indentation of the shader literals
+    fn contains_snippet(source: &str, snippet: &str) -> bool {
+        let lines = |text: &str| {
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        let (source, snippet) = (lines(source), lines(snippet));
+        source
+            .windows(snippet.len())
+            .any(|window| window == snippet.as_slice())
+    }
+
+    #[test]
+    fn shaders_use_the_shared_sample_cube() {
+        let shaders = [
+            include_str!("skybox.rs"),
+            include_str!("../_4_advanced_opengl/_6_2_cubemaps_environment_mapping.rs"),
+        ];
+        assert!(shaders
+            .iter()
+            .all(|source| contains_snippet(source, SAMPLE_CUBE_GLSL)));
+    }
+
+    #[test]
+    fn faces_follow_the_opengl_orientation() {
+        // the center of each face looks along its axis
+        let axes = [
+            (CubeFace::Right, vec3(1.0, 0.0, 0.0)),
+            (CubeFace::Left, vec3(-1.0, 0.0, 0.0)),
+            (CubeFace::Top, vec3(0.0, 1.0, 0.0)),
+            (CubeFace::Bottom, vec3(0.0, -1.0, 0.0)),
+            (CubeFace::Front, vec3(0.0, 0.0, 1.0)),
+            (CubeFace::Back, vec3(0.0, 0.0, -1.0)),
+        ];
+        axes.iter().for_each(|(face, axis)| {
+            assert!(face.direction(0.5, 0.5).abs_diff_eq(*axis, 1e-6));
+        });
+
+        // the top left corner of +X is +Y and +Z, like a GL_TEXTURE_CUBE_MAP_POSITIVE_X image
+        let corner = CubeFace::Right.direction(0.0, 0.0) * 3f32.sqrt();
+        assert!(corner.abs_diff_eq(vec3(1.0, 1.0, 1.0), 1e-5));
+
+        // `sampleCube` finds the same face and coordinates
+        CubeFace::ALL.iter().for_each(|face| {
+            let (found, s, t) = CubeFace::from_direction(face.direction(0.25, 0.75));
+            assert_eq!(found, *face);
+            assert!((s - 0.25).abs() < 1e-5 && (t - 0.75).abs() < 1e-5);
+        });
+    }
+
+    fn cell_color(col: u32, row: u32) -> Rgba<u8> {
+        Rgba([col as u8 * 10, row as u8 * 10, 0, 255])
+    }
+
+    // cross image where every pixel has the color of its cell, and the pixel at the top left
+    // of each cell is white to check the rotations
+    fn cross(columns: u32, rows: u32, size: u32) -> RgbaImage {
+        RgbaImage::from_fn(columns * size, rows * size, |x, y| {
+            if x % size == 0 && y % size == 0 {
+                Rgba([255; 4])
+            } else {
+                cell_color(x / size, y / size)
+            }
+        })
+    }
+
+    #[test]
+    fn split_horizontal_and_vertical_crosses() {
+        let faces = split_cross(&cross(4, 3, 2)).unwrap();
+        let cells = [(2, 1), (0, 1), (1, 0), (1, 2), (1, 1), (3, 1)];
+        faces.iter().zip(cells).for_each(|(face, (col, row))| {
+            assert_eq!(face.dimensions(), (2, 2));
+            assert_eq!(*face.get_pixel(0, 0), Rgba([255; 4]));
+            assert_eq!(*face.get_pixel(1, 1), cell_color(col, row));
+        });
+
+        // the back face of the vertical cross is upside down
+        let faces = split_cross(&cross(3, 4, 2)).unwrap();
+        let back = &faces[CubeFace::Back as usize];
+        assert_eq!(*back.get_pixel(1, 1), Rgba([255; 4]));
+        assert_eq!(*back.get_pixel(0, 0), cell_color(1, 3));
+
+        assert_eq!(
+            split_cross(&RgbaImage::new(10, 10)).unwrap_err(),
+            "Invalid cubemap cross size 10x10"
+        );
+    }
+
+    #[test]
+    fn equirectangular_projection_looks_at_the_right_longitude() {
+        // every pixel stores its own coordinates, the bilinear sample returns where it looked
+        let (width, height) = (8, 4);
+        let data = project_equirectangular(width, height, 1, |x, y| [x as f32, y as f32, 0.0, 1.0]);
+        let sample = |face: CubeFace| {
+            let (col, row) = face.cell();
+            let i = ((row * CUBEMAP_COLUMNS + col) * 4) as usize;
+            (data[i], data[i + 1])
+        };
+
+        // +X is the center of the panorama, +Z three quarters and -Z one quarter
+        assert_eq!(sample(CubeFace::Right), (3.5, 1.5));
+        assert_eq!(sample(CubeFace::Front), (5.5, 1.5));
+        assert_eq!(sample(CubeFace::Back), (1.5, 1.5));
+        // the poles are the first and last rows
+        assert_eq!(sample(CubeFace::Top).1, 0.0);
+        assert_eq!(sample(CubeFace::Bottom).1, 3.0);
+    }
+}
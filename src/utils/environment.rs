@@ -0,0 +1,143 @@
+use notan::prelude::*;
+
+// Materials that sample an environment cubemap (see `create_cubemap`) instead of,
+// or mixed with, the Phong lighting used by the lighting examples.
+
+// Common indices of refraction
+pub const IOR_AIR: f32 = 1.0;
+pub const IOR_WATER: f32 = 1.33;
+pub const IOR_ICE: f32 = 1.309;
+pub const IOR_GLASS: f32 = 1.52;
+pub const IOR_DIAMOND: f32 = 2.42;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MaterialMode {
+    Phong,
+    Reflective,
+    Refractive,
+    // reflection and refraction mixed using Fresnel-Schlick
+    Fresnel,
+}
+
+impl MaterialMode {
+    // Value used by the shaders to select the mode
+    pub fn id(&self) -> u32 {
+        match self {
+            MaterialMode::Phong => 0,
+            MaterialMode::Reflective => 1,
+            MaterialMode::Refractive => 2,
+            MaterialMode::Fresnel => 3,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EnvironmentMaterial {
+    pub mode: MaterialMode,
+    pub ior: f32,
+    // spread of the index of refraction between the red and blue channels, 0.0 disables it
+    pub dispersion: f32,
+}
+
+impl Default for EnvironmentMaterial {
+    fn default() -> Self {
+        Self {
+            mode: MaterialMode::Phong,
+            ior: IOR_GLASS,
+            dispersion: 0.0,
+        }
+    }
+}
+
+impl EnvironmentMaterial {
+    pub fn phong() -> Self {
+        Self::default()
+    }
+
+    pub fn reflective() -> Self {
+        Self {
+            mode: MaterialMode::Reflective,
+            ..Default::default()
+        }
+    }
+
+    pub fn refractive(ior: f32) -> Self {
+        Self {
+            mode: MaterialMode::Refractive,
+            ior,
+            ..Default::default()
+        }
+    }
+
+    pub fn fresnel(ior: f32) -> Self {
+        Self {
+            mode: MaterialMode::Fresnel,
+            ior,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_dispersion(mut self, dispersion: f32) -> Self {
+        self.dispersion = dispersion;
+        self
+    }
+
+    // Reflectance at normal incidence
+    pub fn f0(&self) -> f32 {
+        let r = (IOR_AIR - self.ior) / (IOR_AIR + self.ior);
+        r * r
+    }
+
+    pub fn to_uniform(&self) -> EnvironmentMaterialData {
+        EnvironmentMaterialData {
+            mode: self.mode.id(),
+            ior: self.ior,
+            dispersion: self.dispersion,
+            f0: self.f0(),
+        }
+    }
+}
+
+// Matches the GLSL struct:
+// struct EnvMaterial {
+//     uint mode;
+//     float ior;
+//     float dispersion;
+//     float f0;
+// };
+#[derive(Copy, Clone)]
+#[uniform]
+pub struct EnvironmentMaterialData {
+    pub mode: u32,
+    pub ior: f32,
+    pub dispersion: f32,
+    pub f0: f32,
+}
+
+// Same approximation used by the shaders, useful to preview or tweak values on the CPU
+pub fn fresnel_schlick(cos_theta: f32, f0: f32) -> f32 {
+    f0 + (1.0 - f0) * (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fresnel_matches_the_shader() {
+        // the fresnel material of the environment mapping example mixes with the same term
+        let shader = include_str!("../_4_advanced_opengl/_6_2_cubemaps_environment_mapping.rs");
+        assert!(shader.contains("float cosTheta = max(dot(viewDir, norm), 0.0);"));
+        assert!(shader.contains("env.f0 + (1.0 - env.f0) * pow(1.0 - cosTheta, 5.0)"));
+
+        let f0 = EnvironmentMaterial::fresnel(IOR_GLASS).f0();
+        assert!((f0 - 0.0426).abs() < 1e-4);
+
+        // looking straight at the surface only f0 is reflected, at grazing angles everything
+        assert_eq!(fresnel_schlick(1.0, f0), f0);
+        assert_eq!(fresnel_schlick(0.0, f0), 1.0);
+        assert_eq!(fresnel_schlick(0.5, f0), f0 + (1.0 - f0) / 32.0);
+        // back faces are clamped like the max() of the shader
+        assert_eq!(fresnel_schlick(-0.5, f0), 1.0);
+    }
+}
@@ -0,0 +1,379 @@
+use notan::prelude::*;
+
+// Radiance HDR (.hdr) decoder, pixels are stored as RGBE (shared exponent)
+// and scanlines can be flat or run-length encoded.
+
+// 16k x 16k, 3 GiB of RGB floats. Larger resolutions are most likely a broken header.
+const MAX_PIXELS: usize = 16384 * 16384;
+
+#[derive(Clone, Debug)]
+pub struct HdrImage {
+    pub width: u32,
+    pub height: u32,
+    // RGB triplets, top to bottom
+    pub data: Vec<f32>,
+}
+
+impl HdrImage {
+    pub fn pixel(&self, x: u32, y: u32) -> [f32; 3] {
+        let i = ((y * self.width + x) * 3) as usize;
+        [self.data[i], self.data[i + 1], self.data[i + 2]]
+    }
+
+    // Half float bits, useful to upload or store the image using half the memory
+    pub fn to_rgb_f16(&self) -> Vec<u16> {
+        self.data.iter().map(|v| f32_to_f16(*v)).collect()
+    }
+
+    // Most backends don't support RGB float textures so we pad the alpha channel
+    pub fn to_rgba_f32(&self) -> Vec<f32> {
+        self.data
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 1.0])
+            .collect()
+    }
+}
+
+pub fn decode_hdr(bytes: &[u8]) -> Result<HdrImage, String> {
+    let mut reader = Reader { bytes, pos: 0 };
+
+    // header
+    let magic = reader.line()?;
+    if magic != "#?RADIANCE" && magic != "#?RGBE" {
+        return Err(format!("Invalid HDR signature '{}'", magic));
+    }
+
+    loop {
+        let line = reader.line()?;
+        if line.is_empty() {
+            break;
+        }
+
+        if let Some(format) = line.strip_prefix("FORMAT=") {
+            if format != "32-bit_rle_rgbe" {
+                return Err(format!("Unsupported HDR format '{}'", format));
+            }
+        }
+    }
+
+    // resolution
+    let resolution = reader.line()?;
+    let (flip_y, width, height) = parse_resolution(&resolution)?;
+
+    // scanlines
+    let len = (width as usize)
+        .checked_mul(height as usize)
+        .filter(|pixels| *pixels <= MAX_PIXELS)
+        .and_then(|pixels| pixels.checked_mul(3))
+        .ok_or_else(|| format!("HDR resolution {}x{} is too big", width, height))?;
+    let mut data = vec![0.0; len];
+    let mut scanline = vec![[0u8; 4]; width as usize];
+    for y in 0..height {
+        read_scanline(&mut reader, &mut scanline)?;
+
+        let row = if flip_y { height - 1 - y } else { y };
+        let offset = row as usize * width as usize * 3;
+        scanline.iter().enumerate().for_each(|(x, rgbe)| {
+            let i = offset + x * 3;
+            data[i..i + 3].copy_from_slice(&rgbe_to_rgb(*rgbe));
+        });
+    }
+
+    Ok(HdrImage {
+        width,
+        height,
+        data,
+    })
+}
+
+pub fn create_hdr_texture(gfx: &mut Graphics, image: &HdrImage) -> Result<Texture, String> {
+    let pixels = image.to_rgba_f32();
+    gfx.create_texture()
+        .from_bytes(
+            bytemuck::cast_slice(&pixels),
+            image.width as _,
+            image.height as _,
+        )
+        .with_format(TextureFormat::Rgba32Float)
+        .build()
+}
+
+fn parse_resolution(line: &str) -> Result<(bool, u32, u32), String> {
+    let parts = line.split_whitespace().collect::<Vec<_>>();
+    let parse = |s: &str| {
+        s.parse::<u32>()
+            .map_err(|_| format!("Invalid HDR resolution '{}'", line))
+    };
+
+    match parts.as_slice() {
+        ["-Y", h, "+X", w] => Ok((false, parse(w)?, parse(h)?)),
+        ["+Y", h, "+X", w] => Ok((true, parse(w)?, parse(h)?)),
+        _ => Err(format!("Unsupported HDR orientation '{}'", line)),
+    }
+}
+
+fn read_scanline(reader: &mut Reader, scanline: &mut [[u8; 4]]) -> Result<(), String> {
+    let width = scanline.len();
+
+    // new RLE scanlines are only used with widths between 8 and 32767
+    if !(8..0x8000).contains(&width) {
+        return read_flat_scanline(reader, scanline);
+    }
+
+    let header = reader.peek(4)?;
+    if header[0] != 2 || header[1] != 2 || header[2] & 0x80 != 0 {
+        return read_flat_scanline(reader, scanline);
+    }
+
+    let encoded_width = ((header[2] as usize) << 8) | header[3] as usize;
+    if encoded_width != width {
+        return Err(format!(
+            "HDR scanline width mismatch, expected {} got {}",
+            width, encoded_width
+        ));
+    }
+    reader.pos += 4;
+
+    // every channel is stored separately as runs or literals
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let count = reader.byte()? as usize;
+            if count > 128 {
+                let count = count - 128;
+                if x + count > width {
+                    return Err("HDR run exceeds the scanline width".to_string());
+                }
+
+                let value = reader.byte()?;
+                scanline[x..x + count]
+                    .iter_mut()
+                    .for_each(|px| px[channel] = value);
+                x += count;
+            } else {
+                if count == 0 || x + count > width {
+                    return Err("Invalid HDR literal run".to_string());
+                }
+
+                let values = reader.take(count)?;
+                scanline[x..x + count]
+                    .iter_mut()
+                    .zip(values)
+                    .for_each(|(px, value)| px[channel] = *value);
+                x += count;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Uncompressed scanline, or the old RLE where (1, 1, 1, n) repeats the previous pixel
+fn read_flat_scanline(reader: &mut Reader, scanline: &mut [[u8; 4]]) -> Result<(), String> {
+    let width = scanline.len();
+    let mut shift = 0;
+    let mut x = 0;
+    while x < width {
+        let rgbe = reader.take(4)?;
+        if rgbe[0] == 1 && rgbe[1] == 1 && rgbe[2] == 1 {
+            if x == 0 {
+                return Err("HDR repeat run without a previous pixel".to_string());
+            }
+
+            let count = (rgbe[3] as usize) << shift;
+            if x + count > width {
+                return Err("HDR run exceeds the scanline width".to_string());
+            }
+
+            let previous = scanline[x - 1];
+            scanline[x..x + count].fill(previous);
+            x += count;
+            shift += 8;
+        } else {
+            scanline[x].copy_from_slice(rgbe);
+            x += 1;
+            shift = 0;
+        }
+    }
+
+    Ok(())
+}
+
+fn rgbe_to_rgb([r, g, b, e]: [u8; 4]) -> [f32; 3] {
+    if e == 0 {
+        return [0.0; 3];
+    }
+
+    let f = 2f32.powi(e as i32 - (128 + 8));
+    [r as f32 * f, g as f32 * f, b as f32 * f]
+}
+
+// IEEE 754 half precision with round to nearest even
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    // NaN and infinity
+    if exp == 0xff {
+        let nan = if mantissa != 0 { 0x0200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let half_exp = exp - 127 + 15;
+
+    // overflow to infinity
+    if half_exp >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    // subnormal or zero
+    if half_exp <= 0 {
+        if half_exp < -10 {
+            return sign;
+        }
+
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - half_exp) as u32;
+        let half_mantissa = mantissa >> shift;
+        let rest = mantissa & ((1 << shift) - 1);
+        let halfway = 1 << (shift - 1);
+        let round = rest > halfway || (rest == halfway && half_mantissa & 1 == 1);
+        return sign | (half_mantissa + round as u32) as u16;
+    }
+
+    let half = ((half_exp as u32) << 10) | (mantissa >> 13);
+    let rest = mantissa & 0x1fff;
+    let round = rest > 0x1000 || (rest == 0x1000 && half & 1 == 1);
+
+    // a carry here can overflow into the exponent, which is the correct result
+    sign | (half + round as u32) as u16
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn peek(&self, len: usize) -> Result<&'a [u8], String> {
+        self.bytes
+            .get(self.pos..self.pos + len)
+            .ok_or_else(|| "Unexpected end of HDR data".to_string())
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self.peek(len)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn line(&mut self) -> Result<String, String> {
+        let rest = &self.bytes[self.pos..];
+        let len = rest
+            .iter()
+            .position(|b| *b == b'\n')
+            .ok_or_else(|| "Unexpected end of HDR header".to_string())?;
+
+        self.pos += len + 1;
+        Ok(String::from_utf8_lossy(&rest[..len]).trim_end().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NEWPORT_LOFT: &[u8] = include_bytes!("../../resources/textures/hdr/newport_loft.hdr");
+
+    // reference values, using the same RGBE conversion as stb_image
+    const SAMPLE_TOP_LEFT: [f32; 3] = [159.0 / 1024.0, 75.0 / 1024.0, 3.0 / 128.0];
+    const SAMPLE_CENTER: [f32; 3] = [127.0 / 2048.0, 85.0 / 2048.0, 97.0 / 4096.0];
+    const SAMPLE_BOTTOM_RIGHT: [f32; 3] = [155.0 / 1024.0, 81.0 / 1024.0, 41.0 / 1024.0];
+
+    fn assert_rgb(a: [f32; 3], b: [f32; 3]) {
+        a.iter().zip(b.iter()).for_each(|(a, b)| {
+            assert!((a - b).abs() <= b.abs() * 1e-6, "{:?} != {:?}", a, b);
+        });
+    }
+
+    #[test]
+    fn decode_newport_loft() {
+        let image = decode_hdr(NEWPORT_LOFT).unwrap();
+        assert_eq!(image.width, 1600);
+        assert_eq!(image.height, 800);
+        assert_eq!(image.data.len(), 1600 * 800 * 3);
+    }
+
+    #[test]
+    fn newport_loft_pixels() {
+        let image = decode_hdr(NEWPORT_LOFT).unwrap();
+        assert_rgb(image.pixel(0, 0), SAMPLE_TOP_LEFT);
+        assert_rgb(image.pixel(800, 400), SAMPLE_CENTER);
+        assert_rgb(image.pixel(1599, 799), SAMPLE_BOTTOM_RIGHT);
+    }
+
+    #[test]
+    fn decode_flat_scanlines() {
+        let mut bytes = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 3\n".to_vec();
+        bytes.extend_from_slice(&[128, 64, 0, 129, 1, 1, 1, 1, 0, 0, 0, 0]);
+
+        let image = decode_hdr(&bytes).unwrap();
+        assert_eq!((image.width, image.height), (3, 1));
+        assert_rgb(image.pixel(0, 0), [1.0, 0.5, 0.0]);
+        assert_rgb(image.pixel(1, 0), [1.0, 0.5, 0.0]);
+        assert_rgb(image.pixel(2, 0), [0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn decode_rle_scanlines() {
+        let mut bytes = b"#?RGBE\n\n+Y 2 +X 8\n".to_vec();
+        for _ in 0..2 {
+            bytes.extend_from_slice(&[2, 2, 0, 8]);
+            // r: run of 8
+            bytes.extend_from_slice(&[136, 64]);
+            // g: 8 literals
+            bytes.extend_from_slice(&[8, 0, 16, 32, 48, 64, 80, 96, 112]);
+            // b: run of 4 + run of 4
+            bytes.extend_from_slice(&[132, 0, 132, 128]);
+            // e: run of 8
+            bytes.extend_from_slice(&[136, 129]);
+        }
+
+        let image = decode_hdr(&bytes).unwrap();
+        assert_eq!((image.width, image.height), (8, 2));
+        assert_rgb(image.pixel(0, 1), [0.5, 0.0, 0.0]);
+        assert_rgb(image.pixel(7, 0), [0.5, 0.875, 1.0]);
+    }
+
+    #[test]
+    fn reject_invalid_data() {
+        assert!(decode_hdr(b"P6\n1 1\n255\n").is_err());
+        assert!(decode_hdr(b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n").is_err());
+        assert!(decode_hdr(b"#?RADIANCE\n\n-Y 2 +X 2\n\x01\x02").is_err());
+
+        // the resolution is checked before allocating the image
+        let error = decode_hdr(b"#?RADIANCE\n\n-Y 4294967295 +X 4294967295\n").unwrap_err();
+        assert!(error.contains("too big"));
+        assert!(decode_hdr(b"#?RADIANCE\n\n-Y 16385 +X 16384\n").is_err());
+    }
+
+    #[test]
+    fn half_float_conversion() {
+        assert_eq!(f32_to_f16(0.0), 0x0000);
+        assert_eq!(f32_to_f16(-0.0), 0x8000);
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        assert_eq!(f32_to_f16(0.5), 0x3800);
+        assert_eq!(f32_to_f16(65504.0), 0x7bff);
+        assert_eq!(f32_to_f16(1e6), 0x7c00);
+        assert_eq!(f32_to_f16(f32::NAN) & 0x7c00, 0x7c00);
+        assert_eq!(f32_to_f16(2f32.powi(-24)), 0x0001);
+        assert_eq!(f32_to_f16(1.0 + 2f32.powi(-11)), 0x3c00);
+    }
+}
@@ -0,0 +1,168 @@
+use image::imageops::FilterType;
+use image::RgbaImage;
+
+// Perceptual image comparison used by the screenshot tests. The color difference is measured
+// in the YIQ color space ("Measuring perceived color difference using YIQ NTSC transmission
+// color space in mobile applications", Kotsarenko and Ramos), like pixelmatch does.
+
+// delta between black and white
+const MAX_YIQ_DELTA: f64 = 35215.0;
+
+#[derive(Clone, Debug)]
+pub struct ImageDiff {
+    pub width: u32,
+    pub height: u32,
+    // pixels with a delta over the threshold
+    pub mismatched: usize,
+    // 0.0 - 1.0
+    pub max_delta: f32,
+    pub mean_delta: f32,
+    // faded copy of the expected image with the mismatched pixels in red
+    pub image: RgbaImage,
+}
+
+impl ImageDiff {
+    pub fn mismatch_ratio(&self) -> f32 {
+        self.mismatched as f32 / (self.width * self.height).max(1) as f32
+    }
+}
+
+// `threshold` is the perceptual delta (0.0 - 1.0) to consider a pixel different,
+// 0.1 is a good value to ignore antialiasing and filtering differences
+pub fn compare_images(
+    expected: &RgbaImage,
+    actual: &RgbaImage,
+    threshold: f32,
+) -> Result<ImageDiff, String> {
+    if expected.dimensions() != actual.dimensions() {
+        return Err(format!(
+            "Image sizes don't match: expected {:?} but got {:?}",
+            expected.dimensions(),
+            actual.dimensions()
+        ));
+    }
+
+    let (width, height) = expected.dimensions();
+    let mut image = RgbaImage::new(width, height);
+    let mut mismatched = 0;
+    let mut max_delta = 0.0f32;
+    let mut total = 0.0f64;
+
+    expected
+        .pixels()
+        .zip(actual.pixels())
+        .zip(image.pixels_mut())
+        .for_each(|((a, b), out)| {
+            let delta = (color_delta(a.0, b.0) / MAX_YIQ_DELTA).sqrt() as f32;
+            total += delta as f64;
+            max_delta = max_delta.max(delta);
+
+            if delta > threshold {
+                mismatched += 1;
+                out.0 = [255, 0, 0, 255];
+            } else {
+                // gray and faded so the differences stand out
+                let y = (luma(blend_white(a.0)) * 0.1 + 255.0 * 0.9) as u8;
+                out.0 = [y, y, y, 255];
+            }
+        });
+
+    Ok(ImageDiff {
+        width,
+        height,
+        mismatched,
+        max_delta,
+        mean_delta: (total / (width * height).max(1) as f64) as f32,
+        image,
+    })
+}
+
+// Screenshots are stored scaled down, so the captures need to be scaled to compare them
+pub fn resize_to(image: &RgbaImage, width: u32, height: u32) -> RgbaImage {
+    if image.dimensions() == (width, height) {
+        return image.clone();
+    }
+
+    image::imageops::resize(image, width, height, FilterType::Triangle)
+}
+
+pub fn load_png(path: &str) -> Result<RgbaImage, String> {
+    image::open(path)
+        .map(|img| img.to_rgba8())
+        .map_err(|err| format!("Cannot load '{}': {}", path, err))
+}
+
+// squared YIQ distance of the colors blended over white
+fn color_delta(a: [u8; 4], b: [u8; 4]) -> f64 {
+    let a = blend_white(a);
+    let b = blend_white(b);
+
+    let y = luma(a) - luma(b);
+    let i = in_phase(a) - in_phase(b);
+    let q = quadrature(a) - quadrature(b);
+
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+fn blend_white(c: [u8; 4]) -> [f64; 3] {
+    let alpha = c[3] as f64 / 255.0;
+    let blend = |v: u8| 255.0 + (v as f64 - 255.0) * alpha;
+    [blend(c[0]), blend(c[1]), blend(c[2])]
+}
+
+fn luma(c: [f64; 3]) -> f64 {
+    c[0] * 0.29889531 + c[1] * 0.58662247 + c[2] * 0.11448223
+}
+
+fn in_phase(c: [f64; 3]) -> f64 {
+    c[0] * 0.59597799 - c[1] * 0.2741761 - c[2] * 0.32180189
+}
+
+fn quadrature(c: [f64; 3]) -> f64 {
+    c[0] * 0.21147017 - c[1] * 0.52261711 + c[2] * 0.31114694
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(color: [u8; 4]) -> RgbaImage {
+        RgbaImage::from_pixel(8, 8, image::Rgba(color))
+    }
+
+    #[test]
+    fn same_images_have_no_difference() {
+        let a = solid([51, 76, 76, 255]);
+        let diff = compare_images(&a, &a, 0.1).unwrap();
+        assert_eq!(diff.mismatched, 0);
+        assert_eq!(diff.max_delta, 0.0);
+    }
+
+    #[test]
+    fn small_differences_are_tolerated() {
+        let diff =
+            compare_images(&solid([51, 76, 76, 255]), &solid([53, 77, 75, 255]), 0.1).unwrap();
+        assert_eq!(diff.mismatched, 0);
+        assert!(diff.max_delta > 0.0);
+    }
+
+    #[test]
+    fn different_pixels_are_marked() {
+        let a = solid([0, 0, 0, 255]);
+        let mut b = a.clone();
+        b.put_pixel(2, 3, image::Rgba([255, 255, 255, 255]));
+
+        let diff = compare_images(&a, &b, 0.1).unwrap();
+        assert_eq!(diff.mismatched, 1);
+        assert!(diff.max_delta > 0.9);
+        assert_eq!(diff.image.get_pixel(2, 3).0, [255, 0, 0, 255]);
+        assert!((diff.mismatch_ratio() - 1.0 / 64.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn different_sizes_fail() {
+        let a = solid([0, 0, 0, 255]);
+        let b = RgbaImage::new(4, 4);
+        assert!(compare_images(&a, &b, 0.1).is_err());
+    }
+}
@@ -0,0 +1,295 @@
+use notan::math::{vec2, Vec2};
+use notan::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+// Input events of the frames that don't run any step, they're kept until the next step
+// so the examples don't miss the keys pressed between steps
+#[derive(Clone, Debug, Default)]
+pub struct PendingInput {
+    keys_pressed: HashSet<KeyCode>,
+    keys_released: HashSet<KeyCode>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    wheel: Vec2,
+}
+
+impl PendingInput {
+    pub fn store(&mut self, app: &App) {
+        self.keys_pressed
+            .extend(app.keyboard.pressed.iter().copied());
+        self.keys_released
+            .extend(app.keyboard.released.iter().copied());
+        self.buttons_pressed
+            .extend(app.mouse.pressed.iter().copied());
+        self.buttons_released
+            .extend(app.mouse.released.iter().copied());
+        self.wheel += app.mouse.wheel_delta;
+    }
+
+    // Adds the stored events to the ones of this frame
+    pub fn restore(&mut self, app: &mut App) {
+        let pending = std::mem::take(self);
+        app.keyboard.pressed.extend(pending.keys_pressed);
+        app.keyboard.released.extend(pending.keys_released);
+        app.mouse.pressed.extend(pending.buttons_pressed);
+        app.mouse.released.extend(pending.buttons_released);
+        app.mouse.wheel_delta += pending.wheel;
+    }
+}
+
+// Removes the events of this frame, used when a frame runs more than one step so only
+// the first one sees them
+pub fn clear_input_events(app: &mut App) {
+    app.keyboard.pressed.clear();
+    app.keyboard.released.clear();
+    app.mouse.pressed.clear();
+    app.mouse.released.clear();
+    app.mouse.wheel_delta = Vec2::ZERO;
+}
+
+// Input replayed in deterministic mode instead of the real one. It's a text file with one
+// event per line, starting with the frame where it happens:
+//
+// # frame  event
+// 0        mouse_move 400 300
+// 10       key_down W
+// 70       key_up W
+// 80       wheel 0 1
+// 90       button_down Left
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InputEvent {
+    KeyDown(KeyCode),
+    KeyUp(KeyCode),
+    ButtonDown(MouseButton),
+    ButtonUp(MouseButton),
+    MouseMove(f32, f32),
+    Wheel(f32, f32),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct InputScript {
+    // sorted by frame
+    events: Vec<(u64, InputEvent)>,
+    next: usize,
+    keys: HashMap<KeyCode, f32>,
+    buttons: HashMap<MouseButton, f32>,
+    mouse: (f32, f32),
+}
+
+impl InputScript {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("Cannot read '{}': {}", path, err))?;
+        Self::parse(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut events = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let event = parse_line(line).map_err(|err| format!("line {}: {}", i + 1, err))?;
+            events.push(event);
+        }
+
+        // stable, so the events of the same frame keep the order of the file
+        events.sort_by_key(|(frame, _)| *frame);
+
+        Ok(Self {
+            events,
+            ..Default::default()
+        })
+    }
+
+    pub fn events(&self) -> &[(u64, InputEvent)] {
+        &self.events
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+
+    // Replaces the input of the app with the scripted one for this frame
+    pub fn apply(&mut self, frame: u64, delta: f32, app: &mut App) {
+        let mut key_pressed = HashSet::new();
+        let mut key_released = HashSet::new();
+        let mut button_pressed = HashSet::new();
+        let mut button_released = HashSet::new();
+        let mut wheel = (0.0, 0.0);
+
+        // held keys and buttons count the time since they were pressed
+        self.keys.values_mut().for_each(|time| *time += delta);
+        self.buttons.values_mut().for_each(|time| *time += delta);
+
+        while let Some((event_frame, event)) = self.events.get(self.next).copied() {
+            if event_frame > frame {
+                break;
+            }
+
+            self.next += 1;
+            match event {
+                InputEvent::KeyDown(key) => {
+                    self.keys.insert(key, 0.0);
+                    key_pressed.insert(key);
+                }
+                InputEvent::KeyUp(key) => {
+                    self.keys.remove(&key);
+                    key_released.insert(key);
+                }
+                InputEvent::ButtonDown(btn) => {
+                    self.buttons.insert(btn, 0.0);
+                    button_pressed.insert(btn);
+                }
+                InputEvent::ButtonUp(btn) => {
+                    self.buttons.remove(&btn);
+                    button_released.insert(btn);
+                }
+                InputEvent::MouseMove(x, y) => self.mouse = (x, y),
+                InputEvent::Wheel(x, y) => wheel = (wheel.0 + x, wheel.1 + y),
+            }
+        }
+
+        app.keyboard.down = self.keys.clone();
+        app.keyboard.pressed = key_pressed;
+        app.keyboard.released = key_released;
+
+        app.mouse.down = self.buttons.clone();
+        app.mouse.pressed = button_pressed;
+        app.mouse.released = button_released;
+        app.mouse.x = self.mouse.0;
+        app.mouse.y = self.mouse.1;
+        app.mouse.wheel_delta = vec2(wheel.0, wheel.1);
+    }
+}
+
+fn parse_line(line: &str) -> Result<(u64, InputEvent), String> {
+    let parts = line.split_whitespace().collect::<Vec<_>>();
+    let frame = parts[0]
+        .parse::<u64>()
+        .map_err(|_| format!("'{}' is not a frame number", parts[0]))?;
+
+    let event = match parts[1..] {
+        ["key_down", key] => InputEvent::KeyDown(parse_key(key)?),
+        ["key_up", key] => InputEvent::KeyUp(parse_key(key)?),
+        ["button_down", btn] => InputEvent::ButtonDown(parse_button(btn)?),
+        ["button_up", btn] => InputEvent::ButtonUp(parse_button(btn)?),
+        ["mouse_move", x, y] => InputEvent::MouseMove(parse_number(x)?, parse_number(y)?),
+        ["wheel", x, y] => InputEvent::Wheel(parse_number(x)?, parse_number(y)?),
+        [] => return Err("missing the event after the frame".to_string()),
+        _ => return Err(format!("invalid event '{}'", parts[1..].join(" "))),
+    };
+
+    Ok((frame, event))
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))
+}
+
+fn parse_button(name: &str) -> Result<MouseButton, String> {
+    match name {
+        "Left" => Ok(MouseButton::Left),
+        "Right" => Ok(MouseButton::Right),
+        "Middle" => Ok(MouseButton::Middle),
+        _ => Err(format!("unknown mouse button '{}'", name)),
+    }
+}
+
+fn parse_key(name: &str) -> Result<KeyCode, String> {
+    use KeyCode::*;
+
+    let key = match name {
+        "A" => A,
+        "B" => B,
+        "C" => C,
+        "D" => D,
+        "E" => E,
+        "F" => F,
+        "G" => G,
+        "H" => H,
+        "I" => I,
+        "J" => J,
+        "K" => K,
+        "L" => L,
+        "M" => M,
+        "N" => N,
+        "O" => O,
+        "P" => P,
+        "Q" => Q,
+        "R" => R,
+        "S" => S,
+        "T" => T,
+        "U" => U,
+        "V" => V,
+        "W" => W,
+        "X" => X,
+        "Y" => Y,
+        "Z" => Z,
+        "Key0" => Key0,
+        "Key1" => Key1,
+        "Key2" => Key2,
+        "Key3" => Key3,
+        "Key4" => Key4,
+        "Key5" => Key5,
+        "Key6" => Key6,
+        "Key7" => Key7,
+        "Key8" => Key8,
+        "Key9" => Key9,
+        "Up" => Up,
+        "Down" => Down,
+        "Left" => Left,
+        "Right" => Right,
+        "Space" => Space,
+        "Return" => Return,
+        "Tab" => Tab,
+        "LShift" => LShift,
+        "LControl" => LControl,
+        _ => return Err(format!("unknown key '{}'", name)),
+    };
+
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_are_sorted_by_frame() {
+        let script = InputScript::parse(
+            "# walk forward\n\
+             10 key_down W\n\
+             70 key_up W # stop\n\
+             \n\
+             0 mouse_move 400 300\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            script.events(),
+            &[
+                (0, InputEvent::MouseMove(400.0, 300.0)),
+                (10, InputEvent::KeyDown(KeyCode::W)),
+                (70, InputEvent::KeyUp(KeyCode::W)),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_have_the_line_number() {
+        let err = InputScript::parse("0 key_down W\n5 key_down Enter").unwrap_err();
+        assert_eq!(err, "line 2: unknown key 'Enter'");
+
+        let err = InputScript::parse("x wheel 0 1").unwrap_err();
+        assert_eq!(err, "line 1: 'x' is not a frame number");
+
+        let err = InputScript::parse("\n\n3 wheel 1").unwrap_err();
+        assert_eq!(err, "line 3: invalid event 'wheel 1'");
+    }
+}
@@ -0,0 +1,269 @@
+use super::procedural::hash_unit;
+use crate::set_buffer_data;
+use notan::math::{vec3, Mat4, Vec3};
+use notan::prelude::*;
+
+// Model matrices of the objects drawn with one instanced draw call. They're stored in a
+// vertex buffer with instance step mode, as 4 vec4 attributes (one per column) starting at
+// `location`, so the vertex shader rebuilds the matrix:
+//
+//     layout (location = 3) in vec4 aModel0;
+//     ...
+//     mat4 model = mat4(aModel0, aModel1, aModel2, aModel3);
+//
+// The matrices of the last upload are kept, so the objects that don't move are not uploaded
+// again on each frame.
+pub struct ModelInstances {
+    buffer: Buffer,
+    location: u32,
+    models: Vec<Mat4>,
+}
+
+impl ModelInstances {
+    // used to build the pipeline and the buffer
+    pub fn vertex_info(location: u32) -> VertexInfo {
+        (0..4)
+            .fold(VertexInfo::new(), |info, i| {
+                info.attr(location + i, VertexFormat::Float32x4)
+            })
+            .step_mode(VertexStepMode::Instance)
+    }
+
+    pub fn new(gfx: &mut Graphics, location: u32, models: &[Mat4]) -> Result<Self, String> {
+        let buffer = create_buffer(gfx, location, models)?;
+        Ok(Self {
+            buffer,
+            location,
+            models: models.to_vec(),
+        })
+    }
+
+    // Uploads the matrices if they changed since the last upload, the buffer is created again
+    // when the number of instances changes
+    pub fn update(&mut self, gfx: &mut Graphics, models: &[Mat4]) {
+        if !needs_upload(&self.models, models) {
+            return;
+        }
+
+        if models.len() != self.models.len() {
+            match create_buffer(gfx, self.location, models) {
+                Ok(buffer) => self.buffer = buffer,
+                Err(err) => {
+                    notan::log::error!("Cannot create the instance buffer: {}", err);
+                    return;
+                }
+            }
+        } else {
+            set_buffer_data!(gfx, &self.buffer, &instance_data(models)[..]);
+        }
+
+        self.models.clear();
+        self.models.extend_from_slice(models);
+    }
+
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    // number of instances, the `length` of `draw_instanced`
+    pub fn len(&self) -> usize {
+        self.models.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.models.is_empty()
+    }
+}
+
+fn create_buffer(gfx: &mut Graphics, location: u32, models: &[Mat4]) -> Result<Buffer, String> {
+    // the buffer can't be empty, it's never drawn without instances anyway
+    let models = if models.is_empty() {
+        &[Mat4::IDENTITY]
+    } else {
+        models
+    };
+
+    gfx.create_vertex_buffer()
+        .with_info(&ModelInstances::vertex_info(location))
+        .with_data(&instance_data(models))
+        .build()
+}
+
+// The matrices are uploaded again only if they are not the last ones uploaded
+pub fn needs_upload(uploaded: &[Mat4], models: &[Mat4]) -> bool {
+    uploaded != models
+}
+
+// Column major, like the mat4 of the shaders
+pub fn instance_data(models: &[Mat4]) -> Vec<f32> {
+    models
+        .iter()
+        .flat_map(|model| model.to_cols_array())
+        .collect()
+}
+
+// Distribution of the rocks around the planet of the instancing chapter. The rocks are
+// spread in a circle of `radius` and displaced randomly up to `offset`, less on the y axis
+// to keep the ring flat, with a random scale and rotation. The same seed gives the same ring.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AsteroidRing {
+    pub amount: usize,
+    pub radius: f32,
+    pub offset: f32,
+    pub seed: u32,
+}
+
+impl Default for AsteroidRing {
+    fn default() -> Self {
+        Self {
+            amount: 10000,
+            radius: 150.0,
+            offset: 25.0,
+            seed: 0,
+        }
+    }
+}
+
+impl AsteroidRing {
+    pub const MIN_SCALE: f32 = 0.05;
+    pub const MAX_SCALE: f32 = 0.25;
+
+    pub fn models(&self) -> Vec<Mat4> {
+        (0..self.amount)
+            .map(|i| {
+                // every rock takes its random values from its own row of the hash
+                let random = |k: i32| hash_unit(i as i32, k, self.seed);
+                let displacement = |k: i32| (random(k) * 2.0 - 1.0) * self.offset;
+
+                // 1. translation: displace along the circle in the range [-offset, offset]
+                let angle = i as f32 / self.amount as f32 * std::f32::consts::TAU;
+                let position = vec3(
+                    angle.sin() * self.radius + displacement(0),
+                    displacement(1) * 0.4,
+                    angle.cos() * self.radius + displacement(2),
+                );
+
+                // 2. scale between MIN_SCALE and MAX_SCALE
+                let scale = Self::MIN_SCALE + random(3) * (Self::MAX_SCALE - Self::MIN_SCALE);
+
+                // 3. rotation around a (semi) randomly picked axis
+                let rotation = random(4) * std::f32::consts::TAU;
+
+                Mat4::from_translation(position)
+                    * Mat4::from_scale(Vec3::splat(scale))
+                    * Mat4::from_axis_angle(vec3(0.4, 0.6, 0.8).normalize(), rotation)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::FrameStats;
+    use std::mem::{size_of, size_of_val};
+
+    fn cube_models() -> Vec<Mat4> {
+        (0..10)
+            .map(|i| Mat4::from_translation(vec3(i as f32, 0.0, 0.0)))
+            .collect()
+    }
+
+    // Counters of a frame of `1_6_3` before the instancing: every cube had its own pass and
+    // uploaded its Transform (model, view and projection)
+    fn frame_per_cube(models: &[Mat4]) -> FrameStats {
+        let mut stats = FrameStats::default();
+        models.iter().for_each(|_| {
+            stats.count_upload(size_of::<[Mat4; 3]>());
+            stats.count_pass();
+            stats.count_pipeline();
+            stats.count_buffers(2);
+            stats.count_texture();
+            stats.count_texture();
+            stats.count_draw(36);
+        });
+        stats
+    }
+
+    // and after: one pass, the Transform has only the view and projection and the instances
+    // are uploaded when they change, like `ModelInstances::update`
+    fn frame_instanced(uploaded: &mut Vec<Mat4>, models: &[Mat4]) -> FrameStats {
+        let mut stats = FrameStats::default();
+        if needs_upload(uploaded, models) {
+            stats.count_upload(size_of_val(&instance_data(models)[..]));
+            *uploaded = models.to_vec();
+        }
+        stats.count_upload(size_of::<[Mat4; 2]>());
+        stats.count_pass();
+        stats.count_pipeline();
+        stats.count_buffers(3);
+        stats.count_texture();
+        stats.count_texture();
+        stats.count_draw_instanced(36, models.len() as _);
+        stats
+    }
+
+    #[test]
+    fn instanced_cubes_use_one_pass_per_frame() {
+        let models = cube_models();
+
+        let before = frame_per_cube(&models);
+        assert_eq!(before.passes, 10);
+        assert_eq!(before.draw_calls, 10);
+        assert_eq!(before.bytes_uploaded, 1920);
+
+        // the first frame uploads the instances, the next ones only the view and projection
+        let mut uploaded = vec![];
+        let first = frame_instanced(&mut uploaded, &models);
+        let next = frame_instanced(&mut uploaded, &models);
+        assert_eq!((first.passes, next.passes), (1, 1));
+        assert_eq!((first.draw_calls, next.draw_calls), (1, 1));
+        assert_eq!(first.bytes_uploaded, 128 + 640);
+        assert_eq!(next.bytes_uploaded, 128);
+        assert_eq!(before.triangles, next.triangles);
+
+        // a moved cube uploads them again
+        let mut moved = models.clone();
+        moved[3] = Mat4::IDENTITY;
+        assert_eq!(
+            frame_instanced(&mut uploaded, &moved).bytes_uploaded,
+            128 + 640
+        );
+    }
+
+    #[test]
+    fn instance_data_has_the_columns_of_each_model() {
+        let models = [Mat4::IDENTITY, Mat4::from_translation(vec3(1.0, 2.0, 3.0))];
+        let data = instance_data(&models);
+        assert_eq!(data.len(), 32);
+        assert_eq!(&data[..4], &[1.0, 0.0, 0.0, 0.0]);
+        // the translation is the last column
+        assert_eq!(&data[28..], &[1.0, 2.0, 3.0, 1.0]);
+    }
+
+    #[test]
+    fn asteroids_stay_in_the_ring() {
+        let ring = AsteroidRing {
+            amount: 1000,
+            radius: 50.0,
+            offset: 2.5,
+            seed: 7,
+        };
+        let models = ring.models();
+        assert_eq!(models.len(), 1000);
+
+        models.iter().for_each(|model| {
+            let (scale, _, position) = model.to_scale_rotation_translation();
+            let distance = vec3(position.x, 0.0, position.z).length();
+            assert!((distance - ring.radius).abs() <= ring.offset * 2.0_f32.sqrt());
+            assert!(position.y.abs() <= ring.offset * 0.4);
+            assert!(scale.x >= AsteroidRing::MIN_SCALE - 1e-5);
+            assert!(scale.x <= AsteroidRing::MAX_SCALE + 1e-5);
+        });
+
+        // the same seed gives the same ring
+        assert_eq!(ring.models(), models);
+        let other = AsteroidRing { seed: 8, ..ring };
+        assert_ne!(other.models(), models);
+    }
+}
@@ -0,0 +1,661 @@
+use super::compressed::{is_texture_container, parse_texture_container};
+use notan::math::{vec2, vec4, Vec2, Vec4};
+use notan::prelude::*;
+use std::f32::consts::PI;
+
+// Notan's textures don't expose their mip levels, so the chain is generated on the CPU
+// and packed into an atlas (base level on the left, the rest stacked on its right).
+// The shaders sample it with `textureMip`, which does the lod selection, the mip
+// filtering, the anisotropic taps and the wrapping:
+//
+// layout(set = 0, binding = N) uniform MipmapData {
+//     vec4 levels[MAX_MIP_LEVELS];
+//     vec2 atlasSize;
+//     vec2 baseSize;
+//     uint levelCount;
+//     uint minFilter;
+//     uint magFilter;
+//     uint mipFilter;
+//     uint anisotropy;
+//     uint wrapX;
+//     uint wrapY;
+// };
+//
+// see src/_1_getting_started/_4_7_textures_mipmaps.rs for the GLSL side.
+
+pub const MAX_MIP_LEVELS: usize = 13;
+
+// Every level has a 1px border with the wrapped texels so the hardware bilinear
+// filter doesn't read from the neighbour levels.
+const BORDER: u32 = 1;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MipFilter {
+    // 2x2 average
+    Box,
+    // windowed sinc, sharp but with less ringing than lanczos
+    Kaiser,
+    // lanczos3, the sharpest one
+    Lanczos,
+}
+
+// What the texels of the texture represent, this changes how the levels are averaged
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextureUsage {
+    // sRGB colors, averaged in linear space
+    Color,
+    // data like specular or roughness maps, averaged as they are
+    Linear,
+    // tangent space normals, renormalized on every level
+    Normal,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SamplerSettings {
+    pub min_filter: TextureFilter,
+    pub mag_filter: TextureFilter,
+    // None disables the mipmaps
+    pub mip_filter: Option<TextureFilter>,
+    // max number of taps along the anisotropy axis, 1 disables it
+    pub anisotropy: u32,
+}
+
+impl Default for SamplerSettings {
+    fn default() -> Self {
+        Self {
+            min_filter: TextureFilter::Linear,
+            mag_filter: TextureFilter::Linear,
+            mip_filter: Some(TextureFilter::Linear),
+            anisotropy: 1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MipmapOptions {
+    pub filter: MipFilter,
+    pub usage: TextureUsage,
+    pub sampler: SamplerSettings,
+    // the wrap is used to filter the edges of the levels and to fill the borders of the
+    // atlas, so unlike the sampler it can't change after the texture is created
+    pub wrap_x: TextureWrap,
+    pub wrap_y: TextureWrap,
+}
+
+impl Default for MipmapOptions {
+    fn default() -> Self {
+        Self {
+            filter: MipFilter::Kaiser,
+            usage: TextureUsage::Color,
+            sampler: Default::default(),
+            wrap_x: TextureWrap::Repeat,
+            wrap_y: TextureWrap::Repeat,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MipLevel {
+    pub width: u32,
+    pub height: u32,
+    // RGBA8
+    pub data: Vec<u8>,
+}
+
+// Uniform data for `textureMip`
+#[derive(Copy, Clone)]
+#[uniform]
+pub struct MipmapData {
+    pub levels: [Vec4; MAX_MIP_LEVELS],
+    pub atlas_size: Vec2,
+    pub base_size: Vec2,
+    pub level_count: u32,
+    pub min_filter: u32,
+    pub mag_filter: u32,
+    pub mip_filter: u32,
+    pub anisotropy: u32,
+    pub wrap_x: u32,
+    pub wrap_y: u32,
+}
+
+impl MipmapData {
+    // Change the sampler settings without rebuilding the atlas, the wrap stays the one
+    // of the `MipmapOptions` used to create it
+    pub fn with_sampler(mut self, sampler: &SamplerSettings) -> Self {
+        let filter_id = |filter: TextureFilter| (filter == TextureFilter::Linear) as u32;
+
+        self.min_filter = filter_id(sampler.min_filter);
+        self.mag_filter = filter_id(sampler.mag_filter);
+        self.mip_filter = sampler.mip_filter.map_or(0, |filter| 1 + filter_id(filter));
+        self.anisotropy = sampler.anisotropy.max(1);
+        self
+    }
+}
+
+// Decodes the image, generates the mip chain and uploads it as an atlas.
+// KTX2 and DDS files use the levels stored in the file when they have more than one.
+pub fn create_mipmapped_texture(
+    gfx: &mut Graphics,
+    bytes: &[u8],
+    options: &MipmapOptions,
+) -> Result<(Texture, MipmapData), String> {
+    if is_texture_container(bytes) {
+        let container = parse_texture_container(bytes)?;
+        if container.levels.len() > 1 {
+            return upload_mip_levels(gfx, &container.decode_levels()?, options);
+        }
+
+        // the colors of a container flagged as linear are averaged as they are
+        let options = MipmapOptions {
+            usage: match options.usage {
+                TextureUsage::Color if !container.srgb => TextureUsage::Linear,
+                usage => usage,
+            },
+            ..*options
+        };
+        let base = container.decode_level(0)?;
+        let levels = generate_mipmaps(base.width, base.height, &base.data, &options);
+        return upload_mip_levels(gfx, &levels, &options);
+    }
+
+    let image = image::load_from_memory(bytes)
+        .map_err(|e| e.to_string())?
+        .into_rgba8();
+
+    let levels = generate_mipmaps(image.width(), image.height(), image.as_raw(), options);
+    upload_mip_levels(gfx, &levels, options)
+}
+
+// Packs levels generated elsewhere (like the ones stored in a KTX2 or DDS file) into an atlas,
+// the filter and usage of the options are not used
+pub fn upload_mip_levels(
+    gfx: &mut Graphics,
+    levels: &[MipLevel],
+    options: &MipmapOptions,
+) -> Result<(Texture, MipmapData), String> {
+    if levels.is_empty() {
+        return Err("Missing mip levels".to_string());
+    }
+
+    let (width, height, data, rects) =
+        pack_levels(&levels[..levels.len().min(MAX_MIP_LEVELS)], options);
+
+    let texture = gfx
+        .create_texture()
+        .from_bytes(&data, width, height)
+        .with_filter(TextureFilter::Linear, TextureFilter::Linear)
+        .build()?;
+
+    let mut mip_levels = [Vec4::ZERO; MAX_MIP_LEVELS];
+    mip_levels
+        .iter_mut()
+        .zip(rects.iter())
+        .for_each(|(level, rect)| *level = *rect);
+
+    let wrap_id = |wrap: TextureWrap| (wrap == TextureWrap::Repeat) as u32;
+    let data = MipmapData {
+        levels: mip_levels,
+        atlas_size: vec2(width as _, height as _),
+        base_size: vec2(levels[0].width as _, levels[0].height as _),
+        level_count: rects.len() as _,
+        min_filter: 0,
+        mag_filter: 0,
+        mip_filter: 0,
+        anisotropy: 1,
+        wrap_x: wrap_id(options.wrap_x),
+        wrap_y: wrap_id(options.wrap_y),
+    }
+    .with_sampler(&options.sampler);
+
+    Ok((texture, data))
+}
+
+// Returns all the levels, the first one is the image itself
+pub fn generate_mipmaps(
+    width: u32,
+    height: u32,
+    data: &[u8],
+    options: &MipmapOptions,
+) -> Vec<MipLevel> {
+    let MipmapOptions { filter, usage, .. } = *options;
+    let repeat_x = options.wrap_x == TextureWrap::Repeat;
+    let repeat_y = options.wrap_y == TextureWrap::Repeat;
+
+    let mut levels = vec![MipLevel {
+        width,
+        height,
+        data: data.to_vec(),
+    }];
+
+    // every level is filtered from the previous one using float texels
+    let mut current = decode(data, usage);
+    let (mut w, mut h) = (width, height);
+    while (w > 1 || h > 1) && levels.len() < MAX_MIP_LEVELS {
+        let (nw, nh) = ((w / 2).max(1), (h / 2).max(1));
+        let horizontal = resample(&current, w, h, nw, true, filter, repeat_x);
+        current = resample(&horizontal, nw, h, nh, false, filter, repeat_y);
+
+        if usage == TextureUsage::Normal {
+            renormalize(&mut current);
+        }
+
+        levels.push(MipLevel {
+            width: nw,
+            height: nh,
+            data: encode(&current, usage),
+        });
+
+        w = nw;
+        h = nh;
+    }
+
+    levels
+}
+
+fn srgb_to_linear(v: f32) -> f32 {
+    if v <= 0.04045 {
+        v / 12.92
+    } else {
+        ((v + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(v: f32) -> f32 {
+    if v <= 0.0031308 {
+        v * 12.92
+    } else {
+        1.055 * v.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn decode(data: &[u8], usage: TextureUsage) -> Vec<[f32; 4]> {
+    data.chunks_exact(4)
+        .map(|px| {
+            let [r, g, b, a] = [px[0], px[1], px[2], px[3]].map(|v| v as f32 / 255.0);
+            match usage {
+                TextureUsage::Color => [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a],
+                TextureUsage::Linear => [r, g, b, a],
+                TextureUsage::Normal => [r * 2.0 - 1.0, g * 2.0 - 1.0, b * 2.0 - 1.0, a],
+            }
+        })
+        .collect()
+}
+
+fn encode(texels: &[[f32; 4]], usage: TextureUsage) -> Vec<u8> {
+    let to_byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    texels
+        .iter()
+        .flat_map(|[r, g, b, a]| {
+            let rgb = match usage {
+                TextureUsage::Color => [linear_to_srgb(*r), linear_to_srgb(*g), linear_to_srgb(*b)],
+                TextureUsage::Linear => [*r, *g, *b],
+                TextureUsage::Normal => [r * 0.5 + 0.5, g * 0.5 + 0.5, b * 0.5 + 0.5],
+            };
+            [
+                to_byte(rgb[0]),
+                to_byte(rgb[1]),
+                to_byte(rgb[2]),
+                to_byte(*a),
+            ]
+        })
+        .collect()
+}
+
+fn renormalize(texels: &mut [[f32; 4]]) {
+    texels.iter_mut().for_each(|texel| {
+        let len = (texel[0] * texel[0] + texel[1] * texel[1] + texel[2] * texel[2]).sqrt();
+        if len > f32::EPSILON {
+            texel[0] /= len;
+            texel[1] /= len;
+            texel[2] /= len;
+        } else {
+            *texel = [0.0, 0.0, 1.0, texel[3]];
+        }
+    });
+}
+
+fn sinc(x: f32) -> f32 {
+    if x.abs() < 1e-5 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+// modified bessel function of the first kind, order 0
+fn bessel_i0(x: f32) -> f32 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half = x * 0.5;
+    for k in 1..32 {
+        term *= half / k as f32;
+        sum += term * term;
+        if term * term < sum * 1e-8 {
+            break;
+        }
+    }
+    sum
+}
+
+impl MipFilter {
+    // in destination texels
+    fn support(&self) -> f32 {
+        match self {
+            MipFilter::Box => 0.5,
+            MipFilter::Kaiser => 3.0,
+            MipFilter::Lanczos => 3.0,
+        }
+    }
+
+    fn weight(&self, x: f32) -> f32 {
+        match self {
+            MipFilter::Box => {
+                if x.abs() <= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            MipFilter::Kaiser => {
+                const ALPHA: f32 = 4.0;
+                let t = x / self.support();
+                if t.abs() > 1.0 {
+                    return 0.0;
+                }
+                sinc(x) * bessel_i0(ALPHA * (1.0 - t * t).sqrt()) / bessel_i0(ALPHA)
+            }
+            MipFilter::Lanczos => {
+                let support = self.support();
+                if x.abs() >= support {
+                    return 0.0;
+                }
+                sinc(x) * sinc(x / support)
+            }
+        }
+    }
+}
+
+// Separable 1D resample of one axis
+fn resample(
+    src: &[[f32; 4]],
+    width: u32,
+    height: u32,
+    new_size: u32,
+    horizontal: bool,
+    filter: MipFilter,
+    repeat: bool,
+) -> Vec<[f32; 4]> {
+    let (src_size, lines) = if horizontal {
+        (width, height)
+    } else {
+        (height, width)
+    };
+
+    if src_size == new_size {
+        return src.to_vec();
+    }
+
+    let scale = src_size as f32 / new_size as f32;
+    let support = filter.support() * scale;
+
+    // the weights are the same for every line
+    let taps = (0..new_size)
+        .map(|i| {
+            let center = (i as f32 + 0.5) * scale;
+            let start = (center - support).floor() as i64;
+            let end = (center + support).ceil() as i64;
+            let mut taps = (start..end)
+                .map(|j| {
+                    let x = (j as f32 + 0.5 - center) / scale;
+                    (wrap_index(j, src_size, repeat), filter.weight(x))
+                })
+                .filter(|(_, w)| *w != 0.0)
+                .collect::<Vec<_>>();
+
+            let total: f32 = taps.iter().map(|(_, w)| w).sum();
+            taps.iter_mut().for_each(|(_, w)| *w /= total);
+            taps
+        })
+        .collect::<Vec<_>>();
+
+    let (out_w, out_h) = if horizontal {
+        (new_size, height)
+    } else {
+        (width, new_size)
+    };
+
+    let mut out = vec![[0.0; 4]; (out_w * out_h) as usize];
+    for line in 0..lines {
+        for (i, taps) in taps.iter().enumerate() {
+            let mut texel = [0.0; 4];
+            taps.iter().for_each(|(j, w)| {
+                let index = if horizontal {
+                    line * width + j
+                } else {
+                    j * width + line
+                };
+                let src = src[index as usize];
+                texel.iter_mut().zip(src).for_each(|(t, s)| *t += s * w);
+            });
+
+            let index = if horizontal {
+                line * out_w + i as u32
+            } else {
+                i as u32 * out_w + line
+            };
+            out[index as usize] = texel;
+        }
+    }
+
+    out
+}
+
+fn wrap_index(i: i64, size: u32, repeat: bool) -> u32 {
+    if repeat {
+        i.rem_euclid(size as i64) as u32
+    } else {
+        i.clamp(0, size as i64 - 1) as u32
+    }
+}
+
+// Returns the atlas size, its RGBA8 data and the rect (in texels, without the border) of each level
+fn pack_levels(levels: &[MipLevel], options: &MipmapOptions) -> (u32, u32, Vec<u8>, Vec<Vec4>) {
+    let base = &levels[0];
+    let column_x = base.width + BORDER * 2;
+
+    // level 0 on the left, the rest stacked on a column
+    let mut rects = vec![vec4(
+        BORDER as _,
+        BORDER as _,
+        base.width as _,
+        base.height as _,
+    )];
+    let mut y = 0;
+    levels.iter().skip(1).for_each(|level| {
+        rects.push(vec4(
+            (column_x + BORDER) as _,
+            (y + BORDER) as _,
+            level.width as _,
+            level.height as _,
+        ));
+        y += level.height + BORDER * 2;
+    });
+
+    let column_width = levels.get(1).map_or(0, |level| level.width + BORDER * 2);
+    let width = column_x + column_width;
+    let height = (base.height + BORDER * 2).max(y);
+    let mut data = vec![0; (width * height * 4) as usize];
+
+    let repeat_x = options.wrap_x == TextureWrap::Repeat;
+    let repeat_y = options.wrap_y == TextureWrap::Repeat;
+    levels.iter().zip(rects.iter()).for_each(|(level, rect)| {
+        let (ox, oy) = (rect.x as u32 - BORDER, rect.y as u32 - BORDER);
+        let border = BORDER as i64;
+        for y in -border..level.height as i64 + border {
+            let sy = wrap_index(y, level.height, repeat_y);
+            for x in -border..level.width as i64 + border {
+                let sx = wrap_index(x, level.width, repeat_x);
+                let src = ((sy * level.width + sx) * 4) as usize;
+                let dx = ox + (x + border) as u32;
+                let dy = oy + (y + border) as u32;
+                let dst = ((dy * width + dx) * 4) as usize;
+                data[dst..dst + 4].copy_from_slice(&level.data[src..src + 4]);
+            }
+        }
+    });
+
+    (width, height, data, rects)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(filter: MipFilter, usage: TextureUsage) -> MipmapOptions {
+        MipmapOptions {
+            filter,
+            usage,
+            ..Default::default()
+        }
+    }
+
+    fn gray(values: &[u8]) -> Vec<u8> {
+        values.iter().flat_map(|v| [*v, *v, *v, 255]).collect()
+    }
+
+    #[test]
+    fn chain_halves_every_level() {
+        let levels = generate_mipmaps(8, 2, &gray(&[0; 16]), &Default::default());
+        let sizes = levels
+            .iter()
+            .map(|level| (level.width, level.height))
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, vec![(8, 2), (4, 1), (2, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn filters_keep_flat_images_and_differ_on_details() {
+        let flat = gray(&[90; 64]);
+        let filters = [MipFilter::Box, MipFilter::Kaiser, MipFilter::Lanczos];
+        filters.iter().for_each(|filter| {
+            let levels = generate_mipmaps(8, 8, &flat, &options(*filter, TextureUsage::Linear));
+            assert!(levels
+                .iter()
+                .all(|level| level.data == gray(&[90; 64])[..level.data.len()]));
+        });
+
+        // the box filter averages each 2x2 block
+        #[rustfmt::skip]
+        let image = gray(&[
+            0, 100, 200, 200,
+            100, 200, 200, 200,
+            40, 40, 0, 0,
+            40, 40, 0, 0,
+        ]);
+        let box_level =
+            &generate_mipmaps(4, 4, &image, &options(MipFilter::Box, TextureUsage::Linear))[1];
+        assert_eq!(box_level.data, gray(&[100, 200, 40, 0]));
+
+        // a single bright texel on a repeating texture: the box filter only spreads it to its
+        // block, the wider filters blur it to the neighbours and lanczos keeps it sharper
+        let mut dot = [0; 64];
+        dot[3 * 8 + 3] = 255;
+        let texel = |filter: MipFilter, x: usize, y: usize| {
+            let levels =
+                generate_mipmaps(8, 8, &gray(&dot), &options(filter, TextureUsage::Linear));
+            levels[1].data[(y * 4 + x) * 4]
+        };
+        assert_eq!(texel(MipFilter::Box, 1, 1), 64);
+        assert_eq!(texel(MipFilter::Box, 2, 1), 0);
+        assert!(texel(MipFilter::Kaiser, 2, 1) > 0);
+        assert!(texel(MipFilter::Lanczos, 1, 1) > texel(MipFilter::Kaiser, 1, 1));
+    }
+
+    #[test]
+    fn colors_are_averaged_in_linear_space() {
+        let checker = gray(&[0, 255, 255, 0]);
+        let level =
+            |usage| generate_mipmaps(2, 2, &checker, &options(MipFilter::Box, usage))[1].data[0];
+
+        // half the light is 0.5 in linear space, that's 188 in sRGB
+        assert_eq!(level(TextureUsage::Color), 188);
+        assert_eq!(level(TextureUsage::Linear), 128);
+    }
+
+    #[test]
+    fn normals_are_renormalized() {
+        // +X and +Z normals, the average is shorter than 1 before the renormalization
+        #[rustfmt::skip]
+        let normals = [
+            255, 128, 128, 255,   128, 128, 255, 255,
+            128, 128, 255, 255,   255, 128, 128, 255,
+        ];
+        let levels = generate_mipmaps(
+            2,
+            2,
+            &normals,
+            &options(MipFilter::Box, TextureUsage::Normal),
+        );
+        let n = decode(&levels[1].data, TextureUsage::Normal)[0];
+        let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        assert!((length - 1.0).abs() < 0.02);
+        assert_eq!(levels[1].data[0], levels[1].data[2]);
+    }
+
+    #[test]
+    fn levels_are_packed_with_wrapped_borders() {
+        #[rustfmt::skip]
+        let base = [
+            1, 2, 3, 4,
+            5, 6, 7, 8,
+            9, 10, 11, 12,
+            13, 14, 15, 16,
+        ];
+        let levels = vec![
+            MipLevel {
+                width: 4,
+                height: 4,
+                data: gray(&base),
+            },
+            MipLevel {
+                width: 2,
+                height: 2,
+                data: gray(&[20, 21, 22, 23]),
+            },
+            MipLevel {
+                width: 1,
+                height: 1,
+                data: gray(&[30]),
+            },
+        ];
+
+        let repeat = MipmapOptions::default();
+        let (width, height, data, rects) = pack_levels(&levels, &repeat);
+        assert_eq!((width, height), (10, 7));
+        assert_eq!(
+            rects,
+            vec![
+                vec4(1.0, 1.0, 4.0, 4.0),
+                vec4(7.0, 1.0, 2.0, 2.0),
+                vec4(7.0, 5.0, 1.0, 1.0)
+            ]
+        );
+
+        let texel = |data: &[u8], x: u32, y: u32| data[((y * width + x) * 4) as usize];
+        assert_eq!(texel(&data, 1, 1), 1);
+        assert_eq!(texel(&data, 8, 2), 23);
+        assert_eq!(texel(&data, 7, 5), 30);
+        // the border of the first row repeats the last texel of the row
+        assert_eq!(texel(&data, 0, 1), 4);
+        assert_eq!(texel(&data, 1, 0), 13);
+
+        let clamp = MipmapOptions {
+            wrap_x: TextureWrap::Clamp,
+            wrap_y: TextureWrap::Clamp,
+            ..Default::default()
+        };
+        let (_, _, data, _) = pack_levels(&levels, &clamp);
+        assert_eq!(texel(&data, 0, 1), 1);
+        assert_eq!(texel(&data, 1, 0), 1);
+    }
+}
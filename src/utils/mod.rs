@@ -1,3 +1,57 @@
+mod assets;
+mod astc;
+mod atlas;
+mod bptc;
 mod camera;
+mod capture;
+mod clock;
+mod compressed;
+mod cubemap;
+mod environment;
+mod hdr;
+mod image_diff;
+mod input;
+mod instancing;
+mod mipmaps;
+mod obj;
+mod outline;
+mod post_process;
+mod procedural;
+mod raster;
+mod recorder;
+mod render_queue;
+mod render_target;
+mod scene;
+mod scene_graph;
+mod skybox;
+mod stats;
+mod tweak;
 
+pub use assets::*;
+pub use astc::*;
+pub use atlas::*;
+pub use bptc::*;
 pub use camera::*;
+pub use capture::*;
+pub use clock::*;
+pub use compressed::*;
+pub use cubemap::*;
+pub use environment::*;
+pub use hdr::*;
+pub use image_diff::*;
+pub use input::*;
+pub use instancing::*;
+pub use mipmaps::*;
+pub use obj::*;
+pub use outline::*;
+pub use post_process::*;
+pub use procedural::*;
+pub use raster::*;
+pub use recorder::*;
+pub use render_queue::*;
+pub use render_target::*;
+pub use scene::*;
+pub use scene_graph::*;
+pub use skybox::*;
+pub use stats::*;
+pub use tweak::*;
@@ -0,0 +1,196 @@
+use notan::math::{Vec2, Vec3};
+
+// Floats of each vertex of `ObjMesh`: position, normal and texture coords, the same layout
+// than the cubes of the lighting examples
+pub const OBJ_VERTEX_SIZE: usize = 8;
+
+// Triangles of a wavefront obj file (like `resources/objects/rock/rock.obj`) ready to be
+// uploaded as a vertex buffer and drawn without indices. Only the geometry is read, the
+// objects, groups and materials are ignored and the textures are loaded by the examples.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ObjMesh {
+    pub vertices: Vec<f32>,
+}
+
+impl ObjMesh {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut positions: Vec<Vec3> = vec![];
+        let mut normals: Vec<Vec3> = vec![];
+        let mut tex_coords: Vec<Vec2> = vec![];
+        let mut vertices = vec![];
+
+        for (i, line) in text.lines().enumerate() {
+            let line_error = |msg: String| format!("line {}: {}", i + 1, msg);
+
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("v") => positions.push(parse_vec3(parts).map_err(line_error)?),
+                Some("vn") => normals.push(parse_vec3(parts).map_err(line_error)?),
+                Some("vt") => {
+                    let values = parse_floats(parts).map_err(line_error)?;
+                    if values.len() < 2 {
+                        return Err(line_error("'vt' needs 2 values".to_string()));
+                    }
+                    tex_coords.push(Vec2::new(values[0], values[1]));
+                }
+                Some("f") => {
+                    let corners = parts
+                        .map(|corner| parse_corner(corner, &positions, &tex_coords, &normals))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(line_error)?;
+
+                    if corners.len() < 3 {
+                        return Err(line_error("A face needs 3 vertices".to_string()));
+                    }
+
+                    // polygons are split in a fan of triangles
+                    for k in 1..corners.len() - 1 {
+                        push_triangle(&mut vertices, [corners[0], corners[k], corners[k + 1]]);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { vertices })
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.vertices.len() / OBJ_VERTEX_SIZE
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Corner {
+    position: Vec3,
+    tex_coords: Vec2,
+    normal: Option<Vec3>,
+}
+
+fn parse_floats<'a>(parts: impl Iterator<Item = &'a str>) -> Result<Vec<f32>, String> {
+    parts
+        .map(|value| {
+            value
+                .parse::<f32>()
+                .map_err(|_| format!("Invalid number '{}'", value))
+        })
+        .collect()
+}
+
+fn parse_vec3<'a>(parts: impl Iterator<Item = &'a str>) -> Result<Vec3, String> {
+    let values = parse_floats(parts)?;
+    if values.len() < 3 {
+        return Err(format!("Expected 3 values, found {}", values.len()));
+    }
+
+    Ok(Vec3::new(values[0], values[1], values[2]))
+}
+
+// 1-based, or negative to count from the last one read
+fn resolve_index<T: Copy>(value: &str, list: &[T], kind: &str) -> Result<T, String> {
+    let index = value
+        .parse::<i64>()
+        .map_err(|_| format!("Invalid {} index '{}'", kind, value))?;
+
+    let position = match index {
+        i if i > 0 => i as usize - 1,
+        i if i < 0 && i.unsigned_abs() as usize <= list.len() => {
+            list.len() - i.unsigned_abs() as usize
+        }
+        _ => usize::MAX,
+    };
+
+    list.get(position)
+        .copied()
+        .ok_or_else(|| format!("The {} index {} is out of range", kind, index))
+}
+
+// "v", "v/vt", "v//vn" or "v/vt/vn"
+fn parse_corner(
+    corner: &str,
+    positions: &[Vec3],
+    tex_coords: &[Vec2],
+    normals: &[Vec3],
+) -> Result<Corner, String> {
+    let mut indices = corner.split('/');
+    let position = resolve_index(indices.next().unwrap_or(""), positions, "position")?;
+    let tex_coords = match indices.next() {
+        Some(value) if !value.is_empty() => resolve_index(value, tex_coords, "texture")?,
+        _ => Vec2::ZERO,
+    };
+    let normal = match indices.next() {
+        Some(value) if !value.is_empty() => Some(resolve_index(value, normals, "normal")?),
+        _ => None,
+    };
+
+    Ok(Corner {
+        position,
+        tex_coords,
+        normal,
+    })
+}
+
+fn push_triangle(vertices: &mut Vec<f32>, corners: [Corner; 3]) {
+    // faces without normals use the normal of the triangle
+    let [a, b, c] = corners.map(|corner| corner.position);
+    let face_normal = (b - a).cross(c - a).normalize_or_zero();
+
+    corners.iter().for_each(|corner| {
+        let normal = corner.normal.unwrap_or(face_normal);
+        // the obj uvs start at the bottom, the images at the top
+        let uv = Vec2::new(corner.tex_coords.x, 1.0 - corner.tex_coords.y);
+        vertices.extend_from_slice(&corner.position.to_array());
+        vertices.extend_from_slice(&normal.to_array());
+        vertices.extend_from_slice(&uv.to_array());
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quads_are_split_in_triangles() {
+        let obj = "\
+# a quad with a texture and normals
+o Quad
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vt 1 1
+vn 0 0 1
+f 1/1/1 2/1/1 3/2/1 -1/2/1
+";
+        let mesh = ObjMesh::parse(obj).unwrap();
+        assert_eq!(mesh.vertex_count(), 6);
+
+        let first = &mesh.vertices[..OBJ_VERTEX_SIZE];
+        assert_eq!(first, &[0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0]);
+        // the second triangle is 1, 3, 4
+        let last = &mesh.vertices[5 * OBJ_VERTEX_SIZE..];
+        assert_eq!(last, &[0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn faces_without_normals_use_the_triangle_normal() {
+        let mesh = ObjMesh::parse("v 0 0 0\nv 0 0 1\nv 1 0 0\nf 1 2 3\n").unwrap();
+        assert_eq!(&mesh.vertices[3..6], &[0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn errors_have_the_line() {
+        let err = ObjMesh::parse("v 0 0 0\nv 1 0 0\nf 1 2 3\n").unwrap_err();
+        assert_eq!(err, "line 3: The position index 3 is out of range");
+
+        let err = ObjMesh::parse("v 0 zero 0\n").unwrap_err();
+        assert_eq!(err, "line 1: Invalid number 'zero'");
+    }
+
+    #[test]
+    fn parse_the_rock() {
+        let mesh = ObjMesh::parse(include_str!("../../resources/objects/rock/rock.obj")).unwrap();
+        assert_eq!(mesh.vertex_count(), 192 * 3);
+    }
+}
@@ -0,0 +1,186 @@
+use super::instancing::ModelInstances;
+use super::stats::StatsRenderer;
+use crate::set_buffer_data;
+use notan::math::{Mat4, Vec3, Vec4};
+use notan::prelude::*;
+
+// language=glsl
+const OUTLINE_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec3 aPos;
+
+    // model matrix of each outlined object, already scaled
+    layout (location = 3) in vec4 aModel0;
+    layout (location = 4) in vec4 aModel1;
+    layout (location = 5) in vec4 aModel2;
+    layout (location = 6) in vec4 aModel3;
+
+    layout(set = 0, binding = 0) uniform OutlineData {
+        mat4 view;
+        mat4 projection;
+        vec4 color;
+    };
+
+    void main()
+    {
+        mat4 model = mat4(aModel0, aModel1, aModel2, aModel3);
+        gl_Position = projection * view * model * vec4(aPos, 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const OUTLINE_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout (location = 0) out vec4 FragColor;
+
+    layout(set = 0, binding = 0) uniform OutlineData {
+        mat4 view;
+        mat4 projection;
+        vec4 color;
+    };
+
+    void main()
+    {
+        FragColor = color;
+    }
+  "#
+};
+
+#[derive(Copy, Clone)]
+#[uniform]
+struct OutlineData {
+    view: Mat4,
+    projection: Mat4,
+    color: Vec4,
+}
+
+// Value written to the stencil buffer by the outlined objects
+const OUTLINE_REFERENCE: u8 = 1;
+
+// Stencil of the pipeline used to draw the outlined objects, every fragment drawn writes
+// 1 in the stencil buffer. The pass must clear the stencil to 0.
+pub fn outline_stencil_write() -> StencilOptions {
+    StencilOptions {
+        stencil_fail: StencilAction::Keep,
+        depth_fail: StencilAction::Keep,
+        pass: StencilAction::Replace,
+        compare: CompareMode::Always,
+        read_mask: 0xff,
+        write_mask: 0xff,
+        reference: OUTLINE_REFERENCE,
+    }
+}
+
+// The silhouette is only drawn where the objects didn't write, around them
+fn outline_stencil_test() -> StencilOptions {
+    StencilOptions {
+        stencil_fail: StencilAction::Keep,
+        depth_fail: StencilAction::Keep,
+        pass: StencilAction::Keep,
+        compare: CompareMode::NotEqual,
+        read_mask: 0xff,
+        write_mask: 0x00,
+        reference: OUTLINE_REFERENCE,
+    }
+}
+
+// The models scaled up in their local space, `thickness` is the fraction of the size of the
+// object added to it (0.1 is a 10% bigger silhouette)
+pub fn outline_models(models: &[Mat4], thickness: f32) -> Vec<Mat4> {
+    let scale = Mat4::from_scale(Vec3::splat(1.0 + thickness));
+    models.iter().map(|model| *model * scale).collect()
+}
+
+// Object outlining of the stencil testing chapter. The outlined objects are drawn with a
+// pipeline using `outline_stencil_write`, and after them `draw` renders their silhouettes
+// a bit bigger with a single color, only where the stencil buffer is not 1. The silhouettes
+// ignore the depth so the outline is visible through other objects.
+pub struct Outline {
+    pub color: Color,
+    pub thickness: f32,
+    pipeline: Pipeline,
+    ubo: Buffer,
+    instances: ModelInstances,
+}
+
+impl Outline {
+    // `vertex_info` is the layout of the mesh of the objects, with the positions at the
+    // location 0. The locations 3 to 6 are used by the model matrices.
+    pub fn new(gfx: &mut Graphics, vertex_info: &VertexInfo) -> Result<Self, String> {
+        let pipeline = gfx
+            .create_pipeline()
+            .from(&OUTLINE_VERTEX_SHADER, &OUTLINE_FRAGMENT_SHADER)
+            .with_vertex_info(vertex_info)
+            .with_vertex_info(&ModelInstances::vertex_info(3))
+            .with_depth_stencil(DepthStencil {
+                write: false,
+                compare: CompareMode::Always,
+            })
+            .with_stencil(outline_stencil_test())
+            .build()?;
+
+        let ubo = gfx.create_uniform_buffer(0, "OutlineData").build()?;
+        let instances = ModelInstances::new(gfx, 3, &[])?;
+
+        Ok(Self {
+            color: Color::from_rgb(0.04, 0.28, 0.26),
+            thickness: 0.1,
+            pipeline,
+            ubo,
+            instances,
+        })
+    }
+
+    // Uploads the objects to outline, call it before the pass
+    pub fn update(&mut self, gfx: &mut Graphics, models: &[Mat4], view: Mat4, projection: Mat4) {
+        let color = self.color;
+        set_buffer_data!(
+            gfx,
+            &self.ubo,
+            &OutlineData {
+                view,
+                projection,
+                color: Vec4::new(color.r, color.g, color.b, color.a),
+            }
+        );
+
+        self.instances
+            .update(gfx, &outline_models(models, self.thickness));
+    }
+
+    // Draws the silhouettes of the mesh, after the outlined objects in the same pass
+    pub fn draw(&self, renderer: &mut StatsRenderer, vbo: &Buffer, vertex_count: i32) {
+        if self.instances.is_empty() {
+            return;
+        }
+
+        renderer.set_pipeline(&self.pipeline);
+        renderer.bind_buffers(&[vbo, self.instances.buffer(), &self.ubo]);
+        renderer.draw_instanced(0, vertex_count, self.instances.len() as i32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notan::math::vec3;
+
+    #[test]
+    fn outline_is_scaled_around_the_object() {
+        let model = Mat4::from_translation(vec3(2.0, 0.0, -1.0)) * Mat4::from_rotation_y(0.7);
+        let outline = outline_models(&[model], 0.1)[0];
+
+        // the center stays in place
+        let center = outline.transform_point3(Vec3::ZERO);
+        assert!(center.abs_diff_eq(vec3(2.0, 0.0, -1.0), 1e-5));
+
+        // the corners move away from it
+        let corner = Vec3::splat(0.5);
+        let distance = (outline.transform_point3(corner) - center).length();
+        let original = (model.transform_point3(corner) - center).length();
+        assert!((distance - original * 1.1).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,419 @@
+use super::render_target::{RenderTarget, RenderTargetOptions};
+use super::stats::StatsRenderer;
+use crate::set_buffer_data;
+use notan::math::Vec3;
+use notan::prelude::*;
+
+// language=glsl
+const POST_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 aPos;
+    layout (location = 1) in vec2 aTexCoords;
+
+    layout (location = 0) out vec2 TexCoords;
+
+    void main()
+    {
+        TexCoords = aTexCoords;
+        gl_Position = vec4(aPos.x, aPos.y, 0.0, 1.0);
+    }
+  "#
+};
+
+// The fragment shaders of the passes read the input from `screenTexture`, and their
+// uniform block `PostData` starts with the strength followed by the `params` of the pass
+
+// language=glsl
+const INVERSION_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 TexCoords;
+
+    layout (location = 0) out vec4 FragColor;
+
+    layout(binding = 0) uniform sampler2D screenTexture;
+
+    layout(set = 0, binding = 0) uniform PostData {
+        float strength;
+    };
+
+    void main()
+    {
+        vec3 source = texture(screenTexture, TexCoords).rgb;
+        FragColor = vec4(mix(source, 1.0 - source, strength), 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const GRAYSCALE_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 TexCoords;
+
+    layout (location = 0) out vec4 FragColor;
+
+    layout(binding = 0) uniform sampler2D screenTexture;
+
+    layout(set = 0, binding = 0) uniform PostData {
+        float strength;
+    };
+
+    void main()
+    {
+        vec3 source = texture(screenTexture, TexCoords).rgb;
+        // weighted by the sensitivity of the eye to each channel
+        float average = 0.2126 * source.r + 0.7152 * source.g + 0.0722 * source.b;
+        FragColor = vec4(mix(source, vec3(average), strength), 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const KERNEL_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 TexCoords;
+
+    layout (location = 0) out vec4 FragColor;
+
+    layout(binding = 0) uniform sampler2D screenTexture;
+
+    layout(set = 0, binding = 0) uniform PostData {
+        float strength;
+        // rows of the 3x3 kernel, top to bottom
+        vec3 row0;
+        vec3 row1;
+        vec3 row2;
+    };
+
+    void main()
+    {
+        vec2 texel = 1.0 / vec2(textureSize(screenTexture, 0));
+        vec3 rows[3] = vec3[](row0, row1, row2);
+        vec3 col = vec3(0.0);
+        for (int y = 0; y < 3; y++) {
+            for (int x = 0; x < 3; x++) {
+                vec2 offset = vec2(float(x - 1), float(1 - y)) * texel;
+                col += texture(screenTexture, TexCoords + offset).rgb * rows[y][x];
+            }
+        }
+
+        vec3 source = texture(screenTexture, TexCoords).rgb;
+        FragColor = vec4(mix(source, col, strength), 1.0);
+    }
+  "#
+};
+
+// Kernels of the framebuffers chapter, row by row from the top
+#[rustfmt::skip]
+pub const SHARPEN_KERNEL: [f32; 9] = [
+    -1.0, -1.0, -1.0,
+    -1.0,  9.0, -1.0,
+    -1.0, -1.0, -1.0,
+];
+
+#[rustfmt::skip]
+pub const BLUR_KERNEL: [f32; 9] = [
+    1.0 / 16.0, 2.0 / 16.0, 1.0 / 16.0,
+    2.0 / 16.0, 4.0 / 16.0, 2.0 / 16.0,
+    1.0 / 16.0, 2.0 / 16.0, 1.0 / 16.0,
+];
+
+#[rustfmt::skip]
+pub const EDGE_DETECTION_KERNEL: [f32; 9] = [
+    1.0,  1.0, 1.0,
+    1.0, -8.0, 1.0,
+    1.0,  1.0, 1.0,
+];
+
+#[rustfmt::skip]
+pub const IDENTITY_KERNEL: [f32; 9] = [
+    0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0,
+    0.0, 0.0, 0.0,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PostEffect {
+    Inversion,
+    Grayscale,
+    Sharpen,
+    Blur,
+    EdgeDetection,
+    Kernel([f32; 9]),
+}
+
+impl PostEffect {
+    // the effects with a name, used by `parse`
+    pub const NAMED: [PostEffect; 5] = [
+        PostEffect::Inversion,
+        PostEffect::Grayscale,
+        PostEffect::Sharpen,
+        PostEffect::Blur,
+        PostEffect::EdgeDetection,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PostEffect::Inversion => "inversion",
+            PostEffect::Grayscale => "grayscale",
+            PostEffect::Sharpen => "sharpen",
+            PostEffect::Blur => "blur",
+            PostEffect::EdgeDetection => "edge",
+            PostEffect::Kernel(_) => "kernel",
+        }
+    }
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        Self::NAMED
+            .iter()
+            .find(|effect| effect.name() == name)
+            .copied()
+            .ok_or_else(|| {
+                let names = Self::NAMED.map(|effect| effect.name());
+                format!(
+                    "Unknown effect '{}', expected one of: {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+
+    // The convolution kernel, the inversion and the grayscale don't use it
+    pub fn kernel(&self) -> Option<[f32; 9]> {
+        match self {
+            PostEffect::Inversion | PostEffect::Grayscale => None,
+            PostEffect::Sharpen => Some(SHARPEN_KERNEL),
+            PostEffect::Blur => Some(BLUR_KERNEL),
+            PostEffect::EdgeDetection => Some(EDGE_DETECTION_KERNEL),
+            PostEffect::Kernel(kernel) => Some(*kernel),
+        }
+    }
+
+    fn fragment_shader(&self) -> &'static ShaderSource {
+        match self {
+            PostEffect::Inversion => &INVERSION_FRAGMENT_SHADER,
+            PostEffect::Grayscale => &GRAYSCALE_FRAGMENT_SHADER,
+            _ => &KERNEL_FRAGMENT_SHADER,
+        }
+    }
+}
+
+// "a,b,c,d,e,f,g,h,i" row by row from the top
+pub fn parse_kernel(text: &str) -> Result<[f32; 9], String> {
+    let values = text
+        .split(',')
+        .map(|value| {
+            value
+                .trim()
+                .parse::<f32>()
+                .map_err(|_| format!("Invalid kernel value '{}'", value))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    values
+        .try_into()
+        .map_err(|values: Vec<f32>| format!("A kernel needs 9 values, found {}", values.len()))
+}
+
+// Layout of the quad drawn by the passes
+fn quad_vertex_info() -> VertexInfo {
+    VertexInfo::new()
+        .attr(0, VertexFormat::Float32x2) // positions
+        .attr(1, VertexFormat::Float32x2) // texture coords
+}
+
+// Content of the uniform block `PostData`, with std140 every vec3 starts at a multiple of
+// 16 bytes
+fn uniform_data(strength: f32, params: &[Vec3]) -> Vec<f32> {
+    let mut data = vec![strength, 0.0, 0.0, 0.0];
+    params.iter().for_each(|param| {
+        data.extend_from_slice(&param.to_array());
+        data.push(0.0);
+    });
+    data
+}
+
+// A fullscreen pass with its own pipeline, built from `POST_VERTEX_SHADER` and a fragment
+// shader reading `screenTexture`. The uniform block of the shader has the strength and
+// then one vec3 per value of `params`, the effects of `PostEffect` are presets.
+pub struct PostPass {
+    pub name: String,
+    pub enabled: bool,
+    // mix between the input (0.0) and the effect (1.0)
+    pub strength: f32,
+    pub params: Vec<Vec3>,
+    pipeline: Pipeline,
+    ubo: Buffer,
+}
+
+impl PostPass {
+    pub fn new(
+        gfx: &mut Graphics,
+        name: &str,
+        fragment: &ShaderSource,
+        params: Vec<Vec3>,
+    ) -> Result<Self, String> {
+        let pipeline = gfx
+            .create_pipeline()
+            .from(&POST_VERTEX_SHADER, fragment)
+            .with_vertex_info(&quad_vertex_info())
+            .with_texture_location(0, "screenTexture")
+            .build()?;
+
+        let ubo = gfx.create_uniform_buffer(0, "PostData").build()?;
+
+        Ok(Self {
+            name: name.to_string(),
+            enabled: true,
+            strength: 1.0,
+            params,
+            pipeline,
+            ubo,
+        })
+    }
+
+    // The pass of a built-in effect, the kernels are its params
+    pub fn preset(gfx: &mut Graphics, effect: PostEffect) -> Result<Self, String> {
+        let params = effect.kernel().map_or_else(Vec::new, |kernel| {
+            kernel.chunks(3).map(Vec3::from_slice).collect()
+        });
+        Self::new(gfx, effect.name(), effect.fragment_shader(), params)
+    }
+
+    pub fn disabled(self) -> Self {
+        Self {
+            enabled: false,
+            ..self
+        }
+    }
+}
+
+// Ordered list of fullscreen passes applied to a texture, like the scene rendered to a
+// `RenderTarget`. Every pass reads the output of the previous one, they are rendered
+// alternating between two targets (ping-pong) so any number of them needs only two.
+pub struct PostProcess {
+    pub passes: Vec<PostPass>,
+    vbo: Buffer,
+    targets: [RenderTarget; 2],
+}
+
+impl PostProcess {
+    pub fn new(gfx: &mut Graphics, passes: Vec<PostPass>) -> Result<Self, String> {
+        #[rustfmt::skip]
+        let vertices = [
+            // positions   // texCoords
+            -1.0,  1.0,  0.0, 1.0,
+            -1.0, -1.0,  0.0, 0.0,
+            1.0, -1.0,  1.0, 0.0,
+
+            -1.0,  1.0,  0.0, 1.0,
+            1.0, -1.0,  1.0, 0.0,
+            1.0,  1.0,  1.0, 1.0,
+        ];
+
+        let vbo = gfx
+            .create_vertex_buffer()
+            .with_data(&vertices)
+            .with_info(&quad_vertex_info())
+            .build()?;
+
+        // the passes only draw a quad, they don't need depth
+        let options = RenderTargetOptions::default().with_depth(false);
+        let targets = [
+            RenderTarget::new(gfx, 1, 1, options.clone())?,
+            RenderTarget::new(gfx, 1, 1, options)?,
+        ];
+
+        Ok(Self {
+            passes,
+            vbo,
+            targets,
+        })
+    }
+
+    pub fn push(&mut self, pass: PostPass) {
+        self.passes.push(pass);
+    }
+
+    // Runs the enabled passes in order and returns the result, the input itself if all of
+    // them are disabled
+    pub fn apply<'a>(&'a mut self, gfx: &mut Graphics, input: &'a Texture) -> &'a Texture {
+        let (width, height) = (input.width() as u32, input.height() as u32);
+        for target in self.targets.iter_mut() {
+            if let Err(err) = target.resize(gfx, width, height) {
+                notan::log::error!("Cannot resize the post-process target: {}", err);
+                return input;
+            }
+        }
+
+        let mut output = None;
+        let enabled = self.passes.iter().filter(|pass| pass.enabled);
+        for (i, pass) in enabled.enumerate() {
+            let source = output.map_or(input, |index: usize| self.targets[index].texture(0));
+            let target = i % 2;
+
+            let data = uniform_data(pass.strength, &pass.params);
+            set_buffer_data!(gfx, &pass.ubo, data.as_slice());
+
+            let mut renderer = StatsRenderer::new(gfx);
+            renderer.begin(None);
+            renderer.set_pipeline(&pass.pipeline);
+            renderer.bind_buffers(&[&self.vbo, &pass.ubo]);
+            renderer.bind_texture(0, source);
+            renderer.draw(0, 6);
+            renderer.end();
+
+            self.targets[target].render(gfx, 0, &renderer);
+            output = Some(target);
+        }
+
+        output.map_or(input, |index| self.targets[index].texture(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kernels_keep_or_remove_the_brightness() {
+        let sum = |kernel: [f32; 9]| kernel.iter().sum::<f32>();
+        assert_eq!(sum(SHARPEN_KERNEL), 1.0);
+        assert_eq!(sum(BLUR_KERNEL), 1.0);
+        assert_eq!(sum(IDENTITY_KERNEL), 1.0);
+        // flat areas become black, only the edges stay
+        assert_eq!(sum(EDGE_DETECTION_KERNEL), 0.0);
+    }
+
+    #[test]
+    fn parse_effects_and_kernels() {
+        assert_eq!(PostEffect::parse(" blur"), Ok(PostEffect::Blur));
+        assert_eq!(PostEffect::parse("edge"), Ok(PostEffect::EdgeDetection));
+        assert!(PostEffect::parse("sepia")
+            .unwrap_err()
+            .contains("inversion, grayscale, sharpen, blur, edge"));
+
+        let kernel = parse_kernel("0,-1,0, -1,5,-1, 0,-1,0").unwrap();
+        assert_eq!(kernel[4], 5.0);
+        assert_eq!(
+            parse_kernel("1,2,3").unwrap_err(),
+            "A kernel needs 9 values, found 3"
+        );
+        assert_eq!(PostEffect::Kernel(kernel).kernel(), Some(kernel));
+        assert_eq!(PostEffect::Grayscale.kernel(), None);
+    }
+
+    #[test]
+    fn params_follow_the_std140_layout() {
+        assert_eq!(uniform_data(0.5, &[]), [0.5, 0.0, 0.0, 0.0]);
+        let rows = [Vec3::new(1.0, 2.0, 3.0), Vec3::new(4.0, 5.0, 6.0)];
+        assert_eq!(
+            uniform_data(1.0, &rows),
+            [1.0, 0.0, 0.0, 0.0, 1.0, 2.0, 3.0, 0.0, 4.0, 5.0, 6.0, 0.0]
+        );
+    }
+}
@@ -0,0 +1,553 @@
+use notan::prelude::*;
+
+// Generated RGBA8 images, for the examples that only need a test pattern.
+// They are uploaded like any other texture, see `create_generated_texture`
+// or `AssetManager::add_texture`.
+
+#[derive(Clone, Debug)]
+pub struct GeneratedImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl GeneratedImage {
+    // Calls `pixel` with the texel center in normalized coords
+    pub fn from_fn<F>(width: u32, height: u32, pixel: F) -> Self
+    where
+        F: Fn(f32, f32) -> [u8; 4],
+    {
+        let mut data = Vec::with_capacity((width * height * 4) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                data.extend_from_slice(&pixel(u, v));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+pub fn create_generated_texture(
+    gfx: &mut Graphics,
+    image: &GeneratedImage,
+) -> Result<Texture, String> {
+    gfx.create_texture()
+        .from_bytes(&image.data, image.width, image.height)
+        .with_wrap(TextureWrap::Repeat, TextureWrap::Repeat)
+        .build()
+}
+
+fn to_rgba8(color: Color) -> [u8; 4] {
+    [color.r, color.g, color.b, color.a].map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    Color::new(
+        a.r + (b.r - a.r) * t,
+        a.g + (b.g - a.g) * t,
+        a.b + (b.b - a.b) * t,
+        a.a + (b.a - a.a) * t,
+    )
+}
+
+// `cells` squares on each axis
+pub fn checkerboard(width: u32, height: u32, cells: u32, a: Color, b: Color) -> GeneratedImage {
+    let (a, b) = (to_rgba8(a), to_rgba8(b));
+    let cells = cells.max(1) as f32;
+    GeneratedImage::from_fn(width, height, |u, v| {
+        let cell = (u * cells).floor() as u32 + (v * cells).floor() as u32;
+        if cell & 1 == 0 {
+            a
+        } else {
+            b
+        }
+    })
+}
+
+// Red grows with u and green with v, with grid lines and a darker checker to
+// spot stretching, flipped axes or wrong wrapping
+pub fn uv_grid(width: u32, height: u32, cells: u32) -> GeneratedImage {
+    let cells = cells.max(1) as f32;
+    let line = 1.5 / width.min(height) as f32 * cells;
+    GeneratedImage::from_fn(width, height, |u, v| {
+        let (cu, cv) = (u * cells, v * cells);
+        let on_line = cu.fract() < line || cv.fract() < line;
+        if on_line {
+            return [255, 255, 255, 255];
+        }
+
+        let shade = if (cu.floor() + cv.floor()) as u32 & 1 == 0 {
+            1.0
+        } else {
+            0.7
+        };
+        to_rgba8(Color::new(u * shade, v * shade, 0.25 * shade, 1.0))
+    })
+}
+
+// `angle` in degrees, 0 goes from left to right
+pub fn linear_gradient(
+    width: u32,
+    height: u32,
+    from: Color,
+    to: Color,
+    angle: f32,
+) -> GeneratedImage {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    // project the corners to normalize the gradient to the whole image
+    let project = |u: f32, v: f32| (u - 0.5) * cos + (v - 0.5) * sin;
+    let extent = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)]
+        .iter()
+        .map(|(u, v)| project(*u, *v))
+        .fold(0.0f32, f32::max);
+
+    GeneratedImage::from_fn(width, height, |u, v| {
+        let t = project(u, v) / extent * 0.5 + 0.5;
+        to_rgba8(lerp_color(from, to, t))
+    })
+}
+
+// `inner` at the center to `outer` at the edges
+pub fn radial_gradient(width: u32, height: u32, inner: Color, outer: Color) -> GeneratedImage {
+    GeneratedImage::from_fn(width, height, |u, v| {
+        let (dx, dy) = (u - 0.5, v - 0.5);
+        let t = (dx * dx + dy * dy).sqrt() * 2.0;
+        to_rgba8(lerp_color(inner, outer, t))
+    })
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NoiseKind {
+    Value,
+    Perlin,
+    Simplex,
+    // distance to the closest feature point
+    Worley,
+}
+
+// Fractal noise, with one octave it's the plain noise
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Noise {
+    pub kind: NoiseKind,
+    pub seed: u32,
+    // lattice cells across the image for the first octave
+    pub frequency: f32,
+    pub octaves: u32,
+    pub lacunarity: f32,
+    pub gain: f32,
+    // wraps the lattice so the image repeats without seams, the frequency and
+    // lacunarity are rounded to whole numbers (simplex noise can't tile)
+    pub tileable: bool,
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Self {
+            kind: NoiseKind::Perlin,
+            seed: 0,
+            frequency: 4.0,
+            octaves: 1,
+            lacunarity: 2.0,
+            gain: 0.5,
+            tileable: true,
+        }
+    }
+}
+
+impl Noise {
+    pub fn new(kind: NoiseKind) -> Self {
+        Self {
+            kind,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn with_frequency(mut self, frequency: f32) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    pub fn with_fbm(mut self, octaves: u32, lacunarity: f32, gain: f32) -> Self {
+        self.octaves = octaves;
+        self.lacunarity = lacunarity;
+        self.gain = gain;
+        self
+    }
+
+    pub fn with_tileable(mut self, tileable: bool) -> Self {
+        self.tileable = tileable;
+        self
+    }
+
+    // Value in the range [0, 1] for the normalized coords u, v
+    pub fn sample(&self, u: f32, v: f32) -> f32 {
+        let mut frequency = self.frequency;
+        let mut amplitude = 1.0;
+        let mut total = 0.0;
+        let mut max = 0.0;
+
+        for octave in 0..self.octaves.max(1) {
+            let (octave_frequency, period) = if self.tileable {
+                let f = frequency.round().max(1.0);
+                (f, Some(f as i32))
+            } else {
+                (frequency, None)
+            };
+
+            // every octave uses a different seed to avoid the same features at the origin
+            let seed = self.seed.wrapping_add(octave.wrapping_mul(0x9E37_79B9));
+            let (x, y) = (u * octave_frequency, v * octave_frequency);
+            let value = match self.kind {
+                NoiseKind::Value => value_noise(x, y, seed, period),
+                NoiseKind::Perlin => perlin_noise(x, y, seed, period),
+                NoiseKind::Simplex => simplex_noise(x, y, seed),
+                NoiseKind::Worley => worley_noise(x, y, seed, period),
+            };
+
+            total += value * amplitude;
+            max += amplitude;
+            amplitude *= self.gain;
+            frequency *= if self.tileable {
+                self.lacunarity.round()
+            } else {
+                self.lacunarity
+            };
+        }
+
+        total / max
+    }
+
+    pub fn height_field(&self, width: u32, height: u32) -> HeightField {
+        HeightField::from_fn(width, height, |u, v| self.sample(u, v))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HeightField {
+    pub width: u32,
+    pub height: u32,
+    // values in the range [0, 1]
+    pub data: Vec<f32>,
+}
+
+impl HeightField {
+    pub fn from_fn<F>(width: u32, height: u32, height_at: F) -> Self
+    where
+        F: Fn(f32, f32) -> f32,
+    {
+        let mut data = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / width as f32;
+                let v = (y as f32 + 0.5) / height as f32;
+                data.push(height_at(u, v));
+            }
+        }
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    // From the red channel of a generated image, like the grayscale one of `to_image`
+    pub fn from_image(image: &GeneratedImage) -> Self {
+        Self {
+            width: image.width,
+            height: image.height,
+            data: image
+                .data
+                .chunks_exact(4)
+                .map(|px| px[0] as f32 / 255.0)
+                .collect(),
+        }
+    }
+
+    fn get(&self, x: i64, y: i64, wrap: bool) -> f32 {
+        let (w, h) = (self.width as i64, self.height as i64);
+        let (x, y) = if wrap {
+            (x.rem_euclid(w), y.rem_euclid(h))
+        } else {
+            (x.clamp(0, w - 1), y.clamp(0, h - 1))
+        };
+        self.data[(y * w + x) as usize]
+    }
+
+    pub fn to_image(&self) -> GeneratedImage {
+        GeneratedImage {
+            width: self.width,
+            height: self.height,
+            data: self
+                .data
+                .iter()
+                .flat_map(|v| {
+                    let v = (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+                    [v, v, v, 255]
+                })
+                .collect(),
+        }
+    }
+
+    // Maps the values to a gradient, like `color_a` for the valleys and `color_b` for the peaks
+    pub fn to_colored_image(&self, a: Color, b: Color) -> GeneratedImage {
+        GeneratedImage {
+            width: self.width,
+            height: self.height,
+            data: self
+                .data
+                .iter()
+                .flat_map(|v| to_rgba8(lerp_color(a, b, *v)))
+                .collect(),
+        }
+    }
+
+    // Tangent space normal map (OpenGL convention, green up) using a sobel filter.
+    // `strength` scales the slopes, `wrap` samples the opposite edge on the borders.
+    pub fn to_normal_map(&self, strength: f32, wrap: bool) -> GeneratedImage {
+        let mut data = Vec::with_capacity((self.width * self.height * 4) as usize);
+        for y in 0..self.height as i64 {
+            for x in 0..self.width as i64 {
+                let h = |dx: i64, dy: i64| self.get(x + dx, y + dy, wrap);
+                let sx =
+                    (h(1, -1) + 2.0 * h(1, 0) + h(1, 1)) - (h(-1, -1) + 2.0 * h(-1, 0) + h(-1, 1));
+                // the rows go down in the image, v goes up
+                let sy =
+                    (h(-1, -1) + 2.0 * h(0, -1) + h(1, -1)) - (h(-1, 1) + 2.0 * h(0, 1) + h(1, 1));
+
+                let n = [-sx * strength, -sy * strength, 1.0];
+                let len = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+                let encode = |v: f32| ((v / len * 0.5 + 0.5) * 255.0).round() as u8;
+                data.extend_from_slice(&[encode(n[0]), encode(n[1]), encode(n[2]), 255]);
+            }
+        }
+
+        GeneratedImage {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+}
+
+// integer hash (lowbias32), good enough to look random and cheap
+fn hash(x: i32, y: i32, seed: u32) -> u32 {
+    let mut h = (x as u32)
+        .wrapping_mul(0x8da6_b343)
+        .wrapping_add((y as u32).wrapping_mul(0xd816_3841))
+        .wrapping_add(seed.wrapping_mul(0xcb1a_b31f));
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^= h >> 16;
+    h
+}
+
+pub(super) fn hash_unit(x: i32, y: i32, seed: u32) -> f32 {
+    hash(x, y, seed) as f32 / u32::MAX as f32
+}
+
+fn wrap_cell(i: i32, period: Option<i32>) -> i32 {
+    period.map_or(i, |p| i.rem_euclid(p))
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn value_noise(x: f32, y: f32, seed: u32, period: Option<i32>) -> f32 {
+    let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+    let (tx, ty) = (fade(x - x0 as f32), fade(y - y0 as f32));
+    let corner =
+        |dx: i32, dy: i32| hash_unit(wrap_cell(x0 + dx, period), wrap_cell(y0 + dy, period), seed);
+
+    lerp(
+        lerp(corner(0, 0), corner(1, 0), tx),
+        lerp(corner(0, 1), corner(1, 1), tx),
+        ty,
+    )
+}
+
+// one of 8 unit directions
+fn gradient(x: i32, y: i32, seed: u32) -> (f32, f32) {
+    const DIAGONAL: f32 = std::f32::consts::FRAC_1_SQRT_2;
+    match hash(x, y, seed) & 7 {
+        0 => (1.0, 0.0),
+        1 => (-1.0, 0.0),
+        2 => (0.0, 1.0),
+        3 => (0.0, -1.0),
+        4 => (DIAGONAL, DIAGONAL),
+        5 => (-DIAGONAL, DIAGONAL),
+        6 => (DIAGONAL, -DIAGONAL),
+        _ => (-DIAGONAL, -DIAGONAL),
+    }
+}
+
+fn perlin_noise(x: f32, y: f32, seed: u32, period: Option<i32>) -> f32 {
+    let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+    let corner = |dx: i32, dy: i32| {
+        let (gx, gy) = gradient(wrap_cell(x0 + dx, period), wrap_cell(y0 + dy, period), seed);
+        gx * (fx - dx as f32) + gy * (fy - dy as f32)
+    };
+
+    let (tx, ty) = (fade(fx), fade(fy));
+    let value = lerp(
+        lerp(corner(0, 0), corner(1, 0), tx),
+        lerp(corner(0, 1), corner(1, 1), tx),
+        ty,
+    );
+
+    // the range of 2D perlin noise is [-sqrt(0.5), sqrt(0.5)]
+    (value * std::f32::consts::SQRT_2 * 0.5 + 0.5).clamp(0.0, 1.0)
+}
+
+fn simplex_noise(x: f32, y: f32, seed: u32) -> f32 {
+    // skew factors for 2D
+    let f2 = 0.5 * (3.0f32.sqrt() - 1.0);
+    let g2 = (3.0 - 3.0f32.sqrt()) / 6.0;
+
+    let s = (x + y) * f2;
+    let (i, j) = ((x + s).floor() as i32, (y + s).floor() as i32);
+    let t = (i + j) as f32 * g2;
+    let (x0, y0) = (x - (i as f32 - t), y - (j as f32 - t));
+
+    // the second corner depends on the triangle we are in
+    let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+    let corners = [
+        (0, 0, x0, y0),
+        (i1, j1, x0 - i1 as f32 + g2, y0 - j1 as f32 + g2),
+        (1, 1, x0 - 1.0 + 2.0 * g2, y0 - 1.0 + 2.0 * g2),
+    ];
+
+    let value: f32 = corners
+        .iter()
+        .map(|(di, dj, cx, cy)| {
+            let falloff = 0.5 - cx * cx - cy * cy;
+            if falloff <= 0.0 {
+                return 0.0;
+            }
+            let (gx, gy) = gradient(i + di, j + dj, seed);
+            falloff.powi(4) * (gx * cx + gy * cy)
+        })
+        .sum();
+
+    // scaled to roughly [-1, 1]
+    (value * 70.0 * 0.5 + 0.5).clamp(0.0, 1.0)
+}
+
+fn worley_noise(x: f32, y: f32, seed: u32, period: Option<i32>) -> f32 {
+    let (x0, y0) = (x.floor() as i32, y.floor() as i32);
+    let mut closest = f32::MAX;
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            let (cx, cy) = (x0 + dx, y0 + dy);
+            let (wx, wy) = (wrap_cell(cx, period), wrap_cell(cy, period));
+            // one feature point per cell
+            let px = cx as f32 + hash_unit(wx, wy, seed);
+            let py = cy as f32 + hash_unit(wx, wy, seed ^ 0x5bd1_e995);
+            let d = ((px - x) * (px - x) + (py - y) * (py - y)).sqrt();
+            closest = closest.min(d);
+        }
+    }
+
+    closest.min(1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KINDS: [NoiseKind; 4] = [
+        NoiseKind::Value,
+        NoiseKind::Perlin,
+        NoiseKind::Simplex,
+        NoiseKind::Worley,
+    ];
+
+    #[test]
+    fn noise_is_deterministic() {
+        for kind in KINDS {
+            let noise = Noise::new(kind).with_seed(42).with_fbm(3, 2.0, 0.5);
+            let a = noise.height_field(16, 16);
+            let b = noise.height_field(16, 16);
+            assert_eq!(a.data, b.data, "{:?}", kind);
+            assert!(a.data.iter().all(|v| (0.0..=1.0).contains(v)), "{:?}", kind);
+
+            let other = noise.with_seed(43).height_field(16, 16);
+            assert_ne!(a.data, other.data, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn tileable_noise_has_no_seams() {
+        for kind in [NoiseKind::Value, NoiseKind::Perlin, NoiseKind::Worley] {
+            // the frequency and lacunarity are rounded to whole numbers
+            let noise = Noise::new(kind)
+                .with_seed(7)
+                .with_frequency(3.4)
+                .with_fbm(3, 2.2, 0.5);
+            for i in 0..=16 {
+                let t = i as f32 / 16.0;
+                let (left, right) = (noise.sample(0.0, t), noise.sample(1.0, t));
+                assert!((left - right).abs() < 1e-4, "{:?} at v {}", kind, t);
+                let (top, bottom) = (noise.sample(t, 0.0), noise.sample(t, 1.0));
+                assert!((top - bottom).abs() < 1e-4, "{:?} at u {}", kind, t);
+            }
+
+            // without tiling the lattice doesn't repeat
+            let noise = noise.with_tileable(false);
+            let seam = (0..=16)
+                .map(|i| {
+                    (noise.sample(0.0, i as f32 / 16.0) - noise.sample(1.0, i as f32 / 16.0)).abs()
+                })
+                .fold(0.0, f32::max);
+            assert!(seam > 1e-3, "{:?}", kind);
+        }
+    }
+
+    #[test]
+    fn flat_height_fields_point_up() {
+        let flat = HeightField::from_fn(8, 8, |_, _| 0.5);
+        for wrap in [false, true] {
+            let normals = flat.to_normal_map(4.0, wrap);
+            assert!(normals
+                .data
+                .chunks_exact(4)
+                .all(|px| px == [128, 128, 255, 255]));
+        }
+
+        // a slope going up to the right tilts the normals to the left, and the
+        // height field read back from the grayscale image is the same
+        let slope = HeightField::from_fn(8, 8, |u, _| u);
+        let normals = slope.to_normal_map(1.0, false);
+        let center = &normals.data[(4 * 8 + 4) * 4..][..4];
+        assert!(center[0] < 128);
+        assert_eq!(center[1], 128);
+
+        let read = HeightField::from_image(&slope.to_image());
+        assert!(read
+            .data
+            .iter()
+            .zip(&slope.data)
+            .all(|(a, b)| (a - b).abs() <= 0.5 / 255.0));
+    }
+}
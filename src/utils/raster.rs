@@ -0,0 +1,1008 @@
+use image::RgbaImage;
+use notan::math::{Mat3, Mat4, Vec2, Vec3, Vec4, Vec4Swizzles};
+
+// Small software rasterizer, it runs the same transforms and lighting than the shaders
+// on the CPU so the scenes can be rendered without a GPU (CI, containers...).
+// It follows the OpenGL conventions: counter-clockwise front faces, clip space depth
+// from -w to w, and the first row of the framebuffer is the top of the screen.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CullMode {
+    None,
+    Back,
+    Front,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RasterState {
+    pub cull: CullMode,
+    // depth test with `CompareMode::Less`
+    pub depth_test: bool,
+    pub depth_write: bool,
+}
+
+impl Default for RasterState {
+    fn default() -> Self {
+        Self {
+            cull: CullMode::None,
+            depth_test: true,
+            depth_write: true,
+        }
+    }
+}
+
+// Output of the vertex "shader", the varyings are interpolated with perspective correction
+#[derive(Clone, Copy, Debug)]
+pub struct ClipVertex<const N: usize> {
+    pub position: Vec4,
+    pub varyings: [f32; N],
+}
+
+#[derive(Clone, Debug)]
+pub struct Framebuffer {
+    pub width: u32,
+    pub height: u32,
+    // colors, top to bottom
+    pub color: Vec<Vec4>,
+    pub depth: Vec<f32>,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let len = (width * height) as usize;
+        Self {
+            width,
+            height,
+            color: vec![Vec4::new(0.0, 0.0, 0.0, 1.0); len],
+            depth: vec![1.0; len],
+        }
+    }
+
+    pub fn clear(&mut self, color: Vec4, depth: f32) {
+        self.color.iter_mut().for_each(|c| *c = color);
+        self.depth.iter_mut().for_each(|d| *d = depth);
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> Vec4 {
+        self.color[(y * self.width + x) as usize]
+    }
+
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.color
+            .iter()
+            .flat_map(|c| {
+                c.to_array()
+                    .map(|v| (v.clamp(0.0, 1.0) * 255.0).round() as u8)
+            })
+            .collect()
+    }
+
+    pub fn to_image(&self) -> RgbaImage {
+        RgbaImage::from_raw(self.width, self.height, self.to_rgba8()).unwrap()
+    }
+
+    // Draws a triangle list, every 3 vertices are a triangle. `fragment` returns
+    // `None` to discard the fragment.
+    pub fn draw_triangles<V, VS, FS, const N: usize>(
+        &mut self,
+        state: &RasterState,
+        vertices: &[V],
+        vertex: VS,
+        fragment: FS,
+    ) where
+        VS: Fn(&V) -> ClipVertex<N>,
+        FS: Fn(&[f32; N]) -> Option<Vec4>,
+    {
+        vertices.chunks_exact(3).for_each(|tri| {
+            let tri = [vertex(&tri[0]), vertex(&tri[1]), vertex(&tri[2])];
+            let polygon = clip_polygon(tri.to_vec());
+
+            // the clipped polygon is convex so we can draw it as a fan
+            (1..polygon.len().saturating_sub(1)).for_each(|i| {
+                self.raster_triangle(
+                    state,
+                    [&polygon[0], &polygon[i], &polygon[i + 1]],
+                    &fragment,
+                );
+            });
+        });
+    }
+
+    fn raster_triangle<FS, const N: usize>(
+        &mut self,
+        state: &RasterState,
+        tri: [&ClipVertex<N>; 3],
+        fragment: &FS,
+    ) where
+        FS: Fn(&[f32; N]) -> Option<Vec4>,
+    {
+        let (w, h) = (self.width as f32, self.height as f32);
+
+        // perspective divide and viewport transform
+        let mut verts = tri.map(|v| {
+            let inv_w = 1.0 / v.position.w;
+            let ndc = v.position.xyz() * inv_w;
+            ScreenVertex {
+                x: (ndc.x * 0.5 + 0.5) * w,
+                y: (0.5 - ndc.y * 0.5) * h,
+                z: ndc.z * 0.5 + 0.5,
+                inv_w,
+                varyings: v.varyings,
+            }
+        });
+
+        // the y axis is flipped, so counter-clockwise in ndc is negative area here
+        let area = edge(&verts[0], &verts[1], verts[2].x, verts[2].y);
+        if area == 0.0 {
+            return;
+        }
+
+        let front = area < 0.0;
+        let culled = match state.cull {
+            CullMode::None => false,
+            CullMode::Back => !front,
+            CullMode::Front => front,
+        };
+        if culled {
+            return;
+        }
+
+        // always work with positive area to use the same fill rule
+        if area < 0.0 {
+            verts.swap(1, 2);
+        }
+        let area = area.abs();
+
+        let min_x = verts
+            .iter()
+            .map(|v| v.x)
+            .fold(f32::MAX, f32::min)
+            .floor()
+            .max(0.0) as u32;
+        let min_y = verts
+            .iter()
+            .map(|v| v.y)
+            .fold(f32::MAX, f32::min)
+            .floor()
+            .max(0.0) as u32;
+        let max_x =
+            (verts.iter().map(|v| v.x).fold(f32::MIN, f32::max).ceil() as u32).min(self.width);
+        let max_y =
+            (verts.iter().map(|v| v.y).fold(f32::MIN, f32::max).ceil() as u32).min(self.height);
+
+        let edges = [(1, 2), (2, 0), (0, 1)];
+        let top_left = edges.map(|(a, b)| is_top_left(&verts[a], &verts[b]));
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                // sample at the pixel center
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let weights = edges.map(|(a, b)| edge(&verts[a], &verts[b], px, py));
+
+                let inside = weights
+                    .iter()
+                    .zip(top_left.iter())
+                    .all(|(w, tl)| *w > 0.0 || (*w == 0.0 && *tl));
+                if !inside {
+                    continue;
+                }
+
+                let l = weights.map(|w| w / area);
+                let index = (y * self.width + x) as usize;
+
+                // depth is linear in screen space
+                let z = l[0] * verts[0].z + l[1] * verts[1].z + l[2] * verts[2].z;
+                if state.depth_test && z >= self.depth[index] {
+                    continue;
+                }
+
+                // varyings are linear in clip space, so interpolate v/w and divide by 1/w
+                let inv_w = l[0] * verts[0].inv_w + l[1] * verts[1].inv_w + l[2] * verts[2].inv_w;
+                let mut varyings = [0.0; N];
+                varyings.iter_mut().enumerate().for_each(|(i, v)| {
+                    let sum = (0..3)
+                        .map(|j| l[j] * verts[j].varyings[i] * verts[j].inv_w)
+                        .sum::<f32>();
+                    *v = sum / inv_w;
+                });
+
+                if let Some(color) = fragment(&varyings) {
+                    self.color[index] = color;
+                    if state.depth_write {
+                        self.depth[index] = z;
+                    }
+                }
+            }
+        }
+    }
+}
+
+struct ScreenVertex<const N: usize> {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    varyings: [f32; N],
+}
+
+fn edge<const N: usize>(a: &ScreenVertex<N>, b: &ScreenVertex<N>, x: f32, y: f32) -> f32 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+// pixels exactly on an edge are only drawn for top and left edges,
+// so triangles sharing an edge don't draw the same pixel twice
+fn is_top_left<const N: usize>(a: &ScreenVertex<N>, b: &ScreenVertex<N>) -> bool {
+    let top = a.y == b.y && b.x > a.x;
+    let left = b.y < a.y;
+    top || left
+}
+
+// Sutherland-Hodgman against the near and far planes, x and y are limited by the viewport
+fn clip_polygon<const N: usize>(polygon: Vec<ClipVertex<N>>) -> Vec<ClipVertex<N>> {
+    let near = |v: &ClipVertex<N>| v.position.z + v.position.w;
+    let far = |v: &ClipVertex<N>| v.position.w - v.position.z;
+    let polygon = clip_plane(polygon, near);
+    clip_plane(polygon, far)
+}
+
+fn clip_plane<const N: usize, F>(polygon: Vec<ClipVertex<N>>, distance: F) -> Vec<ClipVertex<N>>
+where
+    F: Fn(&ClipVertex<N>) -> f32,
+{
+    let mut out = Vec::with_capacity(polygon.len() + 1);
+    (0..polygon.len()).for_each(|i| {
+        let a = &polygon[i];
+        let b = &polygon[(i + 1) % polygon.len()];
+        let (da, db) = (distance(a), distance(b));
+
+        if da >= 0.0 {
+            out.push(*a);
+        }
+
+        if (da >= 0.0) != (db >= 0.0) {
+            let t = da / (da - db);
+            let mut varyings = [0.0; N];
+            varyings.iter_mut().enumerate().for_each(|(i, v)| {
+                *v = a.varyings[i] + (b.varyings[i] - a.varyings[i]) * t;
+            });
+            out.push(ClipVertex {
+                position: a.position.lerp(b.position, t),
+                varyings,
+            });
+        }
+    });
+    out
+}
+
+// RGBA texture sampled on the CPU, with repeat wrap and linear filtering
+#[derive(Clone, Debug)]
+pub struct CpuTexture {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<Vec4>,
+}
+
+impl CpuTexture {
+    pub fn from_rgba8(width: u32, height: u32, bytes: &[u8]) -> Self {
+        let data = bytes
+            .chunks_exact(4)
+            .map(|c| Vec4::new(c[0] as f32, c[1] as f32, c[2] as f32, c[3] as f32) / 255.0)
+            .collect();
+
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+
+    pub fn from_image(bytes: &[u8]) -> Result<Self, String> {
+        let image = image::load_from_memory(bytes)
+            .map_err(|err| err.to_string())?
+            .to_rgba8();
+        Ok(Self::from_rgba8(
+            image.width(),
+            image.height(),
+            image.as_raw(),
+        ))
+    }
+
+    pub fn from_color(color: Vec4) -> Self {
+        Self {
+            width: 1,
+            height: 1,
+            data: vec![color],
+        }
+    }
+
+    fn texel(&self, x: i64, y: i64) -> Vec4 {
+        let x = x.rem_euclid(self.width as i64) as u32;
+        let y = y.rem_euclid(self.height as i64) as u32;
+        self.data[(y * self.width + x) as usize]
+    }
+
+    pub fn sample(&self, uv: Vec2) -> Vec4 {
+        let x = uv.x * self.width as f32 - 0.5;
+        let y = uv.y * self.height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.texel(x0, y0).lerp(self.texel(x0 + 1, y0), fx);
+        let bottom = self.texel(x0, y0 + 1).lerp(self.texel(x0 + 1, y0 + 1), fx);
+        top.lerp(bottom, fy)
+    }
+}
+
+// Same lights than the lighting shaders (`CalcDirLight`, `CalcPointLight` and `CalcSpotLight`)
+
+#[derive(Clone, Copy, Debug)]
+pub struct DirLight {
+    pub direction: Vec3,
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SpotLight {
+    pub position: Vec3,
+    pub direction: Vec3,
+    // cosine of the angles
+    pub cut_off: f32,
+    pub outer_cut_off: f32,
+    pub constant: f32,
+    pub linear: f32,
+    pub quadratic: f32,
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+}
+
+// Values of the diffuse and specular maps at the fragment
+#[derive(Clone, Copy, Debug)]
+pub struct SurfaceSample {
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    pub shininess: f32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Lights {
+    pub dir_light: Option<DirLight>,
+    pub point_lights: Vec<PointLight>,
+    pub spot_light: Option<SpotLight>,
+}
+
+impl Lights {
+    // the main of the multiple lights shader
+    pub fn shade(
+        &self,
+        normal: Vec3,
+        frag_pos: Vec3,
+        view_pos: Vec3,
+        surface: &SurfaceSample,
+    ) -> Vec3 {
+        let normal = normal.normalize();
+        let view_dir = (view_pos - frag_pos).normalize();
+
+        let dir = self
+            .dir_light
+            .map(|light| calc_dir_light(&light, normal, view_dir, surface))
+            .unwrap_or(Vec3::ZERO);
+
+        let points = self
+            .point_lights
+            .iter()
+            .map(|light| calc_point_light(light, normal, frag_pos, view_dir, surface))
+            .fold(Vec3::ZERO, |acc, c| acc + c);
+
+        let spot = self
+            .spot_light
+            .map(|light| calc_spot_light(&light, normal, frag_pos, view_dir, surface))
+            .unwrap_or(Vec3::ZERO);
+
+        dir + points + spot
+    }
+}
+
+fn reflect(incident: Vec3, normal: Vec3) -> Vec3 {
+    incident - 2.0 * normal.dot(incident) * normal
+}
+
+// diffuse and specular factors
+fn phong(light_dir: Vec3, normal: Vec3, view_dir: Vec3, shininess: f32) -> (f32, f32) {
+    let diff = normal.dot(light_dir).max(0.0);
+    let reflect_dir = reflect(-light_dir, normal);
+    let spec = view_dir.dot(reflect_dir).max(0.0).powf(shininess);
+    (diff, spec)
+}
+
+fn attenuation(position: Vec3, frag_pos: Vec3, constant: f32, linear: f32, quadratic: f32) -> f32 {
+    let distance = (position - frag_pos).length();
+    1.0 / (constant + linear * distance + quadratic * (distance * distance))
+}
+
+pub fn calc_dir_light(
+    light: &DirLight,
+    normal: Vec3,
+    view_dir: Vec3,
+    surface: &SurfaceSample,
+) -> Vec3 {
+    let light_dir = (-light.direction).normalize();
+    let (diff, spec) = phong(light_dir, normal, view_dir, surface.shininess);
+
+    let ambient = light.ambient * surface.diffuse;
+    let diffuse = light.diffuse * diff * surface.diffuse;
+    let specular = light.specular * spec * surface.specular;
+    ambient + diffuse + specular
+}
+
+pub fn calc_point_light(
+    light: &PointLight,
+    normal: Vec3,
+    frag_pos: Vec3,
+    view_dir: Vec3,
+    surface: &SurfaceSample,
+) -> Vec3 {
+    let light_dir = (light.position - frag_pos).normalize();
+    let (diff, spec) = phong(light_dir, normal, view_dir, surface.shininess);
+    let attenuation = attenuation(
+        light.position,
+        frag_pos,
+        light.constant,
+        light.linear,
+        light.quadratic,
+    );
+
+    let ambient = light.ambient * surface.diffuse;
+    let diffuse = light.diffuse * diff * surface.diffuse;
+    let specular = light.specular * spec * surface.specular;
+    (ambient + diffuse + specular) * attenuation
+}
+
+pub fn calc_spot_light(
+    light: &SpotLight,
+    normal: Vec3,
+    frag_pos: Vec3,
+    view_dir: Vec3,
+    surface: &SurfaceSample,
+) -> Vec3 {
+    let light_dir = (light.position - frag_pos).normalize();
+    let (diff, spec) = phong(light_dir, normal, view_dir, surface.shininess);
+    let attenuation = attenuation(
+        light.position,
+        frag_pos,
+        light.constant,
+        light.linear,
+        light.quadratic,
+    );
+
+    // spotlight intensity
+    let theta = light_dir.dot((-light.direction).normalize());
+    let epsilon = light.cut_off - light.outer_cut_off;
+    let intensity = ((theta - light.outer_cut_off) / epsilon).clamp(0.0, 1.0);
+
+    let ambient = light.ambient * surface.diffuse;
+    let diffuse = light.diffuse * diff * surface.diffuse;
+    let specular = light.specular * spec * surface.specular;
+    (ambient + diffuse + specular) * attenuation * intensity
+}
+
+// Transforms of a lit mesh, like the `Transform` uniform of the lighting examples
+#[derive(Clone, Copy, Debug)]
+pub struct MeshTransform {
+    pub model: Mat4,
+    pub view: Mat4,
+    pub projection: Mat4,
+}
+
+// Maps of the lighting maps examples
+pub struct LitMaterial<'a> {
+    pub diffuse: &'a CpuTexture,
+    pub specular: &'a CpuTexture,
+    pub shininess: f32,
+}
+
+// Draws a mesh with the layout of the lighting examples (position, normal, uv)
+// using the multiple lights shader
+pub fn draw_lit_mesh(
+    fb: &mut Framebuffer,
+    state: &RasterState,
+    vertices: &[f32],
+    transform: &MeshTransform,
+    view_pos: Vec3,
+    lights: &Lights,
+    material: &LitMaterial,
+) {
+    let vertices = vertices.chunks_exact(8).collect::<Vec<_>>();
+    let mvp = transform.projection * transform.view * transform.model;
+    let normal_matrix = Mat3::from_mat4(transform.model).inverse().transpose();
+
+    fb.draw_triangles(
+        state,
+        &vertices,
+        |v| {
+            let pos = Vec3::new(v[0], v[1], v[2]);
+            let frag_pos = transform.model.transform_point3(pos);
+            let normal = normal_matrix * Vec3::new(v[3], v[4], v[5]);
+            ClipVertex {
+                position: mvp * pos.extend(1.0),
+                varyings: [
+                    frag_pos.x, frag_pos.y, frag_pos.z, normal.x, normal.y, normal.z, v[6], v[7],
+                ],
+            }
+        },
+        |v| {
+            let frag_pos = Vec3::new(v[0], v[1], v[2]);
+            let normal = Vec3::new(v[3], v[4], v[5]);
+            let uv = Vec2::new(v[6], v[7]);
+            let surface = SurfaceSample {
+                diffuse: material.diffuse.sample(uv).xyz(),
+                specular: material.specular.sample(uv).xyz(),
+                shininess: material.shininess,
+            };
+            let color = lights.shade(normal, frag_pos, view_pos, &surface);
+            Some(color.extend(1.0))
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notan::math::vec3;
+    use std::cell::RefCell;
+
+    const RED: Vec4 = Vec4::new(1.0, 0.0, 0.0, 1.0);
+    const GREEN: Vec4 = Vec4::new(0.0, 1.0, 0.0, 1.0);
+
+    // vertices already in clip space
+    fn clip(v: &Vec4) -> ClipVertex<0> {
+        ClipVertex {
+            position: *v,
+            varyings: [],
+        }
+    }
+
+    fn quad(z: f32) -> Vec<Vec4> {
+        [
+            (-1.0, -1.0),
+            (1.0, -1.0),
+            (1.0, 1.0),
+            (1.0, 1.0),
+            (-1.0, 1.0),
+            (-1.0, -1.0),
+        ]
+        .iter()
+        .map(|(x, y)| Vec4::new(*x, *y, z, 1.0))
+        .collect()
+    }
+
+    #[test]
+    fn shared_edges_are_drawn_once() {
+        let mut fb = Framebuffer::new(16, 16);
+        let count = RefCell::new(vec![0; 16 * 16]);
+        let state = RasterState {
+            depth_test: false,
+            ..Default::default()
+        };
+
+        // the second triangle uses the varying to know where it is
+        let vertices = quad(0.0)
+            .iter()
+            .map(|p| Vec4::new(p.x * 0.5, p.y * 0.5, 0.0, 1.0))
+            .collect::<Vec<_>>();
+        fb.draw_triangles(
+            &state,
+            &vertices,
+            |v| ClipVertex {
+                position: *v,
+                varyings: [(v.x + 1.0) * 8.0, (1.0 - v.y) * 8.0],
+            },
+            |v| {
+                let (x, y) = (v[0] as usize, v[1] as usize);
+                count.borrow_mut()[y * 16 + x] += 1;
+                Some(RED)
+            },
+        );
+
+        // the quad covers the 8x8 pixels at the center exactly once
+        let count = count.into_inner();
+        assert_eq!(count.iter().sum::<i32>(), 64);
+        assert!(count.iter().all(|c| *c <= 1));
+        assert_eq!(fb.pixel(4, 4), RED);
+        assert_eq!(fb.pixel(11, 11), RED);
+        assert_ne!(fb.pixel(3, 3), RED);
+        assert_ne!(fb.pixel(12, 12), RED);
+    }
+
+    #[test]
+    fn nearest_fragment_wins() {
+        let state = RasterState::default();
+        let mut fb = Framebuffer::new(4, 4);
+        fb.draw_triangles(&state, &quad(0.5), clip, |_| Some(RED));
+        fb.draw_triangles(&state, &quad(-0.5), clip, |_| Some(GREEN));
+        fb.draw_triangles(&state, &quad(0.0), clip, |_| Some(RED));
+        assert!(fb.color.iter().all(|c| *c == GREEN));
+        assert!(fb.depth.iter().all(|d| (*d - 0.25).abs() < 1e-6));
+    }
+
+    #[test]
+    fn back_faces_are_culled() {
+        let state = RasterState {
+            cull: CullMode::Back,
+            ..Default::default()
+        };
+        let mut fb = Framebuffer::new(4, 4);
+
+        // clockwise
+        let mut vertices = quad(0.0);
+        vertices.reverse();
+        fb.draw_triangles(&state, &vertices, clip, |_| Some(RED));
+        assert!(fb.color.iter().all(|c| *c != RED));
+
+        fb.draw_triangles(&state, &quad(0.0), clip, |_| Some(RED));
+        assert!(fb.color.iter().all(|c| *c == RED));
+    }
+
+    #[test]
+    fn triangles_crossing_the_near_plane_are_clipped() {
+        let projection = Mat4::perspective_rh_gl(45.0_f32.to_radians(), 1.0, 0.1, 100.0);
+
+        // a floor going from behind the camera to the distance
+        let vertices = [
+            vec3(-1.0, -1.0, 5.0),
+            vec3(1.0, -1.0, 5.0),
+            vec3(1.0, -1.0, -50.0),
+            vec3(1.0, -1.0, -50.0),
+            vec3(-1.0, -1.0, -50.0),
+            vec3(-1.0, -1.0, 5.0),
+        ];
+
+        let mut fb = Framebuffer::new(32, 32);
+        fb.draw_triangles(
+            &RasterState::default(),
+            &vertices,
+            |v| ClipVertex {
+                position: projection * v.extend(1.0),
+                varyings: [v.z],
+            },
+            |v| {
+                // only the part in front of the near plane is visible
+                assert!(v[0] <= -0.1 + 1e-3);
+                Some(RED)
+            },
+        );
+
+        // bottom of the screen covered, top empty
+        assert_eq!(fb.pixel(16, 31), RED);
+        assert_ne!(fb.pixel(16, 0), RED);
+    }
+
+    #[test]
+    fn varyings_are_perspective_correct() {
+        let projection = Mat4::perspective_rh_gl(90.0_f32.to_radians(), 1.0, 0.1, 100.0);
+
+        // a wall going into the distance, the uv at the middle of the screen
+        // is not the middle of the wall
+        let vertices = [
+            (vec3(-1.0, -1.0, -1.0), 0.0),
+            (vec3(-1.0, -1.0, -9.0), 1.0),
+            (vec3(-1.0, 1.0, -9.0), 1.0),
+            (vec3(-1.0, 1.0, -9.0), 1.0),
+            (vec3(-1.0, 1.0, -1.0), 0.0),
+            (vec3(-1.0, -1.0, -1.0), 0.0),
+        ];
+
+        let mut fb = Framebuffer::new(64, 64);
+        fb.draw_triangles(
+            &RasterState::default(),
+            &vertices,
+            |(pos, u)| ClipVertex {
+                position: projection * pos.extend(1.0),
+                varyings: [*u, pos.z],
+            },
+            |v| {
+                // the interpolated u matches the interpolated depth
+                let expected = (-1.0 - v[1]) / 8.0;
+                assert!((v[0] - expected).abs() < 1e-3);
+                Some(Vec4::new(v[0], 0.0, 0.0, 1.0))
+            },
+        );
+    }
+
+    #[test]
+    fn lights_match_the_shaders() {
+        let surface = SurfaceSample {
+            diffuse: Vec3::splat(1.0),
+            specular: Vec3::splat(1.0),
+            shininess: 32.0,
+        };
+
+        // light, normal and view aligned: full diffuse and specular
+        let light = DirLight {
+            direction: vec3(0.0, 0.0, -1.0),
+            ambient: Vec3::splat(0.1),
+            diffuse: Vec3::splat(0.5),
+            specular: Vec3::splat(0.25),
+        };
+        let color = calc_dir_light(&light, Vec3::Z, Vec3::Z, &surface);
+        assert!((color - Vec3::splat(0.85)).length() < 1e-5);
+
+        // light behind the surface: only ambient
+        let color = calc_dir_light(&light, -Vec3::Z, -Vec3::Z, &surface);
+        assert!((color - Vec3::splat(0.1)).length() < 1e-5);
+
+        // point light at distance 2
+        let light = PointLight {
+            position: vec3(0.0, 0.0, 2.0),
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+            ambient: Vec3::ZERO,
+            diffuse: Vec3::splat(1.0),
+            specular: Vec3::ZERO,
+        };
+        let color = calc_point_light(&light, Vec3::Z, Vec3::ZERO, Vec3::Z, &surface);
+        let attenuation = 1.0 / (1.0 + 0.09 * 2.0 + 0.032 * 4.0);
+        assert!((color - Vec3::splat(attenuation)).length() < 1e-5);
+
+        // spot light pointing away
+        let light = SpotLight {
+            position: vec3(0.0, 0.0, 2.0),
+            direction: Vec3::Z,
+            cut_off: 12.5_f32.to_radians().cos(),
+            outer_cut_off: 15.0_f32.to_radians().cos(),
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+            ambient: Vec3::splat(1.0),
+            diffuse: Vec3::splat(1.0),
+            specular: Vec3::splat(1.0),
+        };
+        let color = calc_spot_light(&light, Vec3::Z, Vec3::ZERO, Vec3::Z, &surface);
+        assert_eq!(color, Vec3::ZERO);
+
+        // and pointing to the fragment
+        let light = SpotLight {
+            direction: -Vec3::Z,
+            ..light
+        };
+        let color = calc_spot_light(&light, Vec3::Z, Vec3::ZERO, Vec3::Z, &surface);
+        assert!((color - Vec3::splat(3.0 * attenuation)).length() < 1e-4);
+    }
+
+    #[test]
+    fn lit_mesh_is_drawn() {
+        // a quad facing the camera, lit by a directional light from the front
+        #[rustfmt::skip]
+        let vertices = [
+            -0.5, -0.5, 0.0,  0.0, 0.0, 1.0,  0.0, 0.0,
+             0.5, -0.5, 0.0,  0.0, 0.0, 1.0,  1.0, 0.0,
+             0.5,  0.5, 0.0,  0.0, 0.0, 1.0,  1.0, 1.0,
+             0.5,  0.5, 0.0,  0.0, 0.0, 1.0,  1.0, 1.0,
+            -0.5,  0.5, 0.0,  0.0, 0.0, 1.0,  0.0, 1.0,
+            -0.5, -0.5, 0.0,  0.0, 0.0, 1.0,  0.0, 0.0,
+        ];
+
+        let view_pos = vec3(0.0, 0.0, 3.0);
+        let transform = MeshTransform {
+            model: Mat4::from_rotation_y(0.3),
+            view: Mat4::look_at_rh(view_pos, Vec3::ZERO, Vec3::Y),
+            projection: Mat4::perspective_rh_gl(45.0_f32.to_radians(), 1.0, 0.1, 100.0),
+        };
+        let lights = Lights {
+            dir_light: Some(DirLight {
+                direction: vec3(0.0, 0.0, -1.0),
+                ambient: Vec3::splat(0.1),
+                diffuse: Vec3::splat(0.8),
+                specular: Vec3::ZERO,
+            }),
+            ..Default::default()
+        };
+        let diffuse = CpuTexture::from_color(Vec4::new(1.0, 0.5, 0.25, 1.0));
+        let specular = CpuTexture::from_color(Vec4::ZERO);
+        let material = LitMaterial {
+            diffuse: &diffuse,
+            specular: &specular,
+            shininess: 32.0,
+        };
+
+        let mut fb = Framebuffer::new(32, 32);
+        fb.clear(Vec4::new(0.0, 0.0, 0.0, 1.0), 1.0);
+        draw_lit_mesh(
+            &mut fb,
+            &RasterState::default(),
+            &vertices,
+            &transform,
+            view_pos,
+            &lights,
+            &material,
+        );
+
+        // the normal is rotated with the model
+        let diff = 0.3_f32.cos();
+        let expected = Vec3::new(1.0, 0.5, 0.25) * (0.1 + 0.8 * diff);
+        let center = fb.pixel(16, 16).xyz();
+        assert!((center - expected).length() < 1e-4);
+        assert_eq!(fb.pixel(0, 0), Vec4::new(0.0, 0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn textures_are_sampled_with_repeat() {
+        let texture = CpuTexture::from_rgba8(2, 1, &[255, 0, 0, 255, 0, 0, 255, 255]);
+        assert_eq!(texture.sample(Vec2::new(0.25, 0.5)), RED);
+        assert_eq!(texture.sample(Vec2::new(1.25, 0.5)), RED);
+        let mid = texture.sample(Vec2::new(0.5, 0.5));
+        assert!((mid - Vec4::new(0.5, 0.0, 0.5, 1.0)).length() < 1e-6);
+    }
+
+    // Renders `resources/scenes/multiple_lights.json` with the rasterizer and compares it
+    // with `screenshots/raster/2_6_1.png`, an earlier render of this same code and not a
+    // capture of the example. It catches changes of the rasterizer and the scene loading,
+    // it doesn't check that the result matches the shaders. When it doesn't match the render
+    // and the differences are saved in the temp folder, copy the render over the stored
+    // image if the change is expected.
+    #[test]
+    fn multiple_lights_scene_render_is_unchanged() {
+        use crate::utils::{compare_images, load_png, Scene};
+
+        let root = env!("CARGO_MANIFEST_DIR");
+        let scene =
+            Scene::load(&format!("{}/resources/scenes/multiple_lights.json", root)).unwrap();
+        let load_texture = |path: &str| {
+            let bytes = std::fs::read(format!("{}/resources/{}", root, path)).unwrap();
+            CpuTexture::from_image(&bytes).unwrap()
+        };
+
+        // the 800x600 window of the example, scaled down
+        let (width, height) = (200, 150);
+        let camera = scene.camera.to_camera();
+        let view = camera.get_view_matrix();
+        let projection = Mat4::perspective_rh_gl(
+            camera.zoom.to_radians(),
+            width as f32 / height as f32,
+            0.1,
+            100.0,
+        );
+
+        let mut lights = scene.lights.clone();
+        if scene.spot_light_follows_camera {
+            if let Some(light) = &mut lights.spot_light {
+                light.position = camera.position;
+                light.direction = camera.front;
+            }
+        }
+
+        let c = scene.clear_color;
+        let mut fb = Framebuffer::new(width, height);
+        fb.clear(Vec4::new(c.r, c.g, c.b, c.a), 1.0);
+
+        let state = RasterState::default();
+        let vertices = lit_cube();
+        let textures = scene
+            .materials
+            .iter()
+            .map(|material| {
+                (
+                    load_texture(&material.diffuse),
+                    load_texture(&material.specular),
+                )
+            })
+            .collect::<Vec<_>>();
+        for object in &scene.objects {
+            let (diffuse, specular) = &textures[object.material];
+            let transform = MeshTransform {
+                model: object.model(),
+                view,
+                projection,
+            };
+            let material = LitMaterial {
+                diffuse,
+                specular,
+                shininess: scene.materials[object.material].shininess,
+            };
+            draw_lit_mesh(
+                &mut fb,
+                &state,
+                &vertices,
+                &transform,
+                camera.position,
+                &lights,
+                &material,
+            );
+        }
+
+        // the white lamps on the point lights
+        let positions = vertices.chunks_exact(8).collect::<Vec<_>>();
+        for light in &lights.point_lights {
+            let model = Mat4::from_translation(light.position) * Mat4::from_scale(Vec3::splat(0.2));
+            let mvp = projection * view * model;
+            fb.draw_triangles(
+                &state,
+                &positions,
+                |v| ClipVertex {
+                    position: mvp * Vec4::new(v[0], v[1], v[2], 1.0),
+                    varyings: [],
+                },
+                |_| Some(Vec4::ONE),
+            );
+        }
+
+        let actual = fb.to_image();
+        let expected = load_png(&format!("{}/screenshots/raster/2_6_1.png", root)).unwrap();
+        let diff = compare_images(&expected, &actual, 0.1).unwrap();
+        if diff.mismatch_ratio() > 0.01 {
+            let out_dir = std::env::temp_dir().join("raster");
+            std::fs::create_dir_all(&out_dir).unwrap();
+            actual.save(out_dir.join("2_6_1.png")).unwrap();
+            diff.image.save(out_dir.join("2_6_1.diff.png")).unwrap();
+            panic!(
+                "{:.2}% of the pixels are different (max delta {:.3}), see {:?}",
+                diff.mismatch_ratio() * 100.0,
+                diff.max_delta,
+                out_dir
+            );
+        }
+    }
+
+    // the cube of the lighting examples: position, normal and uv
+    #[rustfmt::skip]
+    fn lit_cube() -> Vec<f32> {
+        vec![
+            -0.5, -0.5, -0.5,     0.0,  0.0, -1.0,    0.0,  0.0,
+            0.5, -0.5, -0.5,      0.0,  0.0, -1.0,    1.0,  0.0,
+            0.5,  0.5, -0.5,      0.0,  0.0, -1.0,    1.0,  1.0,
+            0.5,  0.5, -0.5,      0.0,  0.0, -1.0,    1.0,  1.0,
+            -0.5,  0.5, -0.5,     0.0,  0.0, -1.0,    0.0,  1.0,
+            -0.5, -0.5, -0.5,     0.0,  0.0, -1.0,    0.0,  0.0,
+
+            -0.5, -0.5,  0.5,     0.0,  0.0,  1.0,    0.0,  0.0,
+            0.5, -0.5,  0.5,      0.0,  0.0,  1.0,    1.0,  0.0,
+            0.5,  0.5,  0.5,      0.0,  0.0,  1.0,    1.0,  1.0,
+            0.5,  0.5,  0.5,      0.0,  0.0,  1.0,    1.0,  1.0,
+            -0.5,  0.5,  0.5,     0.0,  0.0,  1.0,    0.0,  1.0,
+            -0.5, -0.5,  0.5,     0.0,  0.0,  1.0,    0.0,  0.0,
+
+            -0.5,  0.5,  0.5,    -1.0,  0.0,  0.0,    1.0,  0.0,
+            -0.5,  0.5, -0.5,    -1.0,  0.0,  0.0,    1.0,  1.0,
+            -0.5, -0.5, -0.5,    -1.0,  0.0,  0.0,    0.0,  1.0,
+            -0.5, -0.5, -0.5,    -1.0,  0.0,  0.0,    0.0,  1.0,
+            -0.5, -0.5,  0.5,    -1.0,  0.0,  0.0,    0.0,  0.0,
+            -0.5,  0.5,  0.5,    -1.0,  0.0,  0.0,    1.0,  0.0,
+
+            0.5,  0.5,  0.5,      1.0,  0.0,  0.0,    1.0,  0.0,
+            0.5,  0.5, -0.5,      1.0,  0.0,  0.0,    1.0,  1.0,
+            0.5, -0.5, -0.5,      1.0,  0.0,  0.0,    0.0,  1.0,
+            0.5, -0.5, -0.5,      1.0,  0.0,  0.0,    0.0,  1.0,
+            0.5, -0.5,  0.5,      1.0,  0.0,  0.0,    0.0,  0.0,
+            0.5,  0.5,  0.5,      1.0,  0.0,  0.0,    1.0,  0.0,
+
+            -0.5, -0.5, -0.5,     0.0, -1.0,  0.0,    0.0,  1.0,
+            0.5, -0.5, -0.5,      0.0, -1.0,  0.0,    1.0,  1.0,
+            0.5, -0.5,  0.5,      0.0, -1.0,  0.0,    1.0,  0.0,
+            0.5, -0.5,  0.5,      0.0, -1.0,  0.0,    1.0,  0.0,
+            -0.5, -0.5,  0.5,     0.0, -1.0,  0.0,    0.0,  0.0,
+            -0.5, -0.5, -0.5,     0.0, -1.0,  0.0,    0.0,  1.0,
+
+            -0.5,  0.5, -0.5,     0.0,  1.0,  0.0,    0.0,  1.0,
+            0.5,  0.5, -0.5,      0.0,  1.0,  0.0,    1.0,  1.0,
+            0.5,  0.5,  0.5,      0.0,  1.0,  0.0,    1.0,  0.0,
+            0.5,  0.5,  0.5,      0.0,  1.0,  0.0,    1.0,  0.0,
+            -0.5,  0.5,  0.5,     0.0,  1.0,  0.0,    0.0,  0.0,
+            -0.5,  0.5, -0.5,     0.0,  1.0,  0.0,    0.0,  1.0,
+        ]
+    }
+}
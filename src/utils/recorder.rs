@@ -0,0 +1,204 @@
+use super::capture::CapturedFrame;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+// Records the captured frames as a sequence of png files or as an animated gif. The runner
+// advances the clock by `step()` while recording, so the clips always have the same speed
+// no matter how long it takes to capture and encode each frame.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecordFormat {
+    Png,
+    Gif,
+}
+
+impl RecordFormat {
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        match name {
+            "png" => Ok(RecordFormat::Png),
+            "gif" => Ok(RecordFormat::Gif),
+            _ => Err(format!("Unknown record format '{}', use png or gif", name)),
+        }
+    }
+
+    // gif files are recorded as gif, anything else is the folder of a png sequence
+    pub fn from_path(path: &str) -> Self {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some("gif") => RecordFormat::Gif,
+            _ => RecordFormat::Png,
+        }
+    }
+}
+
+enum Output {
+    Png(String),
+    Gif(Box<GifEncoder<BufWriter<File>>>),
+}
+
+pub struct Recorder {
+    path: String,
+    output: Output,
+    fps: u32,
+    max_frames: Option<u32>,
+    frames: u32,
+}
+
+impl Recorder {
+    pub fn new(
+        path: &str,
+        format: RecordFormat,
+        fps: u32,
+        max_frames: Option<u32>,
+    ) -> Result<Self, String> {
+        let fps = fps.max(1);
+        let output = match format {
+            RecordFormat::Png => {
+                std::fs::create_dir_all(path).map_err(|err| err.to_string())?;
+                Output::Png(path.to_string())
+            }
+            RecordFormat::Gif => {
+                if let Some(dir) = Path::new(path).parent() {
+                    std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                }
+
+                let file = File::create(path)
+                    .map_err(|err| format!("Cannot create '{}': {}", path, err))?;
+
+                // speed 10 is the default of gif, 1 (the image's default) is too slow to record
+                let mut encoder = GifEncoder::new_with_speed(BufWriter::new(file), 10);
+                encoder
+                    .set_repeat(Repeat::Infinite)
+                    .map_err(|err| err.to_string())?;
+                Output::Gif(Box::new(encoder))
+            }
+        };
+
+        Ok(Self {
+            path: path.to_string(),
+            output,
+            fps,
+            max_frames,
+            frames: 0,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    // seconds between frames
+    pub fn step(&self) -> f32 {
+        1.0 / self.fps as f32
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    pub fn is_full(&self) -> bool {
+        matches!(self.max_frames, Some(max) if self.frames >= max)
+    }
+
+    pub fn add_frame(&mut self, frame: &CapturedFrame) -> Result<(), String> {
+        if self.is_full() {
+            return Err(format!(
+                "The recording is limited to {} frames",
+                self.frames
+            ));
+        }
+
+        match &mut self.output {
+            Output::Png(dir) => {
+                let path = format!("{}/{:05}.png", dir, self.frames);
+                frame.save_png(&path)?;
+            }
+            Output::Gif(encoder) => {
+                let image = frame.to_image()?;
+                encoder
+                    .encode_frame(gif_frame(image, self.fps))
+                    .map_err(|err| format!("Cannot encode the frame: {}", err))?;
+            }
+        }
+
+        self.frames += 1;
+        Ok(())
+    }
+
+    // Closes the files and returns the number of frames recorded
+    pub fn finish(self) -> Result<u32, String> {
+        // the gif encoder writes the end of the file when it's dropped
+        let frames = self.frames;
+        drop(self.output);
+        Ok(frames)
+    }
+}
+
+fn gif_frame(image: RgbaImage, fps: u32) -> Frame {
+    Frame::from_parts(image, 0, 0, Delay::from_numer_denom_ms(1000, fps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::AnimationDecoder;
+
+    fn frame(value: u8) -> CapturedFrame {
+        CapturedFrame {
+            width: 4,
+            height: 2,
+            data: vec![value; 4 * 2 * 4],
+        }
+    }
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("learn_open_gl_notan_{}", name))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(RecordFormat::from_path("clip.gif"), RecordFormat::Gif);
+        assert_eq!(
+            RecordFormat::from_path("./recordings/1_7_1"),
+            RecordFormat::Png
+        );
+        assert!(RecordFormat::from_name("avi").is_err());
+    }
+
+    #[test]
+    fn png_sequence_stops_at_the_limit() {
+        let dir = temp_path("png_sequence");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut recorder = Recorder::new(&dir, RecordFormat::Png, 30, Some(2)).unwrap();
+        recorder.add_frame(&frame(0)).unwrap();
+        recorder.add_frame(&frame(255)).unwrap();
+        assert!(recorder.is_full());
+        assert!(recorder.add_frame(&frame(0)).is_err());
+        assert_eq!(recorder.finish().unwrap(), 2);
+
+        assert!(Path::new(&dir).join("00000.png").exists());
+        assert!(Path::new(&dir).join("00001.png").exists());
+        assert!(!Path::new(&dir).join("00002.png").exists());
+    }
+
+    #[test]
+    fn gif_contains_every_frame() {
+        let path = temp_path("clip.gif");
+        let mut recorder = Recorder::new(&path, RecordFormat::Gif, 20, None).unwrap();
+        assert_eq!(recorder.step(), 0.05);
+        (0..3).for_each(|i| recorder.add_frame(&frame(i * 100)).unwrap());
+        assert_eq!(recorder.finish().unwrap(), 3);
+
+        let file = File::open(&path).unwrap();
+        let decoder = image::codecs::gif::GifDecoder::new(file).unwrap();
+        let frames = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].delay().numer_denom_ms(), (50, 1));
+    }
+}
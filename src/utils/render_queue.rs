@@ -0,0 +1,132 @@
+use notan::math::Vec3;
+use notan::prelude::*;
+use std::cmp::Ordering;
+
+// How an object is mixed with what is behind it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Transparency {
+    Opaque,
+    // alpha test: the fragments under the cutoff are discarded and the rest are opaque,
+    // like the leaves of the grass
+    Cutout,
+    // alpha blending, like the windows
+    Blended,
+}
+
+// Objects of a frame split by their transparency. The opaque and cutout objects are drawn
+// first in any order, the depth test takes care of them. The blended ones are drawn after
+// them from back to front, so every window is blended with the objects already behind it.
+#[derive(Clone, Debug)]
+pub struct RenderQueue<T> {
+    opaque: Vec<T>,
+    cutout: Vec<T>,
+    blended: Vec<(Vec3, T)>,
+}
+
+impl<T> Default for RenderQueue<T> {
+    fn default() -> Self {
+        Self {
+            opaque: vec![],
+            cutout: vec![],
+            blended: vec![],
+        }
+    }
+}
+
+impl<T> RenderQueue<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The queue is filled again every frame, the camera moves
+    pub fn clear(&mut self) {
+        self.opaque.clear();
+        self.cutout.clear();
+        self.blended.clear();
+    }
+
+    // `position` is only used to sort the blended objects
+    pub fn push(&mut self, transparency: Transparency, position: Vec3, item: T) {
+        match transparency {
+            Transparency::Opaque => self.opaque.push(item),
+            Transparency::Cutout => self.cutout.push(item),
+            Transparency::Blended => self.blended.push((position, item)),
+        }
+    }
+
+    // Orders the blended objects from the farthest to the closest to the camera, the ones at
+    // the same distance keep the order they were pushed
+    pub fn sort(&mut self, camera_position: Vec3) {
+        self.blended.sort_by(|(a, _), (b, _)| {
+            let a = a.distance_squared(camera_position);
+            let b = b.distance_squared(camera_position);
+            b.partial_cmp(&a).unwrap_or(Ordering::Equal)
+        });
+    }
+
+    pub fn opaque(&self) -> &[T] {
+        &self.opaque
+    }
+
+    pub fn cutout(&self) -> &[T] {
+        &self.cutout
+    }
+
+    // In the order of the last `sort`
+    pub fn blended(&self) -> impl Iterator<Item = &T> + '_ {
+        self.blended.iter().map(|(_, item)| item)
+    }
+
+    pub fn len(&self) -> usize {
+        self.opaque.len() + self.cutout.len() + self.blended.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// Depth of the pipelines of the blended objects: they are tested against the opaque objects
+// but don't write, otherwise a window would hide the windows drawn after it
+pub fn blended_depth_stencil() -> DepthStencil {
+    DepthStencil {
+        write: false,
+        compare: CompareMode::Less,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notan::math::vec3;
+
+    #[test]
+    fn blended_objects_are_sorted_back_to_front() {
+        let mut queue = RenderQueue::new();
+        queue.push(Transparency::Opaque, Vec3::ZERO, "floor");
+        queue.push(Transparency::Blended, vec3(0.0, 0.0, -1.0), "near");
+        queue.push(Transparency::Cutout, vec3(0.0, 0.0, -9.0), "grass");
+        queue.push(Transparency::Blended, vec3(0.0, 0.0, -5.0), "far");
+        queue.push(Transparency::Blended, vec3(2.0, 0.0, -3.0), "middle");
+        queue.push(Transparency::Blended, vec3(-2.0, 0.0, -3.0), "middle too");
+
+        queue.sort(vec3(0.0, 0.0, 1.0));
+        assert_eq!(queue.opaque(), &["floor"]);
+        assert_eq!(queue.cutout(), &["grass"]);
+        assert_eq!(
+            queue.blended().copied().collect::<Vec<_>>(),
+            vec!["far", "middle", "middle too", "near"]
+        );
+        assert_eq!(queue.len(), 6);
+
+        // from the other side the order is reversed
+        queue.sort(vec3(0.0, 0.0, -10.0));
+        assert_eq!(
+            queue.blended().copied().collect::<Vec<_>>(),
+            vec!["near", "middle", "middle too", "far"]
+        );
+
+        queue.clear();
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,439 @@
+use super::stats::{add_frame_stats, StatsRenderer};
+use crate::set_buffer_data;
+use notan::graphics::Renderer;
+use notan::prelude::*;
+
+// Offscreen render targets. Multiple render targets are not supported: notan 0.6 renders each
+// pass to a single texture, so the color attachments of a `RenderTarget` are separate textures,
+// each one filled by its own pass with its own depth buffer. A g-buffer needs a pass per
+// attachment. The msaa is supersampling, see `supersample_scale`.
+
+// language=glsl
+const RESOLVE_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec2 aPos;
+
+    void main()
+    {
+        gl_Position = vec4(aPos, 0.0, 1.0);
+    }
+  "#
+};
+
+// language=glsl
+const RESOLVE_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout(location = 0) out vec4 color;
+
+    layout(binding = 0) uniform sampler2D samples;
+
+    layout(set = 0, binding = 0) uniform ResolveData {
+        float scale;
+    };
+
+    // average of the scale x scale samples of the pixel
+    void main()
+    {
+        int size = int(scale);
+        ivec2 origin = ivec2(gl_FragCoord.xy) * size;
+        vec4 sum = vec4(0.0);
+        for (int y = 0; y < size; y++) {
+            for (int x = 0; x < size; x++) {
+                sum += texelFetch(samples, origin + ivec2(x, y), 0);
+            }
+        }
+        color = sum / float(size * size);
+    }
+  "#
+};
+
+#[derive(Copy, Clone)]
+#[uniform]
+struct ResolveData {
+    scale: f32,
+}
+
+// Format of the color attachments
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorFormat {
+    Rgba8,
+    // notan 0.6 has no half float format, it's stored like Rgba32F (twice the memory)
+    Rgba16F,
+    Rgba32F,
+}
+
+impl ColorFormat {
+    fn texture_format(self) -> TextureFormat {
+        match self {
+            ColorFormat::Rgba8 => TextureFormat::Rgba32,
+            ColorFormat::Rgba16F | ColorFormat::Rgba32F => TextureFormat::Rgba32Float,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderTargetOptions {
+    // one color texture per attachment
+    pub colors: Vec<ColorFormat>,
+    pub depth: bool,
+    // 0 or 1 disables the msaa
+    pub samples: u32,
+    pub filter: TextureFilter,
+}
+
+impl Default for RenderTargetOptions {
+    fn default() -> Self {
+        Self {
+            colors: vec![ColorFormat::Rgba8],
+            depth: true,
+            samples: 1,
+            filter: TextureFilter::Linear,
+        }
+    }
+}
+
+impl RenderTargetOptions {
+    pub fn with_colors(mut self, colors: &[ColorFormat]) -> Self {
+        self.colors = colors.to_vec();
+        self
+    }
+
+    pub fn with_depth(mut self, depth: bool) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn with_samples(mut self, samples: u32) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: TextureFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    // Checked before creating any texture
+    fn validate(&self) -> Result<(), String> {
+        if self.colors.is_empty() {
+            return Err("A render target needs at least one color attachment".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+// Notan's render textures don't have multisampling, so the samples are rendered to a bigger
+// texture (a grid of `scale` x `scale` per pixel, at least `samples`) and `resolve` averages
+// them into a texture of the size of the target.
+pub fn supersample_scale(samples: u32) -> u32 {
+    let mut scale = 1;
+    while scale * scale < samples {
+        scale += 1;
+    }
+    scale
+}
+
+// The supersampled textures can't be bigger than the device allows, so the scale is reduced
+// on big targets (16 samples on a 4K window would need a 16K texture). It can end up being 1,
+// without msaa.
+pub fn clamped_supersample_scale(samples: u32, width: u32, height: u32, max_size: u32) -> u32 {
+    let size = width.max(height).max(1);
+    supersample_scale(samples).min(max_size / size).max(1)
+}
+
+// Size of the textures of a target, notan can't create empty textures
+fn texture_size(width: u32, height: u32) -> (u32, u32) {
+    (width.max(1), height.max(1))
+}
+
+fn needs_resize(current: (u32, u32), width: u32, height: u32) -> bool {
+    texture_size(width, height) != current
+}
+
+// Offscreen framebuffer for the passes that render to a texture (post-processing, shadows,
+// mirrors...). Each color attachment is filled by its own pass with `render`, and their
+// textures are sampled later with `texture`. The depth attachment can't be sampled, it's only
+// used for the depth test of the passes.
+pub struct RenderTarget {
+    options: RenderTargetOptions,
+    width: u32,
+    height: u32,
+    // supersampling of the current size, 1 without msaa
+    scale: u32,
+    // where the passes are rendered, `scale` times bigger with msaa
+    attachments: Vec<RenderTexture>,
+    // the averaged samples of each attachment, only with msaa
+    resolved: Vec<RenderTexture>,
+    resolver: Option<Resolver>,
+}
+
+impl RenderTarget {
+    pub fn new(
+        gfx: &mut Graphics,
+        width: u32,
+        height: u32,
+        options: RenderTargetOptions,
+    ) -> Result<Self, String> {
+        options.validate()?;
+
+        let resolver = if supersample_scale(options.samples) > 1 {
+            Some(Resolver::new(gfx)?)
+        } else {
+            None
+        };
+
+        let mut target = Self {
+            options,
+            width: 0,
+            height: 0,
+            scale: 1,
+            attachments: vec![],
+            resolved: vec![],
+            resolver,
+        };
+        target.create_textures(gfx, width, height)?;
+        Ok(target)
+    }
+
+    // A target of the size of the screen, use `fit_window` to keep it that way
+    pub fn from_window(gfx: &mut Graphics, options: RenderTargetOptions) -> Result<Self, String> {
+        let (width, height) = gfx.size();
+        Self::new(gfx, width as _, height as _, options)
+    }
+
+    fn create_textures(
+        &mut self,
+        gfx: &mut Graphics,
+        width: u32,
+        height: u32,
+    ) -> Result<(), String> {
+        let (width, height) = texture_size(width, height);
+        let max_size = gfx.limits().max_texture_size;
+        let scale = clamped_supersample_scale(self.options.samples, width, height, max_size);
+        if scale < supersample_scale(self.options.samples) {
+            notan::log::warn!(
+                "The msaa of the {}x{} render target is reduced to {}x{} samples",
+                width,
+                height,
+                scale,
+                scale
+            );
+        }
+        let filter = self.options.filter;
+
+        let create = |gfx: &mut Graphics, format: ColorFormat, scale: u32, depth: bool| {
+            let builder = gfx
+                .create_render_texture((width * scale) as _, (height * scale) as _)
+                .with_format(format.texture_format())
+                .with_filter(filter, filter);
+
+            if depth {
+                builder.with_depth().build()
+            } else {
+                builder.build()
+            }
+        };
+
+        let mut attachments = vec![];
+        let mut resolved = vec![];
+        for format in self.options.colors.iter() {
+            attachments.push(create(gfx, *format, scale, self.options.depth)?);
+            if scale > 1 {
+                resolved.push(create(gfx, *format, 1, false)?);
+            }
+        }
+
+        self.width = width;
+        self.height = height;
+        self.scale = scale;
+        self.attachments = attachments;
+        self.resolved = resolved;
+        Ok(())
+    }
+
+    // Creates the textures again if the size changed, returns true if they were
+    pub fn resize(&mut self, gfx: &mut Graphics, width: u32, height: u32) -> Result<bool, String> {
+        if !needs_resize(self.size(), width, height) {
+            return Ok(false);
+        }
+
+        self.create_textures(gfx, width, height)?;
+        Ok(true)
+    }
+
+    // Call it before rendering each frame to follow the size of the window
+    pub fn fit_window(&mut self, gfx: &mut Graphics) -> Result<bool, String> {
+        let (width, height) = gfx.size();
+        self.resize(gfx, width as _, height as _)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    pub fn options(&self) -> &RenderTargetOptions {
+        &self.options
+    }
+
+    pub fn attachment_count(&self) -> usize {
+        self.attachments.len()
+    }
+
+    // Like `present` but to the color attachment `index` instead of the screen
+    pub fn render(&self, gfx: &mut Graphics, index: usize, renderer: &StatsRenderer) {
+        add_frame_stats(renderer.stats());
+
+        let renderer: &Renderer = renderer;
+        gfx.render_to(&self.attachments[index], renderer);
+    }
+
+    // Averages the samples of every attachment, it does nothing without msaa. Call it after
+    // the passes and before sampling the textures.
+    pub fn resolve(&self, gfx: &mut Graphics) {
+        if let Some(resolver) = &self.resolver {
+            let scale = self.scale;
+            self.attachments
+                .iter()
+                .zip(self.resolved.iter())
+                .for_each(|(samples, target)| {
+                    resolver.resolve(gfx, samples.texture(), target, scale)
+                });
+        }
+    }
+
+    // Color texture of the attachment `index` for the next passes
+    pub fn texture(&self, index: usize) -> &Texture {
+        if self.resolved.is_empty() {
+            self.attachments[index].texture()
+        } else {
+            self.resolved[index].texture()
+        }
+    }
+}
+
+// Fullscreen pass that averages the samples
+struct Resolver {
+    pipeline: Pipeline,
+    vbo: Buffer,
+    ubo: Buffer,
+}
+
+impl Resolver {
+    fn new(gfx: &mut Graphics) -> Result<Self, String> {
+        let vertex_info = VertexInfo::new().attr(0, VertexFormat::Float32x2); // positions
+
+        let pipeline = gfx
+            .create_pipeline()
+            .from(&RESOLVE_VERTEX_SHADER, &RESOLVE_FRAGMENT_SHADER)
+            .with_vertex_info(&vertex_info)
+            .with_texture_location(0, "samples")
+            .build()?;
+
+        #[rustfmt::skip]
+        let vertices = [
+            -1.0,  1.0,
+            -1.0, -1.0,
+            1.0, -1.0,
+
+            -1.0,  1.0,
+            1.0, -1.0,
+            1.0,  1.0,
+        ];
+
+        let vbo = gfx
+            .create_vertex_buffer()
+            .with_data(&vertices)
+            .with_info(&vertex_info)
+            .build()?;
+
+        let ubo = gfx.create_uniform_buffer(0, "ResolveData").build()?;
+
+        Ok(Self { pipeline, vbo, ubo })
+    }
+
+    fn resolve(&self, gfx: &mut Graphics, samples: &Texture, target: &RenderTexture, scale: u32) {
+        set_buffer_data!(
+            gfx,
+            &self.ubo,
+            &ResolveData {
+                scale: scale as f32
+            }
+        );
+
+        let mut renderer = StatsRenderer::new(gfx);
+        renderer.begin(None);
+        renderer.set_pipeline(&self.pipeline);
+        renderer.bind_buffers(&[&self.vbo, &self.ubo]);
+        renderer.bind_texture(0, samples);
+        renderer.draw(0, 6);
+        renderer.end();
+
+        add_frame_stats(renderer.stats());
+
+        let renderer: &Renderer = &renderer;
+        gfx.render_to(target, renderer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supersample_scale_has_enough_samples() {
+        assert_eq!(supersample_scale(0), 1);
+        assert_eq!(supersample_scale(1), 1);
+        assert_eq!(supersample_scale(2), 2);
+        assert_eq!(supersample_scale(4), 2);
+        assert_eq!(supersample_scale(8), 3);
+        assert_eq!(supersample_scale(16), 4);
+    }
+
+    #[test]
+    fn supersample_scale_fits_the_max_texture_size() {
+        assert_eq!(clamped_supersample_scale(4, 1920, 1080, 8192), 2);
+        assert_eq!(clamped_supersample_scale(16, 1920, 1080, 8192), 4);
+        assert_eq!(clamped_supersample_scale(16, 3840, 2160, 8192), 2);
+        assert_eq!(clamped_supersample_scale(4, 3840, 2160, 4096), 1);
+        assert_eq!(clamped_supersample_scale(0, 0, 0, 4096), 1);
+    }
+
+    #[test]
+    fn resizing_to_the_same_size_does_nothing() {
+        assert!(!needs_resize((800, 600), 800, 600));
+        assert!(needs_resize((800, 600), 800, 601));
+        assert!(needs_resize((800, 600), 600, 800));
+        // the textures are at least 1x1, a minimized window keeps them
+        assert_eq!(texture_size(0, 0), (1, 1));
+        assert!(!needs_resize((1, 1), 0, 0));
+        assert!(!needs_resize((1, 1), 1, 0));
+    }
+
+    #[test]
+    fn options_are_validated() {
+        assert!(RenderTargetOptions::default().validate().is_ok());
+        assert_eq!(
+            RenderTargetOptions::default().with_colors(&[]).validate(),
+            Err("A render target needs at least one color attachment".to_string())
+        );
+        assert!(RenderTargetOptions::default()
+            .with_colors(&[ColorFormat::Rgba8, ColorFormat::Rgba16F])
+            .validate()
+            .is_ok());
+        assert!(matches!(
+            ColorFormat::Rgba16F.texture_format(),
+            TextureFormat::Rgba32Float
+        ));
+    }
+}
@@ -0,0 +1,762 @@
+use super::camera::Camera;
+use super::raster::{DirLight, Lights, PointLight, SpotLight};
+use notan::math::{Mat4, Vec3};
+use notan::prelude::*;
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::cell::RefCell;
+
+// Scenes of the lighting examples described in json: clear color, camera, materials,
+// objects and lights. `Scene::parse` validates the file reporting the line of the errors,
+// and `Scene::to_json` writes the scene back, so the changes of the tweak panel can be saved.
+//
+// {
+//   "clear_color": [0.1, 0.1, 0.1, 1.0],
+//   "camera": { "position": [0.0, 0.0, 3.0] },
+//   "materials": [
+//     { "name": "container", "diffuse": "textures/container2.png",
+//       "specular": "textures/container2_specular.png", "shininess": 32.0 }
+//   ],
+//   "objects": [
+//     { "mesh": "cube", "material": "container", "position": [2.0, 5.0, -15.0],
+//       "rotation": { "axis": [1.0, 0.3, 0.5], "degrees": 20.0 } }
+//   ],
+//   "dir_light": { "direction": [-0.2, -1.0, -0.3] },
+//   "point_lights": [{ "position": [0.7, 0.2, 2.0] }],
+//   "spot_light": { "cut_off": 12.5, "outer_cut_off": 15.0, "follow_camera": true }
+// }
+
+// Size of the point lights array of the multiple lights shader (`NR_POINT_LIGHTS`)
+pub const MAX_POINT_LIGHTS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SceneMesh {
+    // the 36 vertices cube of the examples
+    Cube,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SceneCamera {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub zoom: f32,
+}
+
+impl SceneCamera {
+    pub fn to_camera(&self) -> Camera {
+        let mut camera = Camera {
+            position: self.position,
+            zoom: self.zoom,
+            ..Default::default()
+        };
+        camera.set_angles(self.yaw, self.pitch);
+        camera
+    }
+
+    pub fn from_camera(camera: &Camera) -> Self {
+        Self {
+            position: camera.position,
+            yaw: camera.yaw,
+            pitch: camera.pitch,
+            zoom: camera.zoom,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneMaterial {
+    pub name: String,
+    // paths relative to the resources folder
+    pub diffuse: String,
+    pub specular: String,
+    pub shininess: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SceneObject {
+    pub mesh: SceneMesh,
+    // index of `Scene::materials`
+    pub material: usize,
+    pub position: Vec3,
+    pub rotation_axis: Vec3,
+    pub rotation_degrees: f32,
+    pub scale: Vec3,
+}
+
+impl SceneObject {
+    pub fn model(&self) -> Mat4 {
+        let translation = Mat4::from_translation(self.position);
+        let rotation = Mat4::from_axis_angle(
+            self.rotation_axis.normalize(),
+            self.rotation_degrees.to_radians(),
+        );
+        translation * rotation * Mat4::from_scale(self.scale)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Scene {
+    pub clear_color: Color,
+    pub camera: SceneCamera,
+    pub materials: Vec<SceneMaterial>,
+    pub objects: Vec<SceneObject>,
+    // same data than the uniforms of the lighting shaders
+    pub lights: Lights,
+    // the spot light takes the position and direction of the camera on each frame
+    pub spot_light_follows_camera: bool,
+}
+
+impl Scene {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("Cannot read '{}': {}", path, err))?;
+        Self::parse(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        // the first pass checks the syntax and values and collects the names of the
+        // materials, the second one checks the references to them
+        let file: SceneFile = serde_json::from_str(text).map_err(scene_error)?;
+        let names = file
+            .materials
+            .iter()
+            .map(|material| material.name.clone())
+            .collect::<Vec<_>>();
+
+        MATERIAL_NAMES.with(|current| *current.borrow_mut() = Some(names.clone()));
+        let result = serde_json::from_str::<SceneFile>(text);
+        MATERIAL_NAMES.with(|current| *current.borrow_mut() = None);
+        let file = result.map_err(scene_error)?;
+
+        Ok(file.into_scene(&names))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+        }
+
+        std::fs::write(path, self.to_json())
+            .map_err(|err| format!("Cannot write '{}': {}", path, err))
+    }
+
+    pub fn to_json(&self) -> String {
+        // the file types only contain numbers and strings, so this can't fail
+        serde_json::to_string_pretty(&SceneFile::from_scene(self)).unwrap()
+    }
+
+    pub fn material_index(&self, name: &str) -> Option<usize> {
+        self.materials
+            .iter()
+            .position(|material| material.name == name)
+    }
+}
+
+// serde_json adds the position at the end of the message
+fn scene_error(err: serde_json::Error) -> String {
+    let message = err.to_string();
+    let position = format!(" at line {} column {}", err.line(), err.column());
+    let message = message.strip_suffix(&position).unwrap_or(&message);
+    format!("line {}: {}", err.line(), message)
+}
+
+thread_local! {
+    // names of the materials during the second pass of `Scene::parse`
+    static MATERIAL_NAMES: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+// Layout of the file, the angles are in degrees and the vectors are arrays
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneFile {
+    #[serde(default = "default_clear_color")]
+    clear_color: [f32; 4],
+    #[serde(default)]
+    camera: CameraFile,
+    #[serde(default)]
+    materials: Vec<MaterialFile>,
+    #[serde(default)]
+    objects: Vec<ObjectFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    dir_light: Option<DirLightFile>,
+    #[serde(default, deserialize_with = "point_lights")]
+    point_lights: Vec<PointLightFile>,
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "spot_light"
+    )]
+    spot_light: Option<SpotLightFile>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraFile {
+    position: [f32; 3],
+    #[serde(default = "default_yaw")]
+    yaw: f32,
+    #[serde(default)]
+    pitch: f32,
+    #[serde(default = "default_zoom", deserialize_with = "zoom")]
+    zoom: f32,
+}
+
+impl Default for CameraFile {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 0.0, 3.0],
+            yaw: default_yaw(),
+            pitch: 0.0,
+            zoom: default_zoom(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialFile {
+    #[serde(deserialize_with = "material_name")]
+    name: String,
+    diffuse: String,
+    specular: String,
+    #[serde(deserialize_with = "positive")]
+    shininess: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObjectFile {
+    mesh: SceneMesh,
+    #[serde(deserialize_with = "material_ref")]
+    material: String,
+    position: [f32; 3],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rotation: Option<RotationFile>,
+    #[serde(default = "default_scale")]
+    scale: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RotationFile {
+    #[serde(deserialize_with = "axis")]
+    axis: [f32; 3],
+    degrees: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DirLightFile {
+    #[serde(deserialize_with = "axis")]
+    direction: [f32; 3],
+    #[serde(default = "default_dir_ambient")]
+    ambient: [f32; 3],
+    #[serde(default = "default_dir_diffuse")]
+    diffuse: [f32; 3],
+    #[serde(default = "default_dir_specular")]
+    specular: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AttenuationFile {
+    #[serde(deserialize_with = "positive")]
+    constant: f32,
+    linear: f32,
+    quadratic: f32,
+}
+
+impl Default for AttenuationFile {
+    // distance of 50 units in the table of the light casters chapter
+    fn default() -> Self {
+        Self {
+            constant: 1.0,
+            linear: 0.09,
+            quadratic: 0.032,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PointLightFile {
+    position: [f32; 3],
+    #[serde(default)]
+    attenuation: AttenuationFile,
+    #[serde(default = "default_point_ambient")]
+    ambient: [f32; 3],
+    #[serde(default = "default_light_diffuse")]
+    diffuse: [f32; 3],
+    #[serde(default = "default_light_specular")]
+    specular: [f32; 3],
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpotLightFile {
+    #[serde(default)]
+    position: [f32; 3],
+    #[serde(default = "default_spot_direction", deserialize_with = "axis")]
+    direction: [f32; 3],
+    #[serde(default)]
+    follow_camera: bool,
+    #[serde(deserialize_with = "spot_angle")]
+    cut_off: f32,
+    #[serde(deserialize_with = "spot_angle")]
+    outer_cut_off: f32,
+    #[serde(default)]
+    attenuation: AttenuationFile,
+    #[serde(default)]
+    ambient: [f32; 3],
+    #[serde(default = "default_light_diffuse")]
+    diffuse: [f32; 3],
+    #[serde(default = "default_light_specular")]
+    specular: [f32; 3],
+}
+
+// defaults of the multiple lights example
+
+fn default_clear_color() -> [f32; 4] {
+    [0.1, 0.1, 0.1, 1.0]
+}
+
+fn default_yaw() -> f32 {
+    -90.0
+}
+
+fn default_zoom() -> f32 {
+    45.0
+}
+
+fn default_scale() -> [f32; 3] {
+    [1.0; 3]
+}
+
+fn default_dir_ambient() -> [f32; 3] {
+    [0.05; 3]
+}
+
+fn default_dir_diffuse() -> [f32; 3] {
+    [0.4; 3]
+}
+
+fn default_dir_specular() -> [f32; 3] {
+    [0.5; 3]
+}
+
+fn default_point_ambient() -> [f32; 3] {
+    [0.05; 3]
+}
+
+fn default_light_diffuse() -> [f32; 3] {
+    [0.8; 3]
+}
+
+fn default_light_specular() -> [f32; 3] {
+    [1.0; 3]
+}
+
+fn default_spot_direction() -> [f32; 3] {
+    [0.0, 0.0, -1.0]
+}
+
+// validations, the errors returned here get the position of the value
+
+fn positive<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let value = f32::deserialize(deserializer)?;
+    if value <= 0.0 {
+        return Err(serde::de::Error::custom(format!(
+            "{} must be greater than 0",
+            value
+        )));
+    }
+    Ok(value)
+}
+
+fn zoom<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let value = f32::deserialize(deserializer)?;
+    if !(1.0..=45.0).contains(&value) {
+        return Err(serde::de::Error::custom(format!(
+            "zoom {} must be between 1 and 45",
+            value
+        )));
+    }
+    Ok(value)
+}
+
+fn spot_angle<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let value = f32::deserialize(deserializer)?;
+    if !(0.0..90.0).contains(&value) {
+        return Err(serde::de::Error::custom(format!(
+            "angle {} must be between 0 and 90 degrees",
+            value
+        )));
+    }
+    Ok(value)
+}
+
+// The angles are in degrees, so the inner cone is the smaller one. The check runs while
+// the spot light object is read, this way serde_json reports its line and not the end of
+// the scene
+fn spot_light<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<SpotLightFile>, D::Error> {
+    struct SpotLightVisitor;
+
+    impl<'de> Visitor<'de> for SpotLightVisitor {
+        type Value = Option<SpotLightFile>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a spot light")
+        }
+
+        fn visit_none<E: serde::de::Error>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(self, d: D) -> Result<Self::Value, D::Error> {
+            d.deserialize_map(self)
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            let light = SpotLightFile::deserialize(MapAccessDeserializer::new(map))?;
+            if light.cut_off > light.outer_cut_off {
+                return Err(serde::de::Error::custom(format!(
+                    "the spot light cut_off {} is larger than its outer_cut_off {}",
+                    light.cut_off, light.outer_cut_off
+                )));
+            }
+            Ok(Some(light))
+        }
+    }
+
+    deserializer.deserialize_option(SpotLightVisitor)
+}
+
+fn axis<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[f32; 3], D::Error> {
+    let value = <[f32; 3]>::deserialize(deserializer)?;
+    if Vec3::from(value).length_squared() == 0.0 {
+        return Err(serde::de::Error::custom("the vector can't be zero"));
+    }
+    Ok(value)
+}
+
+fn point_lights<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<PointLightFile>, D::Error> {
+    let lights = Vec::<PointLightFile>::deserialize(deserializer)?;
+    if lights.len() > MAX_POINT_LIGHTS {
+        return Err(serde::de::Error::custom(format!(
+            "{} point lights, the shader supports up to {}",
+            lights.len(),
+            MAX_POINT_LIGHTS
+        )));
+    }
+    Ok(lights)
+}
+
+fn material_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    let count = MATERIAL_NAMES.with(|names| {
+        names
+            .borrow()
+            .as_ref()
+            .map_or(0, |names| names.iter().filter(|n| **n == name).count())
+    });
+
+    if count > 1 {
+        return Err(serde::de::Error::custom(format!(
+            "material '{}' is declared more than once",
+            name
+        )));
+    }
+    Ok(name)
+}
+
+fn material_ref<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    // any name is valid during the first pass
+    let known = MATERIAL_NAMES.with(|names| match names.borrow().as_ref() {
+        Some(names) => names.contains(&name),
+        None => true,
+    });
+
+    if !known {
+        return Err(serde::de::Error::custom(format!(
+            "unknown material '{}'",
+            name
+        )));
+    }
+    Ok(name)
+}
+
+impl SceneFile {
+    // the materials are validated by `Scene::parse`
+    fn into_scene(self, names: &[String]) -> Scene {
+        let [r, g, b, a] = self.clear_color;
+        let camera = SceneCamera {
+            position: self.camera.position.into(),
+            yaw: self.camera.yaw,
+            pitch: self.camera.pitch.clamp(-89.0, 89.0),
+            zoom: self.camera.zoom,
+        };
+
+        let materials = self
+            .materials
+            .into_iter()
+            .map(|material| SceneMaterial {
+                name: material.name,
+                diffuse: material.diffuse,
+                specular: material.specular,
+                shininess: material.shininess,
+            })
+            .collect();
+
+        let objects = self
+            .objects
+            .into_iter()
+            .map(|object| {
+                let (axis, degrees) = object.rotation.map_or(([0.0, 1.0, 0.0], 0.0), |rotation| {
+                    (rotation.axis, rotation.degrees)
+                });
+
+                SceneObject {
+                    mesh: object.mesh,
+                    material: names
+                        .iter()
+                        .position(|name| *name == object.material)
+                        .unwrap_or_default(),
+                    position: object.position.into(),
+                    rotation_axis: axis.into(),
+                    rotation_degrees: degrees,
+                    scale: object.scale.into(),
+                }
+            })
+            .collect();
+
+        let spot_light_follows_camera = matches!(&self.spot_light, Some(l) if l.follow_camera);
+        let lights = Lights {
+            dir_light: self.dir_light.map(|light| DirLight {
+                direction: light.direction.into(),
+                ambient: light.ambient.into(),
+                diffuse: light.diffuse.into(),
+                specular: light.specular.into(),
+            }),
+            point_lights: self
+                .point_lights
+                .into_iter()
+                .map(|light| PointLight {
+                    position: light.position.into(),
+                    constant: light.attenuation.constant,
+                    linear: light.attenuation.linear,
+                    quadratic: light.attenuation.quadratic,
+                    ambient: light.ambient.into(),
+                    diffuse: light.diffuse.into(),
+                    specular: light.specular.into(),
+                })
+                .collect(),
+            spot_light: self.spot_light.map(|light| SpotLight {
+                position: light.position.into(),
+                direction: light.direction.into(),
+                cut_off: light.cut_off.to_radians().cos(),
+                outer_cut_off: light.outer_cut_off.to_radians().cos(),
+                constant: light.attenuation.constant,
+                linear: light.attenuation.linear,
+                quadratic: light.attenuation.quadratic,
+                ambient: light.ambient.into(),
+                diffuse: light.diffuse.into(),
+                specular: light.specular.into(),
+            }),
+        };
+
+        Scene {
+            clear_color: Color::new(r, g, b, a),
+            camera,
+            materials,
+            objects,
+            lights,
+            spot_light_follows_camera,
+        }
+    }
+
+    fn from_scene(scene: &Scene) -> Self {
+        let color = scene.clear_color;
+        let camera = CameraFile {
+            position: scene.camera.position.into(),
+            yaw: scene.camera.yaw,
+            pitch: scene.camera.pitch,
+            zoom: scene.camera.zoom,
+        };
+
+        let materials = scene
+            .materials
+            .iter()
+            .map(|material| MaterialFile {
+                name: material.name.clone(),
+                diffuse: material.diffuse.clone(),
+                specular: material.specular.clone(),
+                shininess: material.shininess,
+            })
+            .collect();
+
+        let objects = scene
+            .objects
+            .iter()
+            .map(|object| ObjectFile {
+                mesh: object.mesh,
+                material: scene
+                    .materials
+                    .get(object.material)
+                    .map(|material| material.name.clone())
+                    .unwrap_or_default(),
+                position: object.position.into(),
+                rotation: (object.rotation_degrees != 0.0).then(|| RotationFile {
+                    axis: object.rotation_axis.into(),
+                    degrees: object.rotation_degrees,
+                }),
+                scale: object.scale.into(),
+            })
+            .collect();
+
+        let lights = &scene.lights;
+        Self {
+            clear_color: [color.r, color.g, color.b, color.a],
+            camera,
+            materials,
+            objects,
+            dir_light: lights.dir_light.map(|light| DirLightFile {
+                direction: light.direction.into(),
+                ambient: light.ambient.into(),
+                diffuse: light.diffuse.into(),
+                specular: light.specular.into(),
+            }),
+            point_lights: lights
+                .point_lights
+                .iter()
+                .map(|light| PointLightFile {
+                    position: light.position.into(),
+                    attenuation: AttenuationFile {
+                        constant: light.constant,
+                        linear: light.linear,
+                        quadratic: light.quadratic,
+                    },
+                    ambient: light.ambient.into(),
+                    diffuse: light.diffuse.into(),
+                    specular: light.specular.into(),
+                })
+                .collect(),
+            spot_light: lights.spot_light.map(|light| SpotLightFile {
+                position: light.position.into(),
+                direction: light.direction.into(),
+                follow_camera: scene.spot_light_follows_camera,
+                cut_off: cos_to_degrees(light.cut_off),
+                outer_cut_off: cos_to_degrees(light.outer_cut_off),
+                attenuation: AttenuationFile {
+                    constant: light.constant,
+                    linear: light.linear,
+                    quadratic: light.quadratic,
+                },
+                ambient: light.ambient.into(),
+                diffuse: light.diffuse.into(),
+                specular: light.specular.into(),
+            }),
+        }
+    }
+}
+
+fn cos_to_degrees(cos: f32) -> f32 {
+    cos.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENE: &str = r#"{
+  "camera": { "position": [0.0, 1.0, 5.0], "pitch": -10.0 },
+  "materials": [
+    { "name": "container", "diffuse": "textures/container2.png",
+      "specular": "textures/container2_specular.png", "shininess": 32.0 }
+  ],
+  "objects": [
+    { "mesh": "cube", "material": "container", "position": [0.0, 0.0, 0.0] },
+    { "mesh": "cube", "material": "container", "position": [2.0, 5.0, -15.0],
+      "rotation": { "axis": [1.0, 0.3, 0.5], "degrees": 20.0 } }
+  ],
+  "dir_light": { "direction": [-0.2, -1.0, -0.3] },
+  "point_lights": [{ "position": [0.7, 0.2, 2.0] }],
+  "spot_light": { "cut_off": 12.5, "outer_cut_off": 15.0, "follow_camera": true }
+}"#;
+
+    #[test]
+    fn scene_builds_the_uniform_data() {
+        let scene = Scene::parse(SCENE).unwrap();
+        assert_eq!(scene.camera.position, Vec3::new(0.0, 1.0, 5.0));
+        assert_eq!(scene.camera.yaw, -90.0);
+        assert_eq!(scene.objects.len(), 2);
+        assert_eq!(scene.objects[1].material, 0);
+        assert_eq!(scene.objects[1].rotation_degrees, 20.0);
+
+        let point = scene.lights.point_lights[0];
+        assert_eq!(point.position, Vec3::new(0.7, 0.2, 2.0));
+        assert_eq!(
+            (point.constant, point.linear, point.quadratic),
+            (1.0, 0.09, 0.032)
+        );
+
+        let spot = scene.lights.spot_light.unwrap();
+        assert_eq!(spot.cut_off, 12.5_f32.to_radians().cos());
+        assert!(scene.spot_light_follows_camera);
+    }
+
+    #[test]
+    fn errors_have_the_line() {
+        let err = Scene::parse(&SCENE.replace("[2.0, 5.0", "[2.0, true")).unwrap_err();
+        assert!(err.starts_with("line 9: invalid type: boolean"), "{}", err);
+
+        let err = Scene::parse(&SCENE.replace(
+            "\"container\", \"position\": [2.0",
+            "\"crate\", \"position\": [2.0",
+        ))
+        .unwrap_err();
+        assert_eq!(err, "line 9: unknown material 'crate'");
+
+        let err = Scene::parse(&SCENE.replace("32.0", "0.0")).unwrap_err();
+        assert_eq!(err, "line 5: 0 must be greater than 0");
+
+        let err = Scene::parse(&SCENE.replace("\"cube\"", "\"sphere\"")).unwrap_err();
+        assert!(
+            err.starts_with("line 8: unknown variant `sphere`"),
+            "{}",
+            err
+        );
+
+        let err = Scene::parse(&SCENE.replace("\"pitch\"", "\"roll\"")).unwrap_err();
+        assert!(err.starts_with("line 2: unknown field `roll`"), "{}", err);
+
+        let light = "{ \"position\": [0.7, 0.2, 2.0] }";
+        let lights = format!("{}, {}, {}, {}, {}", light, light, light, light, light);
+        let err = Scene::parse(&SCENE.replace(light, &lights)).unwrap_err();
+        assert_eq!(err, "line 13: 5 point lights, the shader supports up to 4");
+
+        let err = Scene::parse(&SCENE.replace("15.0", "10.0")).unwrap_err();
+        assert_eq!(
+            err,
+            "line 14: the spot light cut_off 12.5 is larger than its outer_cut_off 10"
+        );
+    }
+
+    #[test]
+    fn saved_scene_is_the_same() {
+        let scene = Scene::parse(SCENE).unwrap();
+        let saved = Scene::parse(&scene.to_json()).unwrap();
+        assert_eq!(saved.to_json(), scene.to_json());
+        assert_eq!(saved.objects, scene.objects);
+        assert_eq!(saved.materials, scene.materials);
+        assert_eq!(saved.camera, scene.camera);
+    }
+}
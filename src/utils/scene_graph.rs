@@ -0,0 +1,381 @@
+use notan::math::{Mat3, Mat4, Quat, Vec3};
+
+// Hierarchy of nodes with a transform relative to their parent, like an orbiting light
+// attached to a rotating pivot or the nodes of a glTF file. The world matrices are cached,
+// changing a node marks it and its descendants as dirty and `update` recomputes them.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LocalTransform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Default for LocalTransform {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+impl LocalTransform {
+    pub const IDENTITY: Self = Self {
+        translation: Vec3::ZERO,
+        rotation: Quat::IDENTITY,
+        scale: Vec3::ONE,
+    };
+
+    pub fn from_translation(translation: Vec3) -> Self {
+        Self {
+            translation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_rotation(rotation: Quat) -> Self {
+        Self {
+            rotation,
+            ..Self::IDENTITY
+        }
+    }
+
+    pub fn from_scale(scale: Vec3) -> Self {
+        Self {
+            scale,
+            ..Self::IDENTITY
+        }
+    }
+
+    // glTF nodes can store a matrix instead of the TRS values
+    pub fn from_matrix(matrix: Mat4) -> Self {
+        let (scale, rotation, translation) = matrix.to_scale_rotation_translation();
+        Self {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn with_translation(mut self, translation: Vec3) -> Self {
+        self.translation = translation;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: Quat) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_scale(mut self, scale: Vec3) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    // translation * rotation * scale, the same order than the examples
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+#[derive(Clone, Debug)]
+struct Node {
+    name: String,
+    local: LocalTransform,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    world: Mat4,
+    normal: Mat3,
+    dirty: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SceneGraph {
+    nodes: Vec<Node>,
+}
+
+impl SceneGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_node(
+        &mut self,
+        name: &str,
+        local: LocalTransform,
+        parent: Option<NodeId>,
+    ) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            name: name.to_string(),
+            local,
+            parent,
+            children: vec![],
+            world: Mat4::IDENTITY,
+            normal: Mat3::IDENTITY,
+            dirty: true,
+        });
+
+        if let Some(parent) = parent {
+            self.nodes[parent.0].children.push(id);
+        }
+
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn find(&self, name: &str) -> Option<NodeId> {
+        self.nodes
+            .iter()
+            .position(|node| node.name == name)
+            .map(NodeId)
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.nodes[id.0].name
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].parent
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id.0].children
+    }
+
+    // nodes without a parent
+    pub fn roots(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.parent.is_none())
+            .map(|(i, _)| NodeId(i))
+    }
+
+    // Moves the node and its descendants under another parent, keeping the local transform
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) -> Result<(), String> {
+        if let Some(parent) = parent {
+            let mut ancestor = Some(parent);
+            while let Some(current) = ancestor {
+                if current == id {
+                    return Err(format!(
+                        "Node '{}' can't be a child of its descendant '{}'",
+                        self.name(id),
+                        self.name(parent)
+                    ));
+                }
+                ancestor = self.parent(current);
+            }
+        }
+
+        if let Some(old) = self.nodes[id.0].parent {
+            self.nodes[old.0].children.retain(|child| *child != id);
+        }
+        if let Some(parent) = parent {
+            self.nodes[parent.0].children.push(id);
+        }
+
+        self.nodes[id.0].parent = parent;
+        self.mark_dirty(id);
+        Ok(())
+    }
+
+    pub fn local(&self, id: NodeId) -> &LocalTransform {
+        &self.nodes[id.0].local
+    }
+
+    pub fn set_local(&mut self, id: NodeId, local: LocalTransform) {
+        self.nodes[id.0].local = local;
+        self.mark_dirty(id);
+    }
+
+    // Changes the local transform in place, like `graph.update_local(id, |t| t.rotation = q)`
+    pub fn update_local(&mut self, id: NodeId, f: impl FnOnce(&mut LocalTransform)) {
+        f(&mut self.nodes[id.0].local);
+        self.mark_dirty(id);
+    }
+
+    // The node needs `update` to refresh its matrices
+    pub fn is_dirty(&self, id: NodeId) -> bool {
+        self.nodes[id.0].dirty
+    }
+
+    fn mark_dirty(&mut self, id: NodeId) {
+        let mut stack = vec![id];
+        while let Some(current) = stack.pop() {
+            let node = &mut self.nodes[current.0];
+            // the descendants of a dirty node are already dirty
+            if node.dirty && current != id {
+                continue;
+            }
+
+            node.dirty = true;
+            stack.extend_from_slice(&node.children);
+        }
+    }
+
+    // Recomputes the world and normal matrices of the dirty nodes, parents first
+    pub fn update(&mut self) {
+        let mut stack = self
+            .roots()
+            .map(|id| (id, Mat4::IDENTITY))
+            .collect::<Vec<_>>();
+
+        while let Some((id, parent_world)) = stack.pop() {
+            let node = &mut self.nodes[id.0];
+            if node.dirty {
+                node.world = parent_world * node.local.matrix();
+                node.normal = Mat3::from_mat4(node.world).inverse().transpose();
+                node.dirty = false;
+            }
+
+            let world = node.world;
+            stack.extend(node.children.iter().map(|child| (*child, world)));
+        }
+    }
+
+    // Model matrix of the node, it's the last computed by `update`
+    pub fn world_matrix(&self, id: NodeId) -> Mat4 {
+        self.nodes[id.0].world
+    }
+
+    // transpose(inverse(model)) for the normals, like the `Normal` of the lighting shaders
+    pub fn normal_matrix(&self, id: NodeId) -> Mat3 {
+        self.nodes[id.0].normal
+    }
+
+    pub fn world_position(&self, id: NodeId) -> Vec3 {
+        self.nodes[id.0].world.w_axis.truncate()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn assert_vec3_eq(a: Vec3, b: Vec3) {
+        assert!(a.abs_diff_eq(b, 1e-5), "{} != {}", a, b);
+    }
+
+    #[test]
+    fn transforms_are_propagated_to_the_children() {
+        let mut graph = SceneGraph::new();
+        let pivot = graph.add_node(
+            "pivot",
+            LocalTransform::from_translation(Vec3::new(0.0, 1.0, 0.0)),
+            None,
+        );
+        let light = graph.add_node(
+            "light",
+            LocalTransform::from_translation(Vec3::new(2.0, 0.0, 0.0)),
+            Some(pivot),
+        );
+        let lamp = graph.add_node(
+            "lamp",
+            LocalTransform::from_scale(Vec3::splat(0.2)),
+            Some(light),
+        );
+        graph.update();
+        assert_vec3_eq(graph.world_position(lamp), Vec3::new(2.0, 1.0, 0.0));
+
+        // a quarter orbit around the y axis moves the light from +x to -z
+        graph.update_local(pivot, |t| t.rotation = Quat::from_rotation_y(FRAC_PI_2));
+        assert!(graph.is_dirty(light) && graph.is_dirty(lamp));
+        graph.update();
+        assert_vec3_eq(graph.world_position(light), Vec3::new(0.0, 1.0, -2.0));
+        assert_vec3_eq(graph.world_position(lamp), Vec3::new(0.0, 1.0, -2.0));
+
+        let corner = graph.world_matrix(lamp).transform_point3(Vec3::X);
+        assert_vec3_eq(corner, Vec3::new(0.0, 1.0, -2.2));
+    }
+
+    #[test]
+    fn only_the_changed_branch_is_dirty() {
+        let mut graph = SceneGraph::new();
+        let root = graph.add_node("root", LocalTransform::IDENTITY, None);
+        let left = graph.add_node("left", LocalTransform::IDENTITY, Some(root));
+        let right = graph.add_node("right", LocalTransform::IDENTITY, Some(root));
+        let leaf = graph.add_node("leaf", LocalTransform::IDENTITY, Some(left));
+        graph.update();
+        assert!(!graph.is_dirty(root) && !graph.is_dirty(leaf));
+
+        graph.set_local(left, LocalTransform::from_translation(Vec3::Z));
+        assert!(!graph.is_dirty(root));
+        assert!(!graph.is_dirty(right));
+        assert!(graph.is_dirty(left) && graph.is_dirty(leaf));
+
+        graph.update();
+        assert_vec3_eq(graph.world_position(leaf), Vec3::Z);
+        assert_vec3_eq(graph.world_position(right), Vec3::ZERO);
+    }
+
+    #[test]
+    fn reparenting_keeps_the_local_transform() {
+        let mut graph = SceneGraph::new();
+        let a = graph.add_node("a", LocalTransform::from_translation(Vec3::X), None);
+        let b = graph.add_node("b", LocalTransform::from_translation(Vec3::Y), None);
+        let child = graph.add_node("child", LocalTransform::from_translation(Vec3::Z), Some(a));
+        graph.update();
+        assert_vec3_eq(graph.world_position(child), Vec3::new(1.0, 0.0, 1.0));
+
+        graph.set_parent(child, Some(b)).unwrap();
+        assert_eq!(graph.children(a), &[]);
+        assert_eq!(graph.children(b), &[child]);
+        graph.update();
+        assert_vec3_eq(graph.world_position(child), Vec3::new(0.0, 1.0, 1.0));
+
+        assert!(graph.set_parent(b, Some(child)).is_err());
+        assert_eq!(graph.find("child"), Some(child));
+        assert_eq!(graph.roots().collect::<Vec<_>>(), vec![a, b]);
+    }
+
+    #[test]
+    fn normal_matrix_handles_non_uniform_scale() {
+        let mut graph = SceneGraph::new();
+        let parent = graph.add_node(
+            "parent",
+            LocalTransform::from_scale(Vec3::new(2.0, 1.0, 1.0)),
+            None,
+        );
+        let child = graph.add_node(
+            "child",
+            LocalTransform::from_translation(Vec3::new(1.0, 2.0, 3.0)),
+            Some(parent),
+        );
+        graph.update();
+
+        // the normal of a surface stays perpendicular to it after the transform
+        let model = graph.world_matrix(child);
+        let tangent = model.transform_vector3(Vec3::new(1.0, -1.0, 0.0).normalize());
+        let normal = graph.normal_matrix(child) * Vec3::new(1.0, 1.0, 0.0).normalize();
+        assert!(tangent.dot(normal).abs() < 1e-5);
+
+        // a plain model matrix would bend it
+        let bent = model.transform_vector3(Vec3::new(1.0, 1.0, 0.0).normalize());
+        assert!(tangent.dot(bent).abs() > 0.1);
+    }
+
+    #[test]
+    fn local_transform_from_matrix() {
+        let local = LocalTransform::IDENTITY
+            .with_translation(Vec3::new(1.0, 2.0, 3.0))
+            .with_rotation(Quat::from_rotation_x(0.5))
+            .with_scale(Vec3::new(1.0, 2.0, 0.5));
+        let expected = Mat4::from_translation(local.translation)
+            * Mat4::from_quat(local.rotation)
+            * Mat4::from_scale(local.scale);
+        assert!(local.matrix().abs_diff_eq(expected, 1e-5));
+
+        let decomposed = LocalTransform::from_matrix(local.matrix());
+        assert!(decomposed.matrix().abs_diff_eq(expected, 1e-5));
+    }
+}
@@ -0,0 +1,194 @@
+use super::capture::present;
+use super::stats::StatsRenderer;
+use crate::set_buffer_data;
+use notan::math::{Mat3, Mat4};
+use notan::prelude::*;
+
+// language=glsl
+const SKYBOX_VERTEX_SHADER: ShaderSource = notan::vertex_shader! {
+  r#"
+    #version 450
+    layout (location = 0) in vec3 aPos;
+
+    layout (location = 0) out vec3 TexCoords;
+
+    layout(set = 0, binding = 0) uniform SkyboxTransform {
+        mat4 view;
+        mat4 projection;
+    };
+
+    void main()
+    {
+        TexCoords = aPos;
+        vec4 pos = projection * view * vec4(aPos, 1.0);
+        // z = w makes the depth always 1.0, the skybox is behind everything
+        gl_Position = pos.xyww;
+    }
+  "#
+};
+
+// language=glsl
+const SKYBOX_FRAGMENT_SHADER: ShaderSource = notan::fragment_shader! {
+  r#"
+    #version 450
+    layout(location = 0) in vec3 TexCoords;
+
+    layout(location = 0) out vec4 color;
+
+    layout(binding = 0) uniform sampler2D skybox;
+
+    // SAMPLE_CUBE_GLSL of utils/cubemap.rs, samples the faces packed in a 3x2 grid
+    vec4 sampleCube(sampler2D cube, vec3 dir)
+    {
+        vec3 a = abs(dir);
+        float face;
+        float ma;
+        vec2 st;
+        if (a.x >= a.y && a.x >= a.z) {
+            ma = a.x;
+            face = dir.x > 0.0 ? 0.0 : 1.0;
+            st = dir.x > 0.0 ? vec2(-dir.z, -dir.y) : vec2(dir.z, -dir.y);
+        } else if (a.y >= a.z) {
+            ma = a.y;
+            face = dir.y > 0.0 ? 2.0 : 3.0;
+            st = dir.y > 0.0 ? vec2(dir.x, dir.z) : vec2(dir.x, -dir.z);
+        } else {
+            ma = a.z;
+            face = dir.z > 0.0 ? 4.0 : 5.0;
+            st = dir.z > 0.0 ? vec2(dir.x, -dir.y) : vec2(-dir.x, -dir.y);
+        }
+
+        st = (st / ma + 1.0) * 0.5;
+
+        // keep the samples inside the face to avoid bleeding from the neighbours
+        vec2 faceSize = vec2(textureSize(cube, 0)) / vec2(3.0, 2.0);
+        vec2 halfTexel = 0.5 / faceSize;
+        st = clamp(st, halfTexel, 1.0 - halfTexel);
+
+        vec2 cell = vec2(mod(face, 3.0), floor(face / 3.0));
+        return textureLod(cube, (cell + st) / vec2(3.0, 2.0), 0.0);
+    }
+
+    void main()
+    {
+        color = sampleCube(skybox, TexCoords);
+    }
+  "#
+};
+
+#[derive(Copy, Clone)]
+#[uniform]
+struct SkyboxTransform {
+    view: Mat4,
+    projection: Mat4,
+}
+
+// Draws a cubemap created with `create_cubemap` around the camera
+pub struct Skybox {
+    pipeline: Pipeline,
+    vbo: Buffer,
+    ubo: Buffer,
+}
+
+impl Skybox {
+    pub fn new(gfx: &mut Graphics) -> Result<Self, String> {
+        let vertex_info = VertexInfo::new().attr(0, VertexFormat::Float32x3); // positions
+
+        // the skybox depth is 1.0 so it needs to pass when the buffer is cleared to 1.0
+        let depth_test = DepthStencil {
+            write: false,
+            compare: CompareMode::LEqual,
+        };
+
+        let pipeline = gfx
+            .create_pipeline()
+            .from(&SKYBOX_VERTEX_SHADER, &SKYBOX_FRAGMENT_SHADER)
+            .with_vertex_info(&vertex_info)
+            .with_depth_stencil(depth_test)
+            .with_texture_location(0, "skybox")
+            .build()?;
+
+        #[rustfmt::skip]
+        let vertices = [
+            -1.0,  1.0, -1.0,
+            -1.0, -1.0, -1.0,
+            1.0, -1.0, -1.0,
+            1.0, -1.0, -1.0,
+            1.0,  1.0, -1.0,
+            -1.0,  1.0, -1.0,
+
+            -1.0, -1.0,  1.0,
+            -1.0, -1.0, -1.0,
+            -1.0,  1.0, -1.0,
+            -1.0,  1.0, -1.0,
+            -1.0,  1.0,  1.0,
+            -1.0, -1.0,  1.0,
+
+            1.0, -1.0, -1.0,
+            1.0, -1.0,  1.0,
+            1.0,  1.0,  1.0,
+            1.0,  1.0,  1.0,
+            1.0,  1.0, -1.0,
+            1.0, -1.0, -1.0,
+
+            -1.0, -1.0,  1.0,
+            -1.0,  1.0,  1.0,
+            1.0,  1.0,  1.0,
+            1.0,  1.0,  1.0,
+            1.0, -1.0,  1.0,
+            -1.0, -1.0,  1.0,
+
+            -1.0,  1.0, -1.0,
+            1.0,  1.0, -1.0,
+            1.0,  1.0,  1.0,
+            1.0,  1.0,  1.0,
+            -1.0,  1.0,  1.0,
+            -1.0,  1.0, -1.0,
+
+            -1.0, -1.0, -1.0,
+            -1.0, -1.0,  1.0,
+            1.0, -1.0, -1.0,
+            1.0, -1.0, -1.0,
+            -1.0, -1.0,  1.0,
+            1.0, -1.0,  1.0,
+        ];
+
+        let vbo = gfx
+            .create_vertex_buffer()
+            .with_data(&vertices)
+            .with_info(&vertex_info)
+            .build()?;
+
+        let ubo = gfx.create_uniform_buffer(0, "SkyboxTransform").build()?;
+
+        Ok(Self { pipeline, vbo, ubo })
+    }
+
+    // Uploads the camera, call it before the pass
+    pub fn update(&self, gfx: &mut Graphics, view: Mat4, projection: Mat4) {
+        // remove the translation, the skybox moves with the camera
+        let view = Mat4::from_mat3(Mat3::from_mat4(view));
+        set_buffer_data!(gfx, &self.ubo, &SkyboxTransform { view, projection });
+    }
+
+    // Draws the skybox in the pass of the caller, after the scene and with its depth buffer
+    pub fn draw(&self, renderer: &mut StatsRenderer, cubemap: &Texture) {
+        renderer.set_pipeline(&self.pipeline);
+        renderer.bind_buffers(&[&self.vbo, &self.ubo]);
+        renderer.bind_texture(0, cubemap);
+        renderer.draw(0, 36);
+    }
+
+    // Draws the skybox in its own pass, must be rendered after the scene without clearing
+    // the depth buffer
+    pub fn render(&self, gfx: &mut Graphics, cubemap: &Texture, view: Mat4, projection: Mat4) {
+        self.update(gfx, view, projection);
+
+        let mut renderer = StatsRenderer::new(gfx);
+        renderer.begin(None);
+        self.draw(&mut renderer, cubemap);
+        renderer.end();
+
+        present(gfx, &renderer);
+    }
+}
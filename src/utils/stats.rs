@@ -0,0 +1,395 @@
+use notan::egui::{self, Color32, Ui};
+use notan::graphics::Renderer;
+use notan::prelude::*;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::ops::{Deref, DerefMut};
+
+// Frame statistics for the overlay (F2). The examples create their renderers with
+// `StatsRenderer::new` and upload the uniforms with `set_buffer_data!`, the counters are
+// added when the renderer is presented.
+
+thread_local! {
+    static FRAME_STATS: Cell<FrameStats> = Cell::new(FrameStats::default());
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameStats {
+    pub passes: u32,
+    pub draw_calls: u32,
+    pub triangles: u32,
+    pub pipeline_binds: u32,
+    pub buffer_binds: u32,
+    pub texture_binds: u32,
+    pub bytes_uploaded: usize,
+}
+
+impl std::ops::AddAssign for FrameStats {
+    fn add_assign(&mut self, other: Self) {
+        self.passes += other.passes;
+        self.draw_calls += other.draw_calls;
+        self.triangles += other.triangles;
+        self.pipeline_binds += other.pipeline_binds;
+        self.buffer_binds += other.buffer_binds;
+        self.texture_binds += other.texture_binds;
+        self.bytes_uploaded += other.bytes_uploaded;
+    }
+}
+
+// Counters of each command, added by `StatsRenderer` and `set_buffer_data!` as they are
+// recorded
+impl FrameStats {
+    pub fn count_pass(&mut self) {
+        self.passes += 1;
+    }
+
+    pub fn count_pipeline(&mut self) {
+        self.pipeline_binds += 1;
+    }
+
+    pub fn count_buffers(&mut self, len: usize) {
+        self.buffer_binds += len as u32;
+    }
+
+    pub fn count_texture(&mut self) {
+        self.texture_binds += 1;
+    }
+
+    // all the pipelines of the examples draw triangle lists
+    pub fn count_draw(&mut self, count: i32) {
+        self.count_draw_instanced(count, 1);
+    }
+
+    pub fn count_draw_instanced(&mut self, count: i32, length: i32) {
+        self.draw_calls += 1;
+        self.triangles += (count.max(0) as u32 / 3) * length.max(0) as u32;
+    }
+
+    pub fn count_upload(&mut self, bytes: usize) {
+        self.bytes_uploaded += bytes;
+    }
+}
+
+pub fn frame_stats() -> FrameStats {
+    FRAME_STATS.with(|stats| stats.get())
+}
+
+pub fn add_frame_stats(other: FrameStats) {
+    FRAME_STATS.with(|stats| {
+        let mut value = stats.get();
+        value += other;
+        stats.set(value);
+    });
+}
+
+// Called by the runner at the start of each frame
+pub fn reset_frame_stats() {
+    FRAME_STATS.with(|stats| stats.set(FrameStats::default()));
+}
+
+pub fn record_upload(bytes: usize) {
+    let mut stats = FrameStats::default();
+    stats.count_upload(bytes);
+    add_frame_stats(stats);
+}
+
+// Same as `gfx.set_buffer_data` but counting the bytes uploaded. It's a macro to accept
+// any data that notan accepts.
+#[macro_export]
+macro_rules! set_buffer_data {
+    ($gfx:expr, $buffer:expr, $data:expr $(,)?) => {{
+        let data = $data;
+        $crate::utils::record_upload(std::mem::size_of_val(data));
+        $gfx.set_buffer_data($buffer, data);
+    }};
+}
+
+// Notan's renderer counting the commands of the pass, the rest of the methods are
+// available through deref
+pub struct StatsRenderer {
+    renderer: Renderer,
+    stats: FrameStats,
+}
+
+impl StatsRenderer {
+    pub fn new(gfx: &mut Graphics) -> Self {
+        Self {
+            renderer: gfx.create_renderer(),
+            stats: FrameStats::default(),
+        }
+    }
+
+    pub fn stats(&self) -> FrameStats {
+        self.stats
+    }
+
+    pub fn begin(&mut self, options: Option<&ClearOptions>) {
+        self.stats.count_pass();
+        self.renderer.begin(options);
+    }
+
+    pub fn end(&mut self) {
+        self.renderer.end();
+    }
+
+    pub fn set_pipeline(&mut self, pipeline: &Pipeline) {
+        self.stats.count_pipeline();
+        self.renderer.set_pipeline(pipeline);
+    }
+
+    pub fn bind_buffer(&mut self, buffer: &Buffer) {
+        self.stats.count_buffers(1);
+        self.renderer.bind_buffer(buffer);
+    }
+
+    pub fn bind_buffers(&mut self, buffers: &[&Buffer]) {
+        self.stats.count_buffers(buffers.len());
+        self.renderer.bind_buffers(buffers);
+    }
+
+    pub fn bind_texture(&mut self, location: u32, texture: &Texture) {
+        self.stats.count_texture();
+        self.renderer.bind_texture(location, texture);
+    }
+
+    pub fn bind_texture_slot(&mut self, slot: u32, location: u32, texture: &Texture) {
+        self.stats.count_texture();
+        self.renderer.bind_texture_slot(slot, location, texture);
+    }
+
+    pub fn draw(&mut self, offset: i32, count: i32) {
+        self.stats.count_draw(count);
+        self.renderer.draw(offset, count);
+    }
+
+    pub fn draw_instanced(&mut self, offset: i32, count: i32, length: i32) {
+        self.stats.count_draw_instanced(count, length);
+        self.renderer.draw_instanced(offset, count, length);
+    }
+}
+
+impl Deref for StatsRenderer {
+    type Target = Renderer;
+
+    fn deref(&self) -> &Self::Target {
+        &self.renderer
+    }
+}
+
+impl DerefMut for StatsRenderer {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.renderer
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameTimeSummary {
+    // milliseconds
+    pub min: f32,
+    pub avg: f32,
+    pub max: f32,
+    pub p50: f32,
+    pub p95: f32,
+    pub p99: f32,
+}
+
+// Frame times of the last frames, for the graph of the overlay
+#[derive(Clone, Debug)]
+pub struct FrameTimes {
+    samples: VecDeque<f32>,
+    capacity: usize,
+}
+
+impl FrameTimes {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    pub fn push(&mut self, ms: f32) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ms);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // oldest first
+    pub fn samples(&self) -> impl Iterator<Item = f32> + '_ {
+        self.samples.iter().copied()
+    }
+
+    pub fn summary(&self) -> FrameTimeSummary {
+        if self.samples.is_empty() {
+            return FrameTimeSummary::default();
+        }
+
+        let mut sorted = self.samples.iter().copied().collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+
+        // nearest rank
+        let percentile = |p: f32| {
+            let rank = (p * sorted.len() as f32).ceil() as usize;
+            sorted[rank.clamp(1, sorted.len()) - 1]
+        };
+
+        FrameTimeSummary {
+            min: sorted[0],
+            avg: sorted.iter().sum::<f32>() / sorted.len() as f32,
+            max: sorted[sorted.len() - 1],
+            p50: percentile(0.5),
+            p95: percentile(0.95),
+            p99: percentile(0.99),
+        }
+    }
+}
+
+// frame time of 60 and 30 fps, used to color the graph
+const GOOD_FRAME_MS: f32 = 1000.0 / 60.0;
+const SLOW_FRAME_MS: f32 = 1000.0 / 30.0;
+
+// Contents of the stats overlay
+pub fn stats_panel(ui: &mut Ui, times: &FrameTimes, stats: &FrameStats) {
+    let summary = times.summary();
+    let fps = if summary.avg > 0.0 {
+        1000.0 / summary.avg
+    } else {
+        0.0
+    };
+
+    ui.label(format!("{:.0} fps ({:.2} ms)", fps, summary.avg));
+    ui.label(format!(
+        "min {:.2}  avg {:.2}  max {:.2} ms",
+        summary.min, summary.avg, summary.max
+    ));
+    ui.label(format!(
+        "p50 {:.2}  p95 {:.2}  p99 {:.2} ms",
+        summary.p50, summary.p95, summary.p99
+    ));
+
+    frame_time_graph(ui, times, summary.max);
+    ui.separator();
+
+    egui::Grid::new("frame_stats").show(ui, |ui| {
+        let rows = [
+            ("passes", stats.passes.to_string()),
+            ("draw calls", stats.draw_calls.to_string()),
+            ("triangles", stats.triangles.to_string()),
+            ("pipeline binds", stats.pipeline_binds.to_string()),
+            ("buffer binds", stats.buffer_binds.to_string()),
+            ("texture binds", stats.texture_binds.to_string()),
+            ("uploaded", format_bytes(stats.bytes_uploaded)),
+        ];
+
+        rows.iter().for_each(|(name, value)| {
+            ui.label(*name);
+            ui.label(value);
+            ui.end_row();
+        });
+    });
+}
+
+fn frame_time_graph(ui: &mut Ui, times: &FrameTimes, max: f32) {
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(240.0, 60.0), egui::Sense::hover());
+    let painter = ui.painter();
+    painter.rect_filled(rect, 0.0, Color32::from_black_alpha(160));
+
+    // at least a 30 fps frame fits in the graph
+    let scale = rect.height() / max.max(SLOW_FRAME_MS);
+    let width = rect.width() / times.capacity() as f32;
+    times.samples().enumerate().for_each(|(i, ms)| {
+        let x = rect.left() + (i as f32 + 0.5) * width;
+        let height = (ms * scale).min(rect.height());
+        let color = if ms > SLOW_FRAME_MS {
+            Color32::RED
+        } else if ms > GOOD_FRAME_MS {
+            Color32::YELLOW
+        } else {
+            Color32::GREEN
+        };
+
+        painter.line_segment(
+            [
+                egui::pos2(x, rect.bottom()),
+                egui::pos2(x, rect.bottom() - height),
+            ],
+            (width.max(1.0), color),
+        );
+    });
+
+    // 60 fps line
+    let y = rect.bottom() - GOOD_FRAME_MS * scale;
+    painter.line_segment(
+        [egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)],
+        (1.0, Color32::GRAY),
+    );
+}
+
+fn format_bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KiB", bytes as f32 / 1024.0)
+    } else {
+        format!("{:.1} MiB", bytes as f32 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_times_summary() {
+        let mut times = FrameTimes::new(100);
+        (1..=100).for_each(|ms| times.push(ms as f32));
+
+        let summary = times.summary();
+        assert_eq!(summary.min, 1.0);
+        assert_eq!(summary.max, 100.0);
+        assert_eq!(summary.avg, 50.5);
+        assert_eq!(summary.p50, 50.0);
+        assert_eq!(summary.p95, 95.0);
+        assert_eq!(summary.p99, 99.0);
+    }
+
+    #[test]
+    fn frame_times_keep_the_last_samples() {
+        let mut times = FrameTimes::new(3);
+        (0..5).for_each(|ms| times.push(ms as f32));
+        assert_eq!(times.samples().collect::<Vec<_>>(), vec![2.0, 3.0, 4.0]);
+        assert_eq!(FrameTimes::new(3).summary(), FrameTimeSummary::default());
+    }
+
+    #[test]
+    fn stats_are_added_to_the_frame() {
+        reset_frame_stats();
+        record_upload(64);
+        add_frame_stats(FrameStats {
+            draw_calls: 2,
+            triangles: 24,
+            ..Default::default()
+        });
+        record_upload(16);
+
+        let stats = frame_stats();
+        assert_eq!(stats.bytes_uploaded, 80);
+        assert_eq!(stats.draw_calls, 2);
+        assert_eq!(stats.triangles, 24);
+
+        reset_frame_stats();
+        assert_eq!(frame_stats(), FrameStats::default());
+    }
+
+    #[test]
+    fn bytes_are_readable() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024), "3.0 MiB");
+    }
+}
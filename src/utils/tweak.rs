@@ -0,0 +1,132 @@
+use super::camera::Camera;
+use super::post_process::PostPass;
+use notan::egui::{self, Ui};
+use notan::math::Vec3;
+use notan::prelude::*;
+use std::ops::RangeInclusive;
+
+// Widgets used by the examples to expose their parameters in the tweak panel (F1), the
+// values are changed in place so they are applied on the next draw.
+
+// Collapsible section of the panel
+pub fn tweak_group(ui: &mut Ui, name: &str, add_contents: impl FnOnce(&mut Ui)) {
+    egui::CollapsingHeader::new(name)
+        .default_open(true)
+        .show(ui, add_contents);
+}
+
+pub fn tweak_slider(ui: &mut Ui, label: &str, value: &mut f32, range: RangeInclusive<f32>) {
+    ui.add(egui::Slider::new(value, range).text(label));
+}
+
+pub fn tweak_vec3(ui: &mut Ui, label: &str, value: &mut Vec3) {
+    ui.horizontal(|ui| {
+        ui.add(egui::DragValue::new(&mut value.x).speed(0.05).prefix("x "));
+        ui.add(egui::DragValue::new(&mut value.y).speed(0.05).prefix("y "));
+        ui.add(egui::DragValue::new(&mut value.z).speed(0.05).prefix("z "));
+        ui.label(label);
+    });
+}
+
+// Light and material colors are stored in vec3 uniforms
+pub fn tweak_rgb(ui: &mut Ui, label: &str, value: &mut Vec3) {
+    ui.horizontal(|ui| {
+        let mut rgb = value.to_array();
+        if ui.color_edit_button_rgb(&mut rgb).changed() {
+            *value = Vec3::from(rgb);
+        }
+        ui.label(label);
+    });
+}
+
+// The spot lights store the cosine of the angle, the slider shows it in degrees
+pub fn tweak_cos_angle(ui: &mut Ui, label: &str, cos: &mut f32, max_degrees: f32) {
+    let mut degrees = cos.clamp(-1.0, 1.0).acos().to_degrees();
+    let slider = egui::Slider::new(&mut degrees, 0.0..=max_degrees)
+        .text(label)
+        .suffix("°");
+    if ui.add(slider).changed() {
+        *cos = degrees.to_radians().cos();
+    }
+}
+
+// Constant, linear and quadratic attenuation of the point and spot lights
+pub fn tweak_attenuation(ui: &mut Ui, constant: &mut f32, linear: &mut f32, quadratic: &mut f32) {
+    tweak_slider(ui, "constant", constant, 0.0..=2.0);
+    tweak_slider(ui, "linear", linear, 0.0..=1.0);
+    tweak_slider(ui, "quadratic", quadratic, 0.0..=2.0);
+}
+
+pub fn tweak_clear_color(ui: &mut Ui, color: &mut Color) {
+    ui.horizontal(|ui| {
+        let mut rgba = [color.r, color.g, color.b, color.a];
+        if ui.color_edit_button_rgba_unmultiplied(&mut rgba).changed() {
+            *color = Color::new(rgba[0], rgba[1], rgba[2], rgba[3]);
+        }
+        ui.label("clear color");
+    });
+}
+
+pub fn tweak_camera(ui: &mut Ui, camera: &mut Camera) {
+    tweak_group(ui, "Camera", |ui| {
+        tweak_vec3(ui, "position", &mut camera.position);
+
+        // the mouse can turn the camera around many times, so the yaw is wrapped
+        let yaw = (camera.yaw + 180.0).rem_euclid(360.0) - 180.0;
+        let pitch = camera.pitch.clamp(-89.0, 89.0);
+        let (mut new_yaw, mut new_pitch) = (yaw, pitch);
+        tweak_slider(ui, "yaw", &mut new_yaw, -180.0..=180.0);
+        tweak_slider(ui, "pitch", &mut new_pitch, -89.0..=89.0);
+        if new_yaw != yaw || new_pitch != pitch {
+            camera.process_mouse_movement(
+                (new_yaw - yaw) / camera.mouse_sensitivity,
+                (new_pitch - pitch) / camera.mouse_sensitivity,
+                true,
+            );
+        }
+
+        tweak_slider(ui, "zoom", &mut camera.zoom, 1.0..=45.0);
+        tweak_slider(ui, "speed", &mut camera.movement_speed, 0.5..=10.0);
+    });
+}
+
+// Enables, mixes and reorders the passes of a `PostProcess`
+pub fn tweak_post_passes(ui: &mut Ui, passes: &mut [PostPass]) {
+    let mut swap = None;
+    let len = passes.len();
+    tweak_group(ui, "Post-processing", |ui| {
+        passes.iter_mut().enumerate().for_each(|(i, pass)| {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut pass.enabled, pass.name.as_str());
+                if ui.add_enabled(i > 0, egui::Button::new("up")).clicked() {
+                    swap = Some((i - 1, i));
+                }
+                if ui
+                    .add_enabled(i + 1 < len, egui::Button::new("down"))
+                    .clicked()
+                {
+                    swap = Some((i, i + 1));
+                }
+            });
+
+            ui.add_enabled_ui(pass.enabled, |ui| {
+                tweak_slider(ui, "strength", &mut pass.strength, 0.0..=1.0);
+                // one row per vec3, like the rows of the kernels
+                if !pass.params.is_empty() {
+                    egui::Grid::new(("params", i)).show(ui, |ui| {
+                        pass.params.iter_mut().for_each(|param| {
+                            ui.add(egui::DragValue::new(&mut param.x).speed(0.05));
+                            ui.add(egui::DragValue::new(&mut param.y).speed(0.05));
+                            ui.add(egui::DragValue::new(&mut param.z).speed(0.05));
+                            ui.end_row();
+                        });
+                    });
+                }
+            });
+        });
+    });
+
+    if let Some((a, b)) = swap {
+        passes.swap(a, b);
+    }
+}
@@ -0,0 +1,83 @@
+use learn_open_gl_notan::utils::{compare_images, load_png, resize_to};
+use std::process::Command;
+
+// Renders the examples with a fixed camera, window size and synthetic time, takes a screenshot at a fixed
+// frame and compares it with the one stored in `screenshots/`. They need a window, so they
+// are ignored by default: `cargo test --test golden -- --ignored`
+//
+// When an example doesn't match, the capture and an image with the differing pixels
+// are saved in `target/tmp/golden/`.
+
+// perceptual delta (0.0 - 1.0) to consider a pixel different
+const THRESHOLD: f32 = 0.1;
+// ratio of pixels that can be different
+const MAX_MISMATCH: f32 = 0.01;
+// frame of the screenshot, giving time to the textures to load
+const FRAME: &str = "30";
+
+fn check_example(id: &str, bin: &str) {
+    let root = env!("CARGO_MANIFEST_DIR");
+    let out_dir = format!("{}/golden", env!("CARGO_TARGET_TMPDIR"));
+    let capture = format!("{}/{}.png", out_dir, id);
+
+    let status = Command::new(bin)
+        .current_dir(root)
+        .args(["--width", "800", "--height", "600"])
+        .args(["--camera", "0,0,3"])
+        .arg("--deterministic")
+        .args(["--screenshot", &capture, "--screenshot-frame", FRAME])
+        .status()
+        .unwrap();
+    assert!(status.success(), "{} exited with {}", id, status);
+
+    let expected = load_png(&format!("{}/screenshots/{}.png", root, id)).unwrap();
+    let actual = load_png(&capture).unwrap();
+    let actual = resize_to(&actual, expected.width(), expected.height());
+
+    let diff = compare_images(&expected, &actual, THRESHOLD).unwrap();
+    if diff.mismatch_ratio() > MAX_MISMATCH {
+        let diff_path = format!("{}/{}.diff.png", out_dir, id);
+        diff.image.save(&diff_path).unwrap();
+        panic!(
+            "{} doesn't match its screenshot: {:.2}% of the pixels are different (max delta {:.3}), see '{}'",
+            id,
+            diff.mismatch_ratio() * 100.0,
+            diff.max_delta,
+            diff_path
+        );
+    }
+}
+
+macro_rules! golden_tests {
+    ($($name:ident => $id:literal),* $(,)?) => {
+        $(
+            #[test]
+            #[ignore = "needs a window"]
+            fn $name() {
+                check_example($id, env!(concat!("CARGO_BIN_EXE_", $id)));
+            }
+        )*
+    };
+}
+
+// The examples animated with the time are not here, their output depends on the frame rate
+golden_tests! {
+    hello_window_clear => "1_1_2",
+    hello_triangle => "1_2_1",
+    hello_triangle_indexed => "1_2_2",
+    hello_triangle_exercise1 => "1_2_3",
+    hello_triangle_exercise2 => "1_2_4",
+    hello_triangle_exercise3 => "1_2_5",
+    shaders_interpolation => "1_3_2",
+    shaders_exercise1 => "1_3_4",
+    shaders_exercise2 => "1_3_5",
+    shaders_exercise3 => "1_3_6",
+    textures => "1_4_1",
+    textures_combined => "1_4_2",
+    textures_exercise1 => "1_4_3",
+    textures_exercise2 => "1_4_4",
+    textures_exercise3 => "1_4_5",
+    textures_exercise4 => "1_4_6",
+    coordinate_systems => "1_6_1",
+    coordinate_systems_multiple => "1_6_3",
+}